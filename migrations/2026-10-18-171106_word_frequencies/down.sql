@@ -0,0 +1,15 @@
+-- This file should undo anything in `up.sql`
+-- SQLite can not drop columns in every version, so words table is rebuilt without it.
+CREATE TABLE new_words(
+    id INTEGER NOT NULL PRIMARY KEY,
+    word TEXT NOT NULL,
+    word_pattern TEXT NOT NULL,
+    language_id INTEGER NOT NULL,
+    FOREIGN KEY (language_id) REFERENCES languages(id)
+        on delete cascade
+        on update cascade
+);
+INSERT INTO new_words(id, word, word_pattern, language_id)
+    SELECT id, word, word_pattern, language_id FROM words;
+DROP TABLE words;
+ALTER TABLE new_words RENAME TO words;
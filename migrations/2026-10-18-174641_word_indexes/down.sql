@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX words_language_word_index;
+DROP INDEX words_language_pattern_index;
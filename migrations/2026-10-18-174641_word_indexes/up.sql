@@ -0,0 +1,3 @@
+-- Your SQL goes here
+CREATE INDEX words_language_pattern_index ON words(language_id, word_pattern);
+CREATE INDEX words_language_word_index ON words(language_id, word);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX letter_frequencies_language_ngram_index;
+DROP TABLE letter_frequencies;
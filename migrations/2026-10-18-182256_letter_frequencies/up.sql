@@ -0,0 +1,25 @@
+-- Your SQL goes here
+CREATE TABLE letter_frequencies(
+    id INTEGER NOT NULL PRIMARY KEY,
+    ngram TEXT NOT NULL,
+    language_id INTEGER NOT NULL,
+    occurrences BIGINT NOT NULL,
+    FOREIGN KEY (language_id) REFERENCES languages(id)
+        on delete cascade
+        on update cascade
+);
+CREATE UNIQUE INDEX letter_frequencies_language_ngram_index ON letter_frequencies(language_id, ngram);
+-- Dictionaries populated before this table existed get their letters and bigrams counted now.
+INSERT INTO letter_frequencies(ngram, language_id, occurrences)
+    WITH RECURSIVE word_letters(word, language_id, frequency, position) AS (
+        SELECT word, language_id, frequency, 1 FROM words WHERE length(word) > 0
+        UNION ALL
+        SELECT word, language_id, frequency, position + 1 FROM word_letters WHERE position < length(word)
+    )
+    SELECT ngram, language_id, sum(frequency) FROM (
+        SELECT substr(word, position, 1) AS ngram, language_id, frequency FROM word_letters
+        UNION ALL
+        SELECT substr(word, position, 2) AS ngram, language_id, frequency FROM word_letters
+            WHERE position < length(word)
+    )
+    GROUP BY language_id, ngram;
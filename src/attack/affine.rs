@@ -10,11 +10,13 @@
 /// won't be detected.
 
 use crate::Result;
-use crate::attack::dictionaries::IdentifiedLanguage;
-use crate::attack::simple_attacks::brute_force as simple_brute_force;
-use crate::attack::simple_attacks::brute_force_mp as simple_brute_force_mp;
+use crate::attack::dictionaries::{DictionaryStore, IdentifiedLanguage};
+use crate::attack::simple_attacks::{brute_force as simple_brute_force,
+                                    brute_force_mp as simple_brute_force_mp};
+use crate::attack::scoring::{LetterProfile, Scorer, find_best_key_with_profiles};
 use crate::attack::simple_attacks::{assess_key, Parameters};
-use crate::cipher::affine::{decipher_par, validate_key};
+use crate::cipher::affine::{decipher, decipher_par, validate_key};
+use crate::cipher::common::get_key_parts;
 
 
 /// Get Affine ciphered text key.
@@ -32,14 +34,17 @@ use crate::cipher::affine::{decipher_par, validate_key};
 /// * charset: Charset used for Affine method substitution. Both ends, ciphering
 ///      and deciphering, should use the same charset or original text won't be properly
 ///      recovered.
+/// * store: Dictionaries to identify deciphered texts with.
 ///
 /// # Returns:
-/// * Affine key found.
-pub fn brute_force<T, U>(ciphered_text: T, charset: U)-> Result<usize>
+/// * Affine key found, as a (multiplying key, adding key) tuple.
+pub fn brute_force<T, U>(ciphered_text: T, charset: U, store: &dyn DictionaryStore)-> Result<(usize, usize)>
     where T: AsRef<str>,
           U: AsRef<str> {
+    let charset_length = charset.as_ref().len();
     let mut parameters = create_parameters(ciphered_text, charset);
-    simple_brute_force(assess_affine_key, &mut parameters)
+    let key = simple_brute_force(assess_affine_key, &mut parameters, store)?;
+    Ok(get_key_parts(key, charset_length))
 }
 
 /// Get Affine ciphered text key.
@@ -57,15 +62,45 @@ pub fn brute_force<T, U>(ciphered_text: T, charset: U)-> Result<usize>
 /// * charset: Charset used for Affine method substitution. Both ends, ciphering
 ///      and deciphering, should use the same charset or original text won't be properly
 ///      recovered.
+/// * store: Dictionaries to identify deciphered texts with.
 ///
 /// # Returns:
-/// * Affine key found.
+/// * Affine key found, as a (multiplying key, adding key) tuple.
 // pub fn brute_force_mp<T, U>(ciphered_text: T, charset: U)-> Result<usize>
 //     where T: AsRef<str>,
 //           U: AsRef<str> {
-pub fn brute_force_mp(ciphered_text: &str, charset: &str)-> Result<usize> {
-    let mut parameters = create_parameters(ciphered_text, charset);
-    simple_brute_force_mp(assess_affine_key, &mut parameters)
+pub fn brute_force_mp(ciphered_text: &str, charset: &str, store: &dyn DictionaryStore)-> Result<(usize, usize)> {
+    let parameters = create_parameters(ciphered_text, charset);
+    let key = simple_brute_force_mp(assess_affine_key, &parameters, store)?;
+    Ok(get_key_parts(key, charset.len()))
+}
+
+/// Get Affine ciphered text key comparing letter frequencies.
+///
+/// Every valid key is tried and the one whose deciphered text letters are the nearest
+/// to any language letter profile is kept. Profiles are those of store dictionaries, or
+/// built-in ones if store has none. Unlike *brute_force_mp*, no dictionary word is needed,
+/// and it is much quicker.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * charset: Charset used for Affine method substitution. Both ends, ciphering
+///      and deciphering, should use the same charset or original text won't be properly
+///      recovered.
+/// * scorer: Scorer to compare deciphered texts with language profiles.
+/// * store: Dictionaries whose letter profiles deciphered texts are compared with.
+///
+/// # Returns:
+/// * Affine key found, as a (multiplying key, adding key) tuple.
+pub fn frequency_attack(ciphered_text: &str, charset: &str, scorer: Scorer,
+                        store: &dyn DictionaryStore)-> Result<(usize, usize)> {
+    let charset_length = charset.len();
+    let keys: Vec<usize> = (0..charset_length.pow(2))
+        .filter(|&key| validate_key(key, charset_length).is_ok())
+        .collect();
+    let profiles = LetterProfile::get_all_with_store(store)?;
+    let key = find_best_key_with_profiles(&keys, |&key| decipher(ciphered_text, key, charset), scorer, &profiles)?;
+    Ok(get_key_parts(key, charset_length))
 }
 
 /// Decipher text with given key and try to find out if returned text can be identified with any
@@ -78,15 +113,16 @@ pub fn brute_force_mp(ciphered_text: &str, charset: &str)-> Result<usize> {
 ///     - charset (str): Charset used for Affine method substitution. Both ends, ciphering
 ///         and deciphering, should use the same charset or original text won't be properly
 ///         recovered.
+/// * store: Dictionaries to identify deciphered text with.
 ///
 /// # Returns:
 /// * A tuple with used key and an *IdentifiedLanguage* object with assessment result.
-fn assess_affine_key(parameters: &Parameters)-> Result<(usize, IdentifiedLanguage)> {
+fn assess_affine_key(parameters: &Parameters, store: &dyn DictionaryStore)-> Result<(usize, IdentifiedLanguage)> {
     let key = parameters.get_int("key")?;
     let charset = parameters.get_str("charset")?;
     let charset_length = charset.len();
     validate_key(key, charset_length)?;
-    assess_key(decipher_par, parameters)
+    assess_key(decipher_par, parameters, store)
 }
 
 
@@ -121,19 +157,20 @@ mod tests {
     use super::*;
 
     use std::time::Instant;
+    use crate::attack::dictionaries::{DatabaseStore, MemoryStore};
     use crate::attack::dictionaries::tests::LoadedDictionaries;
-    use crate::cipher::affine::decipher;
+    use crate::cipher::affine::decipher_with_key_parts;
     use crate::cipher::common::DEFAULT_CHARSET;
 
     const ORIGINAL_MESSAGE: &'static str = "The Times 03/Jan/2009 Chancellor on brink of second bailout for banks";
     const CIPHERED_MESSAGE_KEY_331: &'static str = "eiTven8TXvqH/u.?/CqqlvLi.?JT33DSvD?vESn?xvDYvXTJD?OvE.n3DhcvYDSvE.?xX";
-    const TEST_KEY: usize = 331;
+    const TEST_KEY: (usize, usize) = (5, 1);
 
-    fn assert_found_key(found_key: usize) {
+    fn assert_found_key(found_key: (usize, usize)) {
         assert_eq!(TEST_KEY, found_key,
-                   "Expected key was:\n\t{}\nBut found was:\n\t{}\n",
+                   "Expected key was:\n\t{:?}\nBut found was:\n\t{:?}\n",
                    TEST_KEY, found_key);
-        let deciphered_text = decipher(&CIPHERED_MESSAGE_KEY_331, found_key, &DEFAULT_CHARSET).unwrap();
+        let deciphered_text = decipher_with_key_parts(&CIPHERED_MESSAGE_KEY_331, found_key, &DEFAULT_CHARSET).unwrap();
         assert_eq!(ORIGINAL_MESSAGE, deciphered_text,
         "Expected message was:\n\t{}\nBut found was:\n\t{}\n",
         ORIGINAL_MESSAGE, deciphered_text);
@@ -143,16 +180,24 @@ mod tests {
     fn test_brute_force_affine() {
         let loaded_dictionaries = LoadedDictionaries::new();
         let timer = Instant::now();
-        let found_key = brute_force(CIPHERED_MESSAGE_KEY_331, DEFAULT_CHARSET).unwrap();
+        let found_key = brute_force(CIPHERED_MESSAGE_KEY_331, DEFAULT_CHARSET, &DatabaseStore::default()).unwrap();
         assert_found_key(found_key);
         println!("{}", format!("\n\nElapsed time with test_brute_force_affine: {:.2} seconds.", timer.elapsed().as_secs_f64()));
     }
 
+    #[test]
+    fn test_frequency_attack() {
+        for scorer in Scorer::ALL.iter() {
+            let found_key = frequency_attack(CIPHERED_MESSAGE_KEY_331, DEFAULT_CHARSET, *scorer, &MemoryStore::new()).unwrap();
+            assert_found_key(found_key);
+        }
+    }
+
     #[test]
     fn test_brute_force_affine_mp() {
         let loaded_dictionaries = LoadedDictionaries::new();
         let timer = Instant::now();
-        let found_key = brute_force_mp(CIPHERED_MESSAGE_KEY_331, DEFAULT_CHARSET).unwrap();
+        let found_key = brute_force_mp(CIPHERED_MESSAGE_KEY_331, DEFAULT_CHARSET, &DatabaseStore::default()).unwrap();
         assert_found_key(found_key);
         println!("{}", format!("\n\nElapsed time with test_brute_force_caesar_mp: {:.2} seconds.", timer.elapsed().as_secs_f64()));
     }
@@ -3,11 +3,12 @@
 // use rayon::prelude::*;
 
 use crate::Result;
-use crate::attack::dictionaries::IdentifiedLanguage;
-use crate::attack::simple_attacks::brute_force as simple_brute_force;
-use crate::attack::simple_attacks::brute_force_mp as simple_brute_force_mp;
+use crate::attack::dictionaries::{DictionaryStore, IdentifiedLanguage};
+use crate::attack::simple_attacks::{brute_force as simple_brute_force,
+                                    brute_force_mp as simple_brute_force_mp};
+use crate::attack::scoring::{LetterProfile, Scorer, find_best_key_with_profiles};
 use crate::attack::simple_attacks::{assess_key, Parameters};
-use crate::cipher::caesar::decipher_par;
+use crate::cipher::caesar::{decipher, decipher_par};
 // use crate::cipher::common::DEFAULT_CHARSET;
 
 
@@ -25,14 +26,15 @@ use crate::cipher::caesar::decipher_par;
 /// * charset: Charset used for Caesar method substitution. Both ends, ciphering
 ///     and deciphering, should use the same charset or original text won't be properly
 ///     recovered.
+/// * store: Dictionaries to identify deciphered texts with.
 ///
 /// # Returns:
 /// * Caesar key found.
-pub fn brute_force<T, U>(ciphered_text: T, charset: U) -> Result<usize>
+pub fn brute_force<T, U>(ciphered_text: T, charset: U, store: &dyn DictionaryStore) -> Result<usize>
     where T: AsRef<str>,
           U: AsRef<str> {
     let mut parameters = create_parameters(ciphered_text, charset);
-    simple_brute_force(assess_caesar_key, &mut parameters)
+    simple_brute_force(assess_caesar_key, &mut parameters, store)
 }
 
 /// Get Caesar ciphered text key.
@@ -50,15 +52,40 @@ pub fn brute_force<T, U>(ciphered_text: T, charset: U) -> Result<usize>
 /// * charset: Charset used for Caesar method substitution. Both ends, ciphering
 ///     and deciphering, should use the same charset or original text won't be properly
 ///     recovered.
+/// * store: Dictionaries to identify deciphered texts with.
 ///
 /// # Returns:
 /// * Caesar key found.
 // pub fn brute_force_mp<T,U>(ciphered_text: T, charset: U) -> Result<usize>
 //     where T: AsRef<str> + std::marker::Sync,
 //           U: AsRef<str> + std::marker::Sync {
-pub fn brute_force_mp(ciphered_text: &str, charset: &str) -> Result<usize> {
-    let mut parameters = create_parameters(ciphered_text, charset);
-    simple_brute_force_mp(assess_caesar_key, &mut parameters)
+pub fn brute_force_mp(ciphered_text: &str, charset: &str, store: &dyn DictionaryStore) -> Result<usize> {
+    let parameters = create_parameters(ciphered_text, charset);
+    simple_brute_force_mp(assess_caesar_key, &parameters, store)
+}
+
+/// Get Caesar ciphered text key comparing letter frequencies.
+///
+/// Every key is tried and the one whose deciphered text letters are the nearest to any
+/// language letter profile is kept. Profiles are those of store dictionaries, or built-in
+/// ones if store has none. Unlike *brute_force_mp*, no dictionary word is needed, so it
+/// works with texts whose words are not in our dictionaries.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * charset: Charset used for Caesar method substitution. Both ends, ciphering
+///     and deciphering, should use the same charset or original text won't be properly
+///     recovered.
+/// * scorer: Scorer to compare deciphered texts with language profiles.
+/// * store: Dictionaries whose letter profiles deciphered texts are compared with.
+///
+/// # Returns:
+/// * Caesar key found.
+pub fn frequency_attack(ciphered_text: &str, charset: &str, scorer: Scorer,
+                        store: &dyn DictionaryStore) -> Result<usize> {
+    let keys: Vec<usize> = (0..charset.len()).collect();
+    let profiles = LetterProfile::get_all_with_store(store)?;
+    find_best_key_with_profiles(&keys, |&key| decipher(ciphered_text, key, charset), scorer, &profiles)
 }
 
 /// Get a Parameters type with given arguments.
@@ -97,11 +124,12 @@ fn create_parameters<T,U>(ciphered_text: T, charset: U) -> Parameters
 ///     - charset (str): Charset used for Caesar method substitution. Both ends, ciphering
 ///         and deciphering, should use the same charset or original text won't be properly
 ///         recovered.
+/// * store: Dictionaries to identify deciphered text with.
 ///
 /// # Returns:
 /// * A tuple with used key and an *IdentifiedLanguage* object with assessment result.
-fn assess_caesar_key(parameters: &Parameters)-> Result<(usize, IdentifiedLanguage)> {
-    assess_key(decipher_par, parameters)
+fn assess_caesar_key(parameters: &Parameters, store: &dyn DictionaryStore)-> Result<(usize, IdentifiedLanguage)> {
+    assess_key(decipher_par, parameters, store)
 }
 
 /// Assess a list of IdentifiedLanguage objects and select the most likely.
@@ -134,8 +162,10 @@ fn get_best_result(identified_languages: &Vec<(usize, IdentifiedLanguage)>)-> us
 mod tests {
     use super::*;
     use std::time::Instant;
-    use crate::attack::dictionaries::tests::LoadedDictionaries;
-    use crate::cipher::caesar::decipher;
+    use rstest::*;
+    use crate::attack::dictionaries::{DatabaseStore, MemoryDictionary, MemoryStore};
+    use crate::attack::dictionaries::tests::{GERMAN_TEXT_WITH_PUNCTUATIONS_MARKS, LoadedDictionaries, memory_store};
+    use crate::cipher::caesar::cipher;
     use crate::cipher::common::DEFAULT_CHARSET;
     use diesel::result::Error::DatabaseError;
 
@@ -147,7 +177,7 @@ mod tests {
     fn test_brute_force_caesar() {
         let loaded_dictionaries = LoadedDictionaries::new();
         let timer = Instant::now();
-        let found_key = brute_force(CIPHERED_MESSAGE_KEY_13, DEFAULT_CHARSET);
+        let found_key = brute_force(CIPHERED_MESSAGE_KEY_13, DEFAULT_CHARSET, &DatabaseStore::default());
         assert_found_key(found_key);
         println!("{}", format!("\n\nElapsed time with test_brute_force_caesar: {:.2} seconds.", timer.elapsed().as_secs_f64()));
     }
@@ -156,11 +186,36 @@ mod tests {
     fn test_brute_force_caesar_mp() {
         let loaded_dictionaries = LoadedDictionaries::new();
         let timer = Instant::now();
-        let found_key = brute_force_mp(CIPHERED_MESSAGE_KEY_13, DEFAULT_CHARSET);
+        let found_key = brute_force_mp(CIPHERED_MESSAGE_KEY_13, DEFAULT_CHARSET, &DatabaseStore::default());
         assert_found_key(found_key);
         println!("{}", format!("\n\nElapsed time with test_brute_force_caesar_mp: {:.2} seconds.", timer.elapsed().as_secs_f64()));
     }
 
+    #[rstest]
+    fn test_brute_force_caesar_mp_with_memory_store(memory_store: MemoryStore) {
+        let found_key = brute_force_mp(CIPHERED_MESSAGE_KEY_13, DEFAULT_CHARSET, &memory_store);
+        assert_found_key(found_key);
+    }
+
+    #[test]
+    fn test_frequency_attack() {
+        for scorer in Scorer::ALL.iter() {
+            let found_key = frequency_attack(CIPHERED_MESSAGE_KEY_13, DEFAULT_CHARSET, *scorer, &MemoryStore::new());
+            assert_found_key(found_key);
+        }
+    }
+
+    #[test]
+    fn test_frequency_attack_with_dictionary_profiles() {
+        let store: MemoryStore = vec![MemoryDictionary::from_text_file("german", "resources/german_book.txt").unwrap()]
+            .into_iter().collect();
+        let ciphered_text = cipher(GERMAN_TEXT_WITH_PUNCTUATIONS_MARKS, TEST_KEY, DEFAULT_CHARSET).unwrap();
+        for scorer in Scorer::ALL.iter() {
+            let found_key = frequency_attack(&ciphered_text, DEFAULT_CHARSET, *scorer, &store).unwrap();
+            assert_eq!(TEST_KEY, found_key, "Scorer {} found a wrong key.", scorer);
+        }
+    }
+
     fn assert_found_key(found_key: Result<usize>){
         if let Ok(key) = found_key {
             assert_eq!(key, TEST_KEY);
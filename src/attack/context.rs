@@ -0,0 +1,181 @@
+/// Module with the context attacks run into.
+///
+/// Attacks need dictionaries to tell if a deciphered text is the right one. Instead of
+/// every attack locating cifra database on its own through environment variables, a
+/// *Context* holds the dictionary store to use. So many contexts can live side by side,
+/// each one with its own database or in memory dictionaries, even at the same process.
+///
+/// Context also tells which unicode normalization texts get before looking their words up,
+/// so it should be the same one used to populate its dictionaries.
+///
+/// Attacks try every language at their store. When message language is already known, a
+/// context can be restricted to some languages, so attacks neither waste time with the rest
+/// nor get false positives from them.
+///
+/// A Context is a *DictionaryStore* itself, so it can be passed to every attack function,
+/// like *identify_language*, *hack_substitution_mp* or any *brute_force_mp*. A default
+/// context uses default cifra database, located through environment variables.
+use std::path::Path;
+
+use crate::{ErrorKind, Result};
+use crate::attack::dictionaries::{DatabaseStore, DictionaryStore, LanguageDictionary};
+use crate::cipher::normalization::Normalization;
+
+/// Holder of dictionary store used by attacks.
+pub struct Context {
+    store: Box<dyn DictionaryStore + Send>,
+    normalization: Normalization,
+    languages: Vec<String>
+}
+
+impl Context {
+    /// Create a context using given dictionary store.
+    ///
+    /// # Parameters:
+    /// * store: Dictionaries attacks should look words into.
+    pub fn new<S>(store: S) -> Self
+        where S: DictionaryStore + Send + 'static {
+        Context { store: Box::new(store), normalization: Normalization::default(), languages: Vec::new() }
+    }
+
+    /// Create a context using dictionaries at database in given path.
+    ///
+    /// # Parameters:
+    /// * database_path: Path to database file. It is created if it does not exist yet.
+    pub fn with_database<T>(database_path: T) -> Self
+        where T: AsRef<Path> {
+        Self::new(DatabaseStore::at(database_path))
+    }
+
+    /// Get dictionary store used by this context.
+    pub fn store(&self) -> &dyn DictionaryStore {
+        self.store.as_ref()
+    }
+
+    /// Set unicode normalization to apply to texts before looking their words up.
+    ///
+    /// # Parameters:
+    /// * normalization: Normalization used to populate this context dictionaries.
+    pub fn set_normalization(&mut self, normalization: Normalization) {
+        self.normalization = normalization;
+    }
+
+    /// Restrict attacks to given languages.
+    ///
+    /// # Parameters:
+    /// * languages: Languages attacks should use. If empty, every language at store is used.
+    pub fn set_languages<T>(&mut self, languages: &[T])
+        where T: AsRef<str> {
+        self.languages = languages.iter().map(|_language| _language.as_ref().to_string()).collect();
+    }
+
+    /// Get languages attacks are restricted to, or an empty list if they use every one at store.
+    pub fn languages(&self) -> &[String] {
+        &self.languages
+    }
+
+    /// Check if given language can be used by attacks.
+    fn is_allowed(&self, _language: &str) -> bool {
+        self.languages.is_empty() || self.languages.iter().any(|allowed_language| allowed_language == _language)
+    }
+}
+
+impl Default for Context {
+    /// Create a context using default cifra database.
+    fn default() -> Self {
+        Self::new(DatabaseStore::default())
+    }
+}
+
+impl DictionaryStore for Context {
+    fn get_dictionaries_names(&self) -> Result<Vec<String>> {
+        let dictionaries_names = self.store.get_dictionaries_names()?;
+        // A misspelled language would leave attacks without dictionaries, so it is better to warn.
+        if let Some(missing_language) = self.languages.iter().find(|_language| !dictionaries_names.contains(_language)) {
+            bail!(ErrorKind::NotExistingLanguage(missing_language.clone()))
+        }
+        Ok(dictionaries_names.into_iter().filter(|_language| self.is_allowed(_language)).collect())
+    }
+
+    fn open_dictionary(&self, _language: &str) -> Result<Box<dyn LanguageDictionary + '_>> {
+        if !self.is_allowed(_language) {
+            bail!(ErrorKind::NotExistingLanguage(_language.to_string()))
+        }
+        self.store.open_dictionary(_language)
+    }
+
+    fn normalization(&self) -> Normalization {
+        self.normalization
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use test_common::fs::tmp::TestEnvironment;
+
+    use crate::attack::caesar::brute_force_mp;
+    use crate::attack::dictionaries::{Dictionary, MemoryDictionary, MemoryStore, identify_language};
+    use crate::attack::dictionaries::tests::{ENGLISH_TEXT_WITH_PUNCTUATIONS_MARKS, memory_store};
+    use crate::attack::database::Database;
+    use crate::cipher::caesar::cipher;
+    use crate::cipher::common::DEFAULT_CHARSET;
+    use crate::cipher::normalization::normalize_unicode;
+
+    #[rstest]
+    fn test_context_with_memory_store(memory_store: MemoryStore) {
+        let context = Context::new(memory_store);
+        let ciphered_text = cipher(ENGLISH_TEXT_WITH_PUNCTUATIONS_MARKS, 13, DEFAULT_CHARSET).unwrap();
+        let found_key = brute_force_mp(&ciphered_text, DEFAULT_CHARSET, &context).unwrap();
+        assert_eq!(13, found_key);
+        let identified_language = identify_language(ENGLISH_TEXT_WITH_PUNCTUATIONS_MARKS, &context).unwrap();
+        assert_eq!(Some("english"), identified_language.winner());
+    }
+
+    #[test]
+    fn test_context_with_folded_dictionaries() {
+        let store: MemoryStore = vec![
+            MemoryDictionary::from_text("spanish", normalize_unicode("él comió una canción en la montaña", Normalization::Folded)),
+            MemoryDictionary::from_text("english", "he ate a song on the mountain")
+        ].into_iter().collect();
+        let mut context = Context::new(store);
+        context.set_normalization(Normalization::Folded);
+        for text in ["Él comió una canción", "El comio una cancion"].iter() {
+            let identified_language = identify_language(text, &context).unwrap();
+            assert_eq!(Some("spanish"), identified_language.winner());
+        }
+    }
+
+    #[rstest]
+    fn test_context_restricted_to_languages(memory_store: MemoryStore) {
+        let mut context = Context::new(memory_store);
+        context.set_languages(&["spanish"]);
+        assert_eq!(vec![String::from("spanish")], context.get_dictionaries_names().unwrap());
+        assert!(context.open_dictionary("english").is_err());
+        assert!(context.open_dictionary("spanish").is_ok());
+        // English text can only be taken as Spanish now.
+        let identified_language = identify_language(ENGLISH_TEXT_WITH_PUNCTUATIONS_MARKS, &context).unwrap();
+        assert_ne!(Some("english"), identified_language.winner());
+        context.set_languages(&["klingon"]);
+        assert!(context.get_dictionaries_names().is_err());
+        let ciphered_text = cipher(ENGLISH_TEXT_WITH_PUNCTUATIONS_MARKS, 13, DEFAULT_CHARSET).unwrap();
+        assert!(brute_force_mp(&ciphered_text, DEFAULT_CHARSET, &context).is_err());
+        context.set_languages::<&str>(&[]);
+        assert_eq!(4, context.get_dictionaries_names().unwrap().len());
+    }
+
+    #[test]
+    fn test_contexts_with_different_databases() {
+        let test_folder = TestEnvironment::new();
+        let klingon_database_path = test_folder.path().join("klingon/cifra_database.sqlite");
+        let vulcan_database_path = test_folder.path().join("vulcan/cifra_database.sqlite");
+        Dictionary::from_database(Database::open(&klingon_database_path).unwrap(), "klingon", true).unwrap();
+        Dictionary::from_database(Database::open(&vulcan_database_path).unwrap(), "vulcan", true).unwrap();
+        let klingon_context = Context::with_database(&klingon_database_path);
+        let vulcan_context = Context::with_database(&vulcan_database_path);
+        assert_eq!(vec![String::from("klingon")], klingon_context.get_dictionaries_names().unwrap());
+        assert_eq!(vec![String::from("vulcan")], vulcan_context.store().get_dictionaries_names().unwrap());
+        assert!(klingon_context.open_dictionary("vulcan").is_err());
+    }
+}
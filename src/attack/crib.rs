@@ -0,0 +1,373 @@
+/// Module to use known plaintext fragments to recover or constrain keys.
+///
+/// We almost always know some words of a ciphered message: a greeting like "Dear", a
+/// telegram "STOP", a known header... That fragment is called a crib. If we know where
+/// crib is in the message we can compare it with ciphertext at the same position to
+/// get key directly. If we don't know where it is we can try every position and keep
+/// only keys consistent with crib at any of them.
+///
+/// Crib offsets are counted in characters from the beginning of message.
+use std::collections::{HashMap, HashSet};
+use std::convert::TryInto;
+
+use crate::{ErrorKind, Result, ResultExt};
+use crate::attack::dictionaries::{DictionaryStore, identify_language};
+use crate::attack::membership::CachedStore;
+use crate::cipher::cryptomath::{find_mod_inverse, gcd, modulus};
+use crate::cipher::transposition;
+
+/// Known plaintext fragment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crib {
+    /// Known plaintext fragment.
+    pub text: String,
+    /// Position of fragment in plaintext, in characters. None if position is unknown.
+    pub offset: Option<usize>
+}
+
+impl Crib {
+    /// Create a new crib.
+    ///
+    /// # Parameters:
+    /// * text: Known plaintext fragment.
+    /// * offset: Position of fragment in plaintext. None if position is unknown.
+    ///
+    /// # Returns:
+    /// * A new crib.
+    pub fn new<T>(text: T, offset: Option<usize>) -> Self
+        where T: AsRef<str> {
+        Crib {
+            text: text.as_ref().to_string(),
+            offset
+        }
+    }
+
+    /// Get every offset where crib could be in a text with given length.
+    fn get_offsets(&self, text_length: usize) -> Vec<usize> {
+        let crib_length = self.text.chars().count();
+        match self.offset {
+            Some(offset) if offset + crib_length <= text_length => vec![offset],
+            Some(_) => Vec::new(),
+            None if crib_length <= text_length => (0..=text_length - crib_length).collect(),
+            None => Vec::new()
+        }
+    }
+
+    /// Get pairs of crib characters and ciphertext characters at given offset.
+    ///
+    /// Characters out of charset are left as they are by every cipher that keeps
+    /// text length, so if any of those differs between crib and ciphertext crib can't
+    /// be at this offset and None is returned.
+    ///
+    /// # Parameters:
+    /// * ciphered_chars: Ciphertext characters.
+    /// * offset: Position of crib in ciphertext.
+    /// * charset: Charset used to cipher.
+    /// * ignore_case: Whether characters are lowercased before comparing them.
+    ///
+    /// # Returns:
+    /// * A list of tuples with charset index of crib and ciphertext characters.
+    fn get_charset_pairs(&self, ciphered_chars: &[char], offset: usize, charset: &[char],
+                         ignore_case: bool) -> Option<Vec<(usize, usize)>> {
+        let mut pairs = Vec::new();
+        for (crib_char, ciphered_char) in self.text.chars().zip(ciphered_chars[offset..].iter()) {
+            let (crib_char, ciphered_char) = if ignore_case {
+                (lowercase(crib_char), lowercase(*ciphered_char))
+            } else {
+                (crib_char, *ciphered_char)
+            };
+            match (charset.iter().position(|&x| x == crib_char), charset.iter().position(|&x| x == ciphered_char)) {
+                (Some(crib_index), Some(ciphered_index)) => pairs.push((crib_index, ciphered_index)),
+                (None, None) if crib_char == ciphered_char => continue,
+                _ => return None
+            }
+        }
+        Some(pairs)
+    }
+}
+
+fn lowercase(char: char) -> char {
+    char.to_lowercase().next().unwrap_or(char)
+}
+
+/// Get every Caesar key consistent with given crib.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * crib: Known plaintext fragment.
+/// * charset: Charset used for Caesar method substitution.
+///
+/// # Returns:
+/// * Every key that deciphers crib at its offset, or at any offset if crib has none.
+pub fn caesar_keys(ciphered_text: &str, crib: &Crib, charset: &str) -> Result<Vec<usize>> {
+    let ciphered_chars: Vec<char> = ciphered_text.chars().collect();
+    let charset_chars: Vec<char> = charset.chars().collect();
+    let charset_length = to_isize(charset_chars.len())?;
+    let mut keys = Vec::new();
+    for offset in crib.get_offsets(ciphered_chars.len()) {
+        if let Some(pairs) = crib.get_charset_pairs(&ciphered_chars, offset, &charset_chars, false) {
+            let shifts: HashSet<isize> = pairs.iter()
+                .map(|&(plain_index, ciphered_index)| modulus(ciphered_index as isize - plain_index as isize, charset_length))
+                .collect();
+            if shifts.len() == 1 {
+                push_unique(&mut keys, *shifts.iter().next().unwrap() as usize);
+            }
+        }
+    }
+    Ok(keys)
+}
+
+/// Get every Affine key consistent with given crib.
+///
+/// Two crib letters are enough to solve multiplying key with a modular inverse, then
+/// adding key comes directly. Rest of crib is used to check that solution.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * crib: Known plaintext fragment.
+/// * charset: Charset used for Affine method substitution.
+///
+/// # Returns:
+/// * Every key, as a (multiplying key, adding key) tuple, that deciphers crib at its
+///     offset, or at any offset if crib has none.
+pub fn affine_keys(ciphered_text: &str, crib: &Crib, charset: &str) -> Result<Vec<(usize, usize)>> {
+    let ciphered_chars: Vec<char> = ciphered_text.chars().collect();
+    let charset_chars: Vec<char> = charset.chars().collect();
+    let charset_length = to_isize(charset_chars.len())?;
+    let mut keys = Vec::new();
+    for offset in crib.get_offsets(ciphered_chars.len()) {
+        let pairs = match crib.get_charset_pairs(&ciphered_chars, offset, &charset_chars, false) {
+            Some(pairs) if !pairs.is_empty() => pairs,
+            _ => continue
+        };
+        let (first_plain, first_ciphered) = (pairs[0].0 as isize, pairs[0].1 as isize);
+        // Ciphering is c = a*p + b, so c1 - c2 = a*(p1 - p2) and a can be solved if
+        // p1 - p2 has a modular inverse. Otherwise every multiplying key is tried.
+        let solved_multiplying_key = pairs.iter()
+            .filter_map(|&(plain, ciphered)| {
+                let plain_difference = modulus(plain as isize - first_plain, charset_length);
+                let ciphered_difference = modulus(ciphered as isize - first_ciphered, charset_length);
+                find_mod_inverse(plain_difference, charset_length)
+                    .map(|inverse| modulus(ciphered_difference * inverse, charset_length))
+            })
+            .next();
+        let multiplying_keys: Vec<isize> = match solved_multiplying_key {
+            Some(multiplying_key) => vec![multiplying_key],
+            None => (1..charset_length).collect()
+        };
+        for multiplying_key in multiplying_keys {
+            if gcd(multiplying_key, charset_length) != 1 {
+                continue;
+            }
+            let adding_key = modulus(first_ciphered - multiplying_key * first_plain, charset_length);
+            let consistent = pairs.iter()
+                .all(|&(plain, ciphered)| modulus(multiplying_key * plain as isize + adding_key, charset_length) == ciphered as isize);
+            if consistent {
+                push_unique(&mut keys, (multiplying_key as usize, adding_key as usize));
+            }
+        }
+    }
+    Ok(keys)
+}
+
+/// Get every Vigenere key consistent with given crib.
+///
+/// Every crib letter gives the shift, and so the key letter, used at its position.
+/// Key repeats, so crib must span at least two key lengths to find out key length
+/// and to get key from shifts.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * crib: Known plaintext fragment.
+/// * charset: Charset used for Vigenere method.
+///
+/// # Returns:
+/// * Every key that deciphers crib at its offset, or at any offset if crib has none.
+pub fn vigenere_keys(ciphered_text: &str, crib: &Crib, charset: &str) -> Result<Vec<String>> {
+    let ciphered_chars: Vec<char> = ciphered_text.chars().collect();
+    let charset_chars: Vec<char> = charset.chars().collect();
+    let charset_length = to_isize(charset_chars.len())?;
+    let mut keys = Vec::new();
+    for offset in crib.get_offsets(ciphered_chars.len()) {
+        let pairs = match crib.get_charset_pairs(&ciphered_chars, offset, &charset_chars, true) {
+            Some(pairs) if !pairs.is_empty() => pairs,
+            _ => continue
+        };
+        // Vigenere only advances key with charset letters.
+        let key_start = ciphered_chars[..offset].iter()
+            .filter(|&&char| charset_chars.contains(&lowercase(char)))
+            .count();
+        let shifts: Vec<usize> = pairs.iter()
+            .map(|&(plain_index, ciphered_index)| modulus(ciphered_index as isize - plain_index as isize, charset_length) as usize)
+            .collect();
+        let key_length = (1..=shifts.len())
+            .find(|&length| (length..shifts.len()).all(|i| shifts[i] == shifts[i - length]))
+            .unwrap();
+        if 2 * key_length > shifts.len() {
+            continue;
+        }
+        let mut key: Vec<char> = vec![charset_chars[0]; key_length];
+        shifts[..key_length].iter().enumerate()
+            .for_each(|(i, &shift)| key[(key_start + i) % key_length] = charset_chars[shift]);
+        push_unique(&mut keys, key.iter().collect());
+    }
+    Ok(keys)
+}
+
+/// Get every Transposition key consistent with given crib.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * crib: Known plaintext fragment.
+///
+/// # Returns:
+/// * Every key whose deciphered text has crib at its offset, or anywhere if crib has none.
+pub fn transposition_keys(ciphered_text: &str, crib: &Crib) -> Result<Vec<usize>> {
+    let mut keys = Vec::new();
+    for key in 1..ciphered_text.len() {
+        let deciphered_text = transposition::decipher(ciphered_text, key)?;
+        let crib_found = match crib.offset {
+            Some(offset) => deciphered_text.chars().skip(offset).collect::<String>().starts_with(&crib.text),
+            None => deciphered_text.contains(&crib.text)
+        };
+        if crib_found {
+            keys.push(key);
+        }
+    }
+    Ok(keys)
+}
+
+/// Get every substitution partial key consistent with given crib.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * crib: Known plaintext fragment.
+/// * charset: Charset used for substitution method.
+///
+/// # Returns:
+/// * A list of partial keys. Every partial key is a HashMap whose keys are cipherletters
+///     and values are their plaintext letters.
+pub fn substitution_partial_keys(ciphered_text: &str, crib: &Crib, charset: &str) -> Result<Vec<HashMap<char, char>>> {
+    let ciphered_chars: Vec<char> = ciphered_text.chars().collect();
+    let charset_chars: Vec<char> = charset.chars().collect();
+    let mut partial_keys = Vec::new();
+    for offset in crib.get_offsets(ciphered_chars.len()) {
+        let pairs = match crib.get_charset_pairs(&ciphered_chars, offset, &charset_chars, true) {
+            Some(pairs) if !pairs.is_empty() => pairs,
+            _ => continue
+        };
+        let mut partial_key: HashMap<char, char> = HashMap::new();
+        let mut used_plain_letters: HashMap<char, char> = HashMap::new();
+        let consistent = pairs.iter().all(|&(plain_index, ciphered_index)| {
+            let (plain_letter, cipherletter) = (charset_chars[plain_index], charset_chars[ciphered_index]);
+            *partial_key.entry(cipherletter).or_insert(plain_letter) == plain_letter &&
+                *used_plain_letters.entry(plain_letter).or_insert(cipherletter) == cipherletter
+        });
+        if consistent {
+            push_unique(&mut partial_keys, partial_key);
+        }
+    }
+    Ok(partial_keys)
+}
+
+/// Select the key candidate whose deciphered text looks the most like any of our languages.
+///
+/// # Parameters:
+/// * candidates: Key candidates, usually got from a crib.
+/// * decipher: Function to decipher text with a key candidate.
+/// * store: Dictionaries to identify deciphered texts with.
+///
+/// # Returns:
+/// * Best key candidate, or an error if there are no candidates.
+pub fn get_best_candidate<K, F>(candidates: &[K], decipher: F, store: &dyn DictionaryStore) -> Result<K>
+    where K: Clone,
+          F: Fn(&K) -> Result<String> {
+    if candidates.len() == 1 {
+        return Ok(candidates[0].clone());
+    }
+    let store = CachedStore::new(store);
+    let mut best_candidate = None;
+    let mut best_probability = -1.0;
+    for candidate in candidates {
+        let identified_language = identify_language(decipher(candidate)?, &store)?;
+        let probability = identified_language.winner_probability.unwrap_or(0.0);
+        if probability > best_probability {
+            best_candidate = Some(candidate.clone());
+            best_probability = probability;
+        }
+    }
+    match best_candidate {
+        Some(candidate) => Ok(candidate),
+        None => bail!(ErrorKind::KeyError(String::new(), "No key is consistent with given crib.".to_string()))
+    }
+}
+
+fn push_unique<T>(list: &mut Vec<T>, item: T)
+    where T: PartialEq {
+    if !list.contains(&item) {
+        list.push(item);
+    }
+}
+
+fn to_isize(value: usize) -> Result<isize> {
+    value.try_into()
+        .chain_err(|| ErrorKind::ConversionError("charset_length", "usize", "isize"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::cipher::{affine, caesar, substitution, vigenere};
+    use crate::cipher::common::DEFAULT_CHARSET;
+
+    const ORIGINAL_MESSAGE: &'static str = "Dear John, common sense is not so common. Regards.";
+
+    #[test]
+    fn test_caesar_keys() {
+        let ciphered_text = caesar::cipher(ORIGINAL_MESSAGE, 13, DEFAULT_CHARSET).unwrap();
+        assert_eq!(caesar_keys(&ciphered_text, &Crib::new("Dear", Some(0)), DEFAULT_CHARSET).unwrap(), vec![13]);
+        assert_eq!(caesar_keys(&ciphered_text, &Crib::new("Dear", Some(1)), DEFAULT_CHARSET).unwrap(), Vec::<usize>::new());
+        assert_eq!(caesar_keys(&ciphered_text, &Crib::new("Regards", None), DEFAULT_CHARSET).unwrap(), vec![13]);
+    }
+
+    #[test]
+    fn test_affine_keys() {
+        let ciphered_text = affine::cipher_with_key_parts(ORIGINAL_MESSAGE, (43, 56), DEFAULT_CHARSET).unwrap();
+        assert_eq!(affine_keys(&ciphered_text, &Crib::new("Dear", Some(0)), DEFAULT_CHARSET).unwrap(), vec![(43, 56)]);
+        assert_eq!(affine_keys(&ciphered_text, &Crib::new("common sense", None), DEFAULT_CHARSET).unwrap(), vec![(43, 56)]);
+    }
+
+    #[test]
+    fn test_vigenere_keys() {
+        let ciphered_text = vigenere::cipher(ORIGINAL_MESSAGE, "pizza", vigenere::DEFAULT_CHARSET).unwrap();
+        assert_eq!(vigenere_keys(&ciphered_text, &Crib::new("common sense is not", Some(11)), vigenere::DEFAULT_CHARSET).unwrap(),
+                   vec!["pizza".to_string()]);
+        assert_eq!(vigenere_keys(&ciphered_text, &Crib::new("common sense is not", None), vigenere::DEFAULT_CHARSET).unwrap(),
+                   vec!["pizza".to_string()]);
+        // Crib too short to find out key length.
+        assert!(vigenere_keys(&ciphered_text, &Crib::new("Dear", Some(0)), vigenere::DEFAULT_CHARSET).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_transposition_keys() {
+        let ciphered_text = transposition::cipher(ORIGINAL_MESSAGE, 8);
+        let keys = transposition_keys(&ciphered_text, &Crib::new("common sense", Some(11))).unwrap();
+        assert_eq!(keys, vec![8]);
+        let keys = transposition_keys(&ciphered_text, &Crib::new("Regards", None)).unwrap();
+        assert!(keys.contains(&8));
+    }
+
+    #[test]
+    fn test_substitution_partial_keys() {
+        let key = "lfwoayuisvkmnxpbdcrjtqeghz";
+        let ciphered_text = substitution::cipher(ORIGINAL_MESSAGE, key, substitution::DEFAULT_CHARSET).unwrap();
+        let partial_keys = substitution_partial_keys(&ciphered_text, &Crib::new("Dear", Some(0)), substitution::DEFAULT_CHARSET).unwrap();
+        let expected_partial_key: HashMap<char, char> = vec![('o', 'd'), ('a', 'e'), ('l', 'a'), ('c', 'r')].into_iter().collect();
+        assert_eq!(partial_keys, vec![expected_partial_key]);
+        // " common " letter pattern only fits at its real position.
+        let partial_keys = substitution_partial_keys(&ciphered_text, &Crib::new(" common ", None), substitution::DEFAULT_CHARSET).unwrap();
+        assert_eq!(partial_keys.len(), 1);
+        assert_eq!(partial_keys[0].get(&'w'), Some(&'c'));
+    }
+}
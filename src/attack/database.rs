@@ -1,7 +1,10 @@
 /// Cifra database definition.
+use diesel::connection::{SimpleConnection, TransactionManager};
+use diesel::dsl::sql;
 use diesel::prelude::*;
+use diesel::sql_types::Bool;
 use diesel::sqlite::SqliteConnection;
-use dotenv::dotenv;
+use diesel_migrations::{MigrationConnection, setup_database};
 use std::env;
 use std::ffi::{OsStr, OsString};
 use std::fs;
@@ -10,6 +13,7 @@ use std::path::{Path, PathBuf};
 
 use crate::{ErrorKind, Result, ResultExt, Error};
 use crate::schema::languages;
+use crate::schema::letter_frequencies;
 // use crate::schema::languages::dsl::*;
 use crate::schema::words;
 // use crate::schema::words::dsl::*;
@@ -17,6 +21,15 @@ use crate::schema::words;
 
 embed_migrations!("migrations/");
 
+/// Version of the latest migration at *migrations* folder. It is the schema version this cifra
+/// release works with, so databases with an older one are upgraded when opened.
+pub const SCHEMA_VERSION: &'static str = "20261018182256";
+
+/// Milliseconds a connection waits for a locked database before failing. Multiprocessing
+/// attacks open many connections to the same database at once, so they may find it locked
+/// while another of them upgrades its schema.
+const DATABASE_BUSY_TIMEOUT: u32 = 30000;
+
 pub type DatabaseSession = SqliteConnection;
 
 pub const DATABASE_ENV_VAR: &'static str = "DATABASE_URL";
@@ -58,29 +71,41 @@ impl From<DATABASE_STANDARD_PATH> for OsString {
     }
 }
 
-/// Check if DATABASE_URL environment variable actually exists and create it if not.
+/// Get path to database cifra should use by default.
 ///
-/// At tests a .env file is used to shadow default DATABASE_URL var. But at production
-/// that environment variable must be set to let cifra find its database. If this
-/// function does not find DATABASE_URL then it creates that var and populates it
-/// with default value stored at *DATABASE_STANDARD_PATH*, but notifies that situations
-/// returning a VarError.
+/// At tests a temporal environment variable is used to shadow default DATABASE_URL var.
+/// If that var is not set then database is placed at *DATABASE_STANDARD_PATH*. That var
+/// is only read here; loading it from a .env file is up to cifra command.
 ///
 /// # Returns:
-/// * Environment value if DEFAULT_URL exists and a VarError if not.
-fn check_database_url_env_var_exists()-> Result<String>{
-    return env::var(DATABASE_ENV_VAR)
-        .chain_err (|| {
-            ErrorKind::DatabaseError(String::from("Error finding out if database env var existed previously."))
-        })
+/// * Path to default database.
+pub fn get_default_database_path()-> PathBuf {
+    match env::var(DATABASE_ENV_VAR) {
+        Ok(database_path) => PathBuf::from(database_path),
+        Err(_) => PathBuf::from(DATABASE_STANDARD_PATH)
+    }
 }
 
 /// Create and populate database with its default tables.
 pub fn create_database()-> Result<Database> {
-    let database = Database::new()?;
-    embedded_migrations::run(&database.session)
-        .chain_err(|| ErrorKind::DatabaseError(String::from("Error running database migrations.")))?;
-    Ok(database)
+    Database::open(get_default_database_path())
+}
+
+/// Get where a database is copied before upgrading its schema.
+///
+/// Backup is placed beside database, with the schema version it had appended to its name.
+///
+/// # Parameters:
+/// * database_path: Path to database file.
+/// * schema_version: Database schema version before upgrading it, if it had any.
+///
+/// # Returns:
+/// * Path to backup file.
+fn get_backup_path(database_path: &Path, schema_version: Option<&str>) -> PathBuf {
+    let mut backup_name = database_path.file_name()
+        .map_or_else(OsString::new, OsStr::to_os_string);
+    backup_name.push(format!(".{}.backup", schema_version.unwrap_or("unversioned")));
+    database_path.with_file_name(backup_name)
 }
 
 /// Take a path and create all folders that don't actually exists yet.
@@ -95,52 +120,151 @@ fn create_folder_path(path: &Path) -> Result<()>{
 
 pub struct Database {
     pub session: DatabaseSession,
-    database_path: String
+    database_path: PathBuf
 }
 
 impl Database {
 
-    /// Create a new Database type.
+    /// Create a new Database type at default database path.
     ///
-    /// At creation it checks if DATABASE_URL environment variable actually exists and
-    /// create it if not.
+    /// Default path is the one at DATABASE_URL environment variable or, if it is not
+    /// set, *DATABASE_STANDARD_PATH*. That variable is only read, never set, so many
+    /// databases can be used side by side using *Database::open* instead.
+    pub fn new() -> Result<Self> {
+        Self::open(get_default_database_path())
+    }
+
+    /// Open database at given path.
     ///
-    /// At tests a .env file is used to shadow default DATABASE_URL var. But at production
-    /// that environment variable must be set to let cifra find its database. If this
-    /// function does not find DATABASE_URL then it creates that var and populates it
-    /// with default value stored at *DATABASE_STANDARD_PATH*
+    /// If database does not exist yet it is created, along with its folder. If it exists
+    /// but its schema is older than *SCHEMA_VERSION*, it is upgraded after making a backup
+    /// copy of its file.
     ///
-    /// If DATABASE_URL was not set that is a signal about database does not exist yet so
-    /// its created too.
-    pub fn new() -> Result<Self> {
-        if let Ok(database_path) = check_database_url_env_var_exists() {
-            // Database already exists.
-            Ok(Database {
-                session: Self::open_session()?,
-                database_path
-            })
+    /// # Parameters:
+    /// * database_path: Path to database file.
+    pub fn open<T>(database_path: T) -> Result<Self>
+        where T: AsRef<Path> {
+        let database_path = database_path.as_ref().to_path_buf();
+        let already_exists = database_path.exists();
+        if !already_exists {
+            if let Some(database_folder) = database_path.parent() {
+                if !database_folder.as_os_str().is_empty() {
+                    create_folder_path(database_folder)
+                        .chain_err(|| ErrorKind::FolderError(database_folder.to_string_lossy().to_string()))?;
+                }
+            }
+        }
+        let database = Database {
+            session: Self::open_session(&database_path)?,
+            database_path
+        };
+        if already_exists {
+            database.upgrade_schema()?;
         } else {
-            // Database does not exists yet. So we must create it.
-            let database_path: OsString = DATABASE_STANDARD_PATH.into();
-            env::set_var(DATABASE_ENV_VAR, database_path.as_os_str());
-            let database_path = PathBuf::from(DATABASE_STANDARD_PATH);
-            let database_folder = database_path.parent()
-                .chain_err(|| ErrorKind::FolderError(String::from(database_path.as_os_str().to_str().unwrap())))?;
-            create_folder_path(database_folder);
-            create_database()
+            embedded_migrations::run(&database.session)
+                .chain_err(|| ErrorKind::DatabaseError(String::from("Error running database migrations.")))?;
         }
+        Ok(database)
+    }
+
+    /// Get version of the latest migration applied to this database.
+    ///
+    /// Database is only read, so its schema is left as it was.
+    ///
+    /// # Returns:
+    /// * Schema version, or None if no migration has been applied yet.
+    pub fn schema_version(&self) -> Result<Option<String>> {
+        // Databases not created by cifra have no table to keep applied migrations yet.
+        let has_migrations_table = diesel::select(sql::<Bool>(
+            "EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '__diesel_schema_migrations')"))
+            .get_result::<bool>(&self.session)
+            .chain_err(|| ErrorKind::DatabaseError(String::from("Error looking for schema versions table.")))?;
+        if !has_migrations_table {
+            return Ok(None);
+        }
+        self.session.latest_run_migration_version()
+            .chain_err(|| ErrorKind::DatabaseError(String::from("Error getting database schema version.")))
+    }
+
+    /// Apply every migration this database lacks.
+    ///
+    /// Database file is copied before changing anything, so it can be recovered if upgrade
+    /// goes wrong.
+    ///
+    /// # Returns:
+    /// * Path to backup copy, or None if schema was already up to date.
+    fn upgrade_schema(&self) -> Result<Option<PathBuf>> {
+        if self.schema_version()?.as_deref() == Some(SCHEMA_VERSION) {
+            return Ok(None);
+        }
+        // An exclusive transaction keeps any other connection, from this process or another
+        // one, away from database until its schema is upgraded.
+        let transaction_manager = self.session.transaction_manager();
+        transaction_manager.begin_transaction_sql(&self.session, "BEGIN EXCLUSIVE")
+            .chain_err(|| ErrorKind::DatabaseError(String::from("Error locking database to upgrade its schema.")))?;
+        match self.run_pending_migrations() {
+            Ok(backup_path) => {
+                transaction_manager.commit_transaction(&self.session)
+                    .chain_err(|| ErrorKind::DatabaseError(String::from("Error committing schema upgrade.")))?;
+                Ok(backup_path)
+            },
+            Err(error) => {
+                // Upgrade error is more useful than any error rolling back.
+                transaction_manager.rollback_transaction(&self.session).ok();
+                Err(error)
+            }
+        }
+    }
+
+    /// Back database up and apply every migration it lacks.
+    ///
+    /// It must be called inside an exclusive transaction.
+    ///
+    /// # Returns:
+    /// * Path to backup copy, or None if schema was already up to date.
+    fn run_pending_migrations(&self) -> Result<Option<PathBuf>> {
+        // Another connection may have upgraded schema while we were waiting for the lock.
+        let current_version = self.schema_version()?;
+        match current_version.as_deref() {
+            Some(version) if version == SCHEMA_VERSION => return Ok(None),
+            Some(version) if version > SCHEMA_VERSION => bail!(ErrorKind::DatabaseError(
+                format!("Database schema version {} is newer than {}, the one this cifra release works with.",
+                        version, SCHEMA_VERSION))),
+            _ => {}
+        }
+        let backup_path = get_backup_path(&self.database_path, current_version.as_deref());
+        fs::copy(&self.database_path, &backup_path)
+            .chain_err(|| ErrorKind::DatabaseError(format!("Error backing database up to {}.", backup_path.display())))?;
+        setup_database(&self.session)
+            .chain_err(|| ErrorKind::DatabaseError(String::from("Error creating schema versions table.")))?;
+        embedded_migrations::run(&self.session)
+            .chain_err(|| ErrorKind::DatabaseError(String::from("Error running database migrations.")))?;
+        Ok(Some(backup_path))
+    }
+
+    /// Get path to this database file.
+    pub fn path(&self) -> &Path {
+        &self.database_path
     }
 
-    /// Connect to current dictionaries database.
+    /// Connect to given dictionaries database.
+    ///
+    /// # Parameters:
+    /// * database_path: Path to database file.
     ///
     /// Returns:
     /// A connection to underlying database.
-    fn open_session() -> Result<DatabaseSession> {
-        dotenv().ok();
-        let database_url = env::var("DATABASE_URL")
-            .chain_err(|| ErrorKind::DatabaseError(String::from("DATABASE_URL must be set")))?;
-        SqliteConnection::establish(&database_url)
-            .chain_err(|| ErrorKind::DatabaseError(format!("Error connecting to DATABASE_URL: {}", database_url)))
+    fn open_session(database_path: &Path) -> Result<DatabaseSession> {
+        let database_url = database_path.to_string_lossy();
+        let session = SqliteConnection::establish(&database_url)
+            .chain_err(|| ErrorKind::DatabaseError(format!("Error connecting to database: {}", database_url)))?;
+        // SQLite ignores foreign keys unless told otherwise, so removed languages would leave
+        // their words behind.
+        session.execute("PRAGMA foreign_keys = ON;")
+            .chain_err(|| ErrorKind::DatabaseError(String::from("Error enabling foreign keys.")))?;
+        session.batch_execute(&format!("PRAGMA busy_timeout = {};", DATABASE_BUSY_TIMEOUT))
+            .chain_err(|| ErrorKind::DatabaseError(String::from("Error setting database busy timeout.")))?;
+        Ok(session)
     }
 }
 
@@ -168,7 +292,8 @@ pub struct Word {
     pub id: i32,
     pub word: String,
     pub word_pattern: String,
-    pub language_id: i32
+    pub language_id: i32,
+    pub frequency: i32
 }
 
 #[derive(Insertable)]
@@ -176,12 +301,26 @@ pub struct Word {
 pub struct NewWord<'a> {
     pub word: &'a str,
     pub word_pattern: String,
-    pub language_id: i32
+    pub language_id: i32,
+    pub frequency: i32
+}
+
+/// Model to insert rows at Letter_frequencies database table.
+///
+/// Every row keeps how many times a letter, or a bigram of letters inside a word, has been
+/// seen in a language.
+#[derive(Insertable)]
+#[table_name="letter_frequencies"]
+pub struct NewLetterFrequency<'a> {
+    pub ngram: &'a str,
+    pub language_id: i32,
+    pub occurrences: i64
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use diesel::connection::SimpleConnection;
     use std::ffi::OsStr;
     use std::path::Path;
     use test_common::fs::tmp::TestEnvironment;
@@ -214,6 +353,125 @@ mod tests {
         assert!(database_folder.exists());
     }
 
+    #[test]
+    fn test_open_database_at_path() {
+        let test_folder = TestEnvironment::new();
+        let first_database_path = test_folder.path().join("first/cifra_database.sqlite");
+        let second_database_path = test_folder.path().join("second/cifra_database.sqlite");
+        let first_database = Database::open(&first_database_path).unwrap();
+        let second_database = Database::open(&second_database_path).unwrap();
+        assert!(first_database_path.exists());
+        assert!(second_database_path.exists());
+        assert_eq!(first_database.path(), first_database_path.as_path());
+        assert_eq!(second_database.path(), second_database_path.as_path());
+        diesel::insert_into(languages::table)
+            .values(&NewLanguage { language: "klingon" })
+            .execute(&first_database.session)
+            .unwrap();
+        let first_languages = languages::table.select(languages::language)
+            .load::<String>(&first_database.session).unwrap();
+        let second_languages = languages::table.select(languages::language)
+            .load::<String>(&second_database.session).unwrap();
+        assert_eq!(first_languages, vec![String::from("klingon")]);
+        assert!(second_languages.is_empty());
+    }
+
+    #[test]
+    fn test_schema_version_is_latest_migration() {
+        let latest_migration = fs::read_dir("migrations").unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().to_str()
+                .and_then(|migration_name| migration_name.split('_').next())
+                .map(|migration_date| migration_date.replace('-', "")))
+            .max();
+        assert_eq!(Some(SCHEMA_VERSION.to_string()), latest_migration);
+    }
+
+    #[test]
+    fn test_open_upgrades_old_database() {
+        let test_folder = TestEnvironment::new();
+        let database_path = test_folder.path().join("cifra_database.sqlite");
+        let old_version = "20261018174641";
+        {
+            let database = Database::open(&database_path).unwrap();
+            assert_eq!(Some(SCHEMA_VERSION.to_string()), database.schema_version().unwrap());
+            // Take database back to the schema it had before letter frequencies were stored.
+            database.session.batch_execute(&format!("
+                DROP TABLE letter_frequencies;
+                DELETE FROM __diesel_schema_migrations WHERE version > '{}';
+                INSERT INTO languages(id, language) VALUES (1, 'english');
+                INSERT INTO words(word, word_pattern, language_id, frequency) VALUES ('tea', '0.1.2', 1, 2);",
+                old_version)).unwrap();
+            assert_eq!(Some(old_version.to_string()), database.schema_version().unwrap());
+        }
+        let database = Database::open(&database_path).unwrap();
+        assert_eq!(Some(SCHEMA_VERSION.to_string()), database.schema_version().unwrap());
+        assert!(get_backup_path(&database_path, Some(old_version)).exists());
+        // Words stored before upgrading get their letters counted.
+        let letter_occurrences = letter_frequencies::table
+            .filter(letter_frequencies::ngram.eq("t"))
+            .select(letter_frequencies::occurrences)
+            .first::<i64>(&database.session).unwrap();
+        assert_eq!(2, letter_occurrences);
+        // Opening an up to date database does not back it up again.
+        fs::remove_file(get_backup_path(&database_path, Some(old_version))).unwrap();
+        Database::open(&database_path).unwrap();
+        assert!(!get_backup_path(&database_path, Some(old_version)).exists());
+    }
+
+    #[test]
+    fn test_schema_version_does_not_write() {
+        let test_folder = TestEnvironment::new();
+        let database_path = test_folder.path().join("cifra_database.sqlite");
+        {
+            // A database not created by cifra.
+            let database = Database {
+                session: Database::open_session(&database_path).unwrap(),
+                database_path: database_path.clone()
+            };
+            database.session.batch_execute("CREATE TABLE notes(note TEXT);").unwrap();
+            assert_eq!(None, database.schema_version().unwrap());
+            let tables = diesel::select(sql::<Bool>("EXISTS (SELECT 1 FROM sqlite_master WHERE name != 'notes')"))
+                .get_result::<bool>(&database.session).unwrap();
+            assert!(!tables, "Schema versions table was created.");
+        }
+        let database = Database::open(&database_path).unwrap();
+        assert_eq!(Some(SCHEMA_VERSION.to_string()), database.schema_version().unwrap());
+        assert!(get_backup_path(&database_path, None).exists());
+    }
+
+    #[test]
+    fn test_concurrent_opens_upgrade_once() {
+        let test_folder = TestEnvironment::new();
+        let database_path = test_folder.path().join("cifra_database.sqlite");
+        let old_version = "20261018174641";
+        Database::open(&database_path).unwrap().session.batch_execute(&format!("
+            DROP TABLE letter_frequencies;
+            DELETE FROM __diesel_schema_migrations WHERE version > '{}';", old_version)).unwrap();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let database_path = database_path.clone();
+                std::thread::spawn(move || Database::open(&database_path)
+                    .and_then(|database| database.schema_version()))
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(Some(SCHEMA_VERSION.to_string()), handle.join().unwrap().unwrap());
+        }
+        assert!(get_backup_path(&database_path, Some(old_version)).exists());
+    }
+
+    #[test]
+    fn test_open_newer_database_fails() {
+        let test_folder = TestEnvironment::new();
+        let database_path = test_folder.path().join("cifra_database.sqlite");
+        Database::open(&database_path).unwrap().session
+            .batch_execute("INSERT INTO __diesel_schema_migrations(version) VALUES ('99991231000000');").unwrap();
+        assert!(Database::open(&database_path).is_err());
+        assert!(!get_backup_path(&database_path, Some("99991231000000")).exists());
+    }
+
     #[test]
     fn test_database_standard_path() {
         let mut expected_database_folder = dirs::home_dir().unwrap();
@@ -2,7 +2,8 @@
 ///
 /// A dictionary is a repository of distinct words present in an actual language.
 use std::collections::{HashSet, HashMap};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 // use std::error::Error;
 use std::fs::File;
 // use std::fmt;
@@ -10,19 +11,39 @@ use std::fs::File;
 use diesel::RunQueryDsl;
 use diesel::prelude::*;
 
-use crate::attack::database::{Database, DatabaseSession, NewLanguage, NewWord};
-use crate::cipher::common::normalize_text;
+use crate::attack::database::{Database, DatabaseSession, NewLanguage, NewLetterFrequency, NewWord};
+use crate::attack::segmentation::{MIN_COVERED_WORD_LENGTH, Segmenter, is_spaceless};
+use crate::cipher::common::{normalize_text, Counter, WORDS_REGEX};
+use crate::cipher::normalization::{Normalization, normalize_unicode};
 use crate::{Result, ErrorKind, ResultExt};
 // use crate::schema::*;
 use crate::schema::languages;
 use crate::schema::languages::dsl::*;
+use crate::schema::letter_frequencies;
 use crate::schema::words;
 use crate::schema::words::dsl::*;
 // use diesel::result::Error::DatabaseError;
-// use regex::Regex;
-use std::io::Read;
+use regex::Regex;
+use std::io::{BufRead, BufReader, Read};
 use std::iter::FromIterator;
 
+/// SQLite limits how many variables a query can have, so words and patterns are looked up
+/// in chunks of this size.
+const VALUES_PER_QUERY: usize = 500;
+/// Words are stored in transactions of this size, so a big corpus neither needs a transaction
+/// per word nor a single huge one.
+const WORDS_PER_TRANSACTION: usize = 10_000;
+
+/// Summary of words added to a dictionary.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PopulationReport {
+    /// Words that were not at dictionary yet.
+    pub new_words: usize,
+    /// Words already at dictionary whose frequencies were updated.
+    pub existing_words: usize,
+    /// Words left out because they were seen less times than the minimum frequency.
+    pub discarded_words: usize
+}
 
 /// Cifra stores word dictionaries in a local database. This class
 /// is a wrapper to not to deal directly with that database.
@@ -46,7 +67,18 @@ impl Dictionary {
     /// * language: Language to remove from database.
     pub fn remove_dictionary<T>(_language: T)-> Result<()>
         where T: AsRef<str> {
-        let database = Database::new()?;
+        Self::remove_dictionary_from(&Database::new()?, _language)
+    }
+
+    /// Remove given language from given database.
+    ///
+    /// Be aware that all its words will be removed too.
+    ///
+    /// # Parameters:
+    /// * database: Database to remove language from.
+    /// * language: Language to remove from database.
+    pub fn remove_dictionary_from<T>(database: &Database, _language: T)-> Result<()>
+        where T: AsRef<str> {
         diesel::delete(languages::table.filter(language.eq(_language.as_ref())))
             .execute(&database.session)
             .chain_err(|| ErrorKind::DatabaseError(String::from("Error deleting language.")))?;
@@ -58,7 +90,17 @@ impl Dictionary {
     /// # Returns:
     /// * A list with names of dictionaries present at database.
     pub fn get_dictionaries_names()-> Result<Vec<String>> {
-        let database = Database::new()?;
+        Self::get_dictionaries_names_from(&Database::new()?)
+    }
+
+    /// Get languages dictionaries present at given database.
+    ///
+    /// # Parameters:
+    /// * database: Database to look languages into.
+    ///
+    /// # Returns:
+    /// * A list with names of dictionaries present at database.
+    pub fn get_dictionaries_names_from(database: &Database)-> Result<Vec<String>> {
         let dictionaries_names = languages::table.select(languages::language)
             .load::<String>(&database.session)
             .chain_err(|| ErrorKind::DatabaseError(String::from("Language list could not be retrieved.")))?;
@@ -72,13 +114,23 @@ impl Dictionary {
     ///    database then a dictionaries.NotExistingLanguage exception is raised, but if it is
     ///    set to True then language is registered in database as a new language.
     pub fn new<T>(_language: T, create: bool)-> Result<Self>
+        where T: AsRef<str> {
+        Self::from_database(Database::new()?, _language, create)
+    }
+
+    /// Open a language dictionary stored at given database.
+    ///
+    /// # Parameters:
+    /// * database: Database where dictionary is stored.
+    /// * language: Language you want to manage its words.
+    /// * create: Whether this language should be created in database if not present yet.
+    pub fn from_database<T>(database: Database, _language: T, create: bool)-> Result<Self>
         where T: AsRef<str> {
         let new_language = _language.as_ref().to_string();
-        let current_database = Database::new()?;
         let mut current_dictionary = Dictionary {
             language: new_language.clone(),
             language_id: 0,
-            database:current_database
+            database
         };
         if current_dictionary.already_created() {
             current_dictionary.language_id = languages::table.filter(language.eq(&current_dictionary.language))
@@ -111,12 +163,16 @@ impl Dictionary {
         let new_word = NewWord {
             word: _word.as_ref(),
             word_pattern: get_word_pattern(_word.as_ref()),
-            language_id: self.language_id
+            language_id: self.language_id,
+            frequency: 1
         };
-        diesel::insert_into(words::table)
-            .values(&new_word)
-            .execute(self.session())
-            .expect("Error saving new word.");
+        let session = self.session();
+        session.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::insert_into(words::table)
+                .values(&new_word)
+                .execute(session)?;
+            self.update_letter_frequencies(&count_letter_ngrams(vec![(_word.as_ref(), 1)]), false)
+        }).expect("Error saving new word.");
     }
 
     /// Add given words to dictionary.
@@ -124,19 +180,135 @@ impl Dictionary {
     /// # Parameters:
     /// * words: Set of words to add to dictionary.
     pub fn add_multiple_words(&mut self, _words: &HashSet<String>){
-        let mut word_list: Vec<NewWord> = Vec::new();
-        _words.iter().map(|new_word| {
-            let word_to_add = NewWord {
+        let word_list: Vec<NewWord> = _words.iter()
+            .map(|new_word| NewWord {
                 word: new_word,
                 word_pattern: get_word_pattern(new_word),
                 language_id: self.language_id,
-            };
-            word_list.push(word_to_add);
-        }).for_each(drop);
-        diesel::insert_into(words::table)
-            .values(&word_list)
-            .execute(self.session())
-            .expect("Error saving new word.");
+                frequency: 1
+            })
+            .collect();
+        let session = self.session();
+        for words_chunk in word_list.chunks(WORDS_PER_TRANSACTION) {
+            session.transaction::<_, diesel::result::Error, _>(|| {
+                diesel::insert_into(words::table)
+                    .values(words_chunk)
+                    .execute(session)?;
+                let chunk_occurrences = words_chunk.iter().map(|new_word| (new_word.word, 1));
+                self.update_letter_frequencies(&count_letter_ngrams(chunk_occurrences), false)
+            }).expect("Error saving new word.");
+        }
+    }
+
+    /// Add given words to dictionary along with how many times they have been seen.
+    ///
+    /// Words already present at dictionary get these occurrences added to their stored ones.
+    /// Words are stored in batches, each one in its own transaction along with their letters
+    /// and bigrams occurrences.
+    ///
+    /// # Parameters:
+    /// * word_counts: Words along with their occurrences.
+    ///
+    /// # Returns:
+    /// * How many words were new and how many were already at dictionary.
+    pub fn add_words_with_frequencies(&mut self, word_counts: &HashMap<String, u64>)-> Result<PopulationReport> {
+        let mut report = PopulationReport::default();
+        let counted_words: Vec<(&String, &u64)> = word_counts.iter().collect();
+        for words_chunk in counted_words.chunks(WORDS_PER_TRANSACTION) {
+            let chunk_words: Vec<&String> = words_chunk.iter().map(|(counted_word, _)| *counted_word).collect();
+            let stored_frequencies = self.get_stored_frequencies(&chunk_words)?;
+            let session = self.session();
+            session.transaction::<_, diesel::result::Error, _>(|| {
+                let mut word_list: Vec<NewWord> = Vec::new();
+                for (new_word, &occurrences) in words_chunk {
+                    match stored_frequencies.get(*new_word) {
+                        Some(&stored_occurrences) => {
+                            diesel::update(words::table.filter(word.eq(new_word).and(language_id.eq(&self.language_id))))
+                                .set(frequency.eq(to_stored_frequency(stored_occurrences + occurrences)))
+                                .execute(session)?;
+                        },
+                        None => word_list.push(NewWord {
+                            word: new_word,
+                            word_pattern: get_word_pattern(new_word),
+                            language_id: self.language_id,
+                            frequency: to_stored_frequency(occurrences)
+                        })
+                    }
+                }
+                diesel::insert_into(words::table)
+                    .values(&word_list)
+                    .execute(session)?;
+                let chunk_occurrences = words_chunk.iter()
+                    .map(|(new_word, &occurrences)| (new_word.as_str(), occurrences));
+                self.update_letter_frequencies(&count_letter_ngrams(chunk_occurrences), false)
+            }).chain_err(|| ErrorKind::DatabaseError(String::from("Error saving words frequencies.")))?;
+            report.existing_words += stored_frequencies.len();
+            report.new_words += words_chunk.len() - stored_frequencies.len();
+        }
+        Ok(report)
+    }
+
+    /// Get stored frequencies of those given words already present at dictionary.
+    ///
+    /// # Parameters:
+    /// * words_to_find: Words to look for.
+    ///
+    /// # Returns:
+    /// * Words found along with their stored frequencies.
+    fn get_stored_frequencies(&self, words_to_find: &[&String])-> Result<HashMap<String, u64>> {
+        let mut stored_frequencies: HashMap<String, u64> = HashMap::new();
+        for words_chunk in words_to_find.chunks(VALUES_PER_QUERY) {
+            let found_words = words::table
+                .filter(language_id.eq(&self.language_id).and(word.eq_any(words_chunk)))
+                .select((word, frequency))
+                .load::<(String, i32)>(self.session())
+                .chain_err(|| ErrorKind::DatabaseError(String::from("Error getting stored words frequencies.")))?;
+            stored_frequencies.extend(found_words.into_iter()
+                .map(|(found_word, found_frequency)| (found_word, found_frequency as u64)));
+        }
+        Ok(stored_frequencies)
+    }
+
+    /// Add or subtract given letters and bigrams occurrences to the ones stored for this language.
+    ///
+    /// It should be called inside the same transaction that changes words, so letter
+    /// frequencies always agree with stored words.
+    ///
+    /// # Parameters:
+    /// * ngram_counts: Letters and bigrams along with their occurrences.
+    /// * removing: Whether occurrences should be subtracted instead of added.
+    fn update_letter_frequencies(&self, ngram_counts: &HashMap<String, u64>, removing: bool)-> QueryResult<()> {
+        let stored_counts: HashMap<String, i64> = letter_frequencies::table
+            .filter(letter_frequencies::language_id.eq(&self.language_id))
+            .select((letter_frequencies::ngram, letter_frequencies::occurrences))
+            .load::<(String, i64)>(self.session())?
+            .into_iter()
+            .collect();
+        let mut updated_counts: Vec<NewLetterFrequency> = Vec::new();
+        let mut emptied_ngrams: Vec<&String> = Vec::new();
+        for (counted_ngram, &count) in ngram_counts.iter().filter(|(_, &count)| count > 0) {
+            let stored_count = stored_counts.get(counted_ngram).cloned().unwrap_or(0);
+            let updated_count = if removing { stored_count - count as i64 } else { stored_count + count as i64 };
+            if updated_count > 0 {
+                updated_counts.push(NewLetterFrequency {
+                    ngram: counted_ngram,
+                    language_id: self.language_id,
+                    occurrences: updated_count
+                });
+            } else {
+                emptied_ngrams.push(counted_ngram);
+            }
+        }
+        diesel::replace_into(letter_frequencies::table)
+            .values(&updated_counts)
+            .execute(self.session())?;
+        for ngrams_chunk in emptied_ngrams.chunks(VALUES_PER_QUERY) {
+            diesel::delete(letter_frequencies::table
+                .filter(letter_frequencies::language_id.eq(&self.language_id)
+                    .and(letter_frequencies::ngram.eq_any(ngrams_chunk))))
+                .execute(self.session())?;
+        }
+        Ok(())
     }
 
     /// Remove given word from dictionary.
@@ -147,9 +319,14 @@ impl Dictionary {
     /// * word: word to remove from dictionary.
     pub fn remove_word<T>(&mut self, _word: T)
         where T: AsRef<str> {
-        diesel::delete(words::table.filter(word.eq(_word.as_ref()).and(language_id.eq(&self.language_id))))
-            .execute(self.session())
-            .expect("Error deleting word");
+        let removed_occurrences = self.get_word_frequency(_word.as_ref())
+            .expect("Error getting removed word frequency");
+        let session = self.session();
+        session.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::delete(words::table.filter(word.eq(_word.as_ref()).and(language_id.eq(&self.language_id))))
+                .execute(session)?;
+            self.update_letter_frequencies(&count_letter_ngrams(vec![(_word.as_ref(), removed_occurrences)]), true)
+        }).expect("Error deleting word");
     }
 
     /// Check if given word exists at this dictionary.
@@ -171,15 +348,104 @@ impl Dictionary {
         }
     }
 
+    /// Get how many times given word has been seen in this language.
+    ///
+    /// # Parameters:
+    /// * word: word to check.
+    ///
+    /// # Returns:
+    /// * Word occurrences, or 0 if word is not present at dictionary.
+    pub fn get_word_frequency<T>(&self, _word: T) -> Result<u64>
+        where T: AsRef<str> {
+        let word_frequency = words::table.filter(word.eq(_word.as_ref()).and(language_id.eq(&self.language_id)))
+            .select(diesel::dsl::sum(frequency))
+            .first::<Option<i64>>(self.session())
+            .chain_err(|| ErrorKind::DatabaseError(format!("Error getting frequency of word {}.", _word.as_ref())))?;
+        Ok(word_frequency.map_or(0, |occurrences| occurrences as u64))
+    }
+
+    /// Get how many word occurrences have been stored for this language.
+    ///
+    /// # Returns:
+    /// * Sum of every word occurrences.
+    pub fn get_total_frequency(&self) -> Result<u64> {
+        let total_frequency = words::table.filter(language_id.eq(&self.language_id))
+            .select(diesel::dsl::sum(frequency))
+            .first::<Option<i64>>(self.session())
+            .chain_err(|| ErrorKind::DatabaseError(String::from("Error getting total words frequency.")))?;
+        Ok(total_frequency.map_or(0, |occurrences| occurrences as u64))
+    }
+
+    /// Get how many times every letter, and every bigram inside words, has been seen in this language.
+    ///
+    /// They are kept up to date whenever words are added or removed, so no word needs to
+    /// be read.
+    ///
+    /// # Returns:
+    /// * Letters and bigrams along with their occurrences.
+    pub fn get_letter_ngram_counts(&self) -> Result<HashMap<String, u64>> {
+        let ngram_counts = letter_frequencies::table
+            .filter(letter_frequencies::language_id.eq(&self.language_id))
+            .select((letter_frequencies::ngram, letter_frequencies::occurrences))
+            .load::<(String, i64)>(self.session())
+            .chain_err(|| ErrorKind::DatabaseError(String::from("Error getting letter frequencies.")))?;
+        Ok(ngram_counts.into_iter()
+            .map(|(counted_ngram, occurrences)| (counted_ngram, occurrences as u64))
+            .collect())
+    }
+
     /// Read a file's words and stores them at this language database.
     ///
+    /// How many times every word appears is stored too, added to any previous count.
     /// # Parameters:
     /// * file_pathname: Absolute path to file with text to analyze.
-    pub fn populate<T>(&mut self, file_pathname: T)-> Result<()>
+    ///
+    /// # Returns:
+    /// * How many words were new and how many were already at dictionary.
+    pub fn populate<T>(&mut self, file_pathname: T)-> Result<PopulationReport>
         where T: AsRef<Path> {
-        let _words = get_words_from_text_file(file_pathname.as_ref())?;
-        self.add_multiple_words(&_words);
-        Ok(())
+        self.populate_with_minimum_frequency(file_pathname, 1)
+    }
+
+    /// Read a file's words and stores those frequent enough at this language database.
+    ///
+    /// File is read line by line, so only its different words are kept in memory. Words seen
+    /// at file less times than given minimum are left out, as they are usually typos.
+    ///
+    /// # Parameters:
+    /// * file_pathname: Absolute path to file with text to analyze.
+    /// * minimum_frequency: How many times a word must appear at file to be stored.
+    ///
+    /// # Returns:
+    /// * How many words were new, how many were already at dictionary and how many were left out.
+    pub fn populate_with_minimum_frequency<T>(&mut self, file_pathname: T, minimum_frequency: u64)-> Result<PopulationReport>
+        where T: AsRef<Path> {
+        self.populate_with_normalization(file_pathname, minimum_frequency, Normalization::default())
+    }
+
+    /// Read a file's words, normalized as requested, and stores those frequent enough at this
+    /// language database.
+    ///
+    /// Use the same normalization later to identify texts or attack ciphertexts, as words
+    /// written differently are different words. For instance, a dictionary populated with
+    /// folded words is needed to attack texts ciphered with a plain latin alphabet.
+    ///
+    /// # Parameters:
+    /// * file_pathname: Absolute path to file with text to analyze.
+    /// * minimum_frequency: How many times a word must appear at file to be stored.
+    /// * normalization: Unicode normalization to apply to file text.
+    ///
+    /// # Returns:
+    /// * How many words were new, how many were already at dictionary and how many were left out.
+    pub fn populate_with_normalization<T>(&mut self, file_pathname: T, minimum_frequency: u64,
+                                          normalization: Normalization)-> Result<PopulationReport>
+        where T: AsRef<Path> {
+        let mut word_counts = get_word_counts_from_text_file(file_pathname.as_ref(), normalization)?;
+        let counted_words = word_counts.len();
+        word_counts.retain(|_, occurrences| *occurrences >= minimum_frequency);
+        let mut report = self.add_words_with_frequencies(&word_counts)?;
+        report.discarded_words = counted_words - word_counts.len();
+        Ok(report)
     }
 
     /// Check if a table for this instance language already exists at database or not.
@@ -218,8 +484,7 @@ impl Dictionary {
     /// * List of words at dictionary with given pattern.
     pub fn get_words_with_pattern<T>(&self, pattern: T) -> Result<Vec<String>>
         where T: AsRef<str> {
-        // words::table.filter(word.eq(_word.as_ref()).and(language_id.eq(&self.language_id)))
-        let words_result = words::table.filter(word_pattern.eq(pattern.as_ref()))
+        let words_result = words::table.filter(word_pattern.eq(pattern.as_ref()).and(language_id.eq(&self.language_id)))
             .select(word)
             .get_results::<String>(self.session());
         match words_result {
@@ -228,6 +493,35 @@ impl Dictionary {
         }
     }
 
+    /// Get words of this language with any of given patterns.
+    ///
+    /// Patterns are looked up in as few queries as possible, instead of one query per pattern.
+    ///
+    /// # Parameters:
+    /// * patterns: Word patterns to look for.
+    ///
+    /// # Returns:
+    /// * A HashMap whose keys are given patterns and values are words with that pattern.
+    ///     Patterns without words are present too, with an empty list.
+    pub fn get_words_with_patterns<T>(&self, patterns: &[T]) -> Result<HashMap<String, Vec<String>>>
+        where T: AsRef<str> {
+        let mut words_by_pattern: HashMap<String, Vec<String>> = patterns.iter()
+            .map(|pattern| (pattern.as_ref().to_string(), Vec::new()))
+            .collect();
+        let unique_patterns: Vec<String> = words_by_pattern.keys().cloned().collect();
+        for patterns_chunk in unique_patterns.chunks(VALUES_PER_QUERY) {
+            let found_words = words::table
+                .filter(language_id.eq(&self.language_id).and(word_pattern.eq_any(patterns_chunk)))
+                .select((word_pattern, word))
+                .load::<(String, String)>(self.session())
+                .chain_err(|| ErrorKind::DatabaseError(String::from("Error getting words with patterns.")))?;
+            for (found_pattern, found_word) in found_words {
+                words_by_pattern.entry(found_pattern).or_insert_with(Vec::new).push(found_word);
+            }
+        }
+        Ok(words_by_pattern)
+    }
+
     /// Get how many words of given set are really present in this dictionary.
     ///
     /// # Parameters:
@@ -244,6 +538,20 @@ impl Dictionary {
         presence
     }
 
+    /// Get every word present at dictionary along with how many times it has been seen.
+    pub fn get_all_word_frequencies(&self) -> Result<HashMap<String, u64>> {
+        let words_result = words::table
+            .filter(language_id.eq(self.language_id))
+            .select((word, frequency))
+            .get_results::<(String, i32)>(self.session())
+            .chain_err(|| ErrorKind::DatabaseError(String::from("Error getting words frequencies.")))?;
+        let mut word_frequencies: HashMap<String, u64> = HashMap::new();
+        for (stored_word, stored_frequency) in words_result {
+            *word_frequencies.entry(stored_word).or_insert(0) += stored_frequency.max(0) as u64;
+        }
+        Ok(word_frequencies)
+    }
+
     /// Get a list of every word present at dictionary.
     pub fn get_all_words(&self) -> Result<Vec<String>> {
         let words_result = words::table
@@ -257,6 +565,488 @@ impl Dictionary {
     }
 }
 
+/// Words of a single language, wherever they are stored.
+///
+/// Attacks only need to look words up, so they work with any type implementing this
+/// trait: database backed *Dictionary* or in memory *MemoryDictionary*.
+pub trait LanguageDictionary {
+    /// Get language these words belong to.
+    fn language(&self) -> &str;
+
+    /// Check if given word exists at this dictionary.
+    fn word_exists(&self, _word: &str) -> bool;
+
+    /// Get how many times given word has been seen in this language, or 0 if word is
+    /// not present at dictionary.
+    fn get_word_frequency(&self, _word: &str) -> Result<u64>;
+
+    /// Get sum of every word occurrences.
+    fn get_total_frequency(&self) -> Result<u64>;
+
+    /// Get a list of every word with given pattern.
+    fn get_words_with_pattern(&self, pattern: &str) -> Result<Vec<String>>;
+
+    /// Get words with any of given patterns.
+    ///
+    /// By default every pattern is looked up on its own. Dictionaries where every lookup
+    /// is expensive should get them all at once.
+    ///
+    /// # Parameters:
+    /// * patterns: Word patterns to look for.
+    ///
+    /// # Returns:
+    /// * A HashMap whose keys are given patterns and values are words with that pattern.
+    ///     Patterns without words are present too, with an empty list.
+    fn get_words_with_patterns(&self, patterns: &[String]) -> Result<HashMap<String, Vec<String>>> {
+        let mut words_by_pattern = HashMap::new();
+        for pattern in patterns {
+            words_by_pattern.insert(pattern.clone(), self.get_words_with_pattern(pattern)?);
+        }
+        Ok(words_by_pattern)
+    }
+
+    /// Get a list of every word present at dictionary.
+    fn get_all_words(&self) -> Result<Vec<String>>;
+
+    /// Get every word present at dictionary along with how many times it has been seen.
+    fn get_all_word_frequencies(&self) -> Result<HashMap<String, u64>>;
+
+    /// Get how many times every letter, and every bigram inside words, has been seen in this language.
+    ///
+    /// By default they are counted from every word frequency. Dictionaries keeping them
+    /// already counted should return those instead.
+    ///
+    /// # Returns:
+    /// * Letters and bigrams along with their occurrences.
+    fn get_letter_ngram_counts(&self) -> Result<HashMap<String, u64>> {
+        let word_frequencies = self.get_all_word_frequencies()?;
+        Ok(count_letter_ngrams(word_frequencies.iter()
+            .map(|(counted_word, &occurrences)| (counted_word.as_str(), occurrences))))
+    }
+
+    /// Get a segmenter to split texts into this dictionary words.
+    ///
+    /// By default a new segmenter is built from every word frequency each time. Dictionaries
+    /// opened many times along an attack should keep it built.
+    ///
+    /// # Returns:
+    /// * Segmenter for this dictionary language.
+    fn get_segmenter(&self) -> Result<Arc<Segmenter>> {
+        Ok(Arc::new(Segmenter::new(self.get_all_word_frequencies()?)))
+    }
+
+    /// Get how many words of given set are really present in this dictionary.
+    ///
+    /// # Parameters:
+    /// * words: Set of words.
+    ///
+    /// # Returns:
+    /// * A float between 0 and 1 being 1 as every word in set is present at dictionary.
+    fn get_words_presence(&self, _words: &HashSet<String>) -> f64 {
+        let current_hits = _words.iter()
+            .filter(|_word| self.word_exists(_word))
+            .count();
+        current_hits as f64 / _words.len() as f64
+    }
+}
+
+impl LanguageDictionary for Dictionary {
+    fn language(&self) -> &str {
+        &self.language
+    }
+
+    fn word_exists(&self, _word: &str) -> bool {
+        Dictionary::word_exists(self, _word)
+    }
+
+    fn get_word_frequency(&self, _word: &str) -> Result<u64> {
+        Dictionary::get_word_frequency(self, _word)
+    }
+
+    fn get_total_frequency(&self) -> Result<u64> {
+        Dictionary::get_total_frequency(self)
+    }
+
+    fn get_words_with_pattern(&self, pattern: &str) -> Result<Vec<String>> {
+        Dictionary::get_words_with_pattern(self, pattern)
+    }
+
+    fn get_words_with_patterns(&self, patterns: &[String]) -> Result<HashMap<String, Vec<String>>> {
+        Dictionary::get_words_with_patterns(self, patterns)
+    }
+
+    fn get_all_words(&self) -> Result<Vec<String>> {
+        Dictionary::get_all_words(self)
+    }
+
+    fn get_all_word_frequencies(&self) -> Result<HashMap<String, u64>> {
+        Dictionary::get_all_word_frequencies(self)
+    }
+
+    fn get_letter_ngram_counts(&self) -> Result<HashMap<String, u64>> {
+        Dictionary::get_letter_ngram_counts(self)
+    }
+}
+
+impl<D> LanguageDictionary for &D
+    where D: LanguageDictionary + ?Sized {
+    fn language(&self) -> &str {
+        (**self).language()
+    }
+
+    fn word_exists(&self, _word: &str) -> bool {
+        (**self).word_exists(_word)
+    }
+
+    fn get_word_frequency(&self, _word: &str) -> Result<u64> {
+        (**self).get_word_frequency(_word)
+    }
+
+    fn get_total_frequency(&self) -> Result<u64> {
+        (**self).get_total_frequency()
+    }
+
+    fn get_words_with_pattern(&self, pattern: &str) -> Result<Vec<String>> {
+        (**self).get_words_with_pattern(pattern)
+    }
+
+    fn get_words_with_patterns(&self, patterns: &[String]) -> Result<HashMap<String, Vec<String>>> {
+        (**self).get_words_with_patterns(patterns)
+    }
+
+    fn get_all_words(&self) -> Result<Vec<String>> {
+        (**self).get_all_words()
+    }
+
+    fn get_all_word_frequencies(&self) -> Result<HashMap<String, u64>> {
+        (**self).get_all_word_frequencies()
+    }
+
+    fn get_letter_ngram_counts(&self) -> Result<HashMap<String, u64>> {
+        (**self).get_letter_ngram_counts()
+    }
+
+    fn get_segmenter(&self) -> Result<Arc<Segmenter>> {
+        (**self).get_segmenter()
+    }
+}
+
+/// A set of language dictionaries attacks can look words into.
+///
+/// Stores are shared between threads by multiprocessing attacks, so every dictionary
+/// is opened by the thread using it.
+pub trait DictionaryStore: Sync {
+    /// Get names of languages present at this store.
+    fn get_dictionaries_names(&self) -> Result<Vec<String>>;
+
+    /// Open given language dictionary.
+    ///
+    /// # Parameters:
+    /// * language: Language to open.
+    ///
+    /// # Returns:
+    /// * Language dictionary, or a NotExistingLanguage error if language is not present
+    ///     at this store.
+    fn open_dictionary(&self, _language: &str) -> Result<Box<dyn LanguageDictionary + '_>>;
+
+    /// Get unicode normalization texts should get before looking their words up at this store.
+    ///
+    /// # Returns:
+    /// * Normalization used to populate this store dictionaries. NFC by default.
+    fn normalization(&self) -> Normalization {
+        Normalization::default()
+    }
+}
+
+impl<S> DictionaryStore for &S
+    where S: DictionaryStore + ?Sized {
+    fn get_dictionaries_names(&self) -> Result<Vec<String>> {
+        (**self).get_dictionaries_names()
+    }
+
+    fn open_dictionary(&self, _language: &str) -> Result<Box<dyn LanguageDictionary + '_>> {
+        (**self).open_dictionary(_language)
+    }
+
+    fn normalization(&self) -> Normalization {
+        (**self).normalization()
+    }
+}
+
+/// Store with dictionaries kept at a cifra local database.
+///
+/// By default database is located using DATABASE_URL environment variable, as *Dictionary*
+/// does, but a given database path can be used instead.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DatabaseStore {
+    database_path: Option<PathBuf>
+}
+
+impl DatabaseStore {
+    /// Create a store using database at given path.
+    ///
+    /// # Parameters:
+    /// * database_path: Path to database file. It is created if it does not exist yet.
+    pub fn at<T>(database_path: T) -> Self
+        where T: AsRef<Path> {
+        DatabaseStore { database_path: Some(database_path.as_ref().to_path_buf()) }
+    }
+
+    /// Open this store database.
+    pub fn open_database(&self) -> Result<Database> {
+        match &self.database_path {
+            Some(database_path) => Database::open(database_path),
+            None => Database::new()
+        }
+    }
+}
+
+impl DictionaryStore for DatabaseStore {
+    fn get_dictionaries_names(&self) -> Result<Vec<String>> {
+        Dictionary::get_dictionaries_names_from(&self.open_database()?)
+    }
+
+    fn open_dictionary(&self, _language: &str) -> Result<Box<dyn LanguageDictionary + '_>> {
+        Ok(Box::new(Dictionary::from_database(self.open_database()?, _language, false)?))
+    }
+}
+
+/// Dictionary kept in memory.
+///
+/// It never touches database nor environment variables, so it is useful when cifra
+/// is embedded as a library or words are only needed for a while.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryDictionary {
+    language: String,
+    word_frequencies: HashMap<String, u64>,
+    words_by_pattern: HashMap<String, Vec<String>>,
+    total_frequency: u64
+}
+
+impl MemoryDictionary {
+    /// Create an empty dictionary.
+    ///
+    /// # Parameters:
+    /// * language: Language these words are going to belong to.
+    pub fn new<T>(_language: T) -> Self
+        where T: AsRef<str> {
+        MemoryDictionary {
+            language: _language.as_ref().to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Create a dictionary from a word list.
+    ///
+    /// Every word is taken as seen once.
+    ///
+    /// # Parameters:
+    /// * language: Language these words belong to.
+    /// * words: Words to add. They are lowercased.
+    pub fn from_words<T, U>(_language: T, _words: &[U]) -> Self
+        where T: AsRef<str>,
+              U: AsRef<str> {
+        let mut dictionary = MemoryDictionary::new(_language);
+        for new_word in _words {
+            dictionary.add_word(new_word);
+        }
+        dictionary
+    }
+
+    /// Create a dictionary with words found at a text, along with how many times they
+    /// appear at it.
+    ///
+    /// # Parameters:
+    /// * language: Language text is written in.
+    /// * text: Text to extract words from.
+    pub fn from_text<T, U>(_language: T, text: U) -> Self
+        where T: AsRef<str>,
+              U: AsRef<str> {
+        let mut dictionary = MemoryDictionary::new(_language);
+        dictionary.add_words_with_frequencies(&get_word_counts_from_text(text));
+        dictionary
+    }
+
+    /// Create a dictionary with words found at a text file.
+    ///
+    /// # Parameters:
+    /// * language: Language text is written in.
+    /// * file_pathname: Absolute path to file with text to analyze.
+    pub fn from_text_file<T, U>(_language: T, file_pathname: U) -> Result<Self>
+        where T: AsRef<str>,
+              U: AsRef<Path> {
+        let mut dictionary = MemoryDictionary::new(_language);
+        dictionary.add_words_with_frequencies(&get_word_counts_from_text_file(file_pathname, Normalization::default())?);
+        Ok(dictionary)
+    }
+
+    /// Add given word to dictionary, once.
+    ///
+    /// # Parameters:
+    /// * word: word to add to dictionary. It is lowercased.
+    pub fn add_word<T>(&mut self, _word: T)
+        where T: AsRef<str> {
+        let new_word = _word.as_ref().to_lowercase();
+        self.add_occurrences(new_word, 1);
+    }
+
+    /// Add given words to dictionary along with how many times they have been seen.
+    ///
+    /// Words already present at dictionary get these occurrences added to their stored ones.
+    ///
+    /// # Parameters:
+    /// * word_counts: Words along with their occurrences.
+    pub fn add_words_with_frequencies(&mut self, word_counts: &HashMap<String, u64>) {
+        for (new_word, &occurrences) in word_counts {
+            self.add_occurrences(new_word.to_lowercase(), occurrences);
+        }
+    }
+
+    /// Remove given word from dictionary.
+    ///
+    /// If word is not already present at dictionary, do nothing.
+    ///
+    /// # Parameters:
+    /// * word: word to remove from dictionary.
+    pub fn remove_word<T>(&mut self, _word: T)
+        where T: AsRef<str> {
+        let removed_word = _word.as_ref().to_lowercase();
+        if let Some(occurrences) = self.word_frequencies.remove(&removed_word) {
+            self.total_frequency -= occurrences;
+            let pattern = get_word_pattern(&removed_word);
+            if let Some(pattern_words) = self.words_by_pattern.get_mut(&pattern) {
+                pattern_words.retain(|pattern_word| *pattern_word != removed_word);
+            }
+        }
+    }
+
+    fn add_occurrences(&mut self, new_word: String, occurrences: u64) {
+        if new_word.is_empty() || occurrences == 0 {
+            return;
+        }
+        self.total_frequency += occurrences;
+        match self.word_frequencies.get_mut(&new_word) {
+            Some(stored_occurrences) => *stored_occurrences += occurrences,
+            None => {
+                self.words_by_pattern.entry(get_word_pattern(&new_word))
+                    .or_insert_with(Vec::new)
+                    .push(new_word.clone());
+                self.word_frequencies.insert(new_word, occurrences);
+            }
+        }
+    }
+}
+
+impl LanguageDictionary for MemoryDictionary {
+    fn language(&self) -> &str {
+        &self.language
+    }
+
+    fn word_exists(&self, _word: &str) -> bool {
+        self.word_frequencies.contains_key(_word)
+    }
+
+    fn get_word_frequency(&self, _word: &str) -> Result<u64> {
+        Ok(self.word_frequencies.get(_word).cloned().unwrap_or(0))
+    }
+
+    fn get_total_frequency(&self) -> Result<u64> {
+        Ok(self.total_frequency)
+    }
+
+    fn get_words_with_pattern(&self, pattern: &str) -> Result<Vec<String>> {
+        Ok(self.words_by_pattern.get(pattern).cloned().unwrap_or_default())
+    }
+
+    fn get_all_words(&self) -> Result<Vec<String>> {
+        Ok(self.word_frequencies.keys().cloned().collect())
+    }
+
+    fn get_all_word_frequencies(&self) -> Result<HashMap<String, u64>> {
+        Ok(self.word_frequencies.clone())
+    }
+}
+
+/// Store with dictionaries kept in memory.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    dictionaries: HashMap<String, MemoryDictionary>
+}
+
+impl MemoryStore {
+    /// Create a store without any dictionary.
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+
+    /// Add a dictionary to this store, replacing any previous one for the same language.
+    ///
+    /// # Parameters:
+    /// * dictionary: Dictionary to add.
+    pub fn add_dictionary(&mut self, dictionary: MemoryDictionary) {
+        self.dictionaries.insert(dictionary.language.clone(), dictionary);
+    }
+
+    /// Remove given language from this store.
+    ///
+    /// # Parameters:
+    /// * language: Language to remove.
+    ///
+    /// # Returns:
+    /// * Removed dictionary, if language was present.
+    pub fn remove_dictionary(&mut self, _language: &str) -> Option<MemoryDictionary> {
+        self.dictionaries.remove(_language)
+    }
+}
+
+impl FromIterator<MemoryDictionary> for MemoryStore {
+    fn from_iter<I: IntoIterator<Item=MemoryDictionary>>(dictionaries: I) -> Self {
+        let mut store = MemoryStore::new();
+        dictionaries.into_iter().for_each(|dictionary| store.add_dictionary(dictionary));
+        store
+    }
+}
+
+impl DictionaryStore for MemoryStore {
+    fn get_dictionaries_names(&self) -> Result<Vec<String>> {
+        let mut dictionaries_names: Vec<String> = self.dictionaries.keys().cloned().collect();
+        dictionaries_names.sort();
+        Ok(dictionaries_names)
+    }
+
+    fn open_dictionary(&self, _language: &str) -> Result<Box<dyn LanguageDictionary + '_>> {
+        match self.dictionaries.get(_language) {
+            Some(dictionary) => Ok(Box::new(dictionary)),
+            None => bail!(ErrorKind::NotExistingLanguage(_language.to_string()))
+        }
+    }
+}
+
+/// Count letters and bigrams inside given words.
+///
+/// Bigrams never cross word boundaries, so they tell which letters follow each other
+/// inside words.
+///
+/// # Parameters:
+/// * word_occurrences: Words along with how many times they have been seen.
+///
+/// # Returns:
+/// * Letters and bigrams along with their occurrences, weighted by their words occurrences.
+pub fn count_letter_ngrams<'a, I>(word_occurrences: I) -> HashMap<String, u64>
+    where I: IntoIterator<Item=(&'a str, u64)> {
+    let mut ngram_counts: HashMap<String, u64> = HashMap::new();
+    for (counted_word, occurrences) in word_occurrences {
+        let letters: Vec<char> = counted_word.chars().collect();
+        for letter in letters.iter() {
+            *ngram_counts.entry(letter.to_string()).or_insert(0) += occurrences;
+        }
+        for bigram in letters.windows(2) {
+            *ngram_counts.entry(bigram.iter().collect()).or_insert(0) += occurrences;
+        }
+    }
+    ngram_counts
+}
+
 /// Get word pattern.
 ///
 /// This pattern is useful to break substitution cipher.
@@ -381,6 +1171,95 @@ pub fn get_words_from_text_file<T>(file_pathname: T) -> Result<HashSet<String>>
     Ok(words_set)
 }
 
+/// Count words at given file.
+///
+/// File is read line by line, so it is never fully loaded in memory.
+///
+/// # Parameters:
+/// * param file_pathname: Absolute filename to file to be read.
+/// * normalization: Unicode normalization to apply to file text.
+///
+/// # Returns:
+/// Words normalized to lowercase and without any punctuation mark, along with how
+/// many times they appear at file.
+pub fn get_word_counts_from_text_file<T>(file_pathname: T, normalization: Normalization) -> Result<HashMap<String, u64>>
+    where T: AsRef<Path> {
+    let file_to_read = File::open(file_pathname.as_ref())
+        .chain_err(|| ErrorKind::IOError(file_pathname.as_ref().to_string_lossy().to_string()))?;
+    get_word_counts_from_reader(BufReader::new(file_to_read), normalization)
+        .chain_err(|| ErrorKind::IOError(file_pathname.as_ref().to_string_lossy().to_string()))
+}
+
+/// Count words read from given reader.
+///
+/// # Parameters:
+/// * reader: Where to read text from, line by line.
+/// * normalization: Unicode normalization to apply to text.
+///
+/// # Returns:
+/// Words normalized to lowercase and without any punctuation mark, along with how
+/// many times they appear at text.
+pub fn get_word_counts_from_reader<R>(mut reader: R, normalization: Normalization) -> Result<HashMap<String, u64>>
+    where R: BufRead {
+    let words_regex = Regex::new(WORDS_REGEX)
+        .expect("Invalid regex to search for normalized words.");
+    let mut word_counts: HashMap<String, u64> = HashMap::new();
+    let mut line = String::new();
+    while reader.read_line(&mut line).chain_err(|| "Error reading text.")? > 0 {
+        for found_word in words_regex.find_iter(&normalize_unicode(&line, normalization).to_lowercase()) {
+            match word_counts.get_mut(found_word.as_str()) {
+                Some(occurrences) => *occurrences += 1,
+                None => { word_counts.insert(found_word.as_str().to_string(), 1); }
+            }
+        }
+        line.clear();
+    }
+    Ok(word_counts)
+}
+
+/// Count words at given text.
+///
+/// # Parameters:
+/// * text: Text to extract words from.
+///
+/// # Returns:
+/// Words normalized to lowercase and without any punctuation mark, along with how
+/// many times they appear at text.
+pub fn get_word_counts_from_text<T>(text: T)-> HashMap<String, u64>
+    where T: AsRef<str> {
+    let word_counter: Counter<String> = Counter::from_iter(normalize_text(text));
+    word_counter.items()
+        .map(|(counted_word, &occurrences)| (counted_word.clone(), occurrences))
+        .collect()
+}
+
+/// Get logarithmic probability of a word in a language.
+///
+/// Words not present at dictionary cost as much as a word seen once for every letter
+/// they have, so the longer an unknown word is the less likely it gets.
+///
+/// # Parameters:
+/// * occurrences: How many times word has been seen in language.
+/// * total_occurrences: How many word occurrences have been seen in language.
+/// * length: Word length in letters.
+///
+/// # Returns:
+/// * Natural logarithm of word probability.
+pub(crate) fn get_word_log_probability(occurrences: u64, total_occurrences: u64, length: usize)-> f64 {
+    // One is added to keep rare word probability below 1 even with a single word known.
+    let rare_word_log_probability = -(total_occurrences as f64 + 1.0).ln();
+    if occurrences > 0 {
+        (occurrences as f64 / (total_occurrences as f64).max(occurrences as f64)).ln()
+    } else {
+        (length + 1) as f64 * rare_word_log_probability
+    }
+}
+
+/// Database stores frequencies as 32 bits integers.
+fn to_stored_frequency(occurrences: u64)-> i32 {
+    occurrences.min(i32::MAX as u64) as i32
+}
+
 /// Extract words from given text.
 ///
 /// Extracted words are normalized to lowercase and any punctuation mark
@@ -404,77 +1283,194 @@ pub fn get_words_from_text<T>(text: T)-> HashSet<String>
 /// * winner: Name of language more likely. If None the no proper language was found.
 /// * winner_probability: Probability this language is actually de right one. If None the no proper language was found.
 /// * candidates: Dict with all languages probabilities. Probabilities are floats from 0 to 1.
+/// * confidences: Dict with how likely every language is compared with the others. They add up to 1.
 pub struct IdentifiedLanguage {
     pub(crate) winner: Option<String>,
     pub(crate) winner_probability: Option<f64>,
-    candidates: HashMap<String, f64>
+    candidates: HashMap<String, f64>,
+    confidences: HashMap<String, f64>
+}
+
+impl IdentifiedLanguage {
+    /// Get name of language more likely, if any.
+    pub fn winner(&self) -> Option<&str> {
+        self.winner.as_deref()
+    }
+
+    /// Get how likely winner language is compared with the other languages.
+    ///
+    /// # Returns:
+    /// * A float from 0 to 1, or None if no proper language was found.
+    pub fn confidence(&self) -> Option<f64> {
+        self.winner.as_ref()
+            .and_then(|winner| self.confidences.get(winner))
+            .cloned()
+    }
+}
+
+/// Evidence found at a text about it being written in a language.
+///
+/// # Members:
+/// * presence: Fraction of text letters that belong to words present at language dictionary.
+/// * log_likelihood: Logarithmic probability of text words in language, weighted by how
+///     often every word appears in that language.
+struct LanguageEvidence {
+    presence: f64,
+    log_likelihood: f64
 }
 
 /// Identify language used to write text.
 ///
 /// It check each word present at text to find out if is present in any language.
-/// The language that has more words is select as winner.
+/// Frequent words in a language, and long ones, are stronger evidence than rare or
+/// short ones, so the language where text words are more likely is select as winner.
+///
+/// Texts written without spaces are split into words with every language dictionary
+/// first.
 ///
 /// # Parameters:
 /// * Text: Text to analyze.
+/// * store: Dictionaries to look words into.
 ///
 /// # Returns:
 /// * Language selected as more likely to be the one used to write text.
-pub fn identify_language<T>(text: T)-> Result<IdentifiedLanguage>
+pub fn identify_language<T>(text: T, store: &dyn DictionaryStore)-> Result<IdentifiedLanguage>
     where T: AsRef<str> {
-    let _words = get_words_from_text(&text);
-    let candidates = get_candidates_frecuency(&_words)?;
-    if let Some(winner) = get_winner(&candidates){
+    let (identified_language, _) = identify_language_with_segmenters(text.as_ref(), store)?;
+    Ok(identified_language)
+}
+
+/// Identify language used to write text, keeping segmenters used to split it.
+///
+/// # Parameters:
+/// * Text: Text to analyze.
+/// * store: Dictionaries to look words into.
+///
+/// # Returns:
+/// * Language selected as more likely to be the one used to write text, along with the
+///     segmenter of every language. Segmenters are only built for texts written without
+///     spaces, so for other texts they are empty.
+pub(crate) fn identify_language_with_segmenters(text: &str, store: &dyn DictionaryStore)-> Result<(IdentifiedLanguage, HashMap<String, Arc<Segmenter>>)> {
+    let (evidences, segmenters) = get_candidates_evidence(text, store)?;
+    let candidates: HashMap<String, f64> = evidences.iter()
+        .map(|(candidate_name, evidence)| (candidate_name.clone(), evidence.presence))
+        .collect();
+    let confidences = get_confidences(&evidences);
+    let identified_language = if let Some(winner) = get_winner(&evidences){
         let winner_probability = *(candidates.get(winner.as_str()).unwrap());
-        Ok(IdentifiedLanguage {
+        IdentifiedLanguage {
             winner: Some(winner),
             winner_probability: Some(winner_probability),
-            candidates
-        })
+            candidates,
+            confidences
+        }
     } else {
-        Ok(IdentifiedLanguage {
+        IdentifiedLanguage {
             winner: None,
             winner_probability: None,
-            candidates
-        })
-    }
+            candidates,
+            confidences
+        }
+    };
+    Ok((identified_language, segmenters))
 }
 
-/// Get frequency of presence of words in each language.
+/// Get evidence of text being written in each language.
 ///
 /// # Parameters:
-/// * words: Text words.
+/// * text: Text to analyze.
+/// * store: Dictionaries to look words into.
 ///
 /// # Returns:
-/// * Dict with all languages probabilities. Probabilities are floats
-///    from 0 to 1. The higher the frequency of presence of words in language
-///    the higher of this probability.
-fn get_candidates_frecuency(_words: &HashSet<String>)-> Result<HashMap<String, f64>> {
-    let total_words = _words.len();
-    let mut candidates: HashMap<String, f64> = HashMap::new();
-    for _language in Dictionary::get_dictionaries_names()? {
-        let dictionary = Dictionary::new(&_language, false)
+/// * Dict with evidence found for every language, and another one with segmenters used
+///     to split text if it was written without spaces.
+fn get_candidates_evidence(text: &str, store: &dyn DictionaryStore)-> Result<(HashMap<String, LanguageEvidence>, HashMap<String, Arc<Segmenter>>)> {
+    let normalized_text = normalize_unicode(text, store.normalization());
+    let text = normalized_text.as_str();
+    let spaceless = is_spaceless(text);
+    let text_words = normalize_text(text);
+    let mut candidates: HashMap<String, LanguageEvidence> = HashMap::new();
+    let mut segmenters: HashMap<String, Arc<Segmenter>> = HashMap::new();
+    for _language in store.get_dictionaries_names()? {
+        let dictionary = store.open_dictionary(&_language)
             .chain_err(|| ErrorKind::DatabaseError(String::from("Error opening language dictionary")))?;
-        let current_hits: u64 = _words.iter().map(|_word| if dictionary.word_exists(_word) {1} else {0}).sum();
-        let frequency = current_hits as f64 / total_words as f64;
-        candidates.insert(_language, frequency);
+        let evidence = if spaceless {
+            let segmenter = dictionary.get_segmenter()?;
+            let evidence = get_language_evidence(dictionary.as_ref(), &segmenter.segment(text), MIN_COVERED_WORD_LENGTH)?;
+            segmenters.insert(_language.clone(), segmenter);
+            evidence
+        } else {
+            get_language_evidence(dictionary.as_ref(), &text_words, 1)?
+        };
+        candidates.insert(_language, evidence);
+    }
+    Ok((candidates, segmenters))
+}
+
+/// Get evidence of some words being written in dictionary language.
+///
+/// # Parameters:
+/// * dictionary: Language dictionary.
+/// * text_words: Words to look for, repeated as many times as they appear at text.
+/// * min_known_length: Shortest word taken as present. Words got from segmentation
+///     should not count single letters, as any text can be split into them.
+///
+/// # Returns:
+/// * Evidence found.
+fn get_language_evidence(dictionary: &dyn LanguageDictionary, text_words: &[String], min_known_length: usize)-> Result<LanguageEvidence> {
+    let total_frequency = dictionary.get_total_frequency()?;
+    let word_counter: Counter<&String> = Counter::from_iter(text_words.iter());
+    let mut total_letters: u64 = 0;
+    let mut known_letters: u64 = 0;
+    let mut log_likelihood: f64 = 0.0;
+    for (text_word, &occurrences) in word_counter.items() {
+        let length = text_word.chars().count();
+        let word_frequency = dictionary.get_word_frequency(text_word)?;
+        total_letters += length as u64 * occurrences;
+        if word_frequency > 0 && length >= min_known_length {
+            known_letters += length as u64 * occurrences;
+        }
+        log_likelihood += occurrences as f64 * get_word_log_probability(word_frequency, total_frequency, length);
     }
-    Ok(candidates)
+    let presence = if total_letters > 0 { known_letters as f64 / total_letters as f64 } else { 0.0 };
+    Ok(LanguageEvidence { presence, log_likelihood })
+}
+
+/// Get how likely every language is compared with the others.
+///
+/// # Parameters:
+/// * evidences: Dict with evidence found for every language.
+///
+/// # Returns:
+/// * Dict with languages probabilities, normalized to add up to 1.
+fn get_confidences(evidences: &HashMap<String, LanguageEvidence>)-> HashMap<String, f64> {
+    let highest_log_likelihood = evidences.values()
+        .map(|evidence| evidence.log_likelihood)
+        .fold(f64::NEG_INFINITY, f64::max);
+    // Subtracting the highest log likelihood avoids underflows when exponentiating.
+    let likelihoods: HashMap<&String, f64> = evidences.iter()
+        .map(|(candidate_name, evidence)| (candidate_name, (evidence.log_likelihood - highest_log_likelihood).exp()))
+        .collect();
+    let total_likelihood: f64 = likelihoods.values().sum();
+    likelihoods.into_iter()
+        .map(|(candidate_name, likelihood)| (candidate_name.clone(), likelihood / total_likelihood))
+        .collect()
 }
 
-/// Return candidate with highest frequency.
+/// Return candidate whose words are more likely.
 ///
 /// # Parameters:
-/// * candidates: Dict with all languages probabilities. Probabilities are floats
-///    from 0 to 1. The higher the frequency of presence of words in language
-///    the higher of this probability
-fn get_winner(candidates: &HashMap<String, f64>)-> Option<String> {
+/// * evidences: Dict with evidence found for every language.
+///
+/// # Returns:
+/// * Winner language name, or None if no text word is present at any language.
+fn get_winner(evidences: &HashMap<String, LanguageEvidence>)-> Option<String> {
     let mut current_winner = None;
-    let mut current_highest_frequency = 0_f64;
-    for (candidate_name, frequency) in candidates {
-        if *frequency > current_highest_frequency {
+    let mut current_highest_log_likelihood = f64::NEG_INFINITY;
+    for (candidate_name, evidence) in evidences {
+        if evidence.presence > 0.0 && evidence.log_likelihood > current_highest_log_likelihood {
             current_winner = Some(candidate_name.clone());
-            current_highest_frequency = *frequency;
+            current_highest_log_likelihood = evidence.log_likelihood;
         }
     }
     current_winner
@@ -613,7 +1609,7 @@ Jahre die Feder zur Hand und beginne bei der Zeit als mein Vater
 noch den Gasthof Zum Admiral Benbow hielt und jener dunkle alte
 Seemann mit dem Säbelhieb über der Wange unter unserem Dache Wohnung
 nahm";
-    const GERMAN_TEXT_WITH_PUNCTUATIONS_MARKS: &'static str = "Da unser Gutsherr, Mr. Trelawney, Dr. Livesay und die übrigen Herren
+    pub const GERMAN_TEXT_WITH_PUNCTUATIONS_MARKS: &'static str = "Da unser Gutsherr, Mr. Trelawney, Dr. Livesay und die übrigen Herren
 mich baten, alle Einzelheiten über die Schatzinsel von Anfang bis zu
 Ende aufzuschreiben und nichts auszulassen als die Lage der Insel, und
 auch die nur, weil noch ungehobene Schätze dort liegen, nehme ich im
@@ -701,6 +1697,19 @@ nahm.";
         LoadedDictionaries::new()
     }
 
+    /// Store with every language book loaded in memory, so neither database nor environment
+    /// variables are used.
+    #[fixture]
+    pub fn memory_store()-> MemoryStore {
+        let mut resources_path = env::current_dir()
+            .expect("Could not get current working dir");
+        resources_path.push("resources");
+        LANGUAGES.iter()
+            .map(|_language| MemoryDictionary::from_text_file(_language, resources_path.join(format!("{}_book.txt", _language)))
+                .expect("Error loading language book."))
+            .collect()
+    }
+
     /// Get tuples with a language name, a text with punctuations marks and a text without it.
     fn get_text_tuples()-> Vec<(&'static str, &'static str, &'static str)> {
         vec![
@@ -960,8 +1969,8 @@ nahm.";
         let test_cases = vec![(ENGLISH_TEXT_WITH_PUNCTUATIONS_MARKS, "english"),
                               (SPANISH_TEXT_WITH_PUNCTUATIONS_MARKS, "spanish")];
         for (text, expected_language) in test_cases{
-            let identified_language = identify_language(text).expect("Error identifying language.");
-            if let Some(winner) = identified_language.winner {
+            let identified_language = identify_language(text, &DatabaseStore::default()).expect("Error identifying language.");
+            if let Some(winner) = identified_language.winner.as_deref() {
                 assert_eq!(winner, expected_language, "Language not correctly identified.");
             } else {
                 assert!(false, "Language not identified")
@@ -971,6 +1980,199 @@ nahm.";
             } else {
                 assert!(false, "Language probability not found.")
             }
+            assert!(identified_language.confidence().unwrap() > 0.99, "Language confidence too low.");
+        }
+    }
+
+    #[test]
+    fn test_identify_language_weighting_by_frequency() {
+        let (temp_dir, temp_env_database_path) = temporary_database_folder(None);
+        database::create_database();
+        let dictionaries_content = vec![("english", vec![("no", 5), ("a", 50), ("dog", 3)]),
+                                        ("spanish", vec![("no", 40), ("a", 30), ("perro", 4)])];
+        for (_language, word_counts) in dictionaries_content {
+            let mut dictionary = Dictionary::new(_language, true).expect("Error opening dictionary.");
+            let word_counts: HashMap<String, u64> = word_counts.into_iter()
+                .map(|(counted_word, occurrences)| (counted_word.to_string(), occurrences))
+                .collect();
+            dictionary.add_words_with_frequencies(&word_counts).expect("Error adding words.");
+        }
+        // Both languages have every word, but they are more frequent in spanish.
+        let identified_language = identify_language("No, a... no.", &DatabaseStore::default()).expect("Error identifying language.");
+        assert_eq!(identified_language.winner(), Some("spanish"));
+        assert_eq!(identified_language.winner_probability, Some(1.0));
+        let confidence = identified_language.confidence().unwrap();
+        assert!(confidence > 0.5 && confidence < 1.0);
+        let identified_language = identify_language("xyzzy", &DatabaseStore::default()).expect("Error identifying language.");
+        assert_eq!(identified_language.winner(), None);
+        assert_eq!(identified_language.confidence(), None);
+    }
+
+    #[test]
+    fn test_word_frequencies() {
+        let (temp_dir, temp_env_database_path) = temporary_database_folder(None);
+        database::create_database();
+        let mut dictionary = Dictionary::new("english", true).expect("Error opening dictionary.");
+        dictionary.add_words_with_frequencies(&get_word_counts_from_text("The dog and the cat.")).unwrap();
+        assert_eq!(dictionary.get_word_frequency("the").unwrap(), 2);
+        assert_eq!(dictionary.get_word_frequency("dog").unwrap(), 1);
+        assert_eq!(dictionary.get_word_frequency("snake").unwrap(), 0);
+        dictionary.add_words_with_frequencies(&get_word_counts_from_text("the snake")).unwrap();
+        assert_eq!(dictionary.get_word_frequency("the").unwrap(), 3);
+        assert_eq!(dictionary.get_word_frequency("snake").unwrap(), 1);
+        assert_eq!(dictionary.get_total_frequency().unwrap(), 7);
+        assert_eq!(dictionary.get_all_word_frequencies().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_populate_with_minimum_frequency() {
+        let (temp_dir, _temp_env_database_path) = temporary_database_folder(None);
+        database::create_database().unwrap();
+        let text_file = temp_dir.path().join("corpus.txt");
+        std::fs::write(&text_file, "The dog and the cat.\nThe cat and the snake\n").unwrap();
+        let mut dictionary = Dictionary::new("english", true).expect("Error opening dictionary.");
+        dictionary.add_word("dog");
+        let report = dictionary.populate_with_minimum_frequency(&text_file, 2).unwrap();
+        assert_eq!(report, PopulationReport { new_words: 3, existing_words: 0, discarded_words: 2 });
+        assert_eq!(dictionary.get_word_frequency("the").unwrap(), 4);
+        assert_eq!(dictionary.get_word_frequency("cat").unwrap(), 2);
+        assert_eq!(dictionary.get_word_frequency("dog").unwrap(), 1);
+        assert!(!dictionary.word_exists("snake"));
+        let report = dictionary.populate(&text_file).unwrap();
+        assert_eq!(report, PopulationReport { new_words: 1, existing_words: 4, discarded_words: 0 });
+        assert_eq!(dictionary.get_word_frequency("dog").unwrap(), 2);
+        assert_eq!(dictionary.get_word_frequency("the").unwrap(), 8);
+    }
+
+    #[test]
+    fn test_get_word_counts_from_reader() {
+        let text = "The dog and the cat.\nThe cat, and\nthe snake";
+        assert_eq!(get_word_counts_from_reader(text.as_bytes(), Normalization::Nfc).unwrap(), get_word_counts_from_text(text));
+    }
+
+    #[test]
+    fn test_populate_with_normalization() {
+        let (temp_dir, _temp_env_database_path) = temporary_database_folder(None);
+        database::create_database().unwrap();
+        let text_file = temp_dir.path().join("corpus.txt");
+        std::fs::write(&text_file, "Canción de la calle.\nLa cancio\u{301}n de Straße.\n").unwrap();
+        let mut dictionary = Dictionary::new("spanish", true).expect("Error opening dictionary.");
+        dictionary.populate(&text_file).unwrap();
+        assert_eq!(dictionary.get_word_frequency("canción").unwrap(), 2);
+        let mut folded_dictionary = Dictionary::new("folded_spanish", true).expect("Error opening dictionary.");
+        folded_dictionary.populate_with_normalization(&text_file, 1, Normalization::Folded).unwrap();
+        assert_eq!(folded_dictionary.get_word_frequency("cancion").unwrap(), 2);
+        assert_eq!(folded_dictionary.get_word_frequency("strasse").unwrap(), 1);
+        assert!(!folded_dictionary.word_exists("canción"));
+    }
+
+    #[test]
+    fn test_letter_frequencies() {
+        let (_temp_dir, _temp_env_database_path) = temporary_database_folder(None);
+        database::create_database().unwrap();
+        let mut dictionary = Dictionary::new("english", true).expect("Error opening dictionary.");
+        dictionary.add_words_with_frequencies(&get_word_counts_from_text("The cat and the hat.")).unwrap();
+        let stored_counts = dictionary.get_letter_ngram_counts().unwrap();
+        assert_eq!(stored_counts.get("t"), Some(&4));
+        assert_eq!(stored_counts.get("h"), Some(&3));
+        assert_eq!(stored_counts.get("th"), Some(&2));
+        assert_eq!(stored_counts.get("at"), Some(&2));
+        assert_eq!(stored_counts.get("ec"), None);
+        dictionary.add_word("that");
+        dictionary.add_multiple_words(&HashSet::from_iter(vec!["tea".to_string()]));
+        dictionary.remove_word("cat");
+        // Stored counts should always be those of stored words.
+        let word_frequencies = dictionary.get_all_word_frequencies().unwrap();
+        let expected_counts = count_letter_ngrams(word_frequencies.iter()
+            .map(|(counted_word, &occurrences)| (counted_word.as_str(), occurrences)));
+        let stored_counts = dictionary.get_letter_ngram_counts().unwrap();
+        assert_eq!(expected_counts, stored_counts);
+        assert_eq!(stored_counts.get("ca"), None);
+        assert_eq!(stored_counts.get("th"), Some(&3));
+    }
+
+    #[test]
+    fn test_delete_language_removes_its_rows() {
+        let (_temp_dir, _temp_env_database_path) = temporary_database_folder(None);
+        database::create_database().unwrap();
+        let mut english_dictionary = Dictionary::new("english", true).expect("Error opening dictionary.");
+        english_dictionary.add_words_with_frequencies(&get_word_counts_from_text("The cat and the hat.")).unwrap();
+        let mut german_dictionary = Dictionary::new("german", true).expect("Error opening dictionary.");
+        german_dictionary.add_words_with_frequencies(&get_word_counts_from_text("Die Katze und der Hut.")).unwrap();
+        let german_id = german_dictionary.language_id;
+        Dictionary::remove_dictionary("german").unwrap();
+        let session = english_dictionary.session();
+        let remaining_words: i64 = words::table.filter(language_id.eq(german_id))
+            .count()
+            .first(session)
+            .unwrap();
+        let remaining_ngrams: i64 = letter_frequencies::table.filter(letter_frequencies::language_id.eq(german_id))
+            .count()
+            .first(session)
+            .unwrap();
+        assert_eq!((remaining_words, remaining_ngrams), (0, 0));
+        // A new language may get removed one id, but never its words.
+        let klingon_dictionary = Dictionary::new("klingon", true).expect("Error opening dictionary.");
+        assert!(klingon_dictionary.get_all_words().unwrap().is_empty());
+        assert_eq!(english_dictionary.get_word_frequency("the").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_memory_dictionary() {
+        let mut dictionary = MemoryDictionary::from_text("english", "The dog and the cat.");
+        assert_eq!(dictionary.language(), "english");
+        assert!(dictionary.word_exists("dog"));
+        assert!(!dictionary.word_exists("snake"));
+        assert_eq!(dictionary.get_word_frequency("the").unwrap(), 2);
+        assert_eq!(dictionary.get_total_frequency().unwrap(), 5);
+        dictionary.add_word("Snake");
+        assert_eq!(dictionary.get_word_frequency("snake").unwrap(), 1);
+        assert_eq!(dictionary.get_words_with_pattern(&get_word_pattern("cat")).unwrap().len(), 4);
+        dictionary.remove_word("cat");
+        assert!(!dictionary.word_exists("cat"));
+        assert_eq!(dictionary.get_words_with_pattern(&get_word_pattern("cat")).unwrap().len(), 3);
+        assert_eq!(dictionary.get_total_frequency().unwrap(), 5);
+        dictionary.remove_word("Snake");
+        assert!(!dictionary.word_exists("snake"));
+        assert_eq!(dictionary.get_total_frequency().unwrap(), 4);
+        let words_to_check: HashSet<String> = HashSet::from_iter(vec!["dog".to_string(), "cow".to_string()]);
+        assert_eq!(dictionary.get_words_presence(&words_to_check), 0.5);
+    }
+
+    #[test]
+    fn test_memory_store() {
+        let store: MemoryStore = get_micro_dictionaries_content().into_iter()
+            .map(|(_language, _words)| MemoryDictionary::from_words(_language, &_words))
+            .collect();
+        assert_eq!(store.get_dictionaries_names().unwrap(), vec!["english", "french", "german", "spanish"]);
+        let dictionary = store.open_dictionary("spanish").expect("Error opening dictionary.");
+        assert!(dictionary.word_exists("perro"));
+        assert!(store.open_dictionary("klingon").is_err());
+        let identified_language = identify_language("Chien, chat... non!", &store)
+            .expect("Error identifying language.");
+        assert_eq!(identified_language.winner(), Some("french"));
+    }
+
+    #[rstest]
+    fn test_identify_language_with_memory_store(memory_store: MemoryStore) {
+        for (expected_language, text, _) in get_text_tuples() {
+            let identified_language = identify_language(text, &memory_store)
+                .expect("Error identifying language.");
+            assert_eq!(identified_language.winner(), Some(expected_language));
+        }
+    }
+
+    #[test]
+    fn test_identify_spaceless_language() {
+        let loaded_dictionaries = LoadedDictionaries::new();
+        let test_cases = vec![(ENGLISH_TEXT_WITHOUT_PUNCTUATIONS_MARKS, "english"),
+                              (SPANISH_TEXT_WITHOUT_PUNCTUATIONS_MARKS, "spanish")];
+        for (text, expected_language) in test_cases {
+            let spaceless_text: String = text.split_whitespace().collect();
+            let identified_language = identify_language(&spaceless_text, &DatabaseStore::default()).expect("Error identifying language.");
+            assert_eq!(identified_language.winner, Some(expected_language.to_string()),
+                       "Language not correctly identified.");
+            assert!(identified_language.winner_probability.unwrap() > 0.9);
         }
     }
 
@@ -1001,6 +2203,28 @@ nahm.";
 
     }
 
+    #[test]
+    fn test_get_words_with_pattern_is_language_scoped() {
+        let (_temp_dir, _temp_env_database_path) = temporary_database_folder(None);
+        database::create_database().unwrap();
+        let mut klingon_dictionary = Dictionary::new("klingon", true).unwrap();
+        let mut vulcan_dictionary = Dictionary::new("vulcan", true).unwrap();
+        klingon_dictionary.add_multiple_words(&["cat", "dog", "moon"].iter().map(|w| w.to_string()).collect());
+        vulcan_dictionary.add_multiple_words(&["sun", "sky"].iter().map(|w| w.to_string()).collect());
+        let mut klingon_words = klingon_dictionary.get_words_with_pattern(&get_word_pattern("cat")).unwrap();
+        klingon_words.sort();
+        assert_eq!(klingon_words, vec!["cat".to_string(), "dog".to_string()]);
+        let patterns = vec![get_word_pattern("cat"), get_word_pattern("moon"), get_word_pattern("tomato")];
+        let klingon_words_by_pattern = klingon_dictionary.get_words_with_patterns(&patterns).unwrap();
+        assert_eq!(klingon_words_by_pattern.len(), 3);
+        assert_eq!(klingon_words_by_pattern[&patterns[0]].len(), 2);
+        assert_eq!(klingon_words_by_pattern[&patterns[1]], vec!["moon".to_string()]);
+        assert!(klingon_words_by_pattern[&patterns[2]].is_empty());
+        let vulcan_words_by_pattern = vulcan_dictionary.get_words_with_patterns(&patterns).unwrap();
+        assert_eq!(vulcan_words_by_pattern[&patterns[0]].len(), 2);
+        assert!(vulcan_words_by_pattern[&patterns[1]].is_empty());
+    }
+
     #[test]
     fn test_insertion_ordered_set() {
         let expected_list = vec!["A".to_string(), "B".to_string(), "C".to_string()];
@@ -0,0 +1,374 @@
+/// Module to export and import language dictionaries in portable formats.
+///
+/// Copying cifra database is not always an option to share dictionaries between
+/// machines, so a language words can be written to, and read from, these formats:
+/// * Word lists: One word per line. Frequencies are lost, so every imported word
+///     counts as seen once.
+/// * CSV: A header line with word, pattern and frequency columns, and a line per word.
+/// * JSON Lines: A JSON object per line with word, pattern and frequency keys.
+///
+/// Word patterns are exported for convenience but they are always recalculated from
+/// words at import, so they are only used to check imported words are right.
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use csv::{ReaderBuilder, StringRecord, Trim, WriterBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::{ErrorKind, Result, ResultExt};
+use crate::attack::dictionaries::{Dictionary, LanguageDictionary, get_word_pattern};
+
+const CSV_HEADER: [&'static str; 3] = ["word", "pattern", "frequency"];
+
+/// Formats dictionaries can be exported to and imported from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DictionaryFormat {
+    WordList,
+    Csv,
+    JsonLines
+}
+
+impl DictionaryFormat {
+    /// Every available format.
+    pub const ALL: [DictionaryFormat; 3] = [DictionaryFormat::WordList, DictionaryFormat::Csv,
+                                            DictionaryFormat::JsonLines];
+
+    /// Guess format from file extension.
+    ///
+    /// # Parameters:
+    /// * path: File to read from or write to.
+    ///
+    /// # Returns:
+    /// * CSV for .csv files, JSON Lines for .jsonl and .ndjson ones and word list for
+    ///     any other.
+    pub fn from_path<T>(path: T) -> Self
+        where T: AsRef<Path> {
+        let extension = path.as_ref().extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        match extension.as_deref() {
+            Some("csv") => DictionaryFormat::Csv,
+            Some("jsonl") | Some("ndjson") => DictionaryFormat::JsonLines,
+            _ => DictionaryFormat::WordList
+        }
+    }
+}
+
+impl Display for DictionaryFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DictionaryFormat::WordList => "words",
+            DictionaryFormat::Csv => "csv",
+            DictionaryFormat::JsonLines => "jsonl"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl TryFrom<&str> for DictionaryFormat {
+    type Error = crate::Error;
+
+    /// Get a format from its name.
+    ///
+    /// # Parameters:
+    /// * value: Format name, like "csv". Case is ignored.
+    ///
+    /// # Returns:
+    /// * OK with format or Err if provided name is not a known format.
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "words" => Ok(DictionaryFormat::WordList),
+            "csv" => Ok(DictionaryFormat::Csv),
+            "jsonl" => Ok(DictionaryFormat::JsonLines),
+            _ => bail!(format!("Unknown dictionary format: {}", value))
+        }
+    }
+}
+
+/// A dictionary word along with its stored data.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WordEntry {
+    pub word: String,
+    pub pattern: String,
+    pub frequency: u64
+}
+
+impl WordEntry {
+    /// Create an entry for given word, calculating its pattern.
+    ///
+    /// # Parameters:
+    /// * word: Dictionary word.
+    /// * frequency: How many times word has been seen.
+    pub fn new<T>(word: T, frequency: u64) -> Self
+        where T: AsRef<str> {
+        WordEntry {
+            word: word.as_ref().to_string(),
+            pattern: get_word_pattern(word.as_ref()),
+            frequency
+        }
+    }
+}
+
+/// A word entry as it is read, before checking its fields.
+#[derive(Debug, Deserialize)]
+struct RawWordEntry {
+    word: Option<String>,
+    pattern: Option<String>,
+    frequency: Option<u64>
+}
+
+/// Write every word of a dictionary to output.
+///
+/// Words are written in alphabetical order, so exports of the same dictionary can be compared.
+///
+/// # Parameters:
+/// * dictionary: Dictionary to export.
+/// * format: Format to write words with.
+/// * output: Where to write words to.
+///
+/// # Returns:
+/// * How many words were written.
+pub fn export_dictionary<W>(dictionary: &dyn LanguageDictionary, format: DictionaryFormat, output: &mut W) -> Result<usize>
+    where W: Write {
+    let mut entries: Vec<WordEntry> = dictionary.get_all_word_frequencies()?.iter()
+        .map(|(word, &frequency)| WordEntry::new(word, frequency))
+        .collect();
+    entries.sort_by(|first, second| first.word.cmp(&second.word));
+    write_word_entries(&entries, format, output)?;
+    Ok(entries.len())
+}
+
+/// Add every word read from input to a dictionary.
+///
+/// Frequencies of words already present at dictionary are added to their stored ones.
+///
+/// # Parameters:
+/// * dictionary: Dictionary to import words into.
+/// * format: Format words are written with.
+/// * input: Where to read words from.
+///
+/// # Returns:
+/// * How many words were read.
+pub fn import_dictionary<R>(dictionary: &mut Dictionary, format: DictionaryFormat, input: R) -> Result<usize>
+    where R: BufRead {
+    let entries = read_word_entries(input, format)?;
+    let mut word_counts: HashMap<String, u64> = HashMap::new();
+    for entry in entries.iter() {
+        *word_counts.entry(entry.word.clone()).or_insert(0) += entry.frequency;
+    }
+    dictionary.add_words_with_frequencies(&word_counts)?;
+    Ok(entries.len())
+}
+
+/// Write word entries to output.
+///
+/// # Parameters:
+/// * entries: Entries to write.
+/// * format: Format to write entries with.
+/// * output: Where to write entries to.
+pub fn write_word_entries<W>(entries: &[WordEntry], format: DictionaryFormat, output: &mut W) -> Result<()>
+    where W: Write {
+    match format {
+        DictionaryFormat::WordList => for entry in entries {
+            writeln!(output, "{}", entry.word).chain_err(|| "Error writing to output.")?;
+        },
+        DictionaryFormat::Csv => {
+            // Header is written by hand so it is present even if there are no entries.
+            let mut writer = WriterBuilder::new().has_headers(false).from_writer(output);
+            writer.write_record(&CSV_HEADER).chain_err(|| "Error writing to output.")?;
+            for entry in entries {
+                writer.serialize(entry).chain_err(|| "Error writing to output.")?;
+            }
+            writer.flush().chain_err(|| "Error writing to output.")?;
+        },
+        DictionaryFormat::JsonLines => for entry in entries {
+            let line = serde_json::to_string(entry).chain_err(|| "Error serializing word entry.")?;
+            writeln!(output, "{}", line).chain_err(|| "Error writing to output.")?;
+        }
+    }
+    Ok(())
+}
+
+/// Read word entries from input.
+///
+/// Words are lowercased, as dictionaries store them. Blank lines are ignored.
+///
+/// # Parameters:
+/// * input: Where to read entries from.
+/// * format: Format entries are written with.
+///
+/// # Returns:
+/// * Entries read, or an error pointing to the first wrong line.
+pub fn read_word_entries<R>(input: R, format: DictionaryFormat) -> Result<Vec<WordEntry>>
+    where R: BufRead {
+    match format {
+        DictionaryFormat::Csv => read_csv_word_entries(input),
+        _ => {
+            let mut entries: Vec<WordEntry> = Vec::new();
+            for (index, line) in input.lines().enumerate() {
+                let line = line.chain_err(|| ErrorKind::IOError(String::from("dictionary")))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let raw_entry = match format {
+                    DictionaryFormat::JsonLines => serde_json::from_str::<RawWordEntry>(&line)
+                        .chain_err(|| format!("Wrong JSON at line {}.", index + 1))?,
+                    _ => RawWordEntry { word: Some(line.trim().to_string()), pattern: None, frequency: None }
+                };
+                entries.push(get_word_entry(raw_entry).chain_err(|| format!("Wrong entry at line {}.", index + 1))?);
+            }
+            Ok(entries)
+        }
+    }
+}
+
+/// Read word entries from a CSV input.
+///
+/// Header columns can be in any order and their case is ignored.
+///
+/// # Parameters:
+/// * input: Where to read entries from.
+///
+/// # Returns:
+/// * Entries read, or an error pointing to the first wrong line.
+fn read_csv_word_entries<R>(input: R) -> Result<Vec<WordEntry>>
+    where R: BufRead {
+    let mut reader = ReaderBuilder::new().trim(Trim::All).from_reader(input);
+    let header: StringRecord = reader.headers()
+        .chain_err(|| "Wrong CSV header.")?
+        .iter()
+        .map(|column| column.to_lowercase())
+        .collect();
+    let mut entries: Vec<WordEntry> = Vec::new();
+    let mut record = StringRecord::new();
+    loop {
+        let line = reader.position().line();
+        if !reader.read_record(&mut record).chain_err(|| format!("Wrong CSV at line {}.", line))? {
+            break;
+        }
+        let line = record.position().map_or(line, |position| position.line());
+        let raw_entry: RawWordEntry = record.deserialize(Some(&header))
+            .chain_err(|| format!("Wrong entry at line {}.", line))?;
+        entries.push(get_word_entry(raw_entry).chain_err(|| format!("Wrong entry at line {}.", line))?);
+    }
+    Ok(entries)
+}
+
+/// Build a word entry from its read fields.
+///
+/// # Parameters:
+/// * raw_entry: Fields read. Word is mandatory but frequency defaults to 1 and
+///     pattern is calculated from word.
+///
+/// # Returns:
+/// * Entry or an error if word is missing, frequency is zero or given pattern is not word one.
+fn get_word_entry(raw_entry: RawWordEntry) -> Result<WordEntry> {
+    let word = match raw_entry.word {
+        Some(word) if !word.is_empty() => word.to_lowercase(),
+        _ => bail!("Word is missing.")
+    };
+    let frequency = raw_entry.frequency.unwrap_or(1);
+    if frequency == 0 {
+        bail!(format!("Frequency of word {} must be greater than zero.", word))
+    }
+    let entry = WordEntry::new(&word, frequency);
+    if let Some(pattern) = raw_entry.pattern {
+        if pattern.trim() != entry.pattern {
+            bail!(format!("Pattern {} does not belong to word {}.", pattern, word))
+        }
+    }
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attack::database;
+    use crate::attack::dictionaries::MemoryDictionary;
+    use test_common::fs::tmp::TestEnvironment;
+    use test_common::system::env::TemporalEnvironmentVariable;
+
+    fn test_dictionary() -> MemoryDictionary {
+        let mut dictionary = MemoryDictionary::from_words("english", &["cat", "dog", "dog", "moon"]);
+        dictionary.add_word("dog");
+        dictionary
+    }
+
+    #[test]
+    fn test_export_dictionary() {
+        let dictionary = test_dictionary();
+        let expected_outputs = [
+            (DictionaryFormat::WordList, "cat\ndog\nmoon\n"),
+            (DictionaryFormat::Csv, "word,pattern,frequency\ncat,0.1.2,1\ndog,0.1.2,3\nmoon,0.1.1.2,1\n"),
+            (DictionaryFormat::JsonLines, "{\"word\":\"cat\",\"pattern\":\"0.1.2\",\"frequency\":1}\n\
+                                           {\"word\":\"dog\",\"pattern\":\"0.1.2\",\"frequency\":3}\n\
+                                           {\"word\":\"moon\",\"pattern\":\"0.1.1.2\",\"frequency\":1}\n")
+        ];
+        for (format, expected_output) in expected_outputs.iter() {
+            let mut output: Vec<u8> = Vec::new();
+            let exported_words = export_dictionary(&dictionary, *format, &mut output).unwrap();
+            assert_eq!(3, exported_words);
+            assert_eq!(*expected_output, String::from_utf8(output).unwrap(), "Wrong {} export.", format);
+        }
+    }
+
+    #[test]
+    fn test_read_word_entries() {
+        let expected_entries = vec![WordEntry::new("cat", 1), WordEntry::new("dog", 3)];
+        let csv = "Frequency, word\n1,Cat\n\n3,\"dog\"\n";
+        assert_eq!(expected_entries, read_word_entries(csv.as_bytes(), DictionaryFormat::Csv).unwrap());
+        let json_lines = "{\"word\": \"cat\"}\n{ \"frequency\": 3, \"word\": \"d\\u006fg\", \"pattern\": \"0.1.2\" }\n";
+        assert_eq!(expected_entries, read_word_entries(json_lines.as_bytes(), DictionaryFormat::JsonLines).unwrap());
+        let word_list = "cat\ndog\ndog\n";
+        assert_eq!(3, read_word_entries(word_list.as_bytes(), DictionaryFormat::WordList).unwrap().len());
+    }
+
+    #[test]
+    fn test_read_wrong_word_entries() {
+        let wrong_inputs = [
+            (DictionaryFormat::Csv, "word,pattern,frequency\ncat,0.1.1,1\n"),
+            (DictionaryFormat::Csv, "word,frequency\ncat,many\n"),
+            (DictionaryFormat::Csv, "pattern\n0.1.2\n"),
+            (DictionaryFormat::Csv, "word,frequency\ncat,0\n"),
+            (DictionaryFormat::Csv, "word,frequency\n\"cat,1\n"),
+            (DictionaryFormat::JsonLines, "{\"word\": \"cat\", \"frequency\": 0}\n"),
+            (DictionaryFormat::JsonLines, "{\"word\": \"cat\", \"frequency\": -1}\n"),
+            (DictionaryFormat::JsonLines, "{\"word\": \"cat\"\n"),
+            (DictionaryFormat::JsonLines, "{\"word\": [\"cat\"]}\n")
+        ];
+        for (format, wrong_input) in wrong_inputs.iter() {
+            assert!(read_word_entries(wrong_input.as_bytes(), *format).is_err(), "{} was accepted.", wrong_input);
+        }
+    }
+
+    #[test]
+    fn test_export_and_import_dictionary() {
+        let temp_dir = TestEnvironment::new();
+        let database_path = temp_dir.path().join("cifra_database.sqlite");
+        let _temp_env = TemporalEnvironmentVariable::new(database::DATABASE_ENV_VAR, database_path.to_str().unwrap());
+        for format in DictionaryFormat::ALL.iter() {
+            let mut exported: Vec<u8> = Vec::new();
+            export_dictionary(&test_dictionary(), *format, &mut exported).unwrap();
+            let language = format!("english_{}", format);
+            let mut dictionary = Dictionary::new(&language, true).unwrap();
+            assert_eq!(3, import_dictionary(&mut dictionary, *format, exported.as_slice()).unwrap());
+            let expected_frequency = if *format == DictionaryFormat::WordList { 1 } else { 3 };
+            assert_eq!(expected_frequency, dictionary.get_word_frequency("dog").unwrap());
+            assert!(dictionary.word_exists("moon"));
+            Dictionary::remove_dictionary(&language).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(DictionaryFormat::Csv, DictionaryFormat::from_path("english.CSV"));
+        assert_eq!(DictionaryFormat::JsonLines, DictionaryFormat::from_path("english.jsonl"));
+        assert_eq!(DictionaryFormat::WordList, DictionaryFormat::from_path("english.txt"));
+        for format in DictionaryFormat::ALL.iter() {
+            assert_eq!(*format, DictionaryFormat::try_from(format.to_string().as_str()).unwrap());
+        }
+    }
+}
@@ -0,0 +1,135 @@
+/// Module to inspect language dictionaries contents.
+///
+/// When an attack fails it is useful to know what its dictionaries actually contain and
+/// which words an attack gets for a given ciphered word pattern.
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::{Result, ResultExt};
+use crate::attack::dictionaries::{LanguageDictionary, get_word_pattern};
+
+/// Statistics about a language dictionary contents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DictionaryStatistics {
+    /// How many different words dictionary has.
+    pub word_count: usize,
+    /// Sum of every word frequency.
+    pub total_frequency: u64,
+    /// Word lengths with how many words have them, from the shortest length to the longest.
+    pub lengths: Vec<(usize, u64)>,
+    /// Word patterns with how many words have them, from the most common pattern to the least.
+    pub patterns: Vec<(String, u64)>,
+    /// Letters with their occurrences at words, weighted by words frequencies, from the
+    /// most frequent to the least.
+    pub letters: Vec<(char, u64)>
+}
+
+/// Get statistics about given dictionary contents.
+///
+/// # Parameters:
+/// * dictionary: Dictionary to inspect.
+///
+/// # Returns:
+/// * Dictionary statistics.
+pub fn get_dictionary_statistics(dictionary: &dyn LanguageDictionary) -> Result<DictionaryStatistics> {
+    let word_frequencies = dictionary.get_all_word_frequencies()?;
+    let mut lengths: HashMap<usize, u64> = HashMap::new();
+    let mut patterns: HashMap<String, u64> = HashMap::new();
+    let mut letters: HashMap<char, u64> = HashMap::new();
+    for (word, frequency) in word_frequencies.iter() {
+        *lengths.entry(word.chars().count()).or_insert(0) += 1;
+        *patterns.entry(get_word_pattern(word)).or_insert(0) += 1;
+        for letter in word.chars() {
+            *letters.entry(letter).or_insert(0) += frequency;
+        }
+    }
+    let mut lengths: Vec<(usize, u64)> = lengths.into_iter().collect();
+    lengths.sort();
+    let mut patterns: Vec<(String, u64)> = patterns.into_iter().collect();
+    patterns.sort_by(|first, second| second.1.cmp(&first.1).then_with(|| first.0.cmp(&second.0)));
+    let mut letters: Vec<(char, u64)> = letters.into_iter().collect();
+    letters.sort_by(|first, second| second.1.cmp(&first.1).then_with(|| first.0.cmp(&second.0)));
+    Ok(DictionaryStatistics {
+        word_count: word_frequencies.len(),
+        total_frequency: word_frequencies.values().sum(),
+        lengths,
+        patterns,
+        letters
+    })
+}
+
+/// Whether given query is a word pattern, like "0.1.2.2", instead of a regex.
+///
+/// # Parameters:
+/// * query: Text to check.
+///
+/// # Returns:
+/// * True if query is a word pattern.
+pub fn is_word_pattern(query: &str) -> bool {
+    !query.is_empty() && query.split('.')
+        .all(|index| !index.is_empty() && index.chars().all(|character| character.is_ascii_digit()))
+}
+
+/// Look for dictionary words matching given query.
+///
+/// # Parameters:
+/// * dictionary: Dictionary to look words into.
+/// * query: Either a word pattern, like "0.1.2.2", or a regex that must match whole words.
+///
+/// # Returns:
+/// * Matching words in alphabetical order, or an error if query is not a valid regex.
+pub fn search_words(dictionary: &dyn LanguageDictionary, query: &str) -> Result<Vec<String>> {
+    let mut found_words = if is_word_pattern(query) {
+        dictionary.get_words_with_pattern(query)?
+    } else {
+        let regex = Regex::new(&format!("^(?:{})$", query))
+            .chain_err(|| format!("{} is neither a word pattern nor a valid regex.", query))?;
+        dictionary.get_all_words()?.into_iter()
+            .filter(|word| regex.is_match(word))
+            .collect()
+    };
+    found_words.sort();
+    Ok(found_words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attack::dictionaries::MemoryDictionary;
+
+    fn test_dictionary() -> MemoryDictionary {
+        MemoryDictionary::from_words("english", &["cat", "dog", "dog", "moon", "see", "tree"])
+    }
+
+    #[test]
+    fn test_get_dictionary_statistics() {
+        let statistics = get_dictionary_statistics(&test_dictionary()).unwrap();
+        assert_eq!(5, statistics.word_count);
+        assert_eq!(6, statistics.total_frequency);
+        assert_eq!(vec![(3, 3), (4, 2)], statistics.lengths);
+        assert_eq!((String::from("0.1.2"), 2), statistics.patterns[0]);
+        assert_eq!(4, statistics.patterns.len());
+        assert_eq!(('e', 4), statistics.letters[0]);
+        assert_eq!(('o', 4), statistics.letters[1]);
+    }
+
+    #[test]
+    fn test_search_words() {
+        let dictionary = test_dictionary();
+        assert_eq!(vec!["cat", "dog"], search_words(&dictionary, "0.1.2").unwrap());
+        assert_eq!(vec!["see"], search_words(&dictionary, &get_word_pattern("add")).unwrap());
+        assert_eq!(vec!["moon", "tree"], search_words(&dictionary, "....").unwrap());
+        assert_eq!(vec!["dog"], search_words(&dictionary, "d.g").unwrap());
+        assert!(search_words(&dictionary, "d(g").is_err());
+    }
+
+    #[test]
+    fn test_is_word_pattern() {
+        assert!(is_word_pattern("0.1.2.2"));
+        assert!(is_word_pattern("0"));
+        assert!(!is_word_pattern("0..1"));
+        assert!(!is_word_pattern("c.t"));
+        assert!(!is_word_pattern(""));
+    }
+}
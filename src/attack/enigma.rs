@@ -0,0 +1,307 @@
+/// Module to attack Enigma ciphered texts.
+///
+/// This is a ciphertext-only attack based on index of coincidence, as described by
+/// James Gillogly. Index of coincidence of a text deciphered with right rotors and
+/// positions is higher than that of random texts, even when plugboard is still unknown,
+/// so attack goes in three phases:
+///
+/// 1. Every rotor order and start position is tried, with no plugboard and every ring
+///     at A. Best candidates are kept.
+/// 2. For every candidate, right and middle ring settings are looked for, moving start
+///     positions at the same time to keep rotors wiring aligned.
+/// 3. Plugboard pairs are added, one at a time, choosing at every step the pair that
+///     raises index of coincidence the most, until no pair improves it.
+///
+/// Left ring setting only matters when middle rotor makes left one step, so it is not
+/// looked for. Attack needs long texts: with a full plugboard, texts of a thousand
+/// letters or more are advisable.
+use rayon::prelude::*;
+
+use crate::{ErrorKind, Result};
+use crate::cipher::enigma::{CHARSET, EnigmaKey, Machine, Reflector, Rotor};
+
+const CHARSET_LENGTH: usize = 26;
+/// How many rotor settings found at first phase go on to next phases.
+const CANDIDATES: usize = 20;
+/// Historical machines had ten plugboard cables.
+const MAX_PLUGBOARD_PAIRS: usize = 10;
+
+/// Rotors, ring settings and positions being assessed.
+#[derive(Debug, Clone)]
+struct Candidate {
+    rotors: Vec<Rotor>,
+    ring_settings: Vec<usize>,
+    positions: Vec<usize>,
+    plugboard: Vec<(usize, usize)>,
+    score: f64
+}
+
+/// Get Enigma ciphered text key.
+///
+/// **You should not use this function. Use *hack_enigma_mp* instead.** This
+/// function is slower than *mp* one because is sequential while the other uses a
+/// multiprocessing approach. This function only stay here to allow comparisons
+/// between sequential and multiprocessing approaches.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered. Only latin letters are taken into account.
+/// * reflector: Reflector used to cipher. Only three rotors machines are attacked, so
+///     it should be B or C.
+/// * rotors: Rotors that might have been used. Every arrangement of three of them is tried.
+///
+/// # Returns:
+/// * A tuple with Enigma key found and index of coincidence of its deciphered text.
+pub fn hack_enigma(ciphered_text: &str, reflector: Reflector, rotors: &[Rotor]) -> Result<(EnigmaKey, f64)> {
+    let letters = get_letters(ciphered_text, reflector)?;
+    let candidates: Vec<Candidate> = get_rotor_tasks(rotors).iter()
+        .map(|(order, left_position)| assess_rotor_order(&letters, reflector, order, *left_position))
+        .collect();
+    let refined_candidates: Vec<Candidate> = get_best_candidates(candidates).into_iter()
+        .map(|candidate| refine_candidate(&letters, reflector, candidate))
+        .collect();
+    get_best_key(refined_candidates, reflector)
+}
+
+/// Get Enigma ciphered text key.
+///
+/// **You should use this function instead of *hack_enigma*.**
+///
+/// Whereas *hack_enigma* uses a sequential approach, this function uses
+/// multiprocessing to improve performance.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered. Only latin letters are taken into account.
+/// * reflector: Reflector used to cipher. Only three rotors machines are attacked, so
+///     it should be B or C.
+/// * rotors: Rotors that might have been used. Every arrangement of three of them is tried.
+///
+/// # Returns:
+/// * A tuple with Enigma key found and index of coincidence of its deciphered text.
+pub fn hack_enigma_mp(ciphered_text: &str, reflector: Reflector, rotors: &[Rotor]) -> Result<(EnigmaKey, f64)> {
+    let letters = get_letters(ciphered_text, reflector)?;
+    let candidates: Vec<Candidate> = get_rotor_tasks(rotors).par_iter()
+        .map(|(order, left_position)| assess_rotor_order(&letters, reflector, order, *left_position))
+        .collect();
+    let refined_candidates: Vec<Candidate> = get_best_candidates(candidates).into_par_iter()
+        .map(|candidate| refine_candidate(&letters, reflector, candidate))
+        .collect();
+    get_best_key(refined_candidates, reflector)
+}
+
+/// Get charset indexes of ciphered text letters, checking attack can be done.
+fn get_letters(ciphered_text: &str, reflector: Reflector) -> Result<Vec<usize>> {
+    if reflector.is_thin() {
+        bail!("Only three rotors machines can be attacked, so reflector should be B or C.")
+    }
+    let letters: Vec<usize> = ciphered_text.to_lowercase().chars()
+        .filter_map(|char| CHARSET.chars().position(|x| x == char))
+        .collect();
+    if letters.len() < 2 {
+        bail!("Ciphered text has not enough letters to be attacked.")
+    }
+    Ok(letters)
+}
+
+/// Split first phase in tasks: every rotor order with every left rotor position.
+///
+/// # Parameters:
+/// * rotors: Rotors that might have been used.
+///
+/// # Returns:
+/// * A list of tuples with rotor order and left rotor position.
+fn get_rotor_tasks(rotors: &[Rotor]) -> Vec<(Vec<Rotor>, usize)> {
+    let regular_rotors: Vec<Rotor> = rotors.iter().cloned()
+        .filter(|rotor| !rotor.is_greek())
+        .collect();
+    let mut tasks = Vec::new();
+    for &left in regular_rotors.iter() {
+        for &middle in regular_rotors.iter().filter(|&&rotor| rotor != left) {
+            for &right in regular_rotors.iter().filter(|&&rotor| rotor != left && rotor != middle) {
+                for left_position in 0..CHARSET_LENGTH {
+                    tasks.push((vec![left, middle, right], left_position));
+                }
+            }
+        }
+    }
+    tasks
+}
+
+/// Find best middle and right positions for given rotor order and left position.
+///
+/// # Parameters:
+/// * letters: Ciphered text letters.
+/// * reflector: Reflector used to cipher.
+/// * order: Rotors from left to right.
+/// * left_position: Left rotor start position.
+///
+/// # Returns:
+/// * Best candidate found.
+fn assess_rotor_order(letters: &[usize], reflector: Reflector, order: &[Rotor], left_position: usize) -> Candidate {
+    let mut best_candidate: Option<Candidate> = None;
+    for middle_position in 0..CHARSET_LENGTH {
+        for right_position in 0..CHARSET_LENGTH {
+            let candidate = assess(letters, reflector, Candidate {
+                rotors: order.to_vec(),
+                ring_settings: vec![0; 3],
+                positions: vec![left_position, middle_position, right_position],
+                plugboard: Vec::new(),
+                score: 0.0
+            });
+            if best_candidate.as_ref().map_or(true, |best| candidate.score > best.score) {
+                best_candidate = Some(candidate);
+            }
+        }
+    }
+    best_candidate.expect("There should be at least a position.")
+}
+
+/// Keep candidates with highest scores.
+fn get_best_candidates(mut candidates: Vec<Candidate>) -> Vec<Candidate> {
+    candidates.sort_by(|one, other| other.score.partial_cmp(&one.score).unwrap_or(std::cmp::Ordering::Equal));
+    candidates.truncate(CANDIDATES);
+    candidates
+}
+
+/// Look for ring settings and plugboard of a candidate found at first phase.
+fn refine_candidate(letters: &[usize], reflector: Reflector, candidate: Candidate) -> Candidate {
+    let mut best_candidate = candidate;
+    // Right ring first, because it sets when middle rotor steps. Middle ring sets when
+    // left rotor steps, which happens much less.
+    for rotor in [2, 1].iter() {
+        let base_candidate = best_candidate.clone();
+        for ring_setting in 1..CHARSET_LENGTH {
+            let mut ring_settings = base_candidate.ring_settings.clone();
+            let mut positions = base_candidate.positions.clone();
+            ring_settings[*rotor] = ring_setting;
+            positions[*rotor] = (positions[*rotor] + ring_setting) % CHARSET_LENGTH;
+            let candidate = assess(letters, reflector, Candidate {
+                ring_settings,
+                positions,
+                ..base_candidate.clone()
+            });
+            if candidate.score > best_candidate.score {
+                best_candidate = candidate;
+            }
+        }
+    }
+    climb_plugboard(letters, reflector, best_candidate)
+}
+
+/// Add plugboard pairs while index of coincidence improves.
+fn climb_plugboard(letters: &[usize], reflector: Reflector, candidate: Candidate) -> Candidate {
+    let mut best_candidate = candidate;
+    while best_candidate.plugboard.len() < MAX_PLUGBOARD_PAIRS {
+        let plugged_letters: Vec<usize> = best_candidate.plugboard.iter()
+            .flat_map(|&(first, second)| vec![first, second])
+            .collect();
+        let base_candidate = best_candidate.clone();
+        for first in (0..CHARSET_LENGTH).filter(|letter| !plugged_letters.contains(letter)) {
+            for second in (first + 1..CHARSET_LENGTH).filter(|letter| !plugged_letters.contains(letter)) {
+                let mut plugboard = base_candidate.plugboard.clone();
+                plugboard.push((first, second));
+                let candidate = assess(letters, reflector, Candidate {
+                    plugboard,
+                    ..base_candidate.clone()
+                });
+                if candidate.score > best_candidate.score {
+                    best_candidate = candidate;
+                }
+            }
+        }
+        if best_candidate.plugboard.len() == base_candidate.plugboard.len() {
+            break;
+        }
+    }
+    best_candidate
+}
+
+/// Decipher letters with candidate settings and score result with its index of coincidence.
+fn assess(letters: &[usize], reflector: Reflector, candidate: Candidate) -> Candidate {
+    let key = EnigmaKey {
+        reflector,
+        rotors: candidate.rotors.clone(),
+        ring_settings: candidate.ring_settings.clone(),
+        positions: candidate.positions.clone(),
+        plugboard: Vec::new()
+    };
+    let mut machine = Machine::new(&key);
+    machine.set_plugboard(&candidate.plugboard);
+    let deciphered_letters = machine.process_indexes(letters);
+    Candidate {
+        score: get_index_of_coincidence(&deciphered_letters),
+        ..candidate
+    }
+}
+
+/// Get index of coincidence of a text already converted to charset indexes.
+///
+/// It does the same than *frequency::get_index_of_coincidence* but it avoids string
+/// conversions, because this is called hundreds of thousands of times per attack.
+fn get_index_of_coincidence(letters: &[usize]) -> f64 {
+    let mut counts = [0_u64; CHARSET_LENGTH];
+    letters.iter().for_each(|&letter| counts[letter] += 1);
+    let total_letters = letters.len() as u64;
+    let coincidences: u64 = counts.iter().map(|&count| count * count.saturating_sub(1)).sum();
+    coincidences as f64 / (total_letters * (total_letters - 1)) as f64
+}
+
+/// Select candidate with highest score and build its key.
+fn get_best_key(candidates: Vec<Candidate>, reflector: Reflector) -> Result<(EnigmaKey, f64)> {
+    let best_candidate = get_best_candidates(candidates).into_iter().next()
+        .ok_or_else(|| ErrorKind::KeyError(String::new(), "At least three regular rotors are needed.".to_string()))?;
+    let charset_chars: Vec<char> = CHARSET.chars().collect();
+    let plugboard: Vec<(char, char)> = best_candidate.plugboard.iter()
+        .map(|&(first, second)| (charset_chars[first], charset_chars[second]))
+        .collect();
+    let key = EnigmaKey::new(reflector, &best_candidate.rotors, &best_candidate.ring_settings,
+                             &best_candidate.positions, &plugboard)?;
+    Ok((key, best_candidate.score))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::read_to_string;
+
+    use crate::cipher::enigma::{cipher, decipher};
+
+    fn get_original_text() -> String {
+        let text = read_to_string("resources/english_book_c1.txt")
+            .expect("Error reading english book.");
+        text.chars().skip(2000).take(2500).collect()
+    }
+
+    #[test]
+    fn test_get_rotor_tasks() {
+        let tasks = get_rotor_tasks(&[Rotor::I, Rotor::II, Rotor::III, Rotor::Beta]);
+        assert_eq!(tasks.len(), 6 * CHARSET_LENGTH);
+    }
+
+    #[test]
+    fn test_climb_plugboard() {
+        let original_text = get_original_text();
+        let key = EnigmaKey::from_key_string("B,II-V-III,AAA,HKT,AQ-BJ-EL-MU-RX").unwrap();
+        let ciphered_text = cipher(&original_text, &key).unwrap();
+        let letters = get_letters(&ciphered_text, Reflector::B).unwrap();
+        let candidate = climb_plugboard(&letters, Reflector::B, Candidate {
+            rotors: key.rotors.clone(),
+            ring_settings: key.ring_settings.clone(),
+            positions: key.positions.clone(),
+            plugboard: Vec::new(),
+            score: 0.0
+        });
+        let (found_key, _) = get_best_key(vec![candidate], Reflector::B).unwrap();
+        assert_eq!(decipher(&ciphered_text, &found_key).unwrap(), original_text);
+    }
+
+    #[test]
+    fn test_hack_enigma_mp() {
+        let original_text = get_original_text();
+        let key = EnigmaKey::from_key_string("B,III-I-II,AAF,MCQ,AQ-EL-RX").unwrap();
+        let ciphered_text = cipher(&original_text, &key).unwrap();
+        let (found_key, _) = hack_enigma_mp(&ciphered_text, Reflector::B, &[Rotor::I, Rotor::II, Rotor::III]).unwrap();
+        assert_eq!(found_key.rotors, key.rotors);
+        assert_eq!(decipher(&ciphered_text, &found_key).unwrap(), original_text);
+    }
+}
@@ -1,4 +1,5 @@
 use linked_hash_map::LinkedHashMap;
+use serde::{Serialize, Serializer};
 use std::collections::{HashMap, HashSet};
 use crate::{ErrorKind, Result, ResultExt};
 use crate::cipher::common::{normalize_text, Counter};
@@ -11,8 +12,14 @@ use crate::{FromStr, FindFromIndex};
 
 /// Module for frequency attacks.
 
-struct LetterHistogram {
-    charset: &'static str,
+/// Lengths of repeated sequences looked for by *get_text_statistics*.
+const REPEATED_SEQUENCES_LENGTHS: [usize; 3] = [3, 4, 5];
+/// Greatest factor counted from repeated sequences separations.
+const MAX_FACTOR: usize = 20;
+
+/// Letter occurrences at a text, ordered from the most frequent letter to the least.
+pub struct LetterHistogram {
+    charset: String,
     total_letters: u64,
     ordered_dict: LinkedHashMap<char, u64>,
     top_matching_letters: Vec<char>,
@@ -34,15 +41,15 @@ impl LetterHistogram {
     ///     letter does not happen in this text (actually that value is
     ///     impossible because it would not exist that key). Keys are ordered from higher
     ///     value to lesser.
-    fn from_text<T>(text: T,
-                    matching_width: usize, charset: &'static str) -> Self
+    pub fn from_text<T>(text: T,
+                    matching_width: usize, charset: &str) -> Self
         where T: AsRef<str> {
         let normalized_words = normalize_text(text);
         let letter_sequence = String::from_iter(normalized_words);
         let letter_counter = Counter::from_iter(letter_sequence.chars());
         let total_letters: u64 = letter_counter.values().sum();
         let new_histogram = LetterHistogram {
-                                charset,
+                                charset: charset.to_string(),
                                 total_letters,
                                 ordered_dict: Default::default(),
                                 top_matching_letters: vec![],
@@ -64,12 +71,12 @@ impl LetterHistogram {
     ///     letter does not happen in this text (actually that value is
     ///     impossible because it would not exist that key). Keys are ordered from higher
     ///     value to lesser.
-    fn from_dict(letters: HashMap<char, u64>,
-                 matching_width: usize, charset: &'static str) -> Self {
+    pub fn from_dict(letters: HashMap<char, u64>,
+                 matching_width: usize, charset: &str) -> Self {
         let total_letters: u64 = letters.values().sum();
         let letter_counter = Counter::from(&letters);
         let new_histogram = LetterHistogram {
-            charset,
+            charset: charset.to_string(),
             total_letters,
             ordered_dict: Default::default(),
             top_matching_letters: vec![],
@@ -159,7 +166,7 @@ impl LetterHistogram {
             .cloned()
             .collect();
         let mut ordered_dict_iter = self.ordered_dict.iter();
-        ordered_dict_iter.advance_by(self.ordered_dict.len()-width);
+        ordered_dict_iter.advance_by(self.ordered_dict.len().saturating_sub(width));
         self.bottom_matching_letters = ordered_dict_iter
             .map(|(key, value)| key)
             .take(width)
@@ -178,7 +185,7 @@ impl LetterHistogram {
     ///
     /// # Returns:
     /// * Probability of occurrence of given letter.
-    fn frequency<T>(&self, key: T) -> Result<f64>
+    pub fn frequency<T>(&self, key: T) -> Result<f64>
         where T: AsRef<str>{
         let ocurrences = self.ordered_dict.get(&char::fromStr(key.as_ref()))
             .chain_err(|| ErrorKind::KeyError(key.as_ref().to_string(), "Error finding letter frequency.".to_string()))?;
@@ -187,10 +194,26 @@ impl LetterHistogram {
     }
 
     /// Return letters whose occurrences we have.
-    fn letters(&self) -> linked_hash_map::Keys<char, u64> {
+    pub fn letters(&self) -> linked_hash_map::Keys<char, u64> {
         self.ordered_dict.keys()
     }
 
+    /// Return how many times given letter appears.
+    ///
+    /// # Parameters:
+    /// * letter: Letter to count.
+    ///
+    /// # Returns:
+    /// * Occurrences of letter, 0 if it is not present.
+    pub fn occurrences(&self, letter: char) -> u64 {
+        *self.ordered_dict.get(&letter).unwrap_or(&0)
+    }
+
+    /// Return how many letters were counted.
+    pub fn total_letters(&self) -> u64 {
+        self.total_letters
+    }
+
     /// Compare two LetterHistogram instances.
     ///
     /// Score is calculated counting how many letters are in matching extremes of
@@ -205,7 +228,7 @@ impl LetterHistogram {
     ///
     /// # Returns:
     /// * Integer score. The higher the more coincidence between two instances.
-    fn match_score(one: &LetterHistogram, other: &LetterHistogram) -> u8 {
+    pub fn match_score(one: &LetterHistogram, other: &LetterHistogram) -> u8 {
         let top_match: u8 = one.top_matching_letters.iter()
             .filter(|letter| other.top_matching_letters.contains(*letter))
             .map(|_| 1)
@@ -338,6 +361,185 @@ pub fn get_substrings<T>(ciphertext: T, step: usize) -> Vec<String>
     substrings
 }
 
+/// Get index of coincidence for given text.
+///
+/// Index of coincidence is the probability of getting the same letter when you pick
+/// two random letters from text. Natural languages have an uneven letter distribution
+/// so their index is higher than that from random texts. Polyalphabetic ciphers flatten
+/// that distribution so their index gets near random.
+///
+/// # Parameters:
+/// * text: Text to analyze. Only charset characters are taken into account.
+/// * charset: Charset whose letters are going to be counted.
+///
+/// # Returns:
+/// * Index of coincidence. If text has less than two letters then 0 is returned.
+pub fn get_index_of_coincidence<T, U>(text: T, charset: U) -> f64
+    where T: AsRef<str>,
+          U: AsRef<str> {
+    let lowercase_text = text.as_ref().to_lowercase();
+    let letters = lowercase_text.chars()
+        .filter(|char| charset.as_ref().contains(*char));
+    let letter_counter: Counter<char> = Counter::from_iter(letters);
+    let total_letters: u64 = letter_counter.values().sum();
+    if total_letters < 2 {
+        return 0.0;
+    }
+    let coincidences: u64 = letter_counter.values()
+        .map(|count| count * (count - 1))
+        .sum();
+    coincidences as f64 / (total_letters * (total_letters - 1)) as f64
+}
+
+
+/// Statistics about a text that help to choose which attack to launch.
+///
+/// Serialized lists of pairs become maps, keeping their order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TextStatistics {
+    /// Letters with their occurrences, from the most frequent to the least.
+    #[serde(serialize_with = "serialize_pairs_as_map")]
+    pub letters: Vec<(char, u64)>,
+    /// Bigrams with their occurrences, from the most frequent to the least.
+    #[serde(serialize_with = "serialize_pairs_as_map")]
+    pub bigrams: Vec<(String, u64)>,
+    /// Trigrams with their occurrences, from the most frequent to the least.
+    #[serde(serialize_with = "serialize_pairs_as_map")]
+    pub trigrams: Vec<(String, u64)>,
+    /// Index of coincidence of charset letters.
+    pub index_of_coincidence: f64,
+    /// Shannon entropy of letters, in bits per letter.
+    pub entropy: f64,
+    /// Repeated sequences with separations between their repetitions, ordered by sequence.
+    #[serde(serialize_with = "serialize_pairs_as_map")]
+    pub repeated_sequences: Vec<(String, Vec<usize>)>,
+    /// Factors of repeated sequences separations with how many separations they divide,
+    /// from the most common factor to the least.
+    #[serde(serialize_with = "serialize_pairs_as_map")]
+    pub factors: Vec<(usize, u64)>
+}
+
+/// Serialize a list of key and value pairs as a map, keeping pairs order.
+fn serialize_pairs_as_map<K, V, S>(pairs: &[(K, V)], serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where K: Serialize,
+          V: Serialize,
+          S: Serializer {
+    serializer.collect_map(pairs.iter().map(|(key, value)| (key, value)))
+}
+
+/// Get frequency statistics of given text.
+///
+/// Repeated sequences are looked for with lengths of 3, 4 and 5 letters, and their
+/// separations factors are counted up to 20.
+///
+/// # Parameters:
+/// * text: Text to analyze.
+/// * charset: Charset whose letters are analyzed. Any other character is ignored by
+///     every statistic.
+///
+/// # Returns:
+/// * Text statistics.
+pub fn get_text_statistics<T, U>(text: T, charset: U) -> TextStatistics
+    where T: AsRef<str>,
+          U: AsRef<str> {
+    let charset_letters: String = normalize_text(&text).concat().chars()
+        .filter(|&letter| charset.as_ref().contains(letter))
+        .collect();
+    let histogram = LetterHistogram::from_text(&charset_letters, 6, charset.as_ref());
+    let letters: Vec<(char, u64)> = histogram.letters()
+        .map(|&letter| (letter, histogram.occurrences(letter)))
+        .filter(|&(_, occurrences)| occurrences > 0)
+        .collect();
+    let mut repeated_sequences: Vec<(String, Vec<usize>)> = REPEATED_SEQUENCES_LENGTHS.iter()
+        .flat_map(|&length| find_repeated_sequences(&charset_letters, length).into_iter())
+        .collect();
+    repeated_sequences.sort();
+    let separations: Vec<usize> = repeated_sequences.iter()
+        .flat_map(|(_, separations)| separations.iter().cloned())
+        .collect();
+    TextStatistics {
+        letters,
+        bigrams: get_ngram_counts(&charset_letters, 2),
+        trigrams: get_ngram_counts(&charset_letters, 3),
+        index_of_coincidence: get_index_of_coincidence(&charset_letters, charset),
+        entropy: get_entropy(&charset_letters),
+        repeated_sequences,
+        factors: get_factor_counts(&separations, MAX_FACTOR)
+    }
+}
+
+/// Count every group of n consecutive letters at given text.
+///
+/// Only letters are taken into account, so groups may span over words boundaries.
+///
+/// # Parameters:
+/// * text: Text to analyze.
+/// * n: Letters per group.
+///
+/// # Returns:
+/// * A list of tuples with every group found and its occurrences, from the most frequent
+///     group to the least. Equally frequent groups are ordered alphabetically.
+pub fn get_ngram_counts<T>(text: T, n: usize) -> Vec<(String, u64)>
+    where T: AsRef<str> {
+    let letters: Vec<char> = normalize_text(&text).concat().chars().collect();
+    if n == 0 || letters.len() < n {
+        return Vec::new();
+    }
+    let ngram_counter: Counter<String> = Counter::from_iter(letters.windows(n)
+        .map(|ngram| ngram.iter().collect::<String>()));
+    let mut ngram_counts: Vec<(String, u64)> = ngram_counter.items()
+        .map(|(ngram, &count)| (ngram.clone(), count))
+        .collect();
+    ngram_counts.sort_by(|one, other| other.1.cmp(&one.1).then_with(|| one.0.cmp(&other.0)));
+    ngram_counts
+}
+
+/// Get Shannon entropy of letters at given text.
+///
+/// Natural languages have an entropy about 4.1 bits per letter, whereas random
+/// texts over a 26 letters alphabet get near 4.7.
+///
+/// # Parameters:
+/// * text: Text to analyze.
+///
+/// # Returns:
+/// * Entropy in bits per letter. If text has no letters then 0 is returned.
+pub fn get_entropy<T>(text: T) -> f64
+    where T: AsRef<str> {
+    let letter_counter: Counter<char> = Counter::from_iter(normalize_text(&text).concat().chars());
+    let total_letters: u64 = letter_counter.values().sum();
+    if total_letters == 0 {
+        return 0.0;
+    }
+    letter_counter.values()
+        .map(|&count| {
+            let probability = count as f64 / total_letters as f64;
+            -probability * probability.log2()
+        })
+        .sum()
+}
+
+/// Count how many separations every factor divides.
+///
+/// Kasiski examination uses this: key length of a polyalphabetic cipher is likely
+/// to be among the most common factors of repeated sequences separations.
+///
+/// # Parameters:
+/// * separations: Separations between repeated sequences.
+/// * max_factor: Greatest factor to count.
+///
+/// # Returns:
+/// * A list of tuples with factors, from 2 to max_factor, and how many separations they
+///     divide, from the most common factor to the least. Factors dividing no separation
+///     are left out.
+pub fn get_factor_counts(separations: &[usize], max_factor: usize) -> Vec<(usize, u64)> {
+    let mut factor_counts: Vec<(usize, u64)> = (2..=max_factor)
+        .map(|factor| (factor, separations.iter().filter(|&&separation| separation % factor == 0).count() as u64))
+        .filter(|&(_, count)| count > 0)
+        .collect();
+    factor_counts.sort_by(|one, other| other.1.cmp(&one.1).then_with(|| one.0.cmp(&other.0)));
+    factor_counts
+}
 
 /// Compare a substring against a known letter histogram.
 ///
@@ -526,6 +728,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_get_ngram_counts() {
+        let ngram_counts = get_ngram_counts("Abab, ba!", 2);
+        assert_eq!(ngram_counts, vec![("ab".to_string(), 2), ("ba".to_string(), 2), ("bb".to_string(), 1)]);
+        assert!(get_ngram_counts("ab", 3).is_empty());
+    }
+
+    #[test]
+    fn test_get_entropy() {
+        assert_eq!(get_entropy("aaaa"), 0.0);
+        assert!(get_entropy("abab").approx_eq(1.0, (0.0, 2)));
+        assert!(get_entropy("abcd").approx_eq(2.0, (0.0, 2)));
+        assert_eq!(get_entropy("1234"), 0.0);
+    }
+
+    #[test]
+    fn test_get_factor_counts() {
+        let factor_counts = get_factor_counts(&[8, 24, 32, 48], 8);
+        assert_eq!(factor_counts, vec![(2, 4), (4, 4), (8, 4), (3, 2), (6, 2)]);
+    }
+
+    #[test]
+    fn test_get_text_statistics() {
+        let ciphered_text = "PPQCA XQVEKG YBNKMAZU YBNGBAL JON I TSZM JYIM. VRAG VOHT VRAU C TKSG. DDWUO XITLAZU VAVV RAZ C VKB QP IWPOU";
+        let statistics = get_text_statistics(ciphered_text, DEFAULT_CHARSET);
+        assert_eq!(statistics.letters[0], ('a', 8));
+        assert_eq!(statistics.letters.iter().map(|(_, count)| count).sum::<u64>(), 85);
+        assert!(statistics.repeated_sequences.contains(&("ybn".to_string(), vec![8])));
+        assert_eq!(statistics.factors[..3], [(2, 5), (4, 5), (8, 5)]);
+        assert_eq!(statistics.index_of_coincidence, get_index_of_coincidence(ciphered_text, DEFAULT_CHARSET));
+        // Only given charset letters should be counted.
+        let statistics = get_text_statistics("Abba, a cab", "ab");
+        assert_eq!(statistics.letters, vec![('a', 4), ('b', 3)]);
+        assert_eq!(statistics.bigrams, vec![("aa".to_string(), 2), ("ab".to_string(), 2),
+                                            ("ba".to_string(), 1), ("bb".to_string(), 1)]);
+        assert_eq!(statistics.entropy, get_entropy("abbaaab"));
+        assert_eq!(statistics.index_of_coincidence, get_index_of_coincidence("abbaaab", "ab"));
+    }
+
     #[test]
     fn test_get_substrings() {
         let ciphertext = "abc dabc dabcd abcd";
@@ -536,6 +777,14 @@ mod tests {
         assert_eq!(substrings[3], "dddd");
     }
 
+    #[test]
+    fn test_get_index_of_coincidence() {
+        assert_eq!(get_index_of_coincidence("Aa, a!", DEFAULT_CHARSET), 1.0);
+        assert_eq!(get_index_of_coincidence("abcd", DEFAULT_CHARSET), 0.0);
+        assert_eq!(get_index_of_coincidence("aabb", DEFAULT_CHARSET), 4.0 / 12.0);
+        assert_eq!(get_index_of_coincidence("a", DEFAULT_CHARSET), 0.0);
+    }
+
     #[rstest]
     fn test_match_substring(language_histogram: LetterHistogram) {
         let substring = "PAEBABANZIAHAKDXAAAKIU";
@@ -0,0 +1,456 @@
+/// Module to attack homophonic substitution ciphered texts.
+///
+/// Homophonic substitution flattens symbol frequencies, so frequency analysis and word
+/// patterns are useless against it. Instead, this module uses a hill climbing approach:
+/// every symbol is assigned a letter and assignments are changed, one symbol at a time,
+/// while every change makes deciphered text score better against an n-gram language
+/// model.
+///
+/// Hill climbing can get stuck at local maximums so, at first, changes that make
+/// score slightly worse are accepted too. Besides, the whole climb is restarted from
+/// random assignments several times, keeping the best result.
+use rand::Rng;
+use rayon::prelude::*;
+
+use crate::{ErrorKind, Result, ResultExt};
+use crate::attack::dictionaries::{DictionaryStore};
+use crate::attack::ngrams::NgramModel;
+use crate::cipher::homophonic::{HomophonicKey, SymbolSet};
+
+/// How many times hill climbing is started from scratch.
+const RESTARTS: usize = 8;
+/// How many random changes are tried at every hill climb.
+const ITERATIONS: usize = 500_000;
+/// How much worse, at first, a change can make score and still be accepted.
+const START_TEMPERATURE: f64 = 5.0;
+/// How much deciphered letter frequencies deviations from language ones are penalized.
+const DIVERGENCE_WEIGHT: f64 = 10.0;
+/// N-grams length used for language models built from dictionaries.
+const NGRAM_LENGTH: usize = 4;
+
+/// Get homophonic substitution ciphered text key.
+///
+/// A language model is built from every available dictionary and text is attacked
+/// against each of them. Deciphered texts have no spaces, so they can not be checked
+/// against dictionaries words. Instead, key whose deciphered text fits best its
+/// language model is returned.
+///
+/// **You should not use this function. Use *hack_homophonic_mp* instead.** This
+/// function is slower than *mp* one because is sequential while the other uses a
+/// multiprocessing approach. This function only stay here to allow comparisons
+/// between sequential and multiprocessing approaches.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * symbol_set: Symbols used to cipher text.
+/// * charset: Letters plaintext was written with.
+/// * store: Dictionaries to build language models from.
+///
+/// # Returns:
+/// * A tuple with homophonic key found and average logarithmic probability of
+///     deciphered text n-grams at its language model.
+pub fn hack_homophonic(ciphered_text: &str, symbol_set: &SymbolSet, charset: &str,
+                       store: &dyn DictionaryStore) -> Result<(HomophonicKey, f64)> {
+    let available_languages = store.get_dictionaries_names()
+        .chain_err(|| ErrorKind::DatabaseError(String::from("We could not get dictionaries names.")))?;
+    let results: Vec<Result<(HomophonicKey, f64)>> = available_languages.iter()
+        .map(|language| hack_homophonic_at_language(ciphered_text, symbol_set, charset, language, store))
+        .collect();
+    get_best_key(results)
+}
+
+/// Get homophonic substitution ciphered text key.
+///
+/// A language model is built from every available dictionary and text is attacked
+/// against each of them. Deciphered texts have no spaces, so they can not be checked
+/// against dictionaries words. Instead, key whose deciphered text fits best its
+/// language model is returned.
+///
+/// **You should use this function instead of *hack_homophonic*.**
+///
+/// Whereas *hack_homophonic* uses a sequential approach, this function uses
+/// multiprocessing to improve performance.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * symbol_set: Symbols used to cipher text.
+/// * charset: Letters plaintext was written with.
+/// * store: Dictionaries to build language models from.
+///
+/// # Returns:
+/// * A tuple with homophonic key found and average logarithmic probability of
+///     deciphered text n-grams at its language model.
+pub fn hack_homophonic_mp(ciphered_text: &str, symbol_set: &SymbolSet, charset: &str,
+                          store: &dyn DictionaryStore) -> Result<(HomophonicKey, f64)> {
+    let available_languages = store.get_dictionaries_names()
+        .chain_err(|| ErrorKind::DatabaseError(String::from("We could not get dictionaries names.")))?;
+    let results: Vec<Result<(HomophonicKey, f64)>> = available_languages.par_iter()
+        .map(|language| hack_homophonic_at_language(ciphered_text, symbol_set, charset, language, store))
+        .collect();
+    get_best_key(results)
+}
+
+/// Attack text with a language model built from given language dictionary.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * symbol_set: Symbols used to cipher text.
+/// * charset: Letters plaintext was written with.
+/// * language: Dictionary to build language model from.
+/// * store: Dictionaries to take language dictionary from.
+///
+/// # Returns:
+/// * A tuple with homophonic key found and average logarithmic probability of
+///     deciphered text n-grams at language model.
+fn hack_homophonic_at_language(ciphered_text: &str, symbol_set: &SymbolSet, charset: &str,
+                               language: &str, store: &dyn DictionaryStore) -> Result<(HomophonicKey, f64)> {
+    let words = store.open_dictionary(language)?.get_all_words()?;
+    let model = NgramModel::from_words(&words, NGRAM_LENGTH, charset)?;
+    let (key, score) = hack_homophonic_with_model(ciphered_text, symbol_set, &model)?;
+    // Scores depend on text length, so they are averaged to be compared between models.
+    let ngrams_count = symbol_set.split(ciphered_text).len().saturating_sub(NGRAM_LENGTH - 1).max(1);
+    Ok((key, score / ngrams_count as f64))
+}
+
+/// Select key with highest score.
+///
+/// # Parameters:
+/// * results: Keys found with their scores.
+///
+/// # Returns:
+/// * A tuple with best key and its score.
+fn get_best_key(results: Vec<Result<(HomophonicKey, f64)>>) -> Result<(HomophonicKey, f64)> {
+    let mut best_result: Option<(HomophonicKey, f64)> = None;
+    for result in results {
+        let (key, score) = result?;
+        if best_result.as_ref().map_or(true, |(_, best_score)| score > *best_score) {
+            best_result = Some((key, score));
+        }
+    }
+    match best_result {
+        Some(best_result) => Ok(best_result),
+        None => bail!(ErrorKind::DatabaseError(String::from("There are no dictionaries to attack with.")))
+    }
+}
+
+/// Get homophonic substitution ciphered text key using given language model.
+///
+/// Hill climbing restarts are run in parallel.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered. The longer the better, with few occurrences
+///     per symbol hill climbing gets unreliable.
+/// * symbol_set: Symbols used to cipher text.
+/// * model: Model of the language plaintext is supposed to be written in. Its charset
+///     is used as key charset.
+///
+/// # Returns:
+/// * A tuple with homophonic key found and its deciphered text score against model.
+///     Letters not present at deciphered text get no symbols at key.
+pub fn hack_homophonic_with_model(ciphered_text: &str, symbol_set: &SymbolSet,
+                                  model: &NgramModel) -> Result<(HomophonicKey, f64)> {
+    let climber = HillClimber::new(ciphered_text, symbol_set, model)?;
+    let best_climb = (0..RESTARTS).into_par_iter()
+        .map(|_| climber.climb())
+        .reduce_with(|one, other| if other.score > one.score { other } else { one })
+        .expect("Hill climbing needs at least a restart.");
+    let score = best_climb.score;
+    Ok((climber.get_key(best_climb)?, score))
+}
+
+/// Symbol assignments found after a hill climb.
+#[derive(Debug, Clone)]
+struct Climb {
+    assignments: Vec<usize>,
+    score: f64
+}
+
+/// Deciphering state while climbing.
+///
+/// Deciphered letters, their counts and their n-grams score are kept up to date at every
+/// change, so a change only needs to rescore n-grams around the changed symbol.
+#[derive(Debug, Clone)]
+struct Deciphering {
+    assignments: Vec<usize>,
+    letters: Vec<usize>,
+    letter_counts: Vec<usize>,
+    ngrams_score: f64,
+    score: f64
+}
+
+/// Hill climbing state shared by all restarts.
+///
+/// Ciphered text is represented as indexes to the list of different symbols found at
+/// it, and assignments as the charset index of the letter given to every symbol.
+struct HillClimber<'a> {
+    symbols: Vec<String>,
+    symbol_indexes: Vec<usize>,
+    symbol_positions: Vec<Vec<usize>>,
+    symbol_windows: Vec<Vec<usize>>,
+    symbol_set: SymbolSet,
+    model: &'a NgramModel,
+    charset_length: usize
+}
+
+impl<'a> HillClimber<'a> {
+    fn new(ciphered_text: &str, symbol_set: &SymbolSet, model: &'a NgramModel) -> Result<Self> {
+        let mut symbols: Vec<String> = Vec::new();
+        let mut symbol_indexes: Vec<usize> = Vec::new();
+        for symbol in symbol_set.split(ciphered_text) {
+            let index = match symbols.iter().position(|x| *x == symbol) {
+                Some(index) => index,
+                None => {
+                    symbols.push(symbol);
+                    symbols.len() - 1
+                }
+            };
+            symbol_indexes.push(index);
+        }
+        if symbol_indexes.is_empty() {
+            bail!("Ciphered text has no symbols from symbol set.")
+        }
+        let mut symbol_positions = vec![Vec::new(); symbols.len()];
+        symbol_indexes.iter().enumerate()
+            .for_each(|(position, &symbol)| symbol_positions[symbol].push(position));
+        // Start of every n-gram that has any occurrence of each symbol. Occurrences closer
+        // than n letters share n-grams, so windows must be sorted to remove repeated ones.
+        let n = model.n();
+        let last_window = symbol_indexes.len().saturating_sub(n);
+        let symbol_windows: Vec<Vec<usize>> = symbol_positions.iter()
+            .map(|positions| {
+                let mut windows: Vec<usize> = positions.iter()
+                    .flat_map(|&position| position.saturating_sub(n - 1)..=position.min(last_window))
+                    .filter(|&window| window + n <= symbol_indexes.len())
+                    .collect();
+                windows.sort_unstable();
+                windows.dedup();
+                windows
+            })
+            .collect();
+        Ok(HillClimber {
+            symbols,
+            symbol_indexes,
+            symbol_positions,
+            symbol_windows,
+            symbol_set: symbol_set.clone(),
+            model,
+            charset_length: model.charset().chars().count()
+        })
+    }
+
+    /// Give every symbol a random letter, following language letter frequencies.
+    fn get_random_assignments(&self) -> Vec<usize> {
+        let mut rng = rand::thread_rng();
+        let probabilities: Vec<f64> = (0..self.charset_length)
+            .map(|letter| 10_f64.powf(self.model.letter_log_probability(letter)))
+            .collect();
+        let total: f64 = probabilities.iter().sum();
+        (0..self.symbols.len())
+            .map(|_| {
+                let mut threshold = rng.gen_range(0.0, total);
+                probabilities.iter()
+                    .position(|probability| {
+                        threshold -= probability;
+                        threshold <= 0.0
+                    })
+                    .unwrap_or(self.charset_length - 1)
+            })
+            .collect()
+    }
+
+    /// Run a hill climb from random assignments.
+    ///
+    /// At first, changes that make score slightly worse are accepted too, so climb can
+    /// cross valleys between local maximums. That tolerance lowers at every iteration
+    /// until only improvements are accepted and climb ends at a local maximum.
+    ///
+    /// # Returns:
+    /// * Best assignments found.
+    fn climb(&self) -> Climb {
+        let mut rng = rand::thread_rng();
+        let mut current = self.assess(self.get_random_assignments());
+        let mut best = Climb { assignments: current.assignments.clone(), score: current.score };
+        for iteration in 0..ITERATIONS {
+            let temperature = START_TEMPERATURE * (1.0 - iteration as f64 / ITERATIONS as f64);
+            let symbol = rng.gen_range(0, self.symbols.len());
+            let letter = rng.gen_range(0, self.charset_length);
+            let previous_letter = current.assignments[symbol];
+            if letter == previous_letter {
+                continue;
+            }
+            let previous_score = current.score;
+            self.change(&mut current, symbol, letter);
+            let delta = current.score - previous_score;
+            if delta >= 0.0 || rng.gen::<f64>() < (delta / temperature).exp() {
+                if current.score > best.score {
+                    best = Climb { assignments: current.assignments.clone(), score: current.score };
+                }
+            } else {
+                self.change(&mut current, symbol, previous_letter);
+            }
+        }
+        self.climb_to_local_maximum(self.assess(best.assignments))
+    }
+
+    /// Change symbol assignments while deciphered text score improves.
+    ///
+    /// # Parameters:
+    /// * start: Deciphering to start from.
+    ///
+    /// # Returns:
+    /// * Assignments at local maximum.
+    fn climb_to_local_maximum(&self, start: Deciphering) -> Climb {
+        let mut current = start;
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for symbol in 0..self.symbols.len() {
+                for letter in 0..self.charset_length {
+                    let previous_letter = current.assignments[symbol];
+                    if letter == previous_letter {
+                        continue;
+                    }
+                    let previous_score = current.score;
+                    self.change(&mut current, symbol, letter);
+                    if current.score > previous_score {
+                        improved = true;
+                    } else {
+                        self.change(&mut current, symbol, previous_letter);
+                    }
+                }
+            }
+        }
+        Climb {
+            assignments: current.assignments,
+            score: current.score
+        }
+    }
+
+    /// Score text deciphered with given assignments.
+    fn assess(&self, assignments: Vec<usize>) -> Deciphering {
+        let letters: Vec<usize> = self.symbol_indexes.iter()
+            .map(|&symbol| assignments[symbol])
+            .collect();
+        let mut letter_counts = vec![0_usize; self.charset_length];
+        letters.iter().for_each(|&letter| letter_counts[letter] += 1);
+        let ngrams_score = self.model.score_indexes(&letters);
+        let mut deciphering = Deciphering {
+            assignments,
+            letters,
+            letter_counts,
+            ngrams_score,
+            score: 0.0
+        };
+        deciphering.score = self.get_score(&deciphering);
+        deciphering
+    }
+
+    /// Give a new letter to a symbol, updating deciphering state.
+    fn change(&self, deciphering: &mut Deciphering, symbol: usize, letter: usize) {
+        let n = self.model.n();
+        let windows = &self.symbol_windows[symbol];
+        let windows_score = |letters: &[usize]| -> f64 {
+            windows.iter()
+                .map(|&window| self.model.score_indexes(&letters[window..window + n]))
+                .sum()
+        };
+        let previous_windows_score = windows_score(&deciphering.letters);
+        let previous_letter = deciphering.assignments[symbol];
+        let positions = &self.symbol_positions[symbol];
+        positions.iter().for_each(|&position| deciphering.letters[position] = letter);
+        deciphering.letter_counts[previous_letter] -= positions.len();
+        deciphering.letter_counts[letter] += positions.len();
+        deciphering.assignments[symbol] = letter;
+        deciphering.ngrams_score += windows_score(&deciphering.letters) - previous_windows_score;
+        deciphering.score = self.get_score(deciphering);
+    }
+
+    /// Get deciphering score.
+    ///
+    /// N-grams score alone favours giving most symbols to a few common letters, because
+    /// texts like "thethesethe" score better than many real ones. So score is penalized
+    /// with how far deciphered letter frequencies are from those of the language.
+    fn get_score(&self, deciphering: &Deciphering) -> f64 {
+        let total_letters = deciphering.letters.len() as f64;
+        // Kullback-Leibler divergence of deciphered letters distribution from language one.
+        let divergence: f64 = deciphering.letter_counts.iter().enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(letter, &count)| {
+                let frequency = count as f64 / total_letters;
+                frequency * (frequency.log10() - self.model.letter_log_probability(letter))
+            })
+            .sum();
+        deciphering.ngrams_score - DIVERGENCE_WEIGHT * total_letters * divergence
+    }
+
+    /// Build a key from a climb result.
+    fn get_key(&self, climb: Climb) -> Result<HomophonicKey> {
+        let mut homophones: Vec<Vec<String>> = vec![Vec::new(); self.charset_length];
+        for (symbol, &letter) in self.symbols.iter().zip(climb.assignments.iter()) {
+            homophones[letter].push(symbol.clone());
+        }
+        homophones.iter_mut().for_each(|symbols| symbols.sort());
+        HomophonicKey::new(self.symbol_set.clone(), self.model.charset(), homophones)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::read_to_string;
+
+    use crate::cipher::homophonic::{cipher, decipher, DEFAULT_CHARSET};
+
+    fn get_original_text() -> String {
+        let text = read_to_string("resources/english_book_c1.txt")
+            .expect("Error reading english book.");
+        text.chars().skip(2000).take(1500).collect()
+    }
+
+    fn get_english_model() -> NgramModel {
+        NgramModel::from_text_file("resources/english_book.txt", 4, DEFAULT_CHARSET)
+            .expect("Error building english model.")
+    }
+
+    #[test]
+    fn test_incremental_score_matches_full_rescore() {
+        let model = NgramModel::from_text(&get_original_text(), 3, DEFAULT_CHARSET).unwrap();
+        // Many occurrences of the same symbol are closer than n-gram length.
+        let ciphered_text = "01010201010302010405050102030101";
+        let climber = HillClimber::new(ciphered_text, &SymbolSet::Codes(2), &model).unwrap();
+        let mut rng = rand::thread_rng();
+        let mut deciphering = climber.assess(climber.get_random_assignments());
+        for _ in 0..1000 {
+            let symbol = rng.gen_range(0, climber.symbols.len());
+            let letter = rng.gen_range(0, climber.charset_length);
+            climber.change(&mut deciphering, symbol, letter);
+        }
+        let rescored = climber.assess(deciphering.assignments.clone());
+        assert!((rescored.ngrams_score - deciphering.ngrams_score).abs() < 1e-6,
+                "Incremental score {} but full rescore {}", deciphering.ngrams_score, rescored.ngrams_score);
+        assert!((rescored.score - deciphering.score).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_hack_homophonic_with_model() {
+        let original_text = get_original_text();
+        let symbol_set = SymbolSet::Codes(2);
+        let sample_text = read_to_string("resources/english_book.txt").unwrap();
+        let sample_text: String = sample_text.chars().take(20000).collect();
+        let key = HomophonicKey::generate(symbol_set.clone(), DEFAULT_CHARSET, &sample_text).unwrap();
+        let ciphered_text = cipher(&original_text, &key).unwrap();
+        let (found_key, _) = hack_homophonic_with_model(&ciphered_text, &symbol_set, &get_english_model()).unwrap();
+        let expected_text: String = original_text.to_lowercase().chars()
+            .filter(|char| DEFAULT_CHARSET.contains(*char))
+            .collect();
+        let deciphered_text = decipher(&ciphered_text, &found_key).unwrap();
+        let right_letters = deciphered_text.chars().zip(expected_text.chars())
+            .filter(|(found, expected)| found == expected)
+            .count();
+        // Symbols appearing only once or twice can be wrongly assigned, but they
+        // should be very few.
+        assert!(right_letters as f64 / expected_text.len() as f64 > 0.95,
+                "Deciphered text was: {}", deciphered_text);
+    }
+}
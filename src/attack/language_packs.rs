@@ -0,0 +1,99 @@
+/// Module to install the dictionaries bundled with cifra.
+///
+/// Attacks need dictionaries, but a new install has none. Cifra carries a book for every
+/// language it knows well, the same ones at *resources* folder, so their dictionaries can
+/// be built right away without any external file. N-gram models used by hill climbing
+/// attacks are built from those same dictionaries, so they are ready too.
+use crate::Result;
+use crate::attack::dictionaries::{DatabaseStore, Dictionary, PopulationReport, get_word_counts_from_reader};
+use crate::cipher::normalization::Normalization;
+
+/// Languages bundled with cifra along with the book their dictionaries are built from.
+const BUNDLED_BOOKS: [(&'static str, &'static str); 4] = [
+    ("english", include_str!("../../resources/english_book.txt")),
+    ("spanish", include_str!("../../resources/spanish_book.txt")),
+    ("french", include_str!("../../resources/french_book.txt")),
+    ("german", include_str!("../../resources/german_book.txt"))];
+
+/// Outcome of installing a bundled language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledLanguage {
+    /// Language name.
+    pub language: String,
+    /// Words added to language dictionary, or None if language was already installed
+    /// and it was left as it was.
+    pub report: Option<PopulationReport>
+}
+
+/// Get languages bundled with cifra.
+pub fn get_bundled_languages() -> Vec<&'static str> {
+    BUNDLED_BOOKS.iter().map(|&(language, _)| language).collect()
+}
+
+/// Build dictionaries for every bundled language.
+///
+/// Languages already present at database are kept as they are, so running this many
+/// times at the same machine does not add the same words again, unless they are asked
+/// to be reinstalled.
+///
+/// # Parameters:
+/// * store: Database store to install dictionaries into.
+/// * reinstall: If true, languages already present are removed and built again.
+/// * normalization: Unicode normalization to apply to bundled books.
+///
+/// # Returns:
+/// * What happened to every bundled language.
+pub fn install_default_dictionaries(store: &DatabaseStore, reinstall: bool,
+                                    normalization: Normalization) -> Result<Vec<InstalledLanguage>> {
+    let installed_languages = Dictionary::get_dictionaries_names_from(&store.open_database()?)?;
+    let mut installations = Vec::new();
+    for &(language, book) in BUNDLED_BOOKS.iter() {
+        let already_installed = installed_languages.iter().any(|installed| installed == language);
+        if already_installed && !reinstall {
+            installations.push(InstalledLanguage { language: language.to_string(), report: None });
+            continue;
+        }
+        let database = store.open_database()?;
+        if already_installed {
+            Dictionary::remove_dictionary_from(&database, language)?;
+        }
+        let mut dictionary = Dictionary::from_database(database, language, true)?;
+        let word_counts = get_word_counts_from_reader(book.as_bytes(), normalization)?;
+        let report = dictionary.add_words_with_frequencies(&word_counts)?;
+        installations.push(InstalledLanguage { language: language.to_string(), report: Some(report) });
+    }
+    Ok(installations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_common::fs::tmp::TestEnvironment;
+
+    use crate::attack::dictionaries::{DictionaryStore, identify_language};
+    use crate::attack::dictionaries::tests::{ENGLISH_TEXT_WITH_PUNCTUATIONS_MARKS, GERMAN_TEXT_WITH_PUNCTUATIONS_MARKS};
+
+    #[test]
+    fn test_install_default_dictionaries() {
+        let test_folder = TestEnvironment::new();
+        let database_path = test_folder.path().join("cifra_database.sqlite");
+        let store = DatabaseStore::at(&database_path);
+        let installations = install_default_dictionaries(&store, false, Normalization::Nfc).unwrap();
+        assert_eq!(get_bundled_languages(), installations.iter().map(|installation| installation.language.as_str()).collect::<Vec<&str>>());
+        assert!(installations.iter().all(|installation| installation.report.unwrap().new_words > 1000));
+        let mut installed_languages = store.get_dictionaries_names().unwrap();
+        installed_languages.sort();
+        assert_eq!(vec!["english", "french", "german", "spanish"], installed_languages);
+        let english_frequency = store.open_dictionary("english").unwrap().get_word_frequency("the").unwrap();
+        assert_eq!(Some("english"), identify_language(ENGLISH_TEXT_WITH_PUNCTUATIONS_MARKS, &store).unwrap().winner());
+        assert_eq!(Some("german"), identify_language(GERMAN_TEXT_WITH_PUNCTUATIONS_MARKS, &store).unwrap().winner());
+        // Installing again keeps dictionaries as they were.
+        let installations = install_default_dictionaries(&store, false, Normalization::Nfc).unwrap();
+        assert!(installations.iter().all(|installation| installation.report.is_none()));
+        assert_eq!(english_frequency, store.open_dictionary("english").unwrap().get_word_frequency("the").unwrap());
+        // Reinstalling builds them again from scratch.
+        let installations = install_default_dictionaries(&store, true, Normalization::Nfc).unwrap();
+        assert!(installations.iter().all(|installation| installation.report.unwrap().existing_words == 0));
+        assert_eq!(english_frequency, store.open_dictionary("english").unwrap().get_word_frequency("the").unwrap());
+    }
+}
@@ -0,0 +1,300 @@
+/// Module to check words membership without querying dictionaries.
+///
+/// Brute force attacks identify the language of every text they get deciphering with each
+/// possible key. Almost all of those texts are garbage whose words are at no dictionary,
+/// but every one of those words would be a database query. A *CachedStore* reads every
+/// language words, along with their frequencies, the first time that language is opened,
+/// so later word lookups are answered from memory without asking database.
+///
+/// Cached words are shared by every thread using the same *CachedStore*, so multiprocessing
+/// attacks read every dictionary only once. Different languages are read at the same time
+/// if different threads need them.
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::Result;
+use crate::attack::dictionaries::{DictionaryStore, LanguageDictionary};
+use crate::attack::segmentation::Segmenter;
+use crate::cipher::normalization::Normalization;
+
+/// Language data cached once for every thread.
+#[derive(Debug)]
+struct LanguageCache {
+    word_frequencies: HashMap<String, u64>,
+    total_frequency: u64,
+    /// Only texts written without spaces need a segmenter, so it is built the first time one is asked for.
+    segmenter: Mutex<Option<Arc<Segmenter>>>
+}
+
+/// Slot where a language cache is placed once it is built.
+type LanguageCacheSlot = Arc<Mutex<Option<Arc<LanguageCache>>>>;
+
+/// Store that answers word lookups from memory instead of asking its dictionaries.
+///
+/// Dictionaries are read when they are first opened, so a CachedStore is meant to be used
+/// along a single attack. Words added to dictionaries afterwards are not seen by it.
+pub struct CachedStore<S> {
+    store: S,
+    dictionaries_names: Mutex<Option<Vec<String>>>,
+    languages: Mutex<HashMap<String, LanguageCacheSlot>>
+}
+
+impl<S> CachedStore<S>
+    where S: DictionaryStore {
+    /// Create a cache for given store.
+    ///
+    /// # Parameters:
+    /// * store: Dictionaries to cache. It can be a reference to a store too.
+    pub fn new(store: S) -> Self {
+        CachedStore {
+            store,
+            dictionaries_names: Mutex::new(None),
+            languages: Mutex::new(HashMap::new())
+        }
+    }
+
+    /// Get cached data for given language, building it if this is the first time it is needed.
+    ///
+    /// Every language has its own lock, kept while building, so threads asking for the same
+    /// language at the same time wait for the first one instead of reading dictionary again,
+    /// while threads asking for different languages build them in parallel.
+    fn get_language_cache(&self, _language: &str) -> Result<Arc<LanguageCache>> {
+        let language_slot = {
+            let mut languages = self.languages.lock().expect("Poisoned language cache.");
+            Arc::clone(languages.entry(_language.to_string()).or_default())
+        };
+        let mut language_cache = language_slot.lock().expect("Poisoned language cache.");
+        if let Some(language_cache) = language_cache.as_ref() {
+            return Ok(Arc::clone(language_cache));
+        }
+        let word_frequencies = self.store.open_dictionary(_language)?.get_all_word_frequencies()?;
+        let built_cache = Arc::new(LanguageCache {
+            total_frequency: word_frequencies.values().sum(),
+            word_frequencies,
+            segmenter: Mutex::new(None)
+        });
+        *language_cache = Some(Arc::clone(&built_cache));
+        Ok(built_cache)
+    }
+}
+
+impl<S> DictionaryStore for CachedStore<S>
+    where S: DictionaryStore {
+    fn get_dictionaries_names(&self) -> Result<Vec<String>> {
+        let mut dictionaries_names = self.dictionaries_names.lock().expect("Poisoned dictionaries names cache.");
+        if dictionaries_names.is_none() {
+            *dictionaries_names = Some(self.store.get_dictionaries_names()?);
+        }
+        Ok(dictionaries_names.clone().unwrap())
+    }
+
+    fn open_dictionary(&self, _language: &str) -> Result<Box<dyn LanguageDictionary + '_>> {
+        Ok(Box::new(CachedDictionary {
+            language: _language.to_string(),
+            cache: self.get_language_cache(_language)?,
+            store: &self.store,
+            dictionary: OnceCell::new()
+        }))
+    }
+
+    fn normalization(&self) -> Normalization {
+        self.store.normalization()
+    }
+}
+
+/// Dictionary whose words are looked up at its language cache.
+///
+/// Real dictionary is only opened when anything other than words and their frequencies
+/// is needed, like words with a given pattern.
+pub struct CachedDictionary<'a, S> {
+    language: String,
+    cache: Arc<LanguageCache>,
+    store: &'a S,
+    dictionary: OnceCell<Box<dyn LanguageDictionary + 'a>>
+}
+
+impl<'a, S> CachedDictionary<'a, S>
+    where S: DictionaryStore {
+    /// Get real dictionary, opening it the first time it is needed.
+    fn dictionary(&self) -> Result<&(dyn LanguageDictionary + 'a)> {
+        if let Some(dictionary) = self.dictionary.get() {
+            return Ok(dictionary.as_ref());
+        }
+        let dictionary = self.store.open_dictionary(&self.language)?;
+        Ok(self.dictionary.get_or_init(|| dictionary).as_ref())
+    }
+}
+
+impl<'a, S> LanguageDictionary for CachedDictionary<'a, S>
+    where S: DictionaryStore {
+    fn language(&self) -> &str {
+        &self.language
+    }
+
+    fn word_exists(&self, _word: &str) -> bool {
+        self.cache.word_frequencies.contains_key(_word)
+    }
+
+    fn get_word_frequency(&self, _word: &str) -> Result<u64> {
+        Ok(self.cache.word_frequencies.get(_word).cloned().unwrap_or(0))
+    }
+
+    fn get_total_frequency(&self) -> Result<u64> {
+        Ok(self.cache.total_frequency)
+    }
+
+    fn get_words_with_pattern(&self, pattern: &str) -> Result<Vec<String>> {
+        self.dictionary()?.get_words_with_pattern(pattern)
+    }
+
+    fn get_words_with_patterns(&self, patterns: &[String]) -> Result<HashMap<String, Vec<String>>> {
+        self.dictionary()?.get_words_with_patterns(patterns)
+    }
+
+    fn get_all_words(&self) -> Result<Vec<String>> {
+        Ok(self.cache.word_frequencies.keys().cloned().collect())
+    }
+
+    fn get_all_word_frequencies(&self) -> Result<HashMap<String, u64>> {
+        Ok(self.cache.word_frequencies.clone())
+    }
+
+    fn get_letter_ngram_counts(&self) -> Result<HashMap<String, u64>> {
+        self.dictionary()?.get_letter_ngram_counts()
+    }
+
+    fn get_segmenter(&self) -> Result<Arc<Segmenter>> {
+        // Lock is kept while building, like at language cache, to build segmenter only once.
+        let mut segmenter = self.cache.segmenter.lock().expect("Poisoned segmenter cache.");
+        if segmenter.is_none() {
+            *segmenter = Some(Arc::new(Segmenter::new(self.get_all_word_frequencies()?)));
+        }
+        Ok(Arc::clone(segmenter.as_ref().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::prelude::*;
+    use rstest::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::attack::dictionaries::{MemoryStore, identify_language};
+    use crate::attack::dictionaries::tests::{ENGLISH_TEXT_WITH_PUNCTUATIONS_MARKS, memory_store};
+    use crate::cipher::caesar::{cipher, decipher};
+    use crate::cipher::common::DEFAULT_CHARSET;
+
+    /// Store counting how many times its dictionaries are opened, how many words are
+    /// looked up at them and how many of those were missing.
+    #[derive(Default)]
+    struct CountingStore {
+        store: MemoryStore,
+        opens: AtomicUsize,
+        lookups: Arc<AtomicUsize>,
+        misses: Arc<AtomicUsize>
+    }
+
+    impl CountingStore {
+        /// Get opens, lookups and misses counted so far, resetting them.
+        fn take_counts(&self) -> (usize, usize, usize) {
+            (self.opens.swap(0, Ordering::SeqCst), self.lookups.swap(0, Ordering::SeqCst),
+             self.misses.swap(0, Ordering::SeqCst))
+        }
+    }
+
+    struct CountingDictionary<'a> {
+        dictionary: Box<dyn LanguageDictionary + 'a>,
+        lookups: Arc<AtomicUsize>,
+        misses: Arc<AtomicUsize>
+    }
+
+    impl<'a> CountingDictionary<'a> {
+        fn count_lookup(&self, frequency: u64) -> u64 {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            if frequency == 0 {
+                self.misses.fetch_add(1, Ordering::SeqCst);
+            }
+            frequency
+        }
+    }
+
+    impl DictionaryStore for CountingStore {
+        fn get_dictionaries_names(&self) -> Result<Vec<String>> {
+            self.store.get_dictionaries_names()
+        }
+
+        fn open_dictionary(&self, _language: &str) -> Result<Box<dyn LanguageDictionary + '_>> {
+            self.opens.fetch_add(1, Ordering::SeqCst);
+            Ok(Box::new(CountingDictionary {
+                dictionary: self.store.open_dictionary(_language)?,
+                lookups: Arc::clone(&self.lookups),
+                misses: Arc::clone(&self.misses)
+            }))
+        }
+    }
+
+    impl<'a> LanguageDictionary for CountingDictionary<'a> {
+        fn language(&self) -> &str { self.dictionary.language() }
+
+        fn word_exists(&self, _word: &str) -> bool {
+            self.dictionary.get_word_frequency(_word).map_or(false, |frequency| self.count_lookup(frequency) > 0)
+        }
+
+        fn get_word_frequency(&self, _word: &str) -> Result<u64> {
+            Ok(self.count_lookup(self.dictionary.get_word_frequency(_word)?))
+        }
+
+        fn get_total_frequency(&self) -> Result<u64> { self.dictionary.get_total_frequency() }
+
+        fn get_words_with_pattern(&self, pattern: &str) -> Result<Vec<String>> { self.dictionary.get_words_with_pattern(pattern) }
+
+        fn get_all_words(&self) -> Result<Vec<String>> { self.dictionary.get_all_words() }
+
+        fn get_all_word_frequencies(&self) -> Result<HashMap<String, u64>> { self.dictionary.get_all_word_frequencies() }
+    }
+
+    #[rstest]
+    fn test_cached_store_identifies_like_its_store(memory_store: MemoryStore) {
+        let cached_store = CachedStore::new(&memory_store);
+        for text in [ENGLISH_TEXT_WITH_PUNCTUATIONS_MARKS, "Xkcd qwrty zzzp"].iter() {
+            let expected_language = identify_language(text, &memory_store).unwrap();
+            let cached_language = identify_language(text, &cached_store).unwrap();
+            assert_eq!(expected_language.winner(), cached_language.winner());
+            assert_eq!(expected_language.confidence(), cached_language.confidence());
+        }
+    }
+
+    #[rstest]
+    fn test_cached_store_avoids_lookups(memory_store: MemoryStore) {
+        let counting_store = CountingStore { store: memory_store, ..Default::default() };
+        let cached_store = CachedStore::new(&counting_store);
+        let ciphered_text = cipher(ENGLISH_TEXT_WITH_PUNCTUATIONS_MARKS, 13, DEFAULT_CHARSET).unwrap();
+        let deciphered_texts: Vec<String> = (1..DEFAULT_CHARSET.len())
+            .map(|key| decipher(&ciphered_text, key, DEFAULT_CHARSET).unwrap())
+            .collect();
+        deciphered_texts.iter()
+            .for_each(|deciphered_text| { identify_language(deciphered_text, &counting_store).unwrap(); });
+        let (_, uncached_lookups, uncached_misses) = counting_store.take_counts();
+        assert!(uncached_misses > 0 && uncached_lookups > uncached_misses);
+        deciphered_texts.par_iter()
+            .for_each(|deciphered_text| { identify_language(deciphered_text, &cached_store).unwrap(); });
+        let (cached_opens, cached_lookups, _) = counting_store.take_counts();
+        // Every dictionary is read only once, whatever the number of texts and threads,
+        // and no word is looked up at it.
+        assert_eq!(counting_store.get_dictionaries_names().unwrap().len(), cached_opens);
+        assert_eq!(0, cached_lookups);
+    }
+
+    #[rstest]
+    fn test_cached_store_builds_segmenter_once(memory_store: MemoryStore) {
+        let cached_store = CachedStore::new(&memory_store);
+        let first_segmenter = cached_store.open_dictionary("english").unwrap().get_segmenter().unwrap();
+        let second_segmenter = cached_store.open_dictionary("english").unwrap().get_segmenter().unwrap();
+        assert!(Arc::ptr_eq(&first_segmenter, &second_segmenter));
+        let spaceless_text = "thisismysecretmessage";
+        assert_eq!(identify_language(spaceless_text, &memory_store).unwrap().winner(),
+                   identify_language(spaceless_text, &cached_store).unwrap().winner());
+    }
+}
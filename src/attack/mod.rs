@@ -1,9 +1,24 @@
 pub mod affine;
 pub mod caesar;
+pub mod context;
+pub mod crib;
 pub mod database;
 pub mod dictionaries;
+pub mod dictionary_exchange;
+pub mod dictionary_inspection;
+pub mod enigma;
+pub mod homophonic;
+pub mod language_packs;
+pub mod membership;
+pub mod ngrams;
+pub mod presets;
+pub mod quagmire;
+pub mod scoring;
+pub mod segmentation;
 pub mod simple_attacks;
 pub mod substitution;
 pub mod transposition;
-mod frequency;
-pub mod vigenere;
\ No newline at end of file
+pub mod frequency;
+pub mod vernam;
+pub mod vigenere;
+pub mod workbench;
\ No newline at end of file
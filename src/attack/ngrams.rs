@@ -0,0 +1,283 @@
+/// Module to score texts against a language n-gram model.
+///
+/// Brute force attacks can tell apart right keys just checking deciphered words against
+/// a dictionary. But hill climbing attacks need a finer grained score to know if a
+/// candidate key is better than a previous one, even when deciphered text is still
+/// mostly garbage. An n-gram model gives that score: the more a text looks like
+/// the language the model was built from, the higher its score.
+///
+/// Models only work with charset letters, everything else in texts is ignored. So scored
+/// texts are taken as a continuous letter stream, with no spaces or punctuation marks.
+use std::fs::File;
+use std::io::Read;
+
+use crate::{ErrorKind, Result, ResultExt};
+use crate::attack::dictionaries::LanguageDictionary;
+
+/// Longest table we allow for a model. With a 26 letters charset, quadgrams table is
+/// only about 450.000 items long, so this is enough for any sensible charset.
+const MAX_TABLE_LENGTH: usize = 1 << 24;
+
+/// Language model with logarithmic probabilities for every possible n-gram in a charset.
+///
+/// N-grams are not stored as strings but as indexes in a dense table, so scoring is fast
+/// enough to be called many thousands of times in a hill climbing attack.
+#[derive(Debug, Clone)]
+pub struct NgramModel {
+    n: usize,
+    charset: String,
+    charset_chars: Vec<char>,
+    log_probabilities: Vec<f64>,
+    letter_log_probabilities: Vec<f64>
+}
+
+impl NgramModel {
+    /// Create a model from a text sample in the language to model.
+    ///
+    /// # Parameters:
+    /// * text: Text sample. The longer the better.
+    /// * n: N-grams length. Quadgrams (4) are usually a good choice.
+    /// * charset: Charset whose letters are going to be modeled.
+    ///
+    /// # Returns:
+    /// * A model for text language.
+    pub fn from_text<T, U>(text: T, n: usize, charset: U) -> Result<Self>
+        where T: AsRef<str>,
+              U: AsRef<str> {
+        let mut model = Self::new_empty(n, charset)?;
+        let letters = model.text_to_indexes(text);
+        let counts = model.count(&[letters]);
+        model.load_counts(counts);
+        Ok(model)
+    }
+
+    /// Create a model from a text file in the language to model.
+    ///
+    /// # Parameters:
+    /// * file_pathname: Absolute pathname to text file.
+    /// * n: N-grams length. Quadgrams (4) are usually a good choice.
+    /// * charset: Charset whose letters are going to be modeled.
+    ///
+    /// # Returns:
+    /// * A model for text language.
+    pub fn from_text_file<T, U>(file_pathname: T, n: usize, charset: U) -> Result<Self>
+        where T: AsRef<str>,
+              U: AsRef<str> {
+        let mut text = String::new();
+        let mut file_to_read = File::open(file_pathname.as_ref())
+            .chain_err(|| ErrorKind::IOError(file_pathname.as_ref().to_string()))?;
+        file_to_read.read_to_string(&mut text)
+            .chain_err(|| ErrorKind::IOError(file_pathname.as_ref().to_string()))?;
+        Self::from_text(&text, n, charset)
+    }
+
+    /// Create a model from a list of words.
+    ///
+    /// N-grams are only counted inside words, so words shorter than n only
+    /// count for letter frequencies.
+    ///
+    /// # Parameters:
+    /// * words: Words from the language to model.
+    /// * n: N-grams length. Quadgrams (4) are usually a good choice.
+    /// * charset: Charset whose letters are going to be modeled.
+    ///
+    /// # Returns:
+    /// * A model for words language.
+    pub fn from_words<T, U>(words: &[T], n: usize, charset: U) -> Result<Self>
+        where T: AsRef<str>,
+              U: AsRef<str> {
+        let mut model = Self::new_empty(n, charset)?;
+        let words_letters: Vec<Vec<usize>> = words.iter()
+            .map(|word| model.text_to_indexes(word))
+            .collect();
+        let counts = model.count(&words_letters);
+        model.load_counts(counts);
+        Ok(model)
+    }
+
+    /// Create a model from words stored at a language dictionary.
+    ///
+    /// # Parameters:
+    /// * dictionary: Language dictionary.
+    /// * n: N-grams length. Quadgrams (4) are usually a good choice.
+    /// * charset: Charset whose letters are going to be modeled.
+    ///
+    /// # Returns:
+    /// * A model for dictionary language.
+    pub fn from_dictionary<T>(dictionary: &dyn LanguageDictionary, n: usize, charset: T) -> Result<Self>
+        where T: AsRef<str> {
+        let words = dictionary.get_all_words()?;
+        Self::from_words(&words, n, charset)
+    }
+
+    fn new_empty<T>(n: usize, charset: T) -> Result<Self>
+        where T: AsRef<str> {
+        let charset_chars: Vec<char> = charset.as_ref().to_lowercase().chars().collect();
+        if n == 0 || charset_chars.is_empty() {
+            bail!("N-gram models need a non empty charset and an n greater than 0.")
+        }
+        let table_length = charset_chars.len().checked_pow(n as u32)
+            .filter(|length| *length <= MAX_TABLE_LENGTH);
+        match table_length {
+            Some(table_length) => Ok(NgramModel {
+                n,
+                charset: charset_chars.iter().collect(),
+                charset_chars,
+                log_probabilities: vec![0.0; table_length],
+                letter_log_probabilities: Vec::new()
+            }),
+            None => bail!(format!("Charset is too long to model {}-grams.", n))
+        }
+    }
+
+    /// Count n-grams and letters present at given letter streams.
+    ///
+    /// # Parameters:
+    /// * streams: Lists of charset indexes. N-grams are not counted across streams.
+    ///
+    /// # Returns:
+    /// * A tuple with n-grams counts and letter counts.
+    fn count(&self, streams: &[Vec<usize>]) -> (Vec<u64>, Vec<u64>) {
+        let mut ngram_counts = vec![0_u64; self.log_probabilities.len()];
+        let mut letter_counts = vec![0_u64; self.charset_chars.len()];
+        for stream in streams {
+            stream.iter().for_each(|&letter| letter_counts[letter] += 1);
+            for window in stream.windows(self.n) {
+                ngram_counts[self.ngram_index(window)] += 1;
+            }
+        }
+        (ngram_counts, letter_counts)
+    }
+
+    /// Turn counts into logarithmic probabilities.
+    ///
+    /// Unseen n-grams get a floor probability a hundred times lower than a single
+    /// occurrence, so they penalize but don't rule out a candidate.
+    fn load_counts(&mut self, (ngram_counts, letter_counts): (Vec<u64>, Vec<u64>)) {
+        self.log_probabilities = Self::log_probabilities(&ngram_counts);
+        self.letter_log_probabilities = Self::log_probabilities(&letter_counts);
+    }
+
+    fn log_probabilities(counts: &[u64]) -> Vec<f64> {
+        let total: u64 = counts.iter().sum();
+        let total = total.max(1) as f64;
+        let floor = (0.01 / total).log10();
+        counts.iter()
+            .map(|&count| if count > 0 { (count as f64 / total).log10() } else { floor })
+            .collect()
+    }
+
+    fn ngram_index(&self, ngram: &[usize]) -> usize {
+        let charset_length = self.charset_chars.len();
+        ngram.iter().fold(0, |index, &letter| index * charset_length + letter)
+    }
+
+    /// Charset modeled.
+    pub fn charset(&self) -> &str {
+        self.charset.as_str()
+    }
+
+    /// N-grams length.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Get charset index for every charset letter of given text.
+    ///
+    /// Text is lowercased first and letters not present at charset are ignored.
+    ///
+    /// # Parameters:
+    /// * text: Text to convert.
+    ///
+    /// # Returns:
+    /// * A list of charset indexes.
+    pub fn text_to_indexes<T>(&self, text: T) -> Vec<usize>
+        where T: AsRef<str> {
+        text.as_ref().to_lowercase().chars()
+            .filter_map(|char| self.charset_chars.iter().position(|&x| x == char))
+            .collect()
+    }
+
+    /// Score a text already converted to charset indexes.
+    ///
+    /// # Parameters:
+    /// * letters: List of charset indexes.
+    ///
+    /// # Returns:
+    /// * Sum of logarithmic probabilities of every n-gram in letters. The higher the
+    ///     more likely letters belong to modeled language.
+    pub fn score_indexes(&self, letters: &[usize]) -> f64 {
+        // N-gram indexes are rolled instead of calculated from scratch for every window,
+        // because this is the hottest spot of hill climbing attacks.
+        let table_length = self.log_probabilities.len();
+        let charset_length = self.charset_chars.len();
+        let mut score = 0.0;
+        let mut index = 0;
+        for (i, &letter) in letters.iter().enumerate() {
+            index = (index * charset_length + letter) % table_length;
+            if i + 1 >= self.n {
+                score += self.log_probabilities[index];
+            }
+        }
+        score
+    }
+
+    /// Score a text against this model.
+    ///
+    /// Be aware that scores depend on text length, so you only can compare scores
+    /// from texts with the same length.
+    ///
+    /// # Parameters:
+    /// * text: Text to score.
+    ///
+    /// # Returns:
+    /// * Text score. The higher the more likely text belongs to modeled language.
+    pub fn score<T>(&self, text: T) -> f64
+        where T: AsRef<str> {
+        self.score_indexes(&self.text_to_indexes(text))
+    }
+
+    /// Logarithmic probability of a single letter.
+    ///
+    /// # Parameters:
+    /// * letter: Charset index of letter.
+    ///
+    /// # Returns:
+    /// * Logarithmic probability of that letter at modeled language.
+    pub fn letter_log_probability(&self, letter: usize) -> f64 {
+        self.letter_log_probabilities[letter]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::cipher::quagmire::DEFAULT_CHARSET;
+
+    #[test]
+    fn test_english_text_scores_higher_than_garbage() {
+        let model = NgramModel::from_text_file("resources/english_book.txt", 4, DEFAULT_CHARSET).unwrap();
+        let english_score = model.score("Common sense is not so common");
+        let garbage_score = model.score("Xqzmmo jvkwq yz wbf zx qbzzqk");
+        assert!(english_score > garbage_score);
+    }
+
+    #[test]
+    fn test_text_to_indexes() {
+        let model = NgramModel::from_text("abc", 2, DEFAULT_CHARSET).unwrap();
+        assert_eq!(model.text_to_indexes("Ab, z!"), vec![0, 1, 25]);
+    }
+
+    #[test]
+    fn test_ngrams_are_not_counted_across_words() {
+        let model = NgramModel::from_words(&["ab", "cd"], 2, DEFAULT_CHARSET).unwrap();
+        assert!(model.score("ab") > model.score("bc"));
+        assert_eq!(model.score("bc"), model.score("zz"));
+    }
+
+    #[test]
+    fn test_too_long_tables_are_rejected() {
+        assert!(NgramModel::from_text("abc", 10, DEFAULT_CHARSET).is_err());
+    }
+}
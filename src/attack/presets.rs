@@ -0,0 +1,69 @@
+/// Module to attack texts ciphered with a keyless preset cipher.
+///
+/// Preset ciphers have no key, so attacking them is just deciphering text with every
+/// preset and checking which result can be identified with any of our languages. That
+/// is a quick pass, so it is worth a try before launching any heavier attack.
+use crate::Result;
+use crate::attack::dictionaries::{DictionaryStore, identify_language};
+use crate::cipher::presets::Preset;
+
+/// Find out which preset was used to cipher given text.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * store: Dictionaries to identify deciphered texts with.
+///
+/// # Returns:
+/// * Preset whose deciphered text got the highest language probability, or an error
+///     if no deciphered text could be identified with any language.
+pub fn brute_force(ciphered_text: &str, store: &dyn DictionaryStore) -> Result<Preset> {
+    let mut best_preset = None;
+    let mut best_probability = 0.0;
+    for preset in Preset::ALL.iter() {
+        let deciphered_text = preset.decipher(ciphered_text)?;
+        let identified_language = identify_language(&deciphered_text, store)?;
+        if let Some(winner_probability) = identified_language.winner_probability {
+            // Wrong presets often turn most letters into symbols, leaving just a few
+            // short words that happen to be in dictionaries, so weight by letters kept.
+            let probability = winner_probability * get_letters_ratio(&deciphered_text);
+            if probability > best_probability {
+                best_preset = Some(*preset);
+                best_probability = probability;
+            }
+        }
+    }
+    match best_preset {
+        Some(preset) => Ok(preset),
+        None => bail!("Text could not be deciphered with any preset.")
+    }
+}
+
+/// Get ratio of alphabetic characters in text, ignoring whitespaces.
+fn get_letters_ratio(text: &str) -> f64 {
+    let characters = text.chars().filter(|character| !character.is_whitespace()).count();
+    if characters == 0 {
+        return 0.0;
+    }
+    let letters = text.chars().filter(|character| character.is_alphabetic()).count();
+    letters as f64 / characters as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::*;
+
+    use crate::attack::dictionaries::DatabaseStore;
+    use crate::attack::dictionaries::tests::{LoadedDictionaries, full_loaded_temp_dictionaries};
+
+    const ORIGINAL_MESSAGE: &'static str = "The Times 03/Jan/2009 Chancellor on brink of second bailout for banks";
+
+    #[rstest]
+    fn test_brute_force(full_loaded_temp_dictionaries: LoadedDictionaries) {
+        for preset in Preset::ALL.iter() {
+            let ciphered_text = preset.cipher(ORIGINAL_MESSAGE).unwrap();
+            assert_eq!(brute_force(&ciphered_text, &DatabaseStore::default()).unwrap(), *preset);
+        }
+    }
+}
@@ -0,0 +1,700 @@
+/// Module to attack Quagmire ciphered texts.
+///
+/// Quagmire key space is too large for brute force, so this module uses a hill climbing
+/// approach. First, period is guessed looking for the indicator length whose columns
+/// have the most language-like index of coincidence. Then, mixed alphabets are improved
+/// swapping letter pairs while every swap makes deciphered text score better against an
+/// n-gram language model. For every candidate alphabets, indicator letters are chosen
+/// as those giving the most language-like letter frequencies for their columns.
+///
+/// Hill climbing can get stuck at local maximums so climbing is restarted several times
+/// from random alphabets and best result is kept.
+///
+/// Recovered alphabets are only known up to rotation, so they are rotated to look like
+/// keyed alphabets (keyword followed by remaining letters in charset order), which lets
+/// us read keywords from them.
+use rand::Rng;
+use rand::seq::SliceRandom;
+use rayon::prelude::*;
+
+use crate::{ErrorKind, Result, ResultExt};
+use crate::attack::dictionaries::{DictionaryStore, identify_language};
+use crate::attack::frequency::get_index_of_coincidence;
+use crate::attack::ngrams::NgramModel;
+use crate::cipher::quagmire::{decipher, keyed_alphabet, keyword_from_alphabet, Quagmire, QuagmireKey};
+
+/// Longest indicator keyword we look for.
+const MAX_PERIOD: usize = 20;
+/// How many times hill climbing is started from scratch.
+const RESTARTS: usize = 8;
+/// How many times best alphabets are shaken to escape from local maximums.
+const SHAKES: usize = 30;
+/// How many random swaps are done to shake alphabets.
+const SHAKE_SWAPS: usize = 3;
+/// How many times columns are aligned against each other.
+const ALIGNMENT_PASSES: usize = 3;
+/// N-grams length used for language models built from dictionaries.
+const NGRAM_LENGTH: usize = 4;
+
+/// Get Quagmire ciphered text key.
+///
+/// A language model is built from every available dictionary and text is attacked
+/// against each of them. Key whose deciphered text is best identified as a language
+/// is returned.
+///
+/// **You should not use this function. Use *hack_quagmire_mp* instead.** This
+/// function is slower than *mp* one because is sequential while the other uses a
+/// multiprocessing approach. This function only stay here to allow comparisons
+/// between sequential and multiprocessing approaches.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * variant: Quagmire variant used to cipher text.
+/// * charset: Charset used for Quagmire method. Both ends, ciphering
+///     and deciphering, should use the same charset or original text won't be properly
+///     recovered.
+/// * store: Dictionaries to build language models from and identify deciphered texts with.
+///
+/// # Returns:
+/// * A tuple with Quagmire key found and success probability.
+pub fn hack_quagmire(ciphered_text: &str, variant: Quagmire, charset: &str,
+                     store: &dyn DictionaryStore) -> Result<(QuagmireKey, f64)> {
+    let available_languages = store.get_dictionaries_names()
+        .chain_err(|| ErrorKind::DatabaseError(String::from("We could not get dictionaries names.")))?;
+    let results: Vec<Result<(QuagmireKey, f64)>> = available_languages.iter()
+        .map(|language| hack_quagmire_at_language(ciphered_text, variant, charset, language, store))
+        .collect();
+    get_best_key(results)
+}
+
+/// Get Quagmire ciphered text key.
+///
+/// A language model is built from every available dictionary and text is attacked
+/// against each of them. Key whose deciphered text is best identified as a language
+/// is returned.
+///
+/// **You should use this function instead of *hack_quagmire*.**
+///
+/// Whereas *hack_quagmire* uses a sequential approach, this function uses
+/// multiprocessing to improve performance.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * variant: Quagmire variant used to cipher text.
+/// * charset: Charset used for Quagmire method. Both ends, ciphering
+///     and deciphering, should use the same charset or original text won't be properly
+///     recovered.
+/// * store: Dictionaries to build language models from and identify deciphered texts with.
+///
+/// # Returns:
+/// * A tuple with Quagmire key found and success probability.
+pub fn hack_quagmire_mp(ciphered_text: &str, variant: Quagmire, charset: &str,
+                        store: &dyn DictionaryStore) -> Result<(QuagmireKey, f64)> {
+    let available_languages = store.get_dictionaries_names()
+        .chain_err(|| ErrorKind::DatabaseError(String::from("We could not get dictionaries names.")))?;
+    let results: Vec<Result<(QuagmireKey, f64)>> = available_languages.par_iter()
+        .map(|language| hack_quagmire_at_language(ciphered_text, variant, charset, language, store))
+        .collect();
+    get_best_key(results)
+}
+
+/// Attack text with a language model built from given language dictionary.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * variant: Quagmire variant used to cipher text.
+/// * charset: Charset used for Quagmire method.
+/// * language: Dictionary to build language model from.
+/// * store: Dictionaries to take language dictionary from and identify deciphered text with.
+///
+/// # Returns:
+/// * A tuple with Quagmire key found and the probability of its deciphered text
+///     being in any of our languages.
+fn hack_quagmire_at_language(ciphered_text: &str, variant: Quagmire, charset: &str,
+                             language: &str, store: &dyn DictionaryStore) -> Result<(QuagmireKey, f64)> {
+    let keywords = store.open_dictionary(language)?.get_all_words()?;
+    let model = NgramModel::from_words(&keywords, NGRAM_LENGTH, charset)?;
+    let (key, _) = hack_quagmire_with_model(ciphered_text, variant, &model, &keywords)?;
+    let deciphered_text = decipher(ciphered_text, &key, charset)?;
+    let identified_language = identify_language(&deciphered_text, store)?;
+    Ok((key, identified_language.winner_probability.unwrap_or(0.0)))
+}
+
+/// Select key with highest probability.
+///
+/// # Parameters:
+/// * results: Keys found with their probabilities.
+///
+/// # Returns:
+/// * A tuple with best key and its probability.
+fn get_best_key(results: Vec<Result<(QuagmireKey, f64)>>) -> Result<(QuagmireKey, f64)> {
+    let mut best_result: Option<(QuagmireKey, f64)> = None;
+    for result in results {
+        let (key, probability) = result?;
+        if best_result.as_ref().map_or(true, |(_, best_probability)| probability > *best_probability) {
+            best_result = Some((key, probability));
+        }
+    }
+    match best_result {
+        Some(best_result) => Ok(best_result),
+        None => bail!(ErrorKind::DatabaseError(String::from("There are no dictionaries to attack with.")))
+    }
+}
+
+/// Get Quagmire ciphered text key using given language model.
+///
+/// Random alphabets are a poor start for hill climbing when ciphertext alphabet is mixed,
+/// so alphabets keyed with candidate keywords are assessed first and climbing starts
+/// from the best one. ACA keywords are usually plain words, so dictionary words make
+/// a good candidate list. Be aware that, with no candidate keywords, attacks to
+/// Quagmire II, III and IV are likely to fail.
+///
+/// Hill climbing restarts are run in parallel.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered. The longer the better, hill climbing
+///     needs several hundreds of letters to be reliable.
+/// * variant: Quagmire variant used to cipher text.
+/// * model: Model of the language plaintext is supposed to be written in. Its charset
+///     is used as Quagmire charset.
+/// * keywords: Candidate keywords to mix alphabets with.
+///
+/// # Returns:
+/// * A tuple with Quagmire key found and its deciphered text score against model.
+pub fn hack_quagmire_with_model<T>(ciphered_text: &str, variant: Quagmire, model: &NgramModel,
+                                   keywords: &[T]) -> Result<(QuagmireKey, f64)>
+    where T: AsRef<str> + Sync {
+    let period = find_period(ciphered_text, model.charset(), MAX_PERIOD);
+    let climber = HillClimber::new(ciphered_text, period, variant, model)?;
+    let keyed_alphabet = climber.get_best_keyed_alphabet(keywords);
+    let best_climb = (0..RESTARTS).into_par_iter()
+        // Half the restarts start from keyed alphabet, in case it was a bad choice.
+        .map(|restart| climber.climb(if restart % 2 == 0 { keyed_alphabet.as_ref() } else { None }))
+        .reduce_with(|one, other| if other.score > one.score { other } else { one })
+        .expect("Hill climbing needs at least a restart.");
+    let score = best_climb.score;
+    Ok((climber.get_key(best_climb), score))
+}
+
+/// Guess indicator keyword length.
+///
+/// Text is split in columns for every candidate period. With the right period, every
+/// column is ciphered with a single alphabet, so its index of coincidence is that of
+/// a natural language. Multiples of the right period give high indexes too, so we
+/// keep the shortest period whose average index is close to the highest one.
+///
+/// # Parameters:
+/// * ciphered_text: Text to analyze.
+/// * charset: Charset used for ciphering.
+/// * max_period: Longest period to try.
+///
+/// # Returns:
+/// * Most likely period.
+pub fn find_period<T, U>(ciphered_text: T, charset: U, max_period: usize) -> usize
+    where T: AsRef<str>,
+          U: AsRef<str> {
+    let letters: Vec<char> = ciphered_text.as_ref().to_lowercase().chars()
+        .filter(|char| charset.as_ref().contains(*char))
+        .collect();
+    // Every column should have some letters to get a meaningful index.
+    let max_period = max_period.min(letters.len() / 2).max(1);
+    let average_indexes: Vec<f64> = (1..=max_period)
+        .map(|period| {
+            let indexes_sum: f64 = (0..period)
+                .map(|column| {
+                    let column_text: String = letters.iter().skip(column).step_by(period).collect();
+                    get_index_of_coincidence(&column_text, &charset)
+                })
+                .sum();
+            indexes_sum / period as f64
+        })
+        .collect();
+    let highest_index = average_indexes.iter().cloned().fold(0.0, f64::max);
+    let period_position = average_indexes.iter()
+        .position(|&index| index >= 0.9 * highest_index)
+        .unwrap_or(0);
+    period_position + 1
+}
+
+/// Alphabets and indicator shifts found after a hill climb.
+#[derive(Debug, Clone)]
+struct Climb {
+    plaintext_alphabet: Vec<usize>,
+    ciphertext_alphabet: Vec<usize>,
+    shifts: Vec<usize>,
+    score: f64
+}
+
+/// Alphabets that can be changed at every hill climbing step.
+#[derive(Debug, Copy, Clone)]
+enum Target {
+    Plaintext,
+    Ciphertext,
+    Both
+}
+
+/// Hill climbing state shared by all restarts.
+///
+/// Alphabets are represented as lists of charset indexes. Deciphering a letter is
+/// looking for its position at ciphertext alphabet, subtracting its column shift and
+/// getting the letter at that position from plaintext alphabet.
+struct HillClimber<'a> {
+    letters: Vec<usize>,
+    column_counts: Vec<Vec<u64>>,
+    variant: Quagmire,
+    model: &'a NgramModel,
+    charset_length: usize
+}
+
+impl<'a> HillClimber<'a> {
+    fn new(ciphered_text: &str, period: usize, variant: Quagmire, model: &'a NgramModel) -> Result<Self> {
+        let letters = model.text_to_indexes(ciphered_text);
+        if letters.is_empty() {
+            bail!("Ciphered text has no letters from charset.")
+        }
+        let charset_length = model.charset().chars().count();
+        let mut column_counts = vec![vec![0_u64; charset_length]; period];
+        letters.iter().enumerate()
+            .for_each(|(i, &letter)| column_counts[i % period][letter] += 1);
+        Ok(HillClimber {
+            letters,
+            column_counts,
+            variant,
+            model,
+            charset_length
+        })
+    }
+
+    /// Get the alphabet keyed with the most promising keyword.
+    ///
+    /// Quagmire I ciphertext alphabet is straight so there is nothing to look for. For
+    /// Quagmire II and III alphabets are assessed with their deciphered text score.
+    /// Quagmire IV plaintext alphabet is unknown yet, so alphabets are assessed by
+    /// index of coincidence of their columns aligned.
+    ///
+    /// # Parameters:
+    /// * keywords: Candidate keywords.
+    ///
+    /// # Returns:
+    /// * Best ciphertext alphabet found, if any.
+    fn get_best_keyed_alphabet<T>(&self, keywords: &[T]) -> Option<Vec<usize>>
+        where T: AsRef<str> + Sync {
+        let straight_alphabet: Vec<usize> = (0..self.charset_length).collect();
+        let assess_alphabet = |alphabet: &Vec<usize>| match self.variant {
+            Quagmire::I => 0.0,
+            Quagmire::II => self.assess(straight_alphabet.clone(), alphabet.clone()).score,
+            Quagmire::III => self.assess(alphabet.clone(), alphabet.clone()).score,
+            Quagmire::IV => self.align_shifts(alphabet).1
+        };
+        match self.variant {
+            Quagmire::I => None,
+            _ => keywords.par_iter()
+                .map(|keyword| self.model.text_to_indexes(keyed_alphabet(keyword, self.model.charset())))
+                .map(|alphabet| {
+                    let score = assess_alphabet(&alphabet);
+                    (alphabet, score)
+                })
+                .reduce_with(|one, other| if other.1 > one.1 { other } else { one })
+                .map(|(alphabet, _)| alphabet)
+        }
+    }
+
+    fn targets(&self) -> Vec<Target> {
+        match self.variant {
+            Quagmire::I => vec![Target::Plaintext],
+            Quagmire::II => vec![Target::Ciphertext],
+            Quagmire::III => vec![Target::Both],
+            Quagmire::IV => vec![Target::Plaintext, Target::Ciphertext]
+        }
+    }
+
+    /// Run a hill climb.
+    ///
+    /// # Parameters:
+    /// * keyed_alphabet: Ciphertext alphabet to start from. If None, a random one is used.
+    ///
+    /// # Returns:
+    /// * Best alphabets found.
+    fn climb(&self, keyed_alphabet: Option<&Vec<usize>>) -> Climb {
+        let mut rng = rand::thread_rng();
+        let straight_alphabet: Vec<usize> = (0..self.charset_length).collect();
+        let mut random_alphabet = straight_alphabet.clone();
+        random_alphabet.shuffle(&mut rng);
+        let ciphertext_alphabet = keyed_alphabet.cloned().unwrap_or_else(|| random_alphabet.clone());
+        let assess = |plaintext_alphabet, ciphertext_alphabet| self.assess(plaintext_alphabet, ciphertext_alphabet);
+        let start = match self.variant {
+            Quagmire::I => self.assess(random_alphabet, straight_alphabet),
+            Quagmire::II => self.assess(straight_alphabet, ciphertext_alphabet),
+            Quagmire::III => self.assess(ciphertext_alphabet.clone(), ciphertext_alphabet),
+            Quagmire::IV => {
+                // Until plaintext alphabet is found, letter frequencies can not be used to
+                // find shifts, so we solve plaintext alphabet as a simple substitution
+                // over columns aligned by their index of coincidence.
+                let (shifts, _) = self.align_shifts(&ciphertext_alphabet);
+                let assess_with_shifts = |plaintext_alphabet, ciphertext_alphabet|
+                    self.assess_with_shifts(plaintext_alphabet, ciphertext_alphabet, shifts.clone());
+                let start = assess_with_shifts(random_alphabet, ciphertext_alphabet);
+                let substitution_solution = self.shake_and_climb(start, &[Target::Plaintext], &assess_with_shifts);
+                assess(substitution_solution.plaintext_alphabet, substitution_solution.ciphertext_alphabet)
+            }
+        };
+        self.shake_and_climb(start, &self.targets(), &assess)
+    }
+
+    /// Climb to a local maximum and then try to escape from it shaking alphabets.
+    ///
+    /// # Parameters:
+    /// * start: Alphabets to start from.
+    /// * targets: Alphabets that can be changed.
+    /// * assess: Function to score candidate alphabets.
+    ///
+    /// # Returns:
+    /// * Best alphabets found.
+    fn shake_and_climb<F>(&self, start: Climb, targets: &[Target], assess: &F) -> Climb
+        where F: Fn(Vec<usize>, Vec<usize>) -> Climb {
+        let mut rng = rand::thread_rng();
+        let mut best = self.climb_to_local_maximum(start, targets, assess);
+        for _ in 0..SHAKES {
+            let mut plaintext_alphabet = best.plaintext_alphabet.clone();
+            let mut ciphertext_alphabet = best.ciphertext_alphabet.clone();
+            for _ in 0..SHAKE_SWAPS {
+                let i = rng.gen_range(0, self.charset_length);
+                let j = rng.gen_range(0, self.charset_length);
+                // Unwrap is safe because every variant has at least a target.
+                let target = targets.choose(&mut rng).unwrap();
+                swap(target, &mut plaintext_alphabet, &mut ciphertext_alphabet, i, j);
+            }
+            let candidate = self.climb_to_local_maximum(assess(plaintext_alphabet, ciphertext_alphabet),
+                                                        targets, assess);
+            if candidate.score > best.score {
+                best = candidate;
+            }
+        }
+        best
+    }
+
+    /// Swap alphabets letters while deciphered text score improves.
+    ///
+    /// # Parameters:
+    /// * start: Alphabets to start from.
+    /// * targets: Alphabets that can be changed.
+    /// * assess: Function to score candidate alphabets.
+    ///
+    /// # Returns:
+    /// * Alphabets at local maximum.
+    fn climb_to_local_maximum<F>(&self, start: Climb, targets: &[Target], assess: &F) -> Climb
+        where F: Fn(Vec<usize>, Vec<usize>) -> Climb {
+        let mut current = start;
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for i in 0..self.charset_length {
+                for j in (i + 1)..self.charset_length {
+                    for target in targets.iter() {
+                        let mut plaintext_alphabet = current.plaintext_alphabet.clone();
+                        let mut ciphertext_alphabet = current.ciphertext_alphabet.clone();
+                        swap(target, &mut plaintext_alphabet, &mut ciphertext_alphabet, i, j);
+                        let candidate = assess(plaintext_alphabet, ciphertext_alphabet);
+                        if candidate.score > current.score {
+                            current = candidate;
+                            improved = true;
+                        }
+                    }
+                }
+            }
+        }
+        current
+    }
+
+    /// Get position at alphabet of every charset letter.
+    fn get_positions(&self, alphabet: &[usize]) -> Vec<usize> {
+        let mut positions = vec![0; self.charset_length];
+        alphabet.iter().enumerate()
+            .for_each(|(position, &letter)| positions[letter] = position);
+        positions
+    }
+
+    /// Choose best shifts for given alphabets and score resulting deciphered text.
+    ///
+    /// Every column shift is the one whose deciphered letters have the most
+    /// language-like frequencies.
+    fn assess(&self, plaintext_alphabet: Vec<usize>, ciphertext_alphabet: Vec<usize>) -> Climb {
+        let n = self.charset_length;
+        let ciphertext_positions = self.get_positions(&ciphertext_alphabet);
+        let shifts: Vec<usize> = self.column_counts.iter()
+            .map(|counts| {
+                let shift_scores: Vec<f64> = (0..n)
+                    .map(|shift| counts.iter().enumerate()
+                        .filter(|(_, &count)| count > 0)
+                        .map(|(letter, &count)| {
+                            let deciphered_letter = plaintext_alphabet[(ciphertext_positions[letter] + n - shift) % n];
+                            count as f64 * self.model.letter_log_probability(deciphered_letter)
+                        })
+                        .sum())
+                    .collect();
+                (0..n).fold(0, |best_shift, shift|
+                    if shift_scores[shift] > shift_scores[best_shift] { shift } else { best_shift })
+            })
+            .collect();
+        self.assess_with_shifts(plaintext_alphabet, ciphertext_alphabet, shifts)
+    }
+
+    /// Score text deciphered with given alphabets and shifts.
+    fn assess_with_shifts(&self, plaintext_alphabet: Vec<usize>, ciphertext_alphabet: Vec<usize>,
+                          shifts: Vec<usize>) -> Climb {
+        let n = self.charset_length;
+        let ciphertext_positions = self.get_positions(&ciphertext_alphabet);
+        let period = shifts.len();
+        let deciphered_letters: Vec<usize> = self.letters.iter().enumerate()
+            .map(|(i, &letter)| plaintext_alphabet[(ciphertext_positions[letter] + n - shifts[i % period]) % n])
+            .collect();
+        let score = self.model.score_indexes(&deciphered_letters);
+        Climb {
+            plaintext_alphabet,
+            ciphertext_alphabet,
+            shifts,
+            score
+        }
+    }
+
+    /// Find column shifts that best align columns for given ciphertext alphabet.
+    ///
+    /// It does not need to know plaintext alphabet: with the right ciphertext alphabet
+    /// and shifts, every column is ciphered with the same substitution, so each column
+    /// is aligned to make its frequencies match those of the other columns as much
+    /// as possible.
+    ///
+    /// # Parameters:
+    /// * ciphertext_alphabet: Candidate ciphertext alphabet.
+    ///
+    /// # Returns:
+    /// * A tuple with shifts found and index of coincidence of all columns aligned.
+    fn align_shifts(&self, ciphertext_alphabet: &[usize]) -> (Vec<usize>, f64) {
+        let n = self.charset_length;
+        let period = self.column_counts.len();
+        let ciphertext_positions = self.get_positions(ciphertext_alphabet);
+        let histograms: Vec<Vec<f64>> = self.column_counts.iter()
+            .map(|counts| {
+                let mut histogram = vec![0.0; n];
+                counts.iter().enumerate()
+                    .for_each(|(letter, &count)| histogram[ciphertext_positions[letter]] += count as f64);
+                histogram
+            })
+            .collect();
+        let aligned_histogram = |shifts: &[usize], excluded_column: usize| {
+            let mut total = vec![0.0; n];
+            for (column, histogram) in histograms.iter().enumerate() {
+                if column != excluded_column {
+                    (0..n).for_each(|position| total[position] += histogram[(position + shifts[column]) % n]);
+                }
+            }
+            total
+        };
+        let mut shifts = vec![0; period];
+        for _ in 0..ALIGNMENT_PASSES {
+            for column in 1..period {
+                let reference = aligned_histogram(&shifts, column);
+                shifts[column] = (0..n)
+                    .map(|shift| (shift, (0..n).map(|position| reference[position] * histograms[column][(position + shift) % n]).sum::<f64>()))
+                    .fold((0, f64::MIN), |best, current| if current.1 > best.1 { current } else { best })
+                    .0;
+            }
+        }
+        let total = aligned_histogram(&shifts, period);
+        let total_letters: f64 = total.iter().sum();
+        let coincidences: f64 = total.iter().map(|count| count * (count - 1.0)).sum();
+        (shifts, coincidences / (total_letters * (total_letters - 1.0)).max(1.0))
+    }
+
+    /// Build a key from a climb result.
+    ///
+    /// Quagmire III and IV alphabets can be decimated without changing deciphered text,
+    /// and every alphabet but straight ones can be rotated. Alphabets are decimated and
+    /// rotated to look like keyed alphabets and shifts are fixed to compensate.
+    fn get_key(&self, climb: Climb) -> QuagmireKey {
+        let n = self.charset_length;
+        let decimations: Vec<usize> = match self.variant {
+            Quagmire::III | Quagmire::IV => (1..n).filter(|&factor| get_modular_inverse(factor, n).is_some()).collect(),
+            _ => vec![1]
+        };
+        // Letters not present at text can not be placed by hill climbing, so they are
+        // free to be sorted to make alphabets look like keyed ones.
+        let ciphertext_positions = self.get_positions(&climb.ciphertext_alphabet);
+        let period = climb.shifts.len();
+        let mut present_plaintext_letters = vec![false; n];
+        let mut present_ciphertext_letters = vec![false; n];
+        for (i, &letter) in self.letters.iter().enumerate() {
+            present_ciphertext_letters[letter] = true;
+            let plaintext_letter = climb.plaintext_alphabet[(ciphertext_positions[letter] + n - climb.shifts[i % period]) % n];
+            present_plaintext_letters[plaintext_letter] = true;
+        }
+        if let Quagmire::III = self.variant {
+            let present_letters: Vec<bool> = present_plaintext_letters.iter().zip(present_ciphertext_letters.iter())
+                .map(|(&plaintext_present, &ciphertext_present)| plaintext_present || ciphertext_present)
+                .collect();
+            present_plaintext_letters = present_letters.clone();
+            present_ciphertext_letters = present_letters;
+        }
+        let (plaintext_alphabet, ciphertext_alphabet, shifts) = decimations.iter()
+            .map(|&factor| {
+                // Unwrap is safe because decimation factors have been filtered to have an inverse.
+                let inverse = get_modular_inverse(factor, n).unwrap();
+                let shifts: Vec<usize> = climb.shifts.iter().map(|shift| (shift * inverse) % n).collect();
+                let (plaintext_alphabet, plaintext_rotation) = match self.variant {
+                    Quagmire::II => (climb.plaintext_alphabet.clone(), 0),
+                    _ => get_keyed_form(&decimate(&climb.plaintext_alphabet, factor), &present_plaintext_letters)
+                };
+                let (ciphertext_alphabet, ciphertext_rotation) = match self.variant {
+                    Quagmire::I => (climb.ciphertext_alphabet.clone(), 0),
+                    _ => get_keyed_form(&decimate(&climb.ciphertext_alphabet, factor), &present_ciphertext_letters)
+                };
+                let shifts: Vec<usize> = shifts.iter()
+                    .map(|shift| (shift + plaintext_rotation + n - ciphertext_rotation) % n)
+                    .collect();
+                (plaintext_alphabet, ciphertext_alphabet, shifts)
+            })
+            .max_by_key(|(plaintext_alphabet, ciphertext_alphabet, _)|
+                get_ordered_tail_length(plaintext_alphabet) + get_ordered_tail_length(ciphertext_alphabet))
+            .expect("There should be at least a decimation.");
+        // Unwrap is safe because every charset index is at alphabet.
+        let anchor_position = plaintext_alphabet.iter().position(|&letter| letter == 0).unwrap();
+        let charset_chars: Vec<char> = self.model.charset().chars().collect();
+        let indicator: String = shifts.iter()
+            .map(|&shift| charset_chars[ciphertext_alphabet[(shift + anchor_position) % n]])
+            .collect();
+        let plaintext_keyword = keyword_from_alphabet(self.to_string(&plaintext_alphabet), self.model.charset());
+        let ciphertext_keyword = keyword_from_alphabet(self.to_string(&ciphertext_alphabet), self.model.charset());
+        match self.variant {
+            Quagmire::I => QuagmireKey::quagmire_i(plaintext_keyword, indicator),
+            Quagmire::II => QuagmireKey::quagmire_ii(ciphertext_keyword, indicator),
+            Quagmire::III => QuagmireKey::quagmire_iii(plaintext_keyword, indicator),
+            Quagmire::IV => QuagmireKey::quagmire_iv(plaintext_keyword, ciphertext_keyword, indicator)
+        }
+    }
+
+    fn to_string(&self, alphabet: &[usize]) -> String {
+        let charset_chars: Vec<char> = self.model.charset().chars().collect();
+        alphabet.iter().map(|&letter| charset_chars[letter]).collect()
+    }
+}
+
+/// Swap two positions at targeted alphabets.
+fn swap(target: &Target, plaintext_alphabet: &mut [usize], ciphertext_alphabet: &mut [usize], i: usize, j: usize) {
+    match target {
+        Target::Plaintext => plaintext_alphabet.swap(i, j),
+        Target::Ciphertext => ciphertext_alphabet.swap(i, j),
+        Target::Both => {
+            plaintext_alphabet.swap(i, j);
+            ciphertext_alphabet.swap(i, j);
+        }
+    }
+}
+
+/// Rotate alphabet so it starts at given position.
+fn rotate(alphabet: &[usize], rotation: usize) -> Vec<usize> {
+    alphabet.iter().cycle().skip(rotation).take(alphabet.len()).cloned().collect()
+}
+
+/// Take every factor-th letter from alphabet, cycling over it.
+fn decimate(alphabet: &[usize], factor: usize) -> Vec<usize> {
+    (0..alphabet.len()).map(|i| alphabet[(i * factor) % alphabet.len()]).collect()
+}
+
+/// Get modular inverse of value, if it exists.
+fn get_modular_inverse(value: usize, modulus: usize) -> Option<usize> {
+    (1..modulus).find(|candidate| (value * candidate) % modulus == 1)
+}
+
+/// Get how many letters at alphabet end are in charset order.
+fn get_ordered_tail_length(alphabet: &[usize]) -> usize {
+    alphabet.windows(2).rev()
+        .take_while(|pair| pair[0] < pair[1])
+        .count()
+}
+
+/// Rotate alphabet and sort its free letters to make it look like a keyed alphabet.
+///
+/// # Parameters:
+/// * alphabet: Alphabet as a list of charset indexes.
+/// * present_letters: Letters whose position is fixed. The rest are sorted in charset order.
+///
+/// # Returns:
+/// * A tuple with alphabet in keyed form and the position of the original alphabet it
+///     starts from.
+fn get_keyed_form(alphabet: &[usize], present_letters: &[bool]) -> (Vec<usize>, usize) {
+    (0..alphabet.len())
+        .map(|rotation| {
+            let mut rotated_alphabet = rotate(alphabet, rotation);
+            let mut free_letters: Vec<usize> = rotated_alphabet.iter().cloned()
+                .filter(|&letter| !present_letters[letter])
+                .collect();
+            free_letters.sort();
+            let mut free_letters_iter = free_letters.into_iter();
+            rotated_alphabet.iter_mut()
+                .filter(|letter| !present_letters[**letter])
+                // Unwrap is safe because there are as many free letters as free positions.
+                .for_each(|letter| *letter = free_letters_iter.next().unwrap());
+            (rotated_alphabet, rotation)
+        })
+        // Ties are solved in favour of the earliest rotation.
+        .max_by_key(|(rotated_alphabet, rotation)| (get_ordered_tail_length(rotated_alphabet), alphabet.len() - rotation))
+        .expect("Alphabets can not be empty.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::read_to_string;
+
+    use rstest::*;
+
+    use crate::attack::dictionaries::get_words_from_text_file;
+    use crate::cipher::quagmire::{cipher, DEFAULT_CHARSET};
+
+    fn get_original_text() -> String {
+        let text = read_to_string("resources/english_book_c1.txt")
+            .expect("Error reading english book.");
+        text.chars().skip(2000).take(1500).collect()
+    }
+
+    fn get_english_model() -> NgramModel {
+        NgramModel::from_text_file("resources/english_book.txt", 4, DEFAULT_CHARSET)
+            .expect("Error building english model.")
+    }
+
+    #[test]
+    fn test_find_period() {
+        let key = QuagmireKey::quagmire_iii("mountain", "flower");
+        let ciphered_text = cipher(&get_original_text(), &key, DEFAULT_CHARSET).unwrap();
+        assert_eq!(find_period(&ciphered_text, DEFAULT_CHARSET, MAX_PERIOD), 6);
+    }
+
+    #[test]
+    fn test_get_best_keyed_alphabet() {
+        let key = QuagmireKey::quagmire_iv("thunder", "mountain", "flower");
+        let ciphered_text = cipher(&get_original_text(), &key, DEFAULT_CHARSET).unwrap();
+        let model = get_english_model();
+        let climber = HillClimber::new(&ciphered_text, 6, Quagmire::IV, &model).unwrap();
+        let keywords = vec!["thunder", "mountain", "flower", "island"];
+        let best_alphabet = climber.get_best_keyed_alphabet(&keywords).unwrap();
+        assert_eq!(climber.to_string(&best_alphabet), keyed_alphabet("mountain", DEFAULT_CHARSET));
+    }
+
+    #[rstest(key, expected_key_string,
+        case(QuagmireKey::quagmire_i("springfever", "flower"), "springfev,flower"),
+        case(QuagmireKey::quagmire_ii("thunder", "flower"), "thunder,flower"),
+        case(QuagmireKey::quagmire_iii("mountain", "flower"), "mountai,flower"),
+        case(QuagmireKey::quagmire_iv("thunder", "mountain", "flower"), "thunder,mountai,flower")
+    )]
+    fn test_hack_quagmire_with_model(key: QuagmireKey, expected_key_string: &str) {
+        let original_text = get_original_text();
+        let ciphered_text = cipher(&original_text, &key, DEFAULT_CHARSET).unwrap();
+        let keywords: Vec<String> = get_words_from_text_file("resources/english_book.txt").unwrap()
+            .into_iter()
+            .collect();
+        let (found_key, _) = hack_quagmire_with_model(&ciphered_text, key.variant, &get_english_model(), &keywords).unwrap();
+        assert_eq!(found_key.to_string(), expected_key_string);
+        assert_eq!(decipher(&ciphered_text, &found_key, DEFAULT_CHARSET).unwrap(), original_text);
+    }
+}
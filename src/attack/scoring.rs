@@ -0,0 +1,567 @@
+/// Module to score how much a text letters distribution looks like a language one.
+///
+/// Frequency attacks decipher a text with every possible key and keep the key whose
+/// deciphered text letters distribution is the nearest to a language profile. Three
+/// scorers are available:
+///
+/// * Chi-squared: Sum of squared differences between observed and expected
+///     occurrences, relative to expected ones.
+/// * Cosine: Cosine similarity between observed occurrences vector and language
+///     frequencies vector.
+/// * Log-likelihood: Average logarithmic probability of every character in text. If
+///     profile has bigrams, every pair of letters inside a word adds how much more, or
+///     less, usual that pair is than its letters on their own.
+///
+/// Letter profiles are built from every language dictionary letters, counted while it is
+/// populated, so any language with a dictionary can be scored against. Some built-in
+/// profiles are available too for those languages without a dictionary.
+///
+/// Every scorer returns a value where higher means more likely, so chi-squared is
+/// returned negated. Whitespaces and usual punctuation marks are counted too, so keys
+/// that place them where letters should be score badly. Characters not present in
+/// profile, like digits, get a tiny probability so texts full of them score badly too.
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::iter::FromIterator;
+
+use rayon::prelude::*;
+
+use crate::Result;
+use crate::attack::dictionaries::{DictionaryStore, LanguageDictionary, count_letter_ngrams};
+use crate::cipher::common::{normalize_text, Counter};
+
+/// Probability given to characters not present in language profile.
+const UNKNOWN_CHARACTER_PROBABILITY: f64 = 0.0001;
+/// Probability of any character not present in language profile, used when they are
+/// counted together.
+const OTHER_CHARACTERS_PROBABILITY: f64 = 0.01;
+/// Probability of a word separator in running text. Words are about five letters
+/// long on average in our languages.
+const WORD_SEPARATOR_PROBABILITY: f64 = 0.18;
+/// Character every whitespace is counted as.
+const WORD_SEPARATOR: char = ' ';
+/// Usual punctuation marks frequencies in running text. They are quite alike in our
+/// languages.
+const PUNCTUATION_MARKS_FREQUENCIES: [(char, f64); 9] = [
+    ('.', 0.009), (',', 0.010), ('\'', 0.003), ('"', 0.003), ('-', 0.002),
+    ('!', 0.0005), ('?', 0.0005), (';', 0.0003), (':', 0.0003)];
+
+const ENGLISH_FREQUENCIES: [(char, f64); 26] = [
+    ('a', 8.167), ('b', 1.492), ('c', 2.782), ('d', 4.253), ('e', 12.702), ('f', 2.228),
+    ('g', 2.015), ('h', 6.094), ('i', 6.966), ('j', 0.153), ('k', 0.772), ('l', 4.025),
+    ('m', 2.406), ('n', 6.749), ('o', 7.507), ('p', 1.929), ('q', 0.095), ('r', 5.987),
+    ('s', 6.327), ('t', 9.056), ('u', 2.758), ('v', 0.978), ('w', 2.360), ('x', 0.150),
+    ('y', 1.974), ('z', 0.074)];
+const SPANISH_FREQUENCIES: [(char, f64); 27] = [
+    ('a', 11.525), ('b', 2.215), ('c', 4.019), ('d', 5.010), ('e', 12.181), ('f', 0.692),
+    ('g', 1.768), ('h', 0.703), ('i', 6.247), ('j', 0.493), ('k', 0.011), ('l', 4.967),
+    ('m', 3.157), ('n', 6.712), ('ñ', 0.311), ('o', 8.683), ('p', 2.510), ('q', 0.877),
+    ('r', 6.871), ('s', 7.977), ('t', 4.632), ('u', 2.927), ('v', 1.138), ('w', 0.017),
+    ('x', 0.215), ('y', 1.008), ('z', 0.467)];
+const FRENCH_FREQUENCIES: [(char, f64); 26] = [
+    ('a', 7.636), ('b', 0.901), ('c', 3.260), ('d', 3.669), ('e', 14.715), ('f', 1.066),
+    ('g', 0.866), ('h', 0.737), ('i', 7.529), ('j', 0.613), ('k', 0.074), ('l', 5.456),
+    ('m', 2.968), ('n', 7.095), ('o', 5.796), ('p', 2.521), ('q', 1.362), ('r', 6.693),
+    ('s', 7.948), ('t', 7.244), ('u', 6.311), ('v', 1.838), ('w', 0.049), ('x', 0.427),
+    ('y', 0.128), ('z', 0.326)];
+const GERMAN_FREQUENCIES: [(char, f64); 26] = [
+    ('a', 6.516), ('b', 1.886), ('c', 2.732), ('d', 5.076), ('e', 16.396), ('f', 1.656),
+    ('g', 3.009), ('h', 4.577), ('i', 6.550), ('j', 0.268), ('k', 1.417), ('l', 3.437),
+    ('m', 2.534), ('n', 9.776), ('o', 2.594), ('p', 0.670), ('q', 0.018), ('r', 7.003),
+    ('s', 7.270), ('t', 6.154), ('u', 4.166), ('v', 0.846), ('w', 1.921), ('x', 0.034),
+    ('y', 0.039), ('z', 1.134)];
+
+/// Letter frequencies of a language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LetterProfile {
+    language: String,
+    frequencies: HashMap<char, f64>,
+    bigram_frequencies: HashMap<String, f64>,
+    /// How often every letter starts a bigram.
+    first_letter_frequencies: HashMap<char, f64>,
+    /// How often every letter ends a bigram.
+    second_letter_frequencies: HashMap<char, f64>
+}
+
+impl LetterProfile {
+    /// Languages with a built-in letter profile.
+    pub const LANGUAGES: [&'static str; 4] = ["english", "spanish", "french", "german"];
+
+    /// Get built-in letter profile for a language.
+    ///
+    /// Built-in profiles only have letter frequencies, not bigram ones.
+    ///
+    /// # Parameters:
+    /// * language: Language name, like "english".
+    ///
+    /// # Returns:
+    /// * Language profile or an error if there is no built-in profile for that language.
+    pub fn get(language: &str) -> Result<Self> {
+        let frequencies: &[(char, f64)] = match language.to_lowercase().as_str() {
+            "english" => &ENGLISH_FREQUENCIES,
+            "spanish" => &SPANISH_FREQUENCIES,
+            "french" => &FRENCH_FREQUENCIES,
+            "german" => &GERMAN_FREQUENCIES,
+            _ => bail!(format!("There is no letter profile for language: {}", language))
+        };
+        let total: f64 = frequencies.iter().map(|(_, frequency)| frequency).sum();
+        Ok(LetterProfile::from_letter_frequencies(language,
+                                                  frequencies.iter().map(|&(letter, frequency)| (letter, frequency / total))))
+    }
+
+    /// Get every built-in letter profile.
+    pub fn get_all() -> Vec<Self> {
+        Self::LANGUAGES.iter()
+            // Unwrap is safe because every language at LANGUAGES has a profile.
+            .map(|language| Self::get(language).unwrap())
+            .collect()
+    }
+
+    /// Get letter profiles for every language at given store.
+    ///
+    /// Languages whose dictionaries have no letters yet get their built-in profile, if
+    /// there is one. If no language at store gets a profile, every built-in one is returned,
+    /// so frequency attacks still work without dictionaries.
+    ///
+    /// # Parameters:
+    /// * store: Dictionaries to build profiles from.
+    ///
+    /// # Returns:
+    /// * Letter profiles.
+    pub fn get_all_with_store(store: &dyn DictionaryStore) -> Result<Vec<Self>> {
+        let mut profiles = Vec::new();
+        for language in store.get_dictionaries_names()? {
+            match Self::from_dictionary(store.open_dictionary(&language)?.as_ref())? {
+                Some(profile) => profiles.push(profile),
+                None => if let Ok(profile) = Self::get(&language) {
+                    profiles.push(profile);
+                }
+            }
+        }
+        if profiles.is_empty() {
+            profiles = Self::get_all();
+        }
+        Ok(profiles)
+    }
+
+    /// Create a letter profile from letters counted at a dictionary.
+    ///
+    /// # Parameters:
+    /// * dictionary: Language dictionary.
+    ///
+    /// # Returns:
+    /// * Language profile, or None if dictionary has no letters.
+    pub fn from_dictionary(dictionary: &dyn LanguageDictionary) -> Result<Option<Self>> {
+        Ok(Self::from_ngram_counts(dictionary.language(), &dictionary.get_letter_ngram_counts()?))
+    }
+
+    /// Create a letter profile from letters and bigrams occurrences.
+    ///
+    /// # Parameters:
+    /// * language: Name for profile language.
+    /// * ngram_counts: Letters and bigrams inside words along with their occurrences, as
+    ///     returned by *count_letter_ngrams*.
+    ///
+    /// # Returns:
+    /// * Language profile, or None if there were no letters counted.
+    pub fn from_ngram_counts(language: &str, ngram_counts: &HashMap<String, u64>) -> Option<Self> {
+        let counts_of_length = |length: usize| ngram_counts.iter()
+            .filter(move |(ngram, _)| ngram.chars().count() == length);
+        let total_letters: u64 = counts_of_length(1).map(|(_, &count)| count).sum();
+        if total_letters == 0 {
+            return None;
+        }
+        let total_bigrams: u64 = counts_of_length(2).map(|(_, &count)| count).sum();
+        let mut profile = LetterProfile::from_letter_frequencies(language, counts_of_length(1)
+            // Unwrap is safe because these ngrams have a single letter.
+            .map(|(letter, &count)| (letter.chars().next().unwrap(), count as f64 / total_letters as f64)));
+        profile.bigram_frequencies = counts_of_length(2)
+            .map(|(bigram, &count)| (bigram.clone(), count as f64 / total_bigrams as f64))
+            .collect();
+        for (bigram, &frequency) in profile.bigram_frequencies.iter() {
+            let mut letters = bigram.chars();
+            // Unwraps are safe because these ngrams have two letters.
+            *profile.first_letter_frequencies.entry(letters.next().unwrap()).or_insert(0.0) += frequency;
+            *profile.second_letter_frequencies.entry(letters.next().unwrap()).or_insert(0.0) += frequency;
+        }
+        Some(profile)
+    }
+
+    /// Create a letter profile counting letters at a text.
+    ///
+    /// # Parameters:
+    /// * language: Name for profile language.
+    /// * text: Text written in language, the longer the better.
+    ///
+    /// # Returns:
+    /// * Language profile.
+    pub fn from_text(language: &str, text: &str) -> Self {
+        let text_words = normalize_text(text);
+        let ngram_counts = count_letter_ngrams(text_words.iter().map(|text_word| (text_word.as_str(), 1)));
+        LetterProfile::from_ngram_counts(language, &ngram_counts)
+            .unwrap_or_else(|| LetterProfile::from_letter_frequencies(language, std::iter::empty()))
+    }
+
+    /// Create a letter profile adding word separators and punctuation marks to letter frequencies.
+    ///
+    /// # Parameters:
+    /// * language: Name for profile language.
+    /// * letter_frequencies: Frequency of every letter. They should add up to 1.
+    ///
+    /// # Returns:
+    /// * Language profile.
+    fn from_letter_frequencies<I>(language: &str, letter_frequencies: I) -> Self
+        where I: Iterator<Item=(char, f64)> {
+        let punctuation_marks_probability: f64 = PUNCTUATION_MARKS_FREQUENCIES.iter()
+            .map(|(_, frequency)| frequency)
+            .sum();
+        let letters_probability = 1.0 - WORD_SEPARATOR_PROBABILITY - punctuation_marks_probability;
+        let mut frequencies: HashMap<char, f64> = letter_frequencies
+            .map(|(letter, frequency)| (letter, frequency * letters_probability))
+            .chain(PUNCTUATION_MARKS_FREQUENCIES.iter().cloned())
+            .collect();
+        frequencies.insert(WORD_SEPARATOR, WORD_SEPARATOR_PROBABILITY);
+        LetterProfile {
+            language: language.to_lowercase(),
+            frequencies,
+            bigram_frequencies: HashMap::new(),
+            first_letter_frequencies: HashMap::new(),
+            second_letter_frequencies: HashMap::new()
+        }
+    }
+
+    /// Get profile language name.
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Get probability of a character in this language.
+    ///
+    /// # Parameters:
+    /// * character: Character to get probability for. Case is ignored and every
+    ///     whitespace is taken as a word separator.
+    ///
+    /// # Returns:
+    /// * Character frequency, or a tiny probability if character is not in profile.
+    pub fn probability(&self, character: char) -> f64 {
+        match self.frequencies.get(&normalize_character(character)) {
+            Some(&frequency) if frequency > 0.0 => frequency,
+            _ => UNKNOWN_CHARACTER_PROBABILITY
+        }
+    }
+
+    /// Get probability of two letters following each other inside a word in this language.
+    ///
+    /// # Parameters:
+    /// * bigram: Two letters, lowercased.
+    ///
+    /// # Returns:
+    /// * Bigram frequency, or a tiny probability if bigram is not in profile. Built-in
+    ///     profiles have no bigrams, so every one of them gets that tiny probability.
+    pub fn bigram_probability(&self, bigram: &str) -> f64 {
+        match self.bigram_frequencies.get(bigram) {
+            Some(&frequency) if frequency > 0.0 => frequency,
+            _ => UNKNOWN_CHARACTER_PROBABILITY
+        }
+    }
+
+    /// Get how much more, or less, usual two letters are following each other inside a
+    /// word than they would be if letters were independent.
+    ///
+    /// # Parameters:
+    /// * first: First letter, lowercased.
+    /// * second: Second letter, lowercased.
+    ///
+    /// # Returns:
+    /// * Logarithm of bigram probability divided by the probabilities of its letters at
+    ///     those positions. Bigrams not in profile get the logarithm of a tiny probability.
+    ///     Profiles without bigrams, like built-in ones, always return 0.
+    pub fn bigram_association(&self, first: char, second: char) -> f64 {
+        if self.bigram_frequencies.is_empty() {
+            return 0.0;
+        }
+        let bigram: String = [first, second].iter().collect();
+        match (self.bigram_frequencies.get(&bigram),
+               self.first_letter_frequencies.get(&first),
+               self.second_letter_frequencies.get(&second)) {
+            (Some(&frequency), Some(&first_frequency), Some(&second_frequency)) if frequency > 0.0 =>
+                (frequency / (first_frequency * second_frequency)).ln(),
+            _ => UNKNOWN_CHARACTER_PROBABILITY.ln()
+        }
+    }
+}
+
+/// Get character as it is counted at profiles: lowercased, and whitespaces as word separators.
+fn normalize_character(character: char) -> char {
+    if character.is_whitespace() {
+        WORD_SEPARATOR
+    } else {
+        character.to_lowercase().next().unwrap_or(character)
+    }
+}
+
+/// Ways to compare a text against a letter profile.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Scorer {
+    ChiSquared,
+    Cosine,
+    LogLikelihood
+}
+
+impl Scorer {
+    /// Every available scorer.
+    pub const ALL: [Scorer; 3] = [Scorer::ChiSquared, Scorer::Cosine, Scorer::LogLikelihood];
+
+    /// Score how much a text looks like a language.
+    ///
+    /// Every character is counted, with case ignored and whitespaces as word separators.
+    ///
+    /// # Parameters:
+    /// * text: Text to score.
+    /// * profile: Letter profile of language to compare against.
+    ///
+    /// # Returns:
+    /// * Score. The higher the more likely text is written in profile language. Texts
+    ///     with nothing to count get the lowest possible score.
+    pub fn score(&self, text: &str, profile: &LetterProfile) -> f64 {
+        let observed: Counter<char> = Counter::from_iter(text.chars().map(normalize_character));
+        let total: u64 = observed.values().sum();
+        if total == 0 {
+            return f64::MIN;
+        }
+        match self {
+            Scorer::ChiSquared => {
+                // Characters out of profile are pooled together. Otherwise, their tiny
+                // expected occurrences would make any of them outweigh every letter.
+                let other_count: u64 = observed.items()
+                    .filter(|(character, _)| !profile.frequencies.contains_key(character))
+                    .map(|(_, &count)| count)
+                    .sum();
+                let other_expected = total as f64 * OTHER_CHARACTERS_PROBABILITY;
+                let observed_chi: f64 = observed.items()
+                    .filter(|(character, _)| profile.frequencies.contains_key(character))
+                    .map(|(&character, &count)| {
+                        let expected = total as f64 * profile.probability(character);
+                        (count as f64 - expected).powi(2) / expected
+                    })
+                    .sum::<f64>() + (other_count as f64 - other_expected).powi(2) / other_expected;
+                // Profile letters absent from text count too.
+                let observed_characters: HashSet<&char> = observed.items().map(|(character, _)| character).collect();
+                let absent_chi: f64 = profile.frequencies.iter()
+                    .filter(|(character, _)| !observed_characters.contains(character))
+                    .map(|(_, &frequency)| total as f64 * frequency)
+                    .sum();
+                -(observed_chi + absent_chi)
+            },
+            Scorer::Cosine => {
+                let dot_product: f64 = observed.items()
+                    .map(|(&character, &count)| count as f64 * profile.probability(character))
+                    .sum();
+                let observed_norm: f64 = observed.values().map(|&count| (count as f64).powi(2)).sum::<f64>().sqrt();
+                let profile_norm: f64 = profile.frequencies.values().map(|frequency| frequency.powi(2)).sum::<f64>().sqrt();
+                dot_product / (observed_norm * profile_norm)
+            },
+            Scorer::LogLikelihood => {
+                let log_likelihood: f64 = observed.items()
+                    .map(|(&character, &count)| count as f64 * profile.probability(character).ln())
+                    .sum();
+                let characters: Vec<char> = text.chars().map(normalize_character).collect();
+                let bigrams_log_likelihood: f64 = characters.windows(2)
+                    .filter(|pair| pair[0].is_alphabetic() && pair[1].is_alphabetic())
+                    .map(|pair| profile.bigram_association(pair[0], pair[1]))
+                    .sum();
+                (log_likelihood + bigrams_log_likelihood) / total as f64
+            }
+        }
+    }
+
+    /// Score a text against several profiles and keep the best score.
+    ///
+    /// # Parameters:
+    /// * text: Text to score.
+    /// * profiles: Letter profiles to compare against.
+    ///
+    /// # Returns:
+    /// * Best score along with the language of the profile that got it.
+    pub fn best_score<'a>(&self, text: &str, profiles: &'a [LetterProfile]) -> (f64, Option<&'a str>) {
+        profiles.iter()
+            .map(|profile| (self.score(text, profile), Some(profile.language())))
+            .fold((f64::MIN, None), |best, current| if current.0 > best.0 { current } else { best })
+    }
+}
+
+/// Find the key whose deciphered text scores the best against any built-in language profile.
+///
+/// Frequency attacks use this to test every key in a key space.
+///
+/// # Parameters:
+/// * keys: Keys to test.
+/// * decipher: Function to decipher text with a key.
+/// * scorer: Scorer to compare deciphered texts with language profiles.
+///
+/// # Returns:
+/// * Best key, or an error if there were no keys to test or deciphering failed.
+pub fn find_best_key<K, F>(keys: &[K], decipher: F, scorer: Scorer) -> Result<K>
+    where K: Clone + Send + Sync,
+          F: Fn(&K) -> Result<String> + Sync {
+    find_best_key_with_profiles(keys, decipher, scorer, &LetterProfile::get_all())
+}
+
+/// Find the key whose deciphered text scores the best against any of given language profiles.
+///
+/// # Parameters:
+/// * keys: Keys to test.
+/// * decipher: Function to decipher text with a key.
+/// * scorer: Scorer to compare deciphered texts with language profiles.
+/// * profiles: Language profiles to compare with.
+///
+/// # Returns:
+/// * Best key, or an error if there were no keys to test or deciphering failed.
+pub fn find_best_key_with_profiles<K, F>(keys: &[K], decipher: F, scorer: Scorer,
+                                         profiles: &[LetterProfile]) -> Result<K>
+    where K: Clone + Send + Sync,
+          F: Fn(&K) -> Result<String> + Sync {
+    let scores: Vec<Result<f64>> = keys.par_iter()
+        .map(|key| Ok(scorer.best_score(&decipher(key)?, profiles).0))
+        .collect();
+    let mut best_key: Option<&K> = None;
+    let mut best_score = f64::MIN;
+    for (key, score) in keys.iter().zip(scores) {
+        let score = score?;
+        if best_key.is_none() || score > best_score {
+            best_key = Some(key);
+            best_score = score;
+        }
+    }
+    match best_key {
+        Some(key) => Ok(key.clone()),
+        None => bail!("There were no keys to test.")
+    }
+}
+
+impl Display for Scorer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Scorer::ChiSquared => "chi-squared",
+            Scorer::Cosine => "cosine",
+            Scorer::LogLikelihood => "log-likelihood"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl TryFrom<&str> for Scorer {
+    type Error = crate::Error;
+
+    /// Get a scorer from its name.
+    ///
+    /// # Parameters:
+    /// * value: Scorer name, like "chi-squared". Case is ignored.
+    ///
+    /// # Returns:
+    /// * OK with scorer or Err if provided name is not a known scorer.
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "chi-squared" => Ok(Scorer::ChiSquared),
+            "cosine" => Ok(Scorer::Cosine),
+            "log-likelihood" => Ok(Scorer::LogLikelihood),
+            _ => bail!(format!("Unknown scorer: {}", value))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::attack::dictionaries::{MemoryDictionary, MemoryStore};
+    use crate::cipher::caesar;
+    use crate::cipher::vigenere::DEFAULT_CHARSET;
+
+    const ENGLISH_TEXT: &'static str = "Common sense is not so common but it is the best thing we have";
+
+    #[test]
+    fn test_letter_profile() {
+        let profile = LetterProfile::get("English").unwrap();
+        assert_eq!(profile.language(), "english");
+        let total: f64 = profile.frequencies.values().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(profile.probability('E') > profile.probability('z'));
+        assert_eq!(profile.probability('7'), UNKNOWN_CHARACTER_PROBABILITY);
+        assert!(LetterProfile::get("klingon").is_err());
+        assert_eq!(LetterProfile::get_all().len(), LetterProfile::LANGUAGES.len());
+        let text_profile = LetterProfile::from_text("test", "aab");
+        assert!((text_profile.probability('a') / text_profile.probability('b') - 2.0).abs() < 1e-9);
+        assert_eq!(text_profile.probability('.'), profile.probability('.'));
+        assert_eq!(text_profile.probability('\t'), WORD_SEPARATOR_PROBABILITY);
+    }
+
+    #[test]
+    fn test_letter_profile_from_dictionary() {
+        let dictionary = MemoryDictionary::from_text("test", "aab ab ba");
+        let profile = LetterProfile::from_dictionary(&dictionary).unwrap().unwrap();
+        assert_eq!(profile.language(), "test");
+        assert!((profile.probability('a') / profile.probability('b') - 4.0 / 3.0).abs() < 1e-9);
+        assert!((profile.bigram_probability("ab") - 0.5).abs() < 1e-9);
+        assert!((profile.bigram_probability("aa") - 0.25).abs() < 1e-9);
+        assert_eq!(profile.bigram_probability("bb"), UNKNOWN_CHARACTER_PROBABILITY);
+        // "ab" is 0.5 of bigrams while "a" starts 0.75 of them and "b" ends 0.5 of them.
+        assert!((profile.bigram_association('a', 'b') - (0.5f64 / (0.75 * 0.5)).ln()).abs() < 1e-9);
+        assert_eq!(profile.bigram_association('b', 'b'), UNKNOWN_CHARACTER_PROBABILITY.ln());
+        assert_eq!(LetterProfile::get("english").unwrap().bigram_association('a', 'b'), 0.0);
+        assert_eq!(LetterProfile::from_dictionary(&MemoryDictionary::new("empty")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_get_all_with_store() {
+        let store: MemoryStore = vec![MemoryDictionary::from_text("klingon", "Qapla batlh"),
+                                      MemoryDictionary::new("spanish")].into_iter().collect();
+        let profiles = LetterProfile::get_all_with_store(&store).unwrap();
+        let languages: Vec<&str> = profiles.iter().map(|profile| profile.language()).collect();
+        // Empty dictionaries fall back to their built-in profile.
+        assert_eq!(vec!["klingon", "spanish"], languages);
+        assert_eq!(LetterProfile::get("spanish").unwrap(), profiles[1]);
+        assert_eq!(LetterProfile::get_all(), LetterProfile::get_all_with_store(&MemoryStore::new()).unwrap());
+    }
+
+    #[test]
+    fn test_scorers_prefer_language() {
+        let profile = LetterProfile::get("english").unwrap();
+        let ciphered_text = caesar::cipher(ENGLISH_TEXT, 7, DEFAULT_CHARSET).unwrap();
+        for scorer in Scorer::ALL.iter() {
+            assert!(scorer.score(ENGLISH_TEXT, &profile) > scorer.score(&ciphered_text, &profile),
+                    "{} scorer failed.", scorer);
+        }
+    }
+
+    #[test]
+    fn test_log_likelihood_uses_bigrams() {
+        let dictionary = MemoryDictionary::from_text("english", ENGLISH_TEXT);
+        let profile = LetterProfile::from_dictionary(&dictionary).unwrap().unwrap();
+        // Same letters, but shuffled inside every word.
+        let shuffled_text = "Cmoonm snees si ont os cmoonm btu ti si hte bset thgin ew ahve";
+        assert!(Scorer::LogLikelihood.score(ENGLISH_TEXT, &profile) >
+                    Scorer::LogLikelihood.score(shuffled_text, &profile));
+        let builtin_profile = LetterProfile::get("english").unwrap();
+        assert!((Scorer::LogLikelihood.score(ENGLISH_TEXT, &builtin_profile) -
+                    Scorer::LogLikelihood.score(shuffled_text, &builtin_profile)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_best_score() {
+        let profiles = LetterProfile::get_all();
+        let (score, language) = Scorer::LogLikelihood.best_score(ENGLISH_TEXT, &profiles);
+        assert_eq!(language, Some("english"));
+        assert!((score - Scorer::LogLikelihood.score(ENGLISH_TEXT, &profiles[0])).abs() < 1e-9);
+        assert_eq!(Scorer::ChiSquared.best_score("", &profiles).1, None);
+    }
+
+    #[test]
+    fn test_scorer_names() {
+        for scorer in Scorer::ALL.iter() {
+            assert_eq!(Scorer::try_from(scorer.to_string().as_str()).unwrap(), *scorer);
+        }
+        assert!(Scorer::try_from("euclidean").is_err());
+    }
+}
@@ -0,0 +1,292 @@
+/// Module to split run-together text into words.
+///
+/// Many ciphertexts drop spaces, so their deciphered texts are a single giant word that
+/// no dictionary can recognize. This module recovers word boundaries using a unigram
+/// model built from a dictionary: every split is scored as the sum of its words
+/// logarithmic probabilities and the best split is found with Viterbi algorithm.
+///
+/// Frequent words are preferred over rare ones. Words not present at dictionary cost as
+/// much as a rare word for every letter they have, so unknown letters are only left alone
+/// when no dictionary word can cover them.
+use std::collections::HashMap;
+
+use crate::Result;
+use crate::attack::dictionaries::{DictionaryStore, LanguageDictionary, get_word_log_probability,
+                                  identify_language_with_segmenters};
+use crate::cipher::common::normalize_text;
+
+/// Longest word segmentation looks for.
+const MAX_WORD_LENGTH: usize = 24;
+/// Shortest word taken as covered when measuring segmentation coverage.
+pub(crate) const MIN_COVERED_WORD_LENGTH: usize = 2;
+/// Texts whose words are longer than this on average are taken as written without spaces.
+/// Natural language words are about five letters long.
+const SPACELESS_AVERAGE_WORD_LENGTH: f64 = 10.0;
+
+/// Unigram model to split text into words.
+#[derive(Debug, Clone)]
+pub struct Segmenter {
+    word_counts: HashMap<String, u64>,
+    total_count: u64,
+    max_word_length: usize
+}
+
+impl Segmenter {
+    /// Create a segmenter from words occurrences.
+    ///
+    /// # Parameters:
+    /// * word_counts: Words along with how many times they appear at language texts.
+    ///
+    /// # Returns:
+    /// * A segmenter that splits text into given words.
+    pub fn new(word_counts: HashMap<String, u64>) -> Self {
+        let word_counts: HashMap<String, u64> = word_counts.into_iter()
+            .filter(|(word, count)| !word.is_empty() && *count > 0)
+            .map(|(word, count)| (word.to_lowercase(), count))
+            .collect();
+        let total_count = word_counts.values().sum::<u64>();
+        let max_word_length = word_counts.keys()
+            .map(|word| word.chars().count())
+            .max()
+            .unwrap_or(1)
+            .min(MAX_WORD_LENGTH);
+        Segmenter {
+            word_counts,
+            total_count,
+            max_word_length
+        }
+    }
+
+    /// Create a segmenter with words from a dictionary.
+    ///
+    /// # Parameters:
+    /// * dictionary: Dictionary whose words are going to be looked for.
+    ///
+    /// # Returns:
+    /// * A segmenter that splits text into dictionary words.
+    pub fn from_dictionary(dictionary: &dyn LanguageDictionary) -> Result<Self> {
+        Ok(Segmenter::new(dictionary.get_all_word_frequencies()?))
+    }
+
+    /// Check if a word is known to this segmenter.
+    pub fn is_known(&self, word: &str) -> bool {
+        self.word_counts.contains_key(word)
+    }
+
+    /// Split a run-together text into its most likely words.
+    ///
+    /// # Parameters:
+    /// * text: Text to split. Only its letters are used, lowercased.
+    ///
+    /// # Returns:
+    /// * Most likely words, in text order.
+    pub fn segment(&self, text: &str) -> Vec<String> {
+        let letters: Vec<char> = normalize_text(text).concat().chars().collect();
+        self.get_boundaries(&letters).windows(2)
+            .map(|boundary| letters[boundary[0]..boundary[1]].iter().collect())
+            .collect()
+    }
+
+    /// Get how much of a list of words is covered by known words.
+    ///
+    /// Segmentation can split any text into single letters, and many dictionaries have
+    /// them as words, so single letter words are not taken as covered.
+    ///
+    /// # Parameters:
+    /// * words: Words, usually got from *segment*.
+    ///
+    /// # Returns:
+    /// * A float between 0 and 1 being 1 as every letter belongs to a known word.
+    pub fn get_coverage(&self, words: &[String]) -> f64 {
+        let total_letters: usize = words.iter().map(|word| word.chars().count()).sum();
+        if total_letters == 0 {
+            return 0.0;
+        }
+        let known_letters: usize = words.iter()
+            .filter(|word| word.chars().count() >= MIN_COVERED_WORD_LENGTH && self.is_known(word))
+            .map(|word| word.chars().count())
+            .sum();
+        known_letters as f64 / total_letters as f64
+    }
+
+    /// Find words boundaries using Viterbi algorithm.
+    ///
+    /// # Parameters:
+    /// * letters: Lowercase letters to split.
+    ///
+    /// # Returns:
+    /// * Positions where every word starts, along with letters length as last position.
+    fn get_boundaries(&self, letters: &[char]) -> Vec<usize> {
+        // best_scores[i] is the score of the best split for first i letters and
+        // word_starts[i] where last word of that split starts.
+        let mut best_scores: Vec<f64> = vec![f64::NEG_INFINITY; letters.len() + 1];
+        let mut word_starts: Vec<usize> = vec![0; letters.len() + 1];
+        best_scores[0] = 0.0;
+        for end in 1..=letters.len() {
+            let first_start = end.saturating_sub(self.max_word_length);
+            for start in first_start..end {
+                let word: String = letters[start..end].iter().collect();
+                let score = best_scores[start] + self.get_word_log_probability(&word);
+                if score > best_scores[end] {
+                    best_scores[end] = score;
+                    word_starts[end] = start;
+                }
+            }
+        }
+        let mut boundaries = vec![letters.len()];
+        let mut end = letters.len();
+        while end > 0 {
+            end = word_starts[end];
+            boundaries.push(end);
+        }
+        boundaries.reverse();
+        if letters.is_empty() {
+            boundaries.clear();
+        }
+        boundaries
+    }
+
+    fn get_word_log_probability(&self, word: &str) -> f64 {
+        let count = self.word_counts.get(word).cloned().unwrap_or(0);
+        get_word_log_probability(count, self.total_count, word.chars().count())
+    }
+}
+
+/// Check if a text looks like written without spaces.
+///
+/// # Parameters:
+/// * text: Text to check.
+///
+/// # Returns:
+/// * True if text words are far longer than natural language ones.
+pub fn is_spaceless<T>(text: T) -> bool
+    where T: AsRef<str> {
+    let words = normalize_text(text);
+    if words.is_empty() {
+        return false;
+    }
+    let total_letters: usize = words.iter().map(|word| word.chars().count()).sum();
+    total_letters as f64 / words.len() as f64 > SPACELESS_AVERAGE_WORD_LENGTH
+}
+
+/// Insert spaces between words of a run-together text.
+///
+/// Every character is kept as it is, so caps, punctuation marks and existing spaces are
+/// preserved. Only chunks of letters are split.
+///
+/// # Parameters:
+/// * text: Text to split.
+/// * segmenter: Segmenter for text language.
+///
+/// # Returns:
+/// * Text with a space between every word found.
+pub fn segment_text(text: &str, segmenter: &Segmenter) -> String {
+    let mut segmented_text = String::with_capacity(text.len());
+    let mut chunk = String::new();
+    for char in text.chars() {
+        if char.is_alphabetic() {
+            chunk.push(char);
+        } else {
+            segmented_text.push_str(&segment_chunk(&chunk, segmenter));
+            chunk.clear();
+            segmented_text.push(char);
+        }
+    }
+    segmented_text.push_str(&segment_chunk(&chunk, segmenter));
+    segmented_text
+}
+
+/// Insert spaces between words of a text written without spaces.
+///
+/// Text language is identified first and its dictionary is used to split text.
+///
+/// # Parameters:
+/// * text: Text to split.
+/// * store: Dictionaries to identify text language and split it with.
+///
+/// # Returns:
+/// * Text with a space between every word found. If text already has spaces or its
+///     language can not be identified then it is returned as it is.
+pub fn split_words(text: &str, store: &dyn DictionaryStore) -> Result<String> {
+    if !is_spaceless(text) {
+        return Ok(text.to_string());
+    }
+    // Every language segmenter was already built to identify text language.
+    let (identified_language, mut segmenters) = identify_language_with_segmenters(text, store)?;
+    match identified_language.winner.and_then(|language| segmenters.remove(&language)) {
+        Some(segmenter) => Ok(segment_text(text, &segmenter)),
+        None => Ok(text.to_string())
+    }
+}
+
+/// Split a chunk of letters keeping its original characters.
+fn segment_chunk(chunk: &str, segmenter: &Segmenter) -> String {
+    let original_letters: Vec<char> = chunk.chars().collect();
+    let lowercase_letters: Vec<char> = original_letters.iter()
+        .map(|letter| letter.to_lowercase().next().unwrap_or(*letter))
+        .collect();
+    segmenter.get_boundaries(&lowercase_letters).windows(2)
+        .map(|boundary| original_letters[boundary[0]..boundary[1]].iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::*;
+
+    use crate::attack::dictionaries::{DatabaseStore, Dictionary, MemoryStore};
+    use crate::attack::dictionaries::tests::{LoadedDictionaries, full_loaded_temp_dictionaries, memory_store};
+
+    fn get_test_segmenter() -> Segmenter {
+        let word_counts = ["this", "is", "my", "secret", "message", "sec", "ret", "a"].iter()
+            .map(|word| (word.to_string(), 1))
+            .collect();
+        Segmenter::new(word_counts)
+    }
+
+    #[test]
+    fn test_segment() {
+        let segmenter = get_test_segmenter();
+        assert_eq!(segmenter.segment("ThisIsMySecretMessage"), vec!["this", "is", "my", "secret", "message"]);
+        assert_eq!(segmenter.segment("thisxqzmessage"), vec!["this", "xqz", "message"]);
+        assert!(segmenter.segment("").is_empty());
+        let words = segmenter.segment("thisxqzmessage");
+        assert!((segmenter.get_coverage(&words) - 11.0 / 14.0).abs() < 1e-9);
+        let words = segmenter.segment("aaa");
+        assert_eq!(words, vec!["a", "a", "a"]);
+        assert_eq!(segmenter.get_coverage(&words), 0.0);
+    }
+
+    #[test]
+    fn test_segment_text() {
+        let segmenter = get_test_segmenter();
+        assert_eq!(segment_text("ThisIsMySecret, message!", &segmenter), "This Is My Secret, message!");
+    }
+
+    #[test]
+    fn test_is_spaceless() {
+        assert!(is_spaceless("thisismysecretmessage"));
+        assert!(!is_spaceless("This is my secret message."));
+        assert!(!is_spaceless(""));
+    }
+
+    #[rstest]
+    fn test_segmenter_from_dictionary(full_loaded_temp_dictionaries: LoadedDictionaries) {
+        let dictionary = Dictionary::new("english", false).unwrap();
+        let segmenter = Segmenter::from_dictionary(&dictionary).unwrap();
+        let words = segmenter.segment("thiseBookisfortheuseofanyone");
+        assert_eq!(words, vec!["this", "ebook", "is", "for", "the", "use", "of", "anyone"]);
+        assert_eq!(segmenter.get_coverage(&words), 1.0);
+        assert_eq!(split_words("ThiseBookisfortheuseofanyone.", &DatabaseStore::default()).unwrap(), "This eBook is for the use of anyone.");
+        assert_eq!(split_words("This eBook is for", &DatabaseStore::default()).unwrap(), "This eBook is for");
+    }
+
+    #[rstest]
+    fn test_split_words_with_memory_store(memory_store: MemoryStore) {
+        assert_eq!(split_words("ThiseBookisfortheuseofanyone.", &memory_store).unwrap(),
+                   "This eBook is for the use of anyone.");
+    }
+}
@@ -3,7 +3,9 @@ use std::collections::HashMap;
 use rayon::prelude::*;
 
 use crate::{ErrorKind, Result, ResultExt};
-use crate::attack::dictionaries::{IdentifiedLanguage, identify_language, get_best_result, Dictionary};
+use crate::attack::dictionaries::{IdentifiedLanguage, identify_language, get_best_result, Dictionary,
+                                  DictionaryStore};
+use crate::attack::membership::CachedStore;
 // use diesel::sql_types::Integer;
 
 
@@ -115,71 +117,51 @@ impl Iterator for IntegerKeyIterator {
     }
 }
 
-/// Iterate through every word in our dictionaries.
-struct DictionaryWordKeyIterator {
+/// Iterate through every word in given dictionaries.
+struct DictionaryWordKeyIterator<'a> {
+    store: &'a dyn DictionaryStore,
     available_languages: Vec<String>,
-    languages_length: usize,
     current_language_index: usize,
     words: Vec<String>,
-    words_length: usize,
     current_word_index: usize
 }
 
-impl DictionaryWordKeyIterator {
-    pub fn new() -> Result<Self> {
-        let available_languages = Dictionary::get_dictionaries_names()?;
-        let languages_length = available_languages.len();
-        let current_language = &available_languages[0];
-        let dictionary = Dictionary::new(current_language, false)?;
-        let words  = dictionary.get_all_words()?;
-        let words_length = words.len();
+impl<'a> DictionaryWordKeyIterator<'a> {
+    /// # Parameters:
+    /// * store: Dictionaries to take words from. A context restricted to some languages
+    ///     only yields words from those.
+    pub fn new(store: &'a dyn DictionaryStore) -> Result<Self> {
         Ok(DictionaryWordKeyIterator {
-            available_languages,
-            languages_length,
+            store,
+            available_languages: store.get_dictionaries_names()?,
             current_language_index: 0,
-            words,
-            words_length,
-            current_word_index:0
+            words: Vec::new(),
+            current_word_index: 0
         })
     }
-
-    fn get_next_word(&mut self) -> Option<String> {
-        let word = self.words[self.current_word_index].clone();
-        self.current_word_index += 1;
-        return Some(word);
-    }
 }
 
-impl Iterator for DictionaryWordKeyIterator {
+impl<'a> Iterator for DictionaryWordKeyIterator<'a> {
     type Item = String;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current_word_index < self.words_length {
-            self.get_next_word()
-        } else if self.current_language_index < self.languages_length {
-            let current_language = &self.available_languages[self.current_language_index];
+        // Skip to next language when current one has no words left.
+        while self.current_word_index >= self.words.len() {
+            let current_language = self.available_languages.get(self.current_language_index)?;
             self.current_language_index += 1;
-            if let Ok(dictionary) = Dictionary::new(current_language, false) {
-                self.words  = match dictionary.get_all_words(){
-                    Ok(_words) => _words,
-                    Err(E) => return None
-                };
-                self.words_length = self.words.len();
-                self.current_word_index = 0;
-                self.get_next_word()
-            } else {
-                return None
-            }
-        } else {
-            None
+            self.words = self.store.open_dictionary(current_language).ok()?.get_all_words().ok()?;
+            self.current_word_index = 0;
         }
+        let word = self.words[self.current_word_index].clone();
+        self.current_word_index += 1;
+        Some(word)
     }
 }
 
 
 
 
-type GetIdentifiedLanguageTuple = fn(&Parameters) -> Result<(usize, IdentifiedLanguage)>;
+type GetIdentifiedLanguageTuple = fn(&Parameters, &dyn DictionaryStore) -> Result<(usize, IdentifiedLanguage)>;
 type GetString = fn(&Parameters)-> Result<String>;
 
 /// Get ciphered text key.
@@ -197,15 +179,21 @@ type GetString = fn(&Parameters)-> Result<String>;
 /// * assess_function_args: Arguments to be used with given *assess_function*. This parameter should
 ///     have all keys-values needed by assess_function **and** next key-value:
 ///     * key_space_length: Key space length of cipher to crack.
+/// * store: Dictionaries to identify deciphered texts with.
 ///
 /// # Returns:
 /// * Found key.
-pub fn brute_force(assess_function: GetIdentifiedLanguageTuple, assess_function_args: &mut Parameters) -> Result<usize> {
+pub fn brute_force(assess_function: GetIdentifiedLanguageTuple, assess_function_args: &mut Parameters,
+                   store: &dyn DictionaryStore) -> Result<usize> {
     let key_space_length = assess_function_args.get_int("key_space_length")?;
+    // Most keys give garbage, so words are filtered before looking them up at dictionaries.
+    let store = CachedStore::new(store);
+    // Failing keys are discarded, so store errors must show up before trying any of them.
+    store.get_dictionaries_names()?;
     let mut results: Vec<Result<(usize, IdentifiedLanguage)>> = Vec::new();
     for key in 1..key_space_length {
         assess_function_args.insert_int("key", key);
-        results.push(assess_function(&assess_function_args));
+        results.push(assess_function(&assess_function_args, &store));
     }
     let best_key = get_best_result(&results);
     Ok(best_key)
@@ -226,17 +214,23 @@ pub fn brute_force(assess_function: GetIdentifiedLanguageTuple, assess_function_
 /// * assess_function_args: Arguments to be used with given *assess_function*. This parameter should
 ///     have all keys-values needed by assess_function **and** next key-value:
 ///     * key_space_length: Key space length of cipher to crack.
+/// * store: Dictionaries to identify deciphered texts with.
 ///
 /// # Returns:
 /// * Found key.
-pub fn brute_force_mp(assess_function: GetIdentifiedLanguageTuple, assess_function_args: &Parameters) -> Result<usize> {
+pub fn brute_force_mp(assess_function: GetIdentifiedLanguageTuple, assess_function_args: &Parameters,
+                      store: &dyn DictionaryStore) -> Result<usize> {
     let key_space_length = assess_function_args.get_int("key_space_length")?;
     let keys_to_try: Vec<usize> = (1..key_space_length).collect();
+    // Filters are built once and shared by every thread.
+    let store = CachedStore::new(store);
+    // Failing keys are discarded, so store errors must show up before trying any of them.
+    store.get_dictionaries_names()?;
     let results: Vec<Result<(usize, IdentifiedLanguage)>> = keys_to_try.par_iter()
         .map(|&key| {
             let mut process_parameters = assess_function_args.clone();
             process_parameters.insert_int("key", key);
-            assess_function(&process_parameters)
+            assess_function(&process_parameters, &store)
         })
         .collect();
     let best_key = get_best_result(&results);
@@ -249,12 +243,14 @@ pub fn brute_force_mp(assess_function: GetIdentifiedLanguageTuple, assess_functi
 /// # Parameters:
 /// * decipher_function: Function to decipher given text.
 /// * decipher_function_args: Key to decipher *ciphered_text*.
+/// * store: Dictionaries to identify deciphered text with.
 ///
 /// # Returns:
 /// * A tuple with used key and an *IdentifiedLanguage* object with assessment result.
-pub fn assess_key(decipher_function: GetString, decipher_function_args: &Parameters) -> Result<(usize, IdentifiedLanguage)> {
+pub fn assess_key(decipher_function: GetString, decipher_function_args: &Parameters,
+                  store: &dyn DictionaryStore) -> Result<(usize, IdentifiedLanguage)> {
     let deciphered_text = decipher_function(decipher_function_args)?;
-    let identified_language = identify_language(deciphered_text)?;
+    let identified_language = identify_language(deciphered_text, store)?;
     let used_key = decipher_function_args.get_int("key")?;
     Ok((used_key, identified_language))
 }
@@ -263,6 +259,7 @@ pub fn assess_key(decipher_function: GetString, decipher_function_args: &Paramet
 mod tests {
     use super::*;
     use rstest::*;
+    use crate::attack::dictionaries::DatabaseStore;
     use crate::attack::dictionaries::tests::{loaded_micro_dictionary_temp_dir, MicroDictionaries};
     use test_common::fs::tmp::TestEnvironment;
     use test_common::system::env::TemporalEnvironmentVariable;
@@ -286,7 +283,8 @@ mod tests {
             .cloned()
             .collect();
         let expected_words_set: HashSet<String> = HashSet::from_iter(expected_words);
-        let word_iterator = DictionaryWordKeyIterator::new().unwrap();
+        let store = DatabaseStore::default();
+        let word_iterator = DictionaryWordKeyIterator::new(&store).unwrap();
         let recovered_words_set: HashSet<String> = HashSet::from_iter(word_iterator);
         assert_eq!(recovered_words_set, expected_words_set);
     }
@@ -9,8 +9,9 @@
 /// message was in a language you don't have a dictionary for, then correct key
 /// won/'t be detected.
 use crate::{ErrorKind, Result, ResultExt, Error};
-use crate::attack::dictionaries::{get_words_from_text, Dictionary, get_word_pattern};
+use crate::attack::dictionaries::{get_words_from_text, get_word_pattern, DictionaryStore, LanguageDictionary};
 use crate::FromStr;
+use crate::attack::crib::{Crib, substitution_partial_keys};
 use crate::cipher::substitution::decipher;
 use std::collections::{HashMap, HashSet};
 // use std::convert::From;
@@ -100,20 +101,19 @@ macro_rules! mapping {
 /// * charset: Charset used for substitution method. Both ends, ciphering
 ///     and deciphering, should use the same charset or original text won't be properly
 ///     recovered.
-/// * database_path: Absolute pathname to database file. Usually you don't
-///      set this parameter, but it is useful for tests.
+/// * store: Dictionaries to look words into.
 ///
 /// # Returns:
 /// * A tuple with substitution key found and success probability.
-pub fn hack_substitution<T, U>(ciphered_text: T, charset: U) -> Result<(String, f64)>
+pub fn hack_substitution<T, U>(ciphered_text: T, charset: U, store: &dyn DictionaryStore) -> Result<(String, f64)>
     where T: AsRef<str>,
           U: AsRef<str> {
     let ciphered_words = get_words_from_text(&ciphered_text);
-    let available_languages = Dictionary::get_dictionaries_names()
+    let available_languages = store.get_dictionaries_names()
         .chain_err(|| ErrorKind::DatabaseError(String::from("We could not get dictionaries names.")))?;
     let mut keys_found: HashMap<String, f64> = HashMap::new();
     for language in available_languages {
-        let language_probabilities = get_keys_probabilities(&ciphered_text, &charset, &ciphered_words, &language)?;
+        let language_probabilities = get_keys_probabilities(&ciphered_text, &charset, &ciphered_words, store, &language, &KeyConstraints::new())?;
         language_probabilities.iter().for_each(|(key, value)| {
             match keys_found.get(key) {
                 Some(previous_value) => {
@@ -129,10 +129,13 @@ pub fn hack_substitution<T, U>(ciphered_text: T, charset: U) -> Result<(String,
     Ok((best_key, best_probability))
 }
 
-fn get_keys_probabilities<T, U>(ciphered_text: &T, charset: &U, ciphered_words: &HashSet<String>, language: &String) -> Result<HashMap<String, f64>>
+fn get_keys_probabilities<T, U>(ciphered_text: &T, charset: &U, ciphered_words: &HashSet<String>,
+                                store: &dyn DictionaryStore, language: &String,
+                                constraints: &KeyConstraints) -> Result<HashMap<String, f64>>
     where T: AsRef<str>, U: AsRef<str> {
-    let (possible_mappings, _) = get_possible_mappings(&language, &ciphered_words, &charset)?;
-    let language_keys = assess_candidate_keys(&ciphered_text, &language,
+    let dictionary = store.open_dictionary(language)?;
+    let (possible_mappings, _) = get_possible_mappings(dictionary.as_ref(), &ciphered_words, &charset, constraints)?;
+    let language_keys = assess_candidate_keys(&ciphered_text, dictionary.as_ref(),
                                               &possible_mappings, &charset)?;
     Ok(language_keys)
 }
@@ -151,20 +154,19 @@ fn get_keys_probabilities<T, U>(ciphered_text: &T, charset: &U, ciphered_words:
 /// * charset: Charset used for substitution method. Both ends, ciphering
 ///     and deciphering, should use the same charset or original text won't be properly
 ///     recovered.
-/// * database_path: Absolute pathname to database file. Usually you don't
-///      set this parameter, but it is useful for tests.
+/// * store: Dictionaries to look words into.
 ///
 /// # Returns:
 /// * A tuple with substitution key found and success probability.
 // pub fn hack_substitution_mp<T, U>(ciphered_text: T, charset: U) -> Result<(String, f64)>
 //     where T: AsRef<str> + std::marker::Sync,
 //           U: AsRef<str> + std::marker::Sync {
-pub fn hack_substitution_mp(ciphered_text: &str, charset: &str) -> Result<(String, f64)> {
+pub fn hack_substitution_mp(ciphered_text: &str, charset: &str, store: &dyn DictionaryStore) -> Result<(String, f64)> {
     let ciphered_words = get_words_from_text(&ciphered_text);
-    let available_languages = Dictionary::get_dictionaries_names()
+    let available_languages = store.get_dictionaries_names()
         .chain_err(|| ErrorKind::DatabaseError(String::from("We could not get dictionaries names.")))?;
     let mut keys_found: HashMap<String, f64> = HashMap::new();
-    let languages_probabilities: Vec<Result<HashMap<String, f64>>> = available_languages.par_iter().map(|language| get_keys_probabilities(&ciphered_text, &charset, &ciphered_words, &language)).collect();
+    let languages_probabilities: Vec<Result<HashMap<String, f64>>> = available_languages.par_iter().map(|language| get_keys_probabilities(&ciphered_text, &charset, &ciphered_words, store, &language, &KeyConstraints::new())).collect();
     for language_probability in languages_probabilities {
         let probabilities = language_probability?;
         keys_found.extend(probabilities.into_iter());
@@ -173,50 +175,227 @@ pub fn hack_substitution_mp(ciphered_text: &str, charset: &str) -> Result<(Strin
     Ok((best_key, best_probability))
 }
 
+/// Get substitution ciphered text key using a known plaintext fragment.
+///
+/// Crib fixes some cipherletters before word pattern matching begins, so fewer mappings
+/// need to be assessed and words not present in dictionaries matter less.
+///
+/// **You should not use this function. Use *hack_substitution_with_crib_mp* instead.** This
+/// function is slower than *mp* one because is sequential while the other uses a
+/// multiprocessing approach. This function only stay here to allow comparisons
+/// between sequential and multiprocessing approaches.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * charset: Charset used for substitution method. Both ends, ciphering
+///     and deciphering, should use the same charset or original text won't be properly
+///     recovered.
+/// * crib: Known plaintext fragment.
+/// * store: Dictionaries to look words into.
+///
+/// # Returns:
+/// * A tuple with substitution key found and success probability.
+pub fn hack_substitution_with_crib(ciphered_text: &str, charset: &str, crib: &Crib,
+                                   store: &dyn DictionaryStore) -> Result<(String, f64)> {
+    let ciphered_words = get_words_from_text(&ciphered_text);
+    let available_languages = store.get_dictionaries_names()
+        .chain_err(|| ErrorKind::DatabaseError(String::from("We could not get dictionaries names.")))?;
+    let crib_constraints = get_crib_constraints(ciphered_text, charset, crib)?;
+    let mut keys_found: HashMap<String, f64> = HashMap::new();
+    for language in available_languages.iter() {
+        for constraints in crib_constraints.iter() {
+            let probabilities = get_keys_probabilities(&ciphered_text, &charset, &ciphered_words, store,
+                                                       language, constraints)?;
+            keys_found.extend(probabilities.into_iter());
+        }
+    }
+    let (best_key, best_probability) = get_best_key(&keys_found);
+    Ok((best_key, best_probability))
+}
+
+/// Get substitution ciphered text key using a known plaintext fragment.
+///
+/// Crib fixes some cipherletters before word pattern matching begins, so fewer mappings
+/// need to be assessed and words not present in dictionaries matter less.
+///
+///  **You should use this function instead of *hack_substitution_with_crib*.**
+///
+///  Whereas *hack_substitution_with_crib* uses a sequential approach, this function uses
+///  multiprocessing to improve performance.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * charset: Charset used for substitution method. Both ends, ciphering
+///     and deciphering, should use the same charset or original text won't be properly
+///     recovered.
+/// * crib: Known plaintext fragment.
+/// * store: Dictionaries to look words into.
+///
+/// # Returns:
+/// * A tuple with substitution key found and success probability.
+pub fn hack_substitution_with_crib_mp(ciphered_text: &str, charset: &str, crib: &Crib,
+                                      store: &dyn DictionaryStore) -> Result<(String, f64)> {
+    let ciphered_words = get_words_from_text(&ciphered_text);
+    let available_languages = store.get_dictionaries_names()
+        .chain_err(|| ErrorKind::DatabaseError(String::from("We could not get dictionaries names.")))?;
+    let crib_constraints = get_crib_constraints(ciphered_text, charset, crib)?;
+    let tasks: Vec<(&String, &KeyConstraints)> = available_languages.iter()
+        .flat_map(|language| crib_constraints.iter().map(move |constraints| (language, constraints)))
+        .collect();
+    let tasks_probabilities: Vec<Result<HashMap<String, f64>>> = tasks.par_iter()
+        .map(|(language, constraints)| get_keys_probabilities(&ciphered_text, &charset, &ciphered_words, store,
+                                                               language, constraints))
+        .collect();
+    let mut keys_found: HashMap<String, f64> = HashMap::new();
+    for task_probabilities in tasks_probabilities {
+        keys_found.extend(task_probabilities?.into_iter());
+    }
+    let (best_key, best_probability) = get_best_key(&keys_found);
+    Ok((best_key, best_probability))
+}
+
+/// Turn every partial key a crib gives into constraints for the solver.
+fn get_crib_constraints(ciphered_text: &str, charset: &str, crib: &Crib) -> Result<Vec<KeyConstraints>> {
+    let partial_keys = substitution_partial_keys(ciphered_text, crib, charset)?;
+    let mut crib_constraints = Vec::new();
+    for partial_key in partial_keys.iter() {
+        let mut constraints = KeyConstraints::new();
+        for (&cipherletter, &plain_letter) in partial_key {
+            constraints.assign(cipherletter, plain_letter)?;
+        }
+        crib_constraints.push(constraints);
+    }
+    Ok(crib_constraints)
+}
+
+/// Get substitution ciphered text key keeping some letter assignments fixed.
+///
+/// Useful when a cryptogram has been partially solved by hand: known assignments
+/// and forbidden ones are respected while word patterns are matched, so the solver
+/// just has to find the remaining letters.
+///
+/// **You should not use this function. Use *hack_substitution_with_constraints_mp* instead.** This
+/// function is slower than *mp* one because is sequential while the other uses a
+/// multiprocessing approach. This function only stay here to allow comparisons
+/// between sequential and multiprocessing approaches.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * charset: Charset used for substitution method. Both ends, ciphering
+///     and deciphering, should use the same charset or original text won't be properly
+///     recovered.
+/// * constraints: Letter assignments found key must respect.
+/// * store: Dictionaries to look words into.
+///
+/// # Returns:
+/// * A tuple with substitution key found and success probability.
+pub fn hack_substitution_with_constraints(ciphered_text: &str, charset: &str, constraints: &KeyConstraints,
+                                          store: &dyn DictionaryStore) -> Result<(String, f64)> {
+    let ciphered_words = get_words_from_text(&ciphered_text);
+    let available_languages = store.get_dictionaries_names()
+        .chain_err(|| ErrorKind::DatabaseError(String::from("We could not get dictionaries names.")))?;
+    let mut keys_found: HashMap<String, f64> = HashMap::new();
+    for language in available_languages.iter() {
+        let probabilities = get_keys_probabilities(&ciphered_text, &charset, &ciphered_words, store,
+                                                   language, constraints)?;
+        keys_found.extend(probabilities.into_iter());
+    }
+    let (best_key, best_probability) = get_best_key(&keys_found);
+    Ok((best_key, best_probability))
+}
+
+/// Get substitution ciphered text key keeping some letter assignments fixed.
+///
+/// Useful when a cryptogram has been partially solved by hand: known assignments
+/// and forbidden ones are respected while word patterns are matched, so the solver
+/// just has to find the remaining letters.
+///
+///  **You should use this function instead of *hack_substitution_with_constraints*.**
+///
+///  Whereas *hack_substitution_with_constraints* uses a sequential approach, this function uses
+///  multiprocessing to improve performance.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * charset: Charset used for substitution method. Both ends, ciphering
+///     and deciphering, should use the same charset or original text won't be properly
+///     recovered.
+/// * constraints: Letter assignments found key must respect.
+/// * store: Dictionaries to look words into.
+///
+/// # Returns:
+/// * A tuple with substitution key found and success probability.
+pub fn hack_substitution_with_constraints_mp(ciphered_text: &str, charset: &str, constraints: &KeyConstraints,
+                                             store: &dyn DictionaryStore) -> Result<(String, f64)> {
+    let ciphered_words = get_words_from_text(&ciphered_text);
+    let available_languages = store.get_dictionaries_names()
+        .chain_err(|| ErrorKind::DatabaseError(String::from("We could not get dictionaries names.")))?;
+    let languages_probabilities: Vec<Result<HashMap<String, f64>>> = available_languages.par_iter()
+        .map(|language| get_keys_probabilities(&ciphered_text, &charset, &ciphered_words, store, language, constraints))
+        .collect();
+    let mut keys_found: HashMap<String, f64> = HashMap::new();
+    for language_probabilities in languages_probabilities {
+        keys_found.extend(language_probabilities?.into_iter());
+    }
+    let (best_key, best_probability) = get_best_key(&keys_found);
+    Ok((best_key, best_probability))
+}
+
 /// Get every possible mapping for given ciphered words in given language.
 ///
 /// # Parameters:
-/// * language: Language to compare with ciphered words.
+/// * dictionary: Language dictionary to compare with ciphered words.
 /// * ciphered_words: Words whose patterns needs to be compared with those from language dictionary.
 /// * charset: Charset used for substitution method. Both ends, ciphering
 ///     and deciphering, should use the same charset or original text won't be properly
 ///     recovered.
+/// * constraints: Letter assignments every mapping must respect.
 ///
 /// # Returns:
 /// * Tuple with a Vec of possible mapping found and a string with language name where those
 ///     mappings where found.
-fn get_possible_mappings<T, U, V>(language: T, ciphered_words: &HashSet<U>, charset: V) -> Result<(Vec<Mapping>, String)>
-    where T: AsRef<str>,
-          U: AsRef<str>,
+fn get_possible_mappings<U, V>(dictionary: &dyn LanguageDictionary, ciphered_words: &HashSet<U>, charset: V,
+                               constraints: &KeyConstraints) -> Result<(Vec<Mapping>, String)>
+    where U: AsRef<str>,
           V: AsRef<str> {
-    let mut global_mapping = generate_language_mapping(&language, ciphered_words, &charset)
+    let mut global_mapping = generate_language_mapping(dictionary, ciphered_words, &charset, constraints)
         .chain_err(|| "Error generating language mapping.")?;
     global_mapping.clean_redundancies();
     let possible_mappings = global_mapping.get_possible_mappings();
-    Ok((possible_mappings, language.as_ref().to_string()))
+    Ok((possible_mappings, dictionary.language().to_string()))
 }
 
 /// Generate a mapping with all letter candidates in given language for every cipherletter.
 ///
 /// # Parameters:
-/// * language: Language to look letter candidates into.
+/// * dictionary: Language dictionary to look letter candidates into.
 /// * ciphered_words: Every cipherword in message.
 /// * charset: Charset used for substitution. Both ends, ciphering
 ///     and deciphering, should use the same charset or original text won't be properly
 ///     recovered.
+/// * constraints: Letter assignments to keep fixed. Dictionary words that contradict
+///     them are not used as candidates.
 ///
 /// # Returns:
 /// * Mapping loaded with all candidates in given language.
-fn generate_language_mapping<T, U, V>(language: T, ciphered_words: &HashSet<U>, charset: V) -> Result<Mapping>
-    where T: AsRef<str>,
-          U: AsRef<str>,
+fn generate_language_mapping<U, V>(dictionary: &dyn LanguageDictionary, ciphered_words: &HashSet<U>, charset: V,
+                                   constraints: &KeyConstraints) -> Result<Mapping>
+    where U: AsRef<str>,
           V: AsRef<str> {
     let mut language_mapping = Mapping::new_empty(&charset);
-    let dictionary = Dictionary::new(&language, false)?;
-    for ciphered_word in ciphered_words {
-        let word_mapping = get_word_mapping(&charset, ciphered_word, &dictionary)?;
+    // Every pattern is looked up at once, as a lookup per cipherword is too slow with big dictionaries.
+    let ciphered_words_patterns: Vec<String> = ciphered_words.iter()
+        .map(|ciphered_word| get_word_pattern(ciphered_word))
+        .collect();
+    let words_by_pattern = dictionary.get_words_with_patterns(&ciphered_words_patterns)
+        .chain_err(|| ErrorKind::DatabaseError(format!("Error getting words with patterns at {}.", dictionary.language())))?;
+    for (ciphered_word, ciphered_word_pattern) in ciphered_words.iter().zip(ciphered_words_patterns.iter()) {
+        let word_candidates = words_by_pattern.get(ciphered_word_pattern)
+            .chain_err(|| ErrorKind::NoMappingAvailable(ciphered_word.as_ref().to_string(), dictionary.language().to_string()))?;
+        let word_mapping = get_word_mapping(&charset, ciphered_word, word_candidates, constraints);
         language_mapping.reduce_mapping(&word_mapping);
     }
+    language_mapping.apply_constraints(constraints);
     Ok(language_mapping)
 }
 
@@ -227,17 +406,19 @@ fn generate_language_mapping<T, U, V>(language: T, ciphered_words: &HashSet<U>,
 ///     and deciphering, should use the same charset or original text won't be properly
 ///     recovered.
 /// * ciphered_word: Ciphered word used to find words with similar patterns.
-/// * dictionary: Dictionary to extract from words with the same pattern than ciphered word.
+/// * dictionary_words: Dictionary words with the same pattern than ciphered word.
+/// * constraints: Letter assignments candidate words must respect.
 ///
 /// # Returns:
 /// * A Mapping class instance.
-fn get_word_mapping<T, U>(charset: T, ciphered_word: U, dictionary: &Dictionary) -> Result<Mapping>
+fn get_word_mapping<T, U>(charset: T, ciphered_word: U, dictionary_words: &[String],
+                          constraints: &KeyConstraints) -> Mapping
     where T: AsRef<str>,
           U: AsRef<str> {
     let mut word_mapping = Mapping::new_empty(&charset);
-    let ciphered_word_pattern: String = get_word_pattern(&ciphered_word);
-    let word_candidates = dictionary.get_words_with_pattern(&ciphered_word_pattern)
-        .chain_err(|| ErrorKind::NoMappingAvailable(ciphered_word.as_ref().to_string(), dictionary.language.clone()))?;
+    let word_candidates: Vec<&String> = dictionary_words.iter()
+        .filter(|word_candidate| constraints.fits(ciphered_word.as_ref(), word_candidate))
+        .collect();
     for (index, char) in ciphered_word.as_ref().chars().enumerate() {
         // let char_string = char.to_string();
         for word_candidate in word_candidates.iter() {
@@ -248,7 +429,7 @@ fn get_word_mapping<T, U>(charset: T, ciphered_word: U, dictionary: &Dictionary)
 
         }
     }
-    Ok(word_mapping)
+    word_mapping
 }
 
 /// Assess every possible mapping and get how many recovered words are identifiable
@@ -256,7 +437,7 @@ fn get_word_mapping<T, U>(charset: T, ciphered_word: U, dictionary: &Dictionary)
 ///
 /// # Parameters:
 /// * ciphered_text: Text to be deciphered.
-/// * language: Language to compare with recovered words.
+/// * dictionary: Language dictionary to compare with recovered words.
 /// * possible_mappings: Possible cipherletter mappings for given text.
 /// * charset: Charset used for substitution method. Both ends, ciphering
 ///    and deciphering, should use the same charset or original text won't be properly
@@ -266,14 +447,13 @@ fn get_word_mapping<T, U>(charset: T, ciphered_word: U, dictionary: &Dictionary)
 /// * A HashMap whose keys are tested keys and values are a 0 to 1 float with
 ///   comparison sucess for given language. 1 means every deciphered word using
 ///   tested key can be found in given language dictionary.
-fn assess_candidate_keys<T, U, V>(ciphered_text: T, language: U,
-                                  possible_mappings: &Vec<Mapping>, charset: V) -> Result<HashMap<String, f64>>
+fn assess_candidate_keys<T, V>(ciphered_text: T, dictionary: &dyn LanguageDictionary,
+                               possible_mappings: &Vec<Mapping>, charset: V) -> Result<HashMap<String, f64>>
     where T: AsRef<str>,
-          U: AsRef<str>,
           V: AsRef<str> {
     let mut keys_found: HashMap<String, f64> = HashMap::new();
     for possible_mapping in possible_mappings {
-        match assess_possible_mapping(possible_mapping, &language, &ciphered_text, &charset) {
+        match assess_possible_mapping(possible_mapping, dictionary, &ciphered_text, &charset) {
             Ok((key, probability)) => { keys_found.insert(key, probability); },
             Err(e) => match e {
                 Error(ErrorKind::WrongKeyLength(_, _), _) => continue,
@@ -290,7 +470,7 @@ fn assess_candidate_keys<T, U, V>(ciphered_text: T, language: U,
 ///
 /// # Parameters:
 /// * possible_mapping: Mapping reduced to maximum.
-/// * language: Language to compare with recovered words.
+/// * dictionary: Language dictionary to compare with recovered words.
 /// * ciphered_text: Text to be deciphered.
 /// * charset: Charset used for substitution method. Both ends, ciphering
 ///      and deciphering, should use the same charset or original text won't be properly
@@ -300,13 +480,12 @@ fn assess_candidate_keys<T, U, V>(ciphered_text: T, language: U,
 /// * A tuple with key generated from given mapping and a 0 to 1 float with
 ///     comparison success for given language. 1 means every deciphered word using
 ///     tested key can be found in given language dictionary.
-fn assess_possible_mapping<T, U, V>(possible_mapping: &Mapping, language: T, ciphered_text: U,
-                                    charset: V) -> Result<(String, f64)>
-    where T: AsRef<str>,
-          U: AsRef<str>,
+fn assess_possible_mapping<U, V>(possible_mapping: &Mapping, dictionary: &dyn LanguageDictionary, ciphered_text: U,
+                                 charset: V) -> Result<(String, f64)>
+    where U: AsRef<str>,
           V: AsRef<str> {
     let key = possible_mapping.generate_key_string();
-    let success = assess_substitution_key(&ciphered_text, &key, &language, &charset)?;
+    let success = assess_substitution_key(&ciphered_text, &key, dictionary, &charset)?;
     Ok((key, success))
 }
 
@@ -319,21 +498,20 @@ fn assess_possible_mapping<T, U, V>(possible_mapping: &Mapping, language: T, cip
 /// # Parameters:
 /// * ciphered_text: Text to be deciphered.
 /// * key: Key to decipher *ciphered_text*.
-/// * language: Language to compare got text.
+/// * dictionary: Language dictionary to compare got text.
 /// * charset: Charset used for substitution. Both ends, ciphering
 ///      and deciphering, should use the same charset or original text won't be properly
 ///      recovered.
 /// # Returns:
 /// * Float from 0 to 1. The higher the frequency of presence of words in language
 ///      the higher of this probability.
-fn assess_substitution_key<T, U, V, W>(ciphered_text: T, key: U, language: V, charset: W) -> Result<f64>
+fn assess_substitution_key<T, U, W>(ciphered_text: T, key: U, dictionary: &dyn LanguageDictionary, charset: W) -> Result<f64>
     where T: AsRef<str>,
           U: AsRef<str>,
-          V: AsRef<str>,
           W: AsRef<str> {
     let recovered_text = decipher(ciphered_text.as_ref(), key.as_ref(), charset.as_ref())?;
     let words = get_words_from_text(&recovered_text);
-    let frequency = get_candidates_frequency_at_language(&words, &language);
+    let frequency = get_candidates_frequency_at_language(&words, dictionary);
     frequency
 }
 
@@ -341,14 +519,12 @@ fn assess_substitution_key<T, U, V, W>(ciphered_text: T, key: U, language: V, ch
 ///
 /// # Parameters:
 /// * words: Text words.
-/// * language: Language you want to look into.
+/// * dictionary: Language dictionary you want to look into.
 ///
 /// # Returns:
 /// * Float from 0 to 1. The higher the frequency of presence of words in language
 ///     the higher of this probability.
-fn get_candidates_frequency_at_language<T>(words: &HashSet<String>, language: T) -> Result<f64>
-    where T: AsRef<str> {
-    let dictionary = Dictionary::new(language.as_ref(), false)?;
+fn get_candidates_frequency_at_language(words: &HashSet<String>, dictionary: &dyn LanguageDictionary) -> Result<f64> {
     let frequency = dictionary.get_words_presence(&words);
     Ok(frequency)
 }
@@ -373,6 +549,162 @@ fn get_best_key(keys_found: &HashMap<String, f64>)-> (String, f64){
     (best_key, best_probability)
 }
 
+/// Letter assignments a substitution attack must respect.
+///
+/// Known assignments fix a cipherletter to a plaintext letter, while forbidden ones
+/// rule out a plaintext letter for a cipherletter. Solver keeps both while it intersects
+/// word mappings, so a cryptogram partially solved by hand can be finished automatically.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyConstraints {
+    known: HashMap<char, char>,
+    forbidden: HashMap<char, HashSet<char>>
+}
+
+impl KeyConstraints {
+    /// Create constraints without any assignment.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse constraints from a comma separated list.
+    ///
+    /// Known assignments are written as "x->e" and forbidden ones as "x!->e". So,
+    /// "x->e, q->t, k!->a" means x deciphers to e, q to t and k to anything but a.
+    ///
+    /// # Parameters:
+    /// * constraints: Constraints list.
+    ///
+    /// # Returns:
+    /// * Parsed constraints or an error if list is malformed or inconsistent.
+    pub fn from_string(constraints: &str) -> Result<Self> {
+        let mut parsed_constraints = Self::new();
+        for constraint in constraints.split(',').map(|constraint| constraint.trim()).filter(|constraint| !constraint.is_empty()) {
+            let (forbidden, letters) = match (constraint.split_once("!->"), constraint.split_once("->")) {
+                (Some(letters), _) => (true, letters),
+                (None, Some(letters)) => (false, letters),
+                (None, None) => bail!(ErrorKind::KeyError(constraint.to_string(),
+                                                          "Constraints must be written as x->e or x!->e.".to_string()))
+            };
+            let (cipherletter, plain_letter) = match (get_single_char(letters.0), get_single_char(letters.1)) {
+                (Some(cipherletter), Some(plain_letter)) => (cipherletter, plain_letter),
+                _ => bail!(ErrorKind::KeyError(constraint.to_string(),
+                                               "Constraints must relate single letters.".to_string()))
+            };
+            if forbidden {
+                parsed_constraints.forbid(cipherletter, plain_letter)?;
+            } else {
+                parsed_constraints.assign(cipherletter, plain_letter)?;
+            }
+        }
+        Ok(parsed_constraints)
+    }
+
+    /// Fix a cipherletter to a plaintext letter.
+    ///
+    /// # Parameters:
+    /// * cipherletter: Letter at ciphered text.
+    /// * plain_letter: Letter cipherletter deciphers to.
+    ///
+    /// # Returns:
+    /// * An error if assignment contradicts any previous one.
+    pub fn assign(&mut self, cipherletter: char, plain_letter: char) -> Result<()> {
+        let constraint = format!("{}->{}", cipherletter, plain_letter);
+        if let Some(&previous_letter) = self.known.get(&cipherletter) {
+            if previous_letter != plain_letter {
+                bail!(ErrorKind::KeyError(constraint, format!("{} is already assigned to {}.", cipherletter, previous_letter)))
+            }
+        }
+        if let Some((&other_cipherletter, _)) = self.known.iter()
+            .find(|(&other_cipherletter, &other_letter)| other_letter == plain_letter && other_cipherletter != cipherletter) {
+            bail!(ErrorKind::KeyError(constraint, format!("{} is already assigned to {}.", other_cipherletter, plain_letter)))
+        }
+        if self.is_forbidden(cipherletter, plain_letter) {
+            bail!(ErrorKind::KeyError(constraint, "Assignment was forbidden.".to_string()))
+        }
+        self.known.insert(cipherletter, plain_letter);
+        Ok(())
+    }
+
+    /// Remove known assignment for a cipherletter.
+    ///
+    /// # Parameters:
+    /// * cipherletter: Letter at ciphered text.
+    ///
+    /// # Returns:
+    /// * Plaintext letter cipherletter was assigned to, if any.
+    pub fn unassign(&mut self, cipherletter: char) -> Option<char> {
+        self.known.remove(&cipherletter)
+    }
+
+    /// Rule out a plaintext letter for a cipherletter.
+    ///
+    /// # Parameters:
+    /// * cipherletter: Letter at ciphered text.
+    /// * plain_letter: Letter cipherletter can not decipher to.
+    ///
+    /// # Returns:
+    /// * An error if that very assignment is already known.
+    pub fn forbid(&mut self, cipherletter: char, plain_letter: char) -> Result<()> {
+        if self.known.get(&cipherletter) == Some(&plain_letter) {
+            bail!(ErrorKind::KeyError(format!("{}!->{}", cipherletter, plain_letter),
+                                      "Assignment is already known.".to_string()))
+        }
+        self.forbidden.entry(cipherletter).or_insert_with(HashSet::new).insert(plain_letter);
+        Ok(())
+    }
+
+    /// Get known assignments.
+    ///
+    /// # Returns:
+    /// * A HashMap whose keys are cipherletters and values their plaintext letters.
+    pub fn known(&self) -> &HashMap<char, char> {
+        &self.known
+    }
+
+    /// Whether there are no constraints at all.
+    pub fn is_empty(&self) -> bool {
+        self.known.is_empty() && self.forbidden.values().all(|letters| letters.is_empty())
+    }
+
+    /// Check if a cipherletter may decipher to given plaintext letter.
+    ///
+    /// # Parameters:
+    /// * cipherletter: Letter at ciphered text.
+    /// * plain_letter: Candidate plaintext letter.
+    ///
+    /// # Returns:
+    /// * True if assignment contradicts no constraint.
+    pub fn allows(&self, cipherletter: char, plain_letter: char) -> bool {
+        match self.known.get(&cipherletter) {
+            Some(&known_letter) => known_letter == plain_letter,
+            None => !self.known.values().any(|&known_letter| known_letter == plain_letter) &&
+                !self.is_forbidden(cipherletter, plain_letter)
+        }
+    }
+
+    /// Check if a dictionary word may be the plaintext of a cipherword.
+    pub(crate) fn fits(&self, ciphered_word: &str, word_candidate: &str) -> bool {
+        ciphered_word.chars().zip(word_candidate.chars())
+            .all(|(cipherletter, plain_letter)| self.allows(cipherletter, plain_letter))
+    }
+
+    fn is_forbidden(&self, cipherletter: char, plain_letter: char) -> bool {
+        match self.forbidden.get(&cipherletter) {
+            Some(letters) => letters.contains(&plain_letter),
+            None => false
+        }
+    }
+}
+
+/// Get the only character of a string, if it has just one.
+fn get_single_char(text: &str) -> Option<char> {
+    let mut chars = text.trim().chars();
+    match (chars.next(), chars.next()) {
+        (Some(char), None) => Some(char),
+        _ => None
+    }
+}
+
 /// Type to manage possible candidates to substitute every cipherletter in charset.
 ///
 /// You can use it as a dict whose keys are letters and values are sets with substitution
@@ -599,6 +931,24 @@ impl Mapping {
         }
     }
 
+    /// Make mapping respect given constraints.
+    ///
+    /// Known cipherletters are reduced to their assigned letter, and any candidate
+    /// constraints do not allow is removed from the rest.
+    ///
+    /// # Parameters:
+    /// * constraints: Letter assignments to keep fixed.
+    fn apply_constraints(&mut self, constraints: &KeyConstraints) {
+        for (&cipherletter, &plain_letter) in constraints.known() {
+            self.create_new_single_entry(cipherletter, plain_letter);
+        }
+        for (cipherletter, candidates) in self.mapping.iter_mut() {
+            if let Some(set) = candidates {
+                set.retain(|&candidate| constraints.allows(*cipherletter, candidate));
+            }
+        }
+    }
+
     /// Remove redundancies from mapping.
     ///
     /// If any cipherletter has been reduced to just one candidate, then that
@@ -798,7 +1148,9 @@ pub mod tests {
     use std::env;
     use std::fs::File;
     use std::time::Instant;
-    use crate::attack::dictionaries::tests::LoadedDictionaries;
+    use rstest::*;
+    use crate::attack::dictionaries::{DatabaseStore, MemoryStore};
+    use crate::attack::dictionaries::tests::{LoadedDictionaries, memory_store};
     use crate::cipher::substitution::{cipher, decipher};
     use std::io::Read;
     use std::path::PathBuf;
@@ -877,7 +1229,7 @@ pub mod tests {
                 Err(E) => {assert!(false, E); String::new()}
             };
             let timer = Instant::now();
-            let found_key = hack_substitution(&ciphered_text, &set.charset)
+            let found_key = hack_substitution(&ciphered_text, &set.charset, &DatabaseStore::default())
                 .expect("Error running hacking_substitution().");
             assert_found_key(&found_key, &set.key, &ciphered_text,
                              &text, &set.charset);
@@ -899,7 +1251,7 @@ pub mod tests {
                 Err(E) => {assert!(false, E); String::new()}
             };
             let timer = Instant::now();
-            let found_key = hack_substitution_mp(&ciphered_text, &set.charset)
+            let found_key = hack_substitution_mp(&ciphered_text, &set.charset, &DatabaseStore::default())
                 .expect("Error running hacking_substitution().");
             assert_found_key(&found_key, &set.key, &ciphered_text,
                              &text, &set.charset);
@@ -907,6 +1259,82 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_hack_substitution_with_crib_mp() {
+        let set = TestSet::new(ENGLISH_TEXT_WITH_PUNCTUATIONS_MARKS, "english", TEST_KEY, TEST_CHARSET);
+        let loaded_dictionaries = LoadedDictionaries::new();
+        let text = get_text_to_cipher(&set);
+        let ciphered_text = cipher(&text, &set.key, &set.charset).unwrap();
+        let crib_text: String = text.chars().take(20).collect();
+        let crib = Crib::new(&crib_text, Some(0));
+        let found_key = hack_substitution_with_crib_mp(&ciphered_text, &set.charset, &crib, &DatabaseStore::default())
+            .expect("Error running hack_substitution_with_crib_mp().");
+        assert_found_key(&found_key, &set.key, &ciphered_text,
+                         &text, &set.charset);
+        assert_eq!(hack_substitution_with_crib(&ciphered_text, &set.charset, &crib, &DatabaseStore::default()).unwrap().0, found_key.0);
+    }
+
+    #[rstest]
+    fn test_hack_substitution_mp_with_memory_store(memory_store: MemoryStore) {
+        let set = TestSet::new(ENGLISH_TEXT_WITH_PUNCTUATIONS_MARKS, "english", TEST_KEY, TEST_CHARSET);
+        let text = get_text_to_cipher(&set);
+        let ciphered_text = cipher(&text, &set.key, &set.charset).unwrap();
+        let found_key = hack_substitution_mp(&ciphered_text, &set.charset, &memory_store)
+            .expect("Error running hack_substitution_mp().");
+        assert_found_key(&found_key, &set.key, &ciphered_text,
+                         &text, &set.charset);
+    }
+
+    #[test]
+    fn test_hack_substitution_with_constraints_mp() {
+        let set = TestSet::new(ENGLISH_TEXT_WITH_PUNCTUATIONS_MARKS, "english", TEST_KEY, TEST_CHARSET);
+        let loaded_dictionaries = LoadedDictionaries::new();
+        let text = get_text_to_cipher(&set);
+        let ciphered_text = cipher(&text, &set.key, &set.charset).unwrap();
+        // With TEST_KEY, e is ciphered as a, t as j and a as l.
+        let constraints = KeyConstraints::from_string("a->e, j->t, l!->b").unwrap();
+        let found_key = hack_substitution_with_constraints_mp(&ciphered_text, &set.charset, &constraints, &DatabaseStore::default())
+            .expect("Error running hack_substitution_with_constraints_mp().");
+        assert_found_key(&found_key, &set.key, &ciphered_text,
+                         &text, &set.charset);
+        assert_eq!(hack_substitution_with_constraints(&ciphered_text, &set.charset, &constraints, &DatabaseStore::default()).unwrap().0, found_key.0);
+    }
+
+    #[test]
+    fn test_key_constraints() {
+        let mut constraints = KeyConstraints::from_string("x->e, q->t, k!->a").unwrap();
+        assert!(constraints.allows('x', 'e'));
+        assert!(!constraints.allows('x', 'a'));
+        assert!(!constraints.allows('z', 'e'));
+        assert!(!constraints.allows('k', 'a'));
+        assert!(constraints.allows('k', 'b'));
+        assert!(constraints.assign('z', 'e').is_err());
+        assert!(constraints.assign('x', 'b').is_err());
+        assert!(constraints.assign('k', 'a').is_err());
+        assert!(constraints.forbid('x', 'e').is_err());
+        assert_eq!(constraints.unassign('x'), Some('e'));
+        assert!(constraints.assign('z', 'e').is_ok());
+        assert!(KeyConstraints::from_string("xe").is_err());
+        assert!(KeyConstraints::from_string("xy->e").is_err());
+        assert!(KeyConstraints::from_string("x->e,y->e").is_err());
+        assert!(KeyConstraints::from_string("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_constraints() {
+        let mut current_mapping = mapping!(TEST_CHARSET,
+                                           {"1" : {"a", "b", "c"},
+                                            "2" : {"a", "d"},
+                                            "3" : {"e", "f"}});
+        let constraints = KeyConstraints::from_string("2->a, 3!->e").unwrap();
+        let expected_mapping = mapping!(TEST_CHARSET,
+                                        {"1" : {"b", "c"},
+                                         "2" : {"a"},
+                                         "3" : {"f"}});
+        current_mapping.apply_constraints(&constraints);
+        assert_eq!(current_mapping, expected_mapping);
+    }
+
     fn get_text_to_cipher(set: &TestSet) -> String {
         let mut text_file_pathname = match env::current_dir() {
             Ok(cwd) => cwd,
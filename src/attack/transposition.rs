@@ -4,10 +4,10 @@
 /// a text using Transposition algorithm.
 
 use crate::Result;
-use crate::attack::dictionaries::IdentifiedLanguage;
+use crate::attack::dictionaries::{DictionaryStore, IdentifiedLanguage};
 use crate::attack::simple_attacks::{Parameters, assess_key};
-use crate::attack::simple_attacks::brute_force as simple_brute_force;
-use crate::attack::simple_attacks::brute_force_mp as simple_brute_force_mp;
+use crate::attack::simple_attacks::{brute_force as simple_brute_force,
+                                    brute_force_mp as simple_brute_force_mp};
 use crate::cipher::transposition::decipher_par;
 
 /// Get Transposition ciphered text key.
@@ -22,13 +22,14 @@ use crate::cipher::transposition::decipher_par;
 ///
 /// # Parameters:
 /// * ciphered_text: Text to be deciphered.
+/// * store: Dictionaries to identify deciphered texts with.
 ///
 /// # Returns:
 /// * Transposition key found.
-pub fn brute_force<T>(ciphered_text: T)-> Result<usize>
+pub fn brute_force<T>(ciphered_text: T, store: &dyn DictionaryStore)-> Result<usize>
     where T: AsRef<str> {
     let mut parameters = create_parameters(ciphered_text);
-    simple_brute_force(assess_transposition_key, &mut parameters)
+    simple_brute_force(assess_transposition_key, &mut parameters, store)
 }
 
 /// Get Transposition ciphered text key.
@@ -43,13 +44,14 @@ pub fn brute_force<T>(ciphered_text: T)-> Result<usize>
 ///
 /// # Parameters:
 /// * ciphered_text: Text to be deciphered.
+/// * store: Dictionaries to identify deciphered texts with.
 ///
 /// # Returns:
 /// * Transposition key found.
 // pub fn brute_force_mp<T>(ciphered_text: T)-> Result<usize>
-pub fn brute_force_mp(ciphered_text: &str)-> Result<usize> {
-    let mut parameters = create_parameters(ciphered_text);
-    simple_brute_force_mp(assess_transposition_key, &mut parameters)
+pub fn brute_force_mp(ciphered_text: &str, store: &dyn DictionaryStore)-> Result<usize> {
+    let parameters = create_parameters(ciphered_text);
+    simple_brute_force_mp(assess_transposition_key, &parameters, store)
 }
 
 /// Get a Parameters type with given arguments.
@@ -77,17 +79,19 @@ fn create_parameters<T>(ciphered_text: T)-> Parameters
 /// * parameters: A Parameters type with at least next key-values.
 ///     * ciphered_text (str): Text to be deciphered.
 ///     * key: Key to decipher *ciphered_text*.
+/// * store: Dictionaries to identify deciphered text with.
 ///
 /// # Returns:
 /// * A tuple with used key ans An *IdentifiedLanguage* object with assessment result.
-fn assess_transposition_key(parameters: &Parameters)-> Result<(usize, IdentifiedLanguage)>{
-    assess_key(decipher_par, parameters)
+fn assess_transposition_key(parameters: &Parameters, store: &dyn DictionaryStore)-> Result<(usize, IdentifiedLanguage)>{
+    assess_key(decipher_par, parameters, store)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::time::Instant;
+    use crate::attack::dictionaries::DatabaseStore;
     use crate::attack::dictionaries::tests::LoadedDictionaries;
     use crate::cipher::transposition::decipher;
 
@@ -99,7 +103,7 @@ mod tests {
     fn test_brute_force_transposition() {
         let loaded_dictionaries = LoadedDictionaries::new();
         let timer = Instant::now();
-        let found_key = brute_force(CIPHERED_MESSAGE_KEY_8);
+        let found_key = brute_force(CIPHERED_MESSAGE_KEY_8, &DatabaseStore::default());
         assert_found_key(found_key);
         println!("{}", format!("\n\nElapsed time with test_brute_force_transposition: {:.2} seconds.", timer.elapsed().as_secs_f64()));
     }
@@ -108,7 +112,7 @@ mod tests {
     fn test_brute_force_transposition_mp() {
         let loaded_dictionaries = LoadedDictionaries::new();
         let timer = Instant::now();
-        let found_key = brute_force_mp(CIPHERED_MESSAGE_KEY_8);
+        let found_key = brute_force_mp(CIPHERED_MESSAGE_KEY_8, &DatabaseStore::default());
         assert_found_key(found_key);
         println!("{}", format!("\n\nElapsed time with test_brute_force_transposition_mp: {:.2} seconds.", timer.elapsed().as_secs_f64()));
     }
@@ -0,0 +1,284 @@
+/// Module to attack Vernam ciphered data when the same pad has been used more than once.
+///
+/// XORing two ciphertexts made with the same pad cancels pad out and leaves the XOR of
+/// both plaintexts. So, if we guess a fragment of one plaintext (a crib), XORing it at
+/// the right offset reveals the fragment of the other plaintext at that same offset. This
+/// is crib dragging: crib is slid along every offset and revealed fragments that look
+/// like natural language point to the right offset.
+///
+/// With three or more ciphertexts pad can even be recovered automatically, guessing
+/// every pad byte as the one that makes all ciphertexts bytes at that position look
+/// the most like language letters.
+///
+/// Fragments are scored with a language model. Model only knows about charset letters,
+/// so spaces and punctuation marks are scored with fixed probabilities.
+use rayon::prelude::*;
+
+use crate::Result;
+use crate::attack::ngrams::NgramModel;
+
+/// Logarithmic probability given to spaces. Spaces are about a sixth of english texts.
+const SPACE_LOG_PROBABILITY: f64 = -0.8;
+/// Logarithmic probability given to printable characters that are not letters nor spaces.
+const PUNCTUATION_LOG_PROBABILITY: f64 = -2.5;
+/// Logarithmic probability given to non printable characters.
+const NON_PRINTABLE_LOG_PROBABILITY: f64 = -8.0;
+
+/// Plaintext fragment revealed by a crib at a given offset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CribMatch {
+    /// Ciphertext whose plaintext is supposed to have the crib.
+    pub crib_ciphertext: usize,
+    /// Ciphertext whose plaintext fragment is revealed.
+    pub revealed_ciphertext: usize,
+    /// Offset where crib was placed.
+    pub offset: usize,
+    /// Plaintext fragment revealed.
+    pub revealed_text: Vec<u8>,
+    /// Average logarithmic probability of revealed fragment. The higher the more it
+    /// looks like natural language.
+    pub score: f64
+}
+
+/// Drag a crib along every ciphertexts pair.
+///
+/// **You should not use this function. Use *crib_drag_mp* instead.** This
+/// function is slower than *mp* one because is sequential while the other uses a
+/// multiprocessing approach. This function only stay here to allow comparisons
+/// between sequential and multiprocessing approaches.
+///
+/// # Parameters:
+/// * ciphertexts: Ciphertexts ciphered with the same pad.
+/// * crib: Fragment guessed to be at one of the plaintexts.
+/// * model: Model of the language plaintexts are supposed to be written in.
+///
+/// # Returns:
+/// * Every crib match, best scored first.
+pub fn crib_drag(ciphertexts: &[Vec<u8>], crib: &[u8], model: &NgramModel) -> Vec<CribMatch> {
+    let mut matches: Vec<CribMatch> = get_drag_tasks(ciphertexts, crib).iter()
+        .map(|&(crib_ciphertext, revealed_ciphertext, offset)|
+            assess_crib(ciphertexts, crib, model, crib_ciphertext, revealed_ciphertext, offset))
+        .collect();
+    sort_matches(&mut matches);
+    matches
+}
+
+/// Drag a crib along every ciphertexts pair.
+///
+/// **You should use this function instead of *crib_drag*.**
+///
+/// Whereas *crib_drag* uses a sequential approach, this function uses
+/// multiprocessing to improve performance.
+///
+/// # Parameters:
+/// * ciphertexts: Ciphertexts ciphered with the same pad.
+/// * crib: Fragment guessed to be at one of the plaintexts.
+/// * model: Model of the language plaintexts are supposed to be written in.
+///
+/// # Returns:
+/// * Every crib match, best scored first.
+pub fn crib_drag_mp(ciphertexts: &[Vec<u8>], crib: &[u8], model: &NgramModel) -> Vec<CribMatch> {
+    let mut matches: Vec<CribMatch> = get_drag_tasks(ciphertexts, crib).par_iter()
+        .map(|&(crib_ciphertext, revealed_ciphertext, offset)|
+            assess_crib(ciphertexts, crib, model, crib_ciphertext, revealed_ciphertext, offset))
+        .collect();
+    sort_matches(&mut matches);
+    matches
+}
+
+/// Get every ciphertexts pair and offset where crib fits.
+///
+/// # Returns:
+/// * A list of tuples with crib ciphertext, revealed ciphertext and offset.
+fn get_drag_tasks(ciphertexts: &[Vec<u8>], crib: &[u8]) -> Vec<(usize, usize, usize)> {
+    let mut tasks = Vec::new();
+    for crib_ciphertext in 0..ciphertexts.len() {
+        for revealed_ciphertext in (0..ciphertexts.len()).filter(|&other| other != crib_ciphertext) {
+            let overlap = ciphertexts[crib_ciphertext].len().min(ciphertexts[revealed_ciphertext].len());
+            if crib.is_empty() || overlap < crib.len() {
+                continue;
+            }
+            (0..=overlap - crib.len())
+                .for_each(|offset| tasks.push((crib_ciphertext, revealed_ciphertext, offset)));
+        }
+    }
+    tasks
+}
+
+fn assess_crib(ciphertexts: &[Vec<u8>], crib: &[u8], model: &NgramModel,
+               crib_ciphertext: usize, revealed_ciphertext: usize, offset: usize) -> CribMatch {
+    let revealed_text: Vec<u8> = crib.iter().enumerate()
+        .map(|(i, crib_byte)| crib_byte ^ ciphertexts[crib_ciphertext][offset + i] ^ ciphertexts[revealed_ciphertext][offset + i])
+        .collect();
+    let score = score_bytes(&revealed_text, model) / revealed_text.len() as f64;
+    CribMatch {
+        crib_ciphertext,
+        revealed_ciphertext,
+        offset,
+        revealed_text,
+        score
+    }
+}
+
+fn sort_matches(matches: &mut Vec<CribMatch>) {
+    matches.sort_by(|one, other| other.score.partial_cmp(&one.score).unwrap_or(std::cmp::Ordering::Equal));
+}
+
+/// Score how much given bytes look like natural language text.
+///
+/// # Parameters:
+/// * bytes: Bytes to score, as ASCII characters.
+/// * model: Language model to score letters with.
+///
+/// # Returns:
+/// * Sum of logarithmic probabilities of every byte. The higher the more likely
+///     bytes are text in modeled language.
+pub fn score_bytes(bytes: &[u8], model: &NgramModel) -> f64 {
+    let charset: Vec<char> = model.charset().chars().collect();
+    bytes.iter()
+        .map(|&byte| {
+            let char = (byte as char).to_ascii_lowercase();
+            match charset.iter().position(|&x| x == char) {
+                Some(letter) => model.letter_log_probability(letter),
+                None if byte == b' ' => SPACE_LOG_PROBABILITY,
+                None if byte.is_ascii_graphic() || byte == b'\n' => PUNCTUATION_LOG_PROBABILITY,
+                None => NON_PRINTABLE_LOG_PROBABILITY
+            }
+        })
+        .sum()
+}
+
+/// Fix pad bytes assuming a crib is at a given offset of a ciphertext.
+///
+/// Once crib dragging has found where a crib is, pad under it is known, so
+/// every other ciphertext can be deciphered at that stretch.
+///
+/// # Parameters:
+/// * pad: Pad recovered so far. It is extended with zeros if crib goes beyond its end.
+/// * ciphertext: Ciphertext whose plaintext has the crib.
+/// * offset: Offset where crib is.
+/// * crib: Known plaintext fragment.
+pub fn apply_crib(pad: &mut Vec<u8>, ciphertext: &[u8], offset: usize, crib: &[u8]) {
+    let end = (offset + crib.len()).min(ciphertext.len());
+    if pad.len() < end {
+        pad.resize(end, 0);
+    }
+    (offset..end).for_each(|i| pad[i] = ciphertext[i] ^ crib[i - offset]);
+}
+
+/// Recover pad automatically from several ciphertexts ciphered with it.
+///
+/// Every pad byte is guessed independently, as the one that makes ciphertexts bytes at
+/// its position score the best as language characters. So, the more ciphertexts the
+/// better, with less than three results are usually poor. Pad positions covered by a
+/// single ciphertext can not be guessed this way and are left as zeros.
+///
+/// # Parameters:
+/// * ciphertexts: Ciphertexts ciphered with the same pad.
+/// * model: Model of the language plaintexts are supposed to be written in.
+///
+/// # Returns:
+/// * Recovered pad, as long as the longest ciphertext.
+pub fn recover_pad(ciphertexts: &[Vec<u8>], model: &NgramModel) -> Result<Vec<u8>> {
+    if ciphertexts.len() < 2 {
+        bail!("At least two ciphertexts with the same pad are needed.")
+    }
+    let pad_length = ciphertexts.iter().map(|ciphertext| ciphertext.len()).max().unwrap_or(0);
+    let pad: Vec<u8> = (0..pad_length).into_par_iter()
+        .map(|position| {
+            let column: Vec<u8> = ciphertexts.iter()
+                .filter_map(|ciphertext| ciphertext.get(position).cloned())
+                .collect();
+            if column.len() < 2 {
+                return 0;
+            }
+            (0..=u8::MAX)
+                .map(|pad_byte| {
+                    let deciphered_column: Vec<u8> = column.iter().map(|byte| byte ^ pad_byte).collect();
+                    (pad_byte, score_bytes(&deciphered_column, model))
+                })
+                .fold((0, f64::MIN), |best, current| if current.1 > best.1 { current } else { best })
+                .0
+        })
+        .collect();
+    Ok(pad)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::read_to_string;
+
+    use crate::cipher::vernam::{cipher, decipher};
+
+    const CHARSET: &'static str = "abcdefghijklmnopqrstuvwxyz";
+
+    fn get_english_model() -> NgramModel {
+        NgramModel::from_text_file("resources/english_book.txt", 4, CHARSET)
+            .expect("Error building english model.")
+    }
+
+    fn get_pad(length: usize) -> Vec<u8> {
+        (0..length).map(|i| (i * 97 + 13) as u8 ^ 0x5a).collect()
+    }
+
+    fn get_plaintexts() -> Vec<String> {
+        let text = read_to_string("resources/english_book_c1.txt")
+            .expect("Error reading english book.");
+        let lines: Vec<&str> = text.lines()
+            .filter(|line| line.len() > 60)
+            .take(8)
+            .collect();
+        lines.iter().map(|line| line.chars().take(60).collect()).collect()
+    }
+
+    #[test]
+    fn test_crib_drag() {
+        let pad = get_pad(60);
+        let first_plaintext = b"We attack at dawn from the north, bring every soldier.";
+        let second_plaintext = b"The weather is fine and the enemy does not expect us.";
+        let ciphertexts = vec![cipher(first_plaintext, &pad).unwrap(), cipher(second_plaintext, &pad).unwrap()];
+        let matches = crib_drag_mp(&ciphertexts, b" the ", &get_english_model());
+        let best_match = &matches[0];
+        // XOR of both plaintexts is symmetric, so crib at one of them reveals the other
+        // whichever ciphertext crib was assumed at.
+        assert!([22, 23].contains(&best_match.offset), "Best match was: {:?}", best_match);
+        let revealed_text = String::from_utf8(best_match.revealed_text.clone()).unwrap();
+        assert!(String::from_utf8_lossy(first_plaintext).contains(&revealed_text) ||
+                String::from_utf8_lossy(second_plaintext).contains(&revealed_text));
+        assert_eq!(crib_drag(&ciphertexts, b" the ", &get_english_model())[0], *best_match);
+    }
+
+    #[test]
+    fn test_apply_crib() {
+        let pad = get_pad(20);
+        let ciphertext = cipher(b"Hello world, again!!", &pad).unwrap();
+        let mut recovered_pad = Vec::new();
+        apply_crib(&mut recovered_pad, &ciphertext, 6, b"world");
+        assert_eq!(recovered_pad.len(), 11);
+        assert_eq!(&recovered_pad[6..11], &pad[6..11]);
+    }
+
+    #[test]
+    fn test_recover_pad() {
+        let pad = get_pad(60);
+        let plaintexts = get_plaintexts();
+        let ciphertexts: Vec<Vec<u8>> = plaintexts.iter()
+            .map(|plaintext| cipher(plaintext.as_bytes(), &pad).unwrap())
+            .collect();
+        let recovered_pad = recover_pad(&ciphertexts, &get_english_model()).unwrap();
+        let right_bytes = recovered_pad.iter().zip(pad.iter())
+            .filter(|(recovered, original)| recovered == original)
+            .count();
+        assert!(right_bytes as f64 / pad.len() as f64 > 0.8, "Only {} right bytes.", right_bytes);
+        // Every byte deciphered with a right pad byte is the original one, and the others can't be.
+        for (plaintext, ciphertext) in plaintexts.iter().zip(ciphertexts.iter()) {
+            let deciphered_text = decipher(ciphertext, &recovered_pad).unwrap();
+            let right_letters = deciphered_text.iter().zip(plaintext.as_bytes().iter())
+                .filter(|(deciphered, original)| deciphered == original)
+                .count();
+            assert_eq!(right_bytes, right_letters, "Deciphered text was: {}", String::from_utf8_lossy(&deciphered_text));
+        }
+    }
+}
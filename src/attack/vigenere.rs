@@ -8,7 +8,21 @@
 /// the good one comparing it with words from a language dictionary. If original
 /// message was in a language you don't have a dictionary for, then correct key
 /// won't be detected.
+use std::collections::HashSet;
+
+use rayon::prelude::*;
+
 use crate::Result;
+use crate::attack::dictionaries::DictionaryStore;
+use crate::attack::frequency::get_index_of_coincidence;
+use crate::attack::scoring::{LetterProfile, Scorer};
+use crate::cipher::vigenere::decipher;
+
+/// Longest key we look for at frequency analysis attack.
+const MAX_KEY_LENGTH: usize = 20;
+/// Index of coincidence, relative to a random text one, a column must reach to be taken
+/// as ciphered with a single key letter. Our languages are about 1.7 times above random.
+const LANGUAGE_RELATIVE_INDEX: f64 = 1.5;
 
 
 /// Get Vigenere ciphered text key.
@@ -50,4 +64,155 @@ pub fn brute_force_mp(ciphered_text: &str, charset: &str, testing: bool)-> Resul
     unimplemented!()
 }
 
-// TODO: Implement this module.
\ No newline at end of file
+/// Get Vigenere ciphered text key using frequency analysis.
+///
+/// Key length is guessed first using index of coincidence. Then text is split in as many
+/// columns as key letters, so every column is Caesar ciphered with a single key letter.
+/// For every language profile, each column key letter is the one whose deciphered column
+/// is the nearest to that profile. Profiles are those of store dictionaries, or built-in
+/// ones if store has none. Key whose whole deciphered text scores best is returned.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * charset: Charset used for Vigenere method substitution. Both ends, ciphering
+///      and deciphering, should use the same charset or original text won't be properly
+///      recovered.
+/// * scorer: Scorer to compare deciphered columns with language profiles.
+/// * store: Dictionaries whose letter profiles deciphered columns are compared with.
+///
+/// # Returns:
+/// * Most probable Vigenere key found.
+pub fn frequency_attack(ciphered_text: &str, charset: &str, scorer: Scorer,
+                        store: &dyn DictionaryStore) -> Result<String> {
+    let letters: Vec<char> = ciphered_text.to_lowercase().chars()
+        .filter(|char| charset.contains(*char))
+        .collect();
+    if letters.is_empty() {
+        bail!("Ciphered text has no letters from charset.")
+    }
+    let key_length = find_key_length(&letters, charset, MAX_KEY_LENGTH);
+    let columns: Vec<String> = (0..key_length)
+        .map(|column| letters.iter().skip(column).step_by(key_length).collect())
+        .collect();
+    let profiles = LetterProfile::get_all_with_store(store)?;
+    let candidates: Vec<Result<(String, f64)>> = profiles.par_iter()
+        .map(|profile| {
+            let mut key = String::new();
+            for column in columns.iter() {
+                key.push(find_best_subkey(column, charset, profile, scorer)?);
+            }
+            let score = scorer.score(&decipher(ciphered_text, &key, charset)?, profile);
+            Ok((key, score))
+        })
+        .collect();
+    let mut best_candidate: Option<(String, f64)> = None;
+    for candidate in candidates {
+        let (key, score) = candidate?;
+        match best_candidate {
+            Some((_, best_score)) if best_score >= score => continue,
+            _ => best_candidate = Some((key, score))
+        }
+    }
+    match best_candidate {
+        Some((key, _)) => Ok(key),
+        None => bail!("There were no language profiles to compare with.")
+    }
+}
+
+/// Guess key length.
+///
+/// Text is split in columns for every candidate length. With the right length (or any
+/// of its multiples), every column is ciphered with a single key letter, so its index
+/// of coincidence is that of a natural language. Columns get shorter as length grows, so
+/// their indexes get noisier too. That's why shortest length whose columns reach a
+/// language-like index is kept instead of the one with the highest index.
+///
+/// # Parameters:
+/// * letters: Ciphered text letters from charset, lowercased.
+/// * charset: Charset used for ciphering.
+/// * max_key_length: Longest key length to try.
+///
+/// # Returns:
+/// * Most likely key length. If no length reaches a language-like index, the one with
+///     the highest index.
+fn find_key_length(letters: &[char], charset: &str, max_key_length: usize) -> usize {
+    // Every column should have some letters to get a meaningful index.
+    let max_key_length = max_key_length.min(letters.len() / 2).max(1);
+    // Wide charsets may have many characters that never appear at texts, so random index is
+    // estimated from characters actually used.
+    let used_characters: HashSet<&char> = letters.iter().collect();
+    let random_index = 1.0 / used_characters.len().max(1) as f64;
+    let average_indexes: Vec<f64> = (1..=max_key_length)
+        .map(|key_length| {
+            let indexes_sum: f64 = (0..key_length)
+                .map(|column| {
+                    let column_text: String = letters.iter().skip(column).step_by(key_length).collect();
+                    get_index_of_coincidence(&column_text, charset)
+                })
+                .sum();
+            indexes_sum / key_length as f64
+        })
+        .collect();
+    let key_length_position = average_indexes.iter()
+        .position(|&index| index >= LANGUAGE_RELATIVE_INDEX * random_index)
+        .unwrap_or_else(|| average_indexes.iter().enumerate()
+            .fold((0, 0.0), |best, (position, &index)| if index > best.1 { (position, index) } else { best })
+            .0);
+    key_length_position + 1
+}
+
+/// Get the key letter whose deciphered column is the nearest to a language profile.
+///
+/// # Parameters:
+/// * column: Letters ciphered with the same key letter.
+/// * charset: Charset used for Vigenere method substitution.
+/// * profile: Language profile to compare deciphered columns with.
+/// * scorer: Scorer to compare deciphered columns with language profile.
+///
+/// # Returns:
+/// * Best key letter.
+fn find_best_subkey(column: &str, charset: &str, profile: &LetterProfile, scorer: Scorer) -> Result<char> {
+    let mut best_subkey: Option<(char, f64)> = None;
+    for subkey in charset.chars() {
+        let score = scorer.score(&decipher(column, &subkey.to_string(), charset)?, profile);
+        match best_subkey {
+            Some((_, best_score)) if best_score >= score => continue,
+            _ => best_subkey = Some((subkey, score))
+        }
+    }
+    match best_subkey {
+        Some((subkey, _)) => Ok(subkey),
+        None => bail!("Charset is empty.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::attack::dictionaries::{MemoryDictionary, MemoryStore};
+    use crate::attack::dictionaries::tests::{ENGLISH_TEXT_WITH_PUNCTUATIONS_MARKS, GERMAN_TEXT_WITH_PUNCTUATIONS_MARKS};
+    use crate::cipher::vigenere::{cipher, DEFAULT_CHARSET};
+
+    const TEST_KEY: &'static str = "pizza";
+
+    #[test]
+    fn test_frequency_attack() {
+        let ciphered_text = cipher(ENGLISH_TEXT_WITH_PUNCTUATIONS_MARKS, TEST_KEY, DEFAULT_CHARSET).unwrap();
+        for scorer in Scorer::ALL.iter() {
+            let found_key = frequency_attack(&ciphered_text, DEFAULT_CHARSET, *scorer, &MemoryStore::new()).unwrap();
+            assert_eq!(TEST_KEY, found_key, "Scorer {} found a wrong key.", scorer);
+        }
+    }
+
+    #[test]
+    fn test_frequency_attack_with_dictionary_profiles() {
+        let store: MemoryStore = vec![MemoryDictionary::from_text_file("german", "resources/german_book.txt").unwrap()]
+            .into_iter().collect();
+        let ciphered_text = cipher(GERMAN_TEXT_WITH_PUNCTUATIONS_MARKS, TEST_KEY, DEFAULT_CHARSET).unwrap();
+        for scorer in Scorer::ALL.iter() {
+            let found_key = frequency_attack(&ciphered_text, DEFAULT_CHARSET, *scorer, &store).unwrap();
+            assert_eq!(TEST_KEY, found_key, "Scorer {} found a wrong key.", scorer);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,258 @@
+/// Module to solve substitution ciphered texts by hand, with some automatic help.
+///
+/// A workbench keeps a ciphered text along with the letter assignments found so far.
+/// It can show the partial decryption those assignments give, cipherletters frequencies
+/// and dictionary words that fit a cipherword. When enough letters are known, substitution
+/// solver can be run keeping them locked to find the remaining ones.
+use crate::{ErrorKind, Result};
+use crate::attack::dictionaries::{DictionaryStore, get_word_pattern};
+use crate::attack::frequency::LetterHistogram;
+use crate::attack::substitution::{KeyConstraints, hack_substitution_with_constraints_mp};
+
+/// Character used at partial decryptions and keys for letters not solved yet.
+pub const UNKNOWN_LETTER: char = '_';
+
+/// Ciphered text being solved along with letter assignments found so far.
+#[derive(Debug, Clone)]
+pub struct Workbench {
+    ciphered_text: String,
+    charset: String,
+    constraints: KeyConstraints
+}
+
+impl Workbench {
+    /// Create a workbench without any letter assignment.
+    ///
+    /// # Parameters:
+    /// * ciphered_text: Text to be solved.
+    /// * charset: Charset used for substitution method. It should be the same used to cipher.
+    pub fn new(ciphered_text: &str, charset: &str) -> Self {
+        Workbench {
+            ciphered_text: ciphered_text.to_string(),
+            charset: charset.to_lowercase(),
+            constraints: KeyConstraints::new()
+        }
+    }
+
+    /// Get text being solved.
+    pub fn ciphered_text(&self) -> &str {
+        &self.ciphered_text
+    }
+
+    /// Get letter assignments found so far.
+    pub fn constraints(&self) -> &KeyConstraints {
+        &self.constraints
+    }
+
+    /// Set which plaintext letter a cipherletter deciphers to.
+    ///
+    /// Any previous assignment for cipherletter is replaced.
+    ///
+    /// # Parameters:
+    /// * cipherletter: Letter at ciphered text.
+    /// * plain_letter: Letter cipherletter deciphers to.
+    ///
+    /// # Returns:
+    /// * An error if any letter is not at charset or plain letter is already assigned
+    ///     to another cipherletter.
+    pub fn assign(&mut self, cipherletter: char, plain_letter: char) -> Result<()> {
+        let (cipherletter, plain_letter) = (self.to_charset_letter(cipherletter)?, self.to_charset_letter(plain_letter)?);
+        let mut constraints = self.constraints.clone();
+        constraints.unassign(cipherletter);
+        constraints.assign(cipherletter, plain_letter)?;
+        self.constraints = constraints;
+        Ok(())
+    }
+
+    /// Remove assignment for a cipherletter.
+    ///
+    /// # Parameters:
+    /// * cipherletter: Letter at ciphered text.
+    ///
+    /// # Returns:
+    /// * Plaintext letter cipherletter was assigned to, if any.
+    pub fn unassign(&mut self, cipherletter: char) -> Option<char> {
+        self.constraints.unassign(cipherletter.to_lowercase().next()?)
+    }
+
+    /// Rule out a plaintext letter for a cipherletter.
+    ///
+    /// # Parameters:
+    /// * cipherletter: Letter at ciphered text.
+    /// * plain_letter: Letter cipherletter can not decipher to.
+    ///
+    /// # Returns:
+    /// * An error if any letter is not at charset or that assignment is already set.
+    pub fn forbid(&mut self, cipherletter: char, plain_letter: char) -> Result<()> {
+        let (cipherletter, plain_letter) = (self.to_charset_letter(cipherletter)?, self.to_charset_letter(plain_letter)?);
+        self.constraints.forbid(cipherletter, plain_letter)
+    }
+
+    /// Get ciphered text deciphered with current assignments.
+    ///
+    /// # Returns:
+    /// * Text with solved letters deciphered, keeping caps, and UNKNOWN_LETTER instead of
+    ///     unsolved ones. Characters out of charset are kept as they are.
+    pub fn partial_decryption(&self) -> String {
+        self.ciphered_text.chars()
+            .map(|char| {
+                let lowercase_char = char.to_lowercase().next().unwrap_or(char);
+                if !self.charset.contains(lowercase_char) {
+                    return char;
+                }
+                match self.constraints.known().get(&lowercase_char) {
+                    Some(&plain_letter) if char.is_uppercase() => plain_letter.to_uppercase().next().unwrap_or(plain_letter),
+                    Some(&plain_letter) => plain_letter,
+                    None => UNKNOWN_LETTER
+                }
+            })
+            .collect()
+    }
+
+    /// Get substitution key found so far.
+    ///
+    /// # Returns:
+    /// * Key in the same format substitution cipher uses, with UNKNOWN_LETTER for
+    ///     plaintext letters whose cipherletter is still unknown.
+    pub fn partial_key(&self) -> String {
+        self.charset.chars()
+            .map(|plain_letter| self.constraints.known().iter()
+                .find(|(_, &known_letter)| known_letter == plain_letter)
+                .map(|(&cipherletter, _)| cipherletter)
+                .unwrap_or(UNKNOWN_LETTER))
+            .collect()
+    }
+
+    /// Get letters frequencies at ciphered text.
+    ///
+    /// # Returns:
+    /// * A list of tuples with every letter and its frequency, from the most frequent
+    ///     letter to the least one.
+    pub fn histogram(&self) -> Result<Vec<(char, f64)>> {
+        let histogram = LetterHistogram::from_text(&self.ciphered_text, 6, &self.charset);
+        let mut frequencies = Vec::new();
+        for &letter in histogram.letters() {
+            frequencies.push((letter, histogram.frequency(letter.to_string())?));
+        }
+        Ok(frequencies)
+    }
+
+    /// Get dictionary words that can be the plaintext of a cipherword.
+    ///
+    /// # Parameters:
+    /// * ciphered_word: Word from ciphered text.
+    /// * language: Dictionary to look words into.
+    /// * store: Dictionaries language dictionary is taken from.
+    ///
+    /// # Returns:
+    /// * Words with the same letter pattern than cipherword that fit current assignments.
+    pub fn pattern_matches(&self, ciphered_word: &str, language: &str,
+                           store: &dyn DictionaryStore) -> Result<Vec<String>> {
+        let ciphered_word = ciphered_word.to_lowercase();
+        let dictionary = store.open_dictionary(language)?;
+        let mut matches: Vec<String> = dictionary.get_words_with_pattern(&get_word_pattern(&ciphered_word))?
+            .into_iter()
+            .filter(|word| self.constraints.fits(&ciphered_word, word))
+            .collect();
+        matches.sort();
+        Ok(matches)
+    }
+
+    /// Run substitution solver keeping current assignments locked and assign every
+    /// letter it finds.
+    ///
+    /// # Parameters:
+    /// * store: Dictionaries solver looks words into.
+    ///
+    /// # Returns:
+    /// * Probability of key found.
+    pub fn auto_complete(&mut self, store: &dyn DictionaryStore) -> Result<f64> {
+        let (key, probability) = hack_substitution_with_constraints_mp(&self.ciphered_text, &self.charset,
+                                                                       &self.constraints, store)?;
+        if key.is_empty() {
+            bail!("No key could be found with current assignments.")
+        }
+        let ciphered_text = self.ciphered_text.to_lowercase();
+        let mut constraints = self.constraints.clone();
+        for (plain_letter, cipherletter) in self.charset.chars().zip(key.chars()) {
+            // Solver leaves letters absent from ciphered text as they are, so they are
+            // not worth an assignment.
+            if ciphered_text.contains(cipherletter) && constraints.allows(cipherletter, plain_letter) {
+                constraints.assign(cipherletter, plain_letter)?;
+            }
+        }
+        self.constraints = constraints;
+        Ok(probability)
+    }
+
+    fn to_charset_letter(&self, letter: char) -> Result<char> {
+        match letter.to_lowercase().next() {
+            Some(lowercase_letter) if self.charset.contains(lowercase_letter) => Ok(lowercase_letter),
+            _ => bail!(ErrorKind::CharacterMappingError(letter.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rstest::*;
+
+    use crate::attack::dictionaries::DatabaseStore;
+    use crate::attack::dictionaries::tests::{LoadedDictionaries, full_loaded_temp_dictionaries};
+    use crate::cipher::substitution::{DEFAULT_CHARSET, cipher};
+
+    const ORIGINAL_MESSAGE: &'static str = "If a man is offered a fact which goes against his instincts, he will scrutinize it closely";
+    const TEST_KEY: &'static str = "lfwoayuisvkmnxpbdcrjtqeghz";
+
+    #[test]
+    fn test_assignments() {
+        let ciphered_text = cipher(ORIGINAL_MESSAGE, TEST_KEY, DEFAULT_CHARSET).unwrap();
+        let mut workbench = Workbench::new(&ciphered_text, DEFAULT_CHARSET);
+        assert!(workbench.partial_decryption().chars().all(|char| char == UNKNOWN_LETTER || !char.is_alphabetic()));
+        // With TEST_KEY, i is ciphered as s and f as y.
+        workbench.assign('S', 'i').unwrap();
+        workbench.assign('y', 'f').unwrap();
+        assert!(workbench.partial_decryption().starts_with("If _ ___ i_ _ff____"));
+        assert!(workbench.assign('l', 'i').is_err());
+        assert!(workbench.assign('1', 'a').is_err());
+        workbench.assign('s', 'e').unwrap();
+        assert!(workbench.partial_decryption().starts_with("Ef"));
+        assert_eq!(workbench.unassign('s'), Some('e'));
+        assert!(workbench.partial_decryption().starts_with("_f"));
+        assert_eq!(workbench.partial_key(), "_____y____________________");
+        workbench.forbid('s', 'e').unwrap();
+        assert!(workbench.assign('s', 'e').is_err());
+    }
+
+    #[test]
+    fn test_histogram() {
+        let workbench = Workbench::new("abca ab", DEFAULT_CHARSET);
+        let histogram = workbench.histogram().unwrap();
+        assert_eq!(histogram[0], ('a', 3.0 / 6.0));
+        assert_eq!(histogram[1], ('b', 2.0 / 6.0));
+        assert_eq!(histogram[2], ('c', 1.0 / 6.0));
+        assert_eq!(histogram.len(), DEFAULT_CHARSET.len());
+        // Histogram should list every letter of workbench charset, even missing ones.
+        let spanish_charset = format!("{}ñ", DEFAULT_CHARSET);
+        let workbench = Workbench::new("abca ab", &spanish_charset);
+        let histogram = workbench.histogram().unwrap();
+        assert_eq!(histogram.len(), spanish_charset.chars().count());
+        assert!(histogram.contains(&('ñ', 0.0)));
+    }
+
+    #[rstest]
+    fn test_pattern_matches(full_loaded_temp_dictionaries: LoadedDictionaries) {
+        let ciphered_text = cipher(ORIGINAL_MESSAGE, TEST_KEY, DEFAULT_CHARSET).unwrap();
+        let mut workbench = Workbench::new(&ciphered_text, DEFAULT_CHARSET);
+        let ciphered_word = cipher("against", TEST_KEY, DEFAULT_CHARSET).unwrap();
+        let matches = workbench.pattern_matches(&ciphered_word, "english", &DatabaseStore::default()).unwrap();
+        assert!(matches.contains(&"against".to_string()));
+        workbench.assign('l', 'a').unwrap();
+        workbench.assign('u', 'g').unwrap();
+        let matches = workbench.pattern_matches(&ciphered_word, "english", &DatabaseStore::default()).unwrap();
+        assert!(matches.contains(&"against".to_string()));
+        assert!(matches.iter().all(|word| word.starts_with("ag")));
+    }
+}
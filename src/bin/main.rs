@@ -4,18 +4,34 @@ use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::env::args;
 use std::fmt::{Display, Formatter};
-use std::fs::{read_to_string, write};
+use std::fs::{File, read_to_string, write};
+use std::io::{BufRead, BufReader, Write};
 use std::path::PathBuf;
 use std::str::FromStr;
 
 use clap::{App, AppSettings, Arg, ArgMatches};
+use dotenv::dotenv;
 use error_chain::bail;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 use cifra::{ErrorKind, Result, ResultExt};
-use cifra::attack::dictionaries::Dictionary;
+use cifra::attack::context::Context;
+use cifra::attack::crib::{Crib, get_best_candidate};
+use cifra::attack::scoring::Scorer;
+use cifra::attack::segmentation::split_words;
+use cifra::attack::dictionaries::{DatabaseStore, Dictionary, DictionaryStore, PopulationReport, get_word_pattern};
+use cifra::attack::dictionary_inspection::{DictionaryStatistics, get_dictionary_statistics, search_words};
+use cifra::attack::dictionary_exchange::{DictionaryFormat, export_dictionary, import_dictionary};
+use cifra::attack::frequency::{TextStatistics, get_text_statistics};
+use cifra::attack::language_packs::install_default_dictionaries;
+use cifra::attack::workbench::Workbench;
 use cifra::cipher::common::DEFAULT_CHARSET;
+use cifra::cipher::enigma::{EnigmaKey, Reflector, Rotor};
+use cifra::cipher::normalization::{Normalization, fold_to_charset, normalize_unicode};
+use cifra::cipher::presets::Preset;
+use cifra::cipher::quagmire::{Quagmire, QuagmireKey};
+use cifra::cipher::quagmire::DEFAULT_CHARSET as QUAGMIRE_DEFAULT_CHARSET;
 use cifra::cipher::substitution::DEFAULT_CHARSET as SUBSTITUTION_DEFAULT_CHARSET;
 
 /// Get an string containing current app version.
@@ -38,6 +54,14 @@ enum CipheringAlgorithms {
     Transposition,
     Affine,
     Vigenere,
+    Quagmire1,
+    Quagmire2,
+    Quagmire3,
+    Quagmire4,
+    Enigma,
+    Atbash,
+    Rot13,
+    Rot47,
 }
 
 impl CipheringAlgorithms {
@@ -54,16 +78,28 @@ impl CipheringAlgorithms {
 
     /// Get a set with every ciphering variant that uses a string as a key.
     pub fn get_string_key_algorithms()-> HashSet<String> {
-        let key_algorithms: HashSet<String> = vec!["substitution", "vigenere"].into_iter()
+        let key_algorithms: HashSet<String> = vec!["substitution", "vigenere", "quagmire1", "quagmire2",
+                                                  "quagmire3", "quagmire4", "enigma"].into_iter()
             .map(|str| String::from(str))
             .collect();
         key_algorithms
     }
 
+    /// Get a set with every ciphering variant that needs no key.
+    pub fn get_keyless_algorithms()-> HashSet<String> {
+        let keyless_algorithms: HashSet<String> = Preset::ALL.iter()
+            .map(|preset| preset.to_string())
+            .collect();
+        keyless_algorithms
+    }
+
     /// Get a set with every ciphering variant that uses a integer number as a key.
     pub fn get_integer_key_algorithms()-> HashSet<String> {
         let all_algorithms: HashSet<String> = Self::get_all_possible_values().into_iter().collect();
-        let key_algorithms: HashSet<String> = Self::get_string_key_algorithms();
+        let key_algorithms: HashSet<String> = Self::get_string_key_algorithms()
+            .union(&Self::get_keyless_algorithms())
+            .map(|Str| Str.clone())
+            .collect();
         let integer_algorithms: HashSet<String> = all_algorithms.difference(&key_algorithms)
             .into_iter()
             .map(|Str| Str.clone())
@@ -71,6 +107,33 @@ impl CipheringAlgorithms {
         integer_algorithms
     }
 
+    /// Get preset for this algorithm.
+    ///
+    /// # Returns:
+    /// * Ok with preset or Err if this algorithm is not a keyless one.
+    pub fn get_preset(&self)-> Result<Preset> {
+        match self {
+            CipheringAlgorithms::Atbash=> Ok(Preset::Atbash),
+            CipheringAlgorithms::Rot13=> Ok(Preset::Rot13),
+            CipheringAlgorithms::Rot47=> Ok(Preset::Rot47),
+            _=> bail!(format!("{} is not a keyless algorithm.", self))
+        }
+    }
+
+    /// Get Quagmire variant for this algorithm.
+    ///
+    /// # Returns:
+    /// * Ok with Quagmire variant or Err if this algorithm is not a Quagmire one.
+    pub fn get_quagmire_variant(&self)-> Result<Quagmire> {
+        match self {
+            CipheringAlgorithms::Quagmire1=> Ok(Quagmire::I),
+            CipheringAlgorithms::Quagmire2=> Ok(Quagmire::II),
+            CipheringAlgorithms::Quagmire3=> Ok(Quagmire::III),
+            CipheringAlgorithms::Quagmire4=> Ok(Quagmire::IV),
+            _=> bail!(format!("{} is not a Quagmire algorithm.", self))
+        }
+    }
+
     /// Get current value as an string.
     pub fn get_string_value(&self)-> String {
         format!("{:?}", self)
@@ -103,6 +166,14 @@ impl TryFrom<&str> for CipheringAlgorithms {
             "transposition"=> Ok(CipheringAlgorithms::Transposition),
             "affine"=> Ok(CipheringAlgorithms::Affine),
             "vigenere"=> Ok(CipheringAlgorithms::Vigenere),
+            "quagmire1"=> Ok(CipheringAlgorithms::Quagmire1),
+            "quagmire2"=> Ok(CipheringAlgorithms::Quagmire2),
+            "quagmire3"=> Ok(CipheringAlgorithms::Quagmire3),
+            "quagmire4"=> Ok(CipheringAlgorithms::Quagmire4),
+            "enigma"=> Ok(CipheringAlgorithms::Enigma),
+            "atbash"=> Ok(CipheringAlgorithms::Atbash),
+            "rot13"=> Ok(CipheringAlgorithms::Rot13),
+            "rot47"=> Ok(CipheringAlgorithms::Rot47),
             _=> bail!(format!("Unknown algorithm: {}", value))
         }
     }
@@ -119,31 +190,129 @@ enum Modes {
     Decipher{algorithm: CipheringAlgorithms, key: String, file_to_decipher: PathBuf, deciphered_file: Option<PathBuf>,
         charset: Option<String>},
     Attack{algorithm: CipheringAlgorithms, file_to_attack: PathBuf, deciphered_file: Option<PathBuf>,
-        output_recovered_key: bool, charset: Option<String>},
+        output_recovered_key: bool, charset: Option<String>, crib: Option<Crib>, scorer: Option<Scorer>,
+        segment: bool},
+    Workbench{file_to_solve: PathBuf, charset: Option<String>},
+    Analyze{file_to_analyze: PathBuf, json: bool, charset: Option<String>},
 }
 
 /// What you can do with a dictionary.
 #[derive(Debug, PartialEq)]
 enum DictionaryActions {
-    Create{dictionary_name: String, initial_words_file: Option<PathBuf>},
+    Create{dictionary_name: String, initial_words_file: Option<PathBuf>, minimum_frequency: u64},
     Delete{dictionary_name: String},
-    Update{dictionary_name: String, words_file: PathBuf},
+    Update{dictionary_name: String, words_file: PathBuf, minimum_frequency: u64},
     List,
+    Export{dictionary_name: String, output_file: PathBuf, format: DictionaryFormat},
+    Import{dictionary_name: String, input_file: PathBuf, format: DictionaryFormat},
+    Stats{dictionary_name: String},
+    Search{dictionary_name: String, query: String, cipherword: bool},
+    InstallDefaults{reinstall: bool},
+}
+
+/// What you can do at workbench interactive mode.
+#[derive(Debug, PartialEq)]
+enum WorkbenchCommands {
+    Show,
+    Assign{cipherletter: char, plain_letter: char},
+    Unassign{cipherletter: char},
+    Forbid{cipherletter: char, plain_letter: char},
+    Histogram,
+    Matches{ciphered_word: String, language: Option<String>},
+    AutoComplete,
+    Key,
+    Help,
+    Quit,
+}
+
+impl TryFrom<&str> for WorkbenchCommands {
+    type Error = cifra::Error;
+
+    /// Get a workbench command from a line typed by user.
+    ///
+    /// # Parameters:
+    /// * value: Typed line, like "assign x e".
+    ///
+    /// # Returns:
+    /// * OK with command or Err if line is not a known command or has wrong arguments.
+    fn try_from(value: &str) -> Result<Self> {
+        let words: Vec<&str> = value.split_whitespace().collect();
+        let letter = |index: usize| -> Result<char> {
+            let mut chars = words[index].chars();
+            match (chars.next(), chars.next()) {
+                (Some(letter), None) => Ok(letter),
+                _ => bail!(format!("Expected a single letter but got: {}", words[index]))
+            }
+        };
+        let command = match (words.first().map(|word| word.to_lowercase()).as_deref(), words.len()) {
+            (Some("show"), 1) => WorkbenchCommands::Show,
+            (Some("assign"), 3) => WorkbenchCommands::Assign{cipherletter: letter(1)?, plain_letter: letter(2)?},
+            (Some("unassign"), 2) => WorkbenchCommands::Unassign{cipherletter: letter(1)?},
+            (Some("forbid"), 3) => WorkbenchCommands::Forbid{cipherletter: letter(1)?, plain_letter: letter(2)?},
+            (Some("histogram"), 1) => WorkbenchCommands::Histogram,
+            (Some("matches"), 2) => WorkbenchCommands::Matches{ciphered_word: words[1].to_string(), language: None},
+            (Some("matches"), 3) => WorkbenchCommands::Matches{ciphered_word: words[1].to_string(),
+                                                               language: Some(words[2].to_string())},
+            (Some("auto"), 1) => WorkbenchCommands::AutoComplete,
+            (Some("key"), 1) => WorkbenchCommands::Key,
+            (Some("help"), 1) => WorkbenchCommands::Help,
+            (Some("quit"), 1) | (Some("exit"), 1) => WorkbenchCommands::Quit,
+            _ => bail!(format!("Unknown command or wrong arguments: {}", value.trim()))
+        };
+        Ok(command)
+    }
 }
 
+const WORKBENCH_HELP: &'static str = "Available commands:
+  show                        Show ciphered text along with its partial decryption.
+  assign CIPHERLETTER LETTER  Set which letter a cipherletter deciphers to.
+  unassign CIPHERLETTER       Remove assignment for a cipherletter.
+  forbid CIPHERLETTER LETTER  Rule out a letter for a cipherletter.
+  histogram                   Show letters frequencies at ciphered text.
+  matches CIPHERWORD [LANG]   Show dictionary words that fit a cipherword.
+  auto                        Run substitution solver keeping current assignments locked.
+  key                         Show substitution key found so far.
+  help                        Show this help.
+  quit                        Leave workbench.";
+
 /// Configuration to run app.
 #[derive(Debug, PartialEq)]
 struct Configuration {
     running_mode: Modes,
+    database: Option<PathBuf>,
+    normalization: Normalization,
+    languages: Vec<String>,
 }
 
 impl Configuration {
     /// Create a new configuration instance with given mode.
     pub fn new(mode: Modes)-> Self {
         Configuration{
-            running_mode: mode
+            running_mode: mode,
+            database: None,
+            normalization: Normalization::default(),
+            languages: Vec::new()
         }
     }
+
+    /// Get dictionary store at database set for this configuration.
+    ///
+    /// # Returns:
+    /// * Store at given database or, if none was given, at default one.
+    pub fn get_database_store(&self)-> DatabaseStore {
+        match &self.database {
+            Some(database_path)=> DatabaseStore::at(database_path),
+            None=> DatabaseStore::default()
+        }
+    }
+
+    /// Get context attacks should run into with this configuration.
+    pub fn get_context(&self)-> Context {
+        let mut context = Context::new(self.get_database_store());
+        context.set_normalization(self.normalization);
+        context.set_languages(&self.languages);
+        context
+    }
 }
 
 impl From<ArgMatches> for Configuration {
@@ -157,40 +326,116 @@ impl From<ArgMatches> for Configuration {
     fn from(matches: ArgMatches) -> Self {
         // I use unwrap() liberally in this function because parse_arguments() enforces which
         // arguments are required, so I'm sure they are there when I unwrap them.
+        let database = matches.value_of("database").map(PathBuf::from);
+        let normalization = matches.value_of("normalization")
+            .map_or(Normalization::default(), |normalization| Normalization::try_from(normalization).unwrap());
+        let languages: Vec<String> = matches.values_of("language")
+            .map_or(Vec::new(), |languages| languages.map(String::from).collect());
         if let Some(_matches) = matches.subcommand_matches("dictionary") {
             if let Some(__matches) = _matches.subcommand_matches("create"){
                 return Configuration{
+                    database: database.clone(),
+                    normalization,
+                    languages: languages.clone(),
                     running_mode: Modes::Dictionary(DictionaryActions::Create {
                         dictionary_name: String::from(__matches.value_of("dictionary_name").unwrap()),
                         initial_words_file: if __matches.is_present("initial_words_file") {
                                 Some(PathBuf::from(__matches.value_of("initial_words_file").unwrap()))
                             } else {
                                 None
-                            }
+                            },
+                        minimum_frequency: get_minimum_frequency(__matches)
                         })}
             } else if let Some(__matches) = _matches.subcommand_matches("delete") {
                 return Configuration{
+                    database: database.clone(),
+                    normalization,
+                    languages: languages.clone(),
                     running_mode: Modes::Dictionary(DictionaryActions::Delete {
                         dictionary_name: String::from(__matches.value_of("dictionary_name").unwrap())
                     })}
             } else if let Some(__matches) = _matches.subcommand_matches("update") {
                 return Configuration {
+                    database: database.clone(),
+                    normalization,
+                    languages: languages.clone(),
                     running_mode: Modes::Dictionary(DictionaryActions::Update {
                         dictionary_name: String::from(__matches.value_of("dictionary_name").unwrap()),
-                        words_file: PathBuf::from(__matches.value_of("words_file").unwrap())
+                        words_file: PathBuf::from(__matches.value_of("words_file").unwrap()),
+                        minimum_frequency: get_minimum_frequency(__matches)
+                    })
+                }
+            } else if let Some(__matches) = _matches.subcommand_matches("export") {
+                let output_file = PathBuf::from(__matches.value_of("output_file").unwrap());
+                return Configuration {
+                    database: database.clone(),
+                    normalization,
+                    languages: languages.clone(),
+                    running_mode: Modes::Dictionary(DictionaryActions::Export {
+                        dictionary_name: String::from(__matches.value_of("dictionary_name").unwrap()),
+                        format: get_dictionary_format(__matches, &output_file),
+                        output_file
+                    })
+                }
+            } else if let Some(__matches) = _matches.subcommand_matches("import") {
+                let input_file = PathBuf::from(__matches.value_of("input_file").unwrap());
+                return Configuration {
+                    database: database.clone(),
+                    normalization,
+                    languages: languages.clone(),
+                    running_mode: Modes::Dictionary(DictionaryActions::Import {
+                        dictionary_name: String::from(__matches.value_of("dictionary_name").unwrap()),
+                        format: get_dictionary_format(__matches, &input_file),
+                        input_file
+                    })
+                }
+            } else if let Some(__matches) = _matches.subcommand_matches("stats") {
+                return Configuration {
+                    database: database.clone(),
+                    normalization,
+                    languages: languages.clone(),
+                    running_mode: Modes::Dictionary(DictionaryActions::Stats {
+                        dictionary_name: String::from(__matches.value_of("dictionary_name").unwrap())
+                    })
+                }
+            } else if let Some(__matches) = _matches.subcommand_matches("search") {
+                return Configuration {
+                    database: database.clone(),
+                    normalization,
+                    languages: languages.clone(),
+                    running_mode: Modes::Dictionary(DictionaryActions::Search {
+                        dictionary_name: String::from(__matches.value_of("dictionary_name").unwrap()),
+                        query: String::from(__matches.value_of("query").unwrap()),
+                        cipherword: __matches.is_present("cipherword")
+                    })
+                }
+            } else if let Some(__matches) = _matches.subcommand_matches("install-defaults") {
+                return Configuration {
+                    database: database.clone(),
+                    normalization,
+                    languages: languages.clone(),
+                    running_mode: Modes::Dictionary(DictionaryActions::InstallDefaults {
+                        reinstall: __matches.is_present("reinstall")
                     })
                 }
             } else {
                 return Configuration{
+                    database: database.clone(),
+                    normalization,
+                    languages: languages.clone(),
                     running_mode: Modes::Dictionary(DictionaryActions::List)
                 }
             }
         } else if let Some(_matches) = matches.subcommand_matches("cipher") {
+            let (key, file_to_cipher) = get_key_and_file(_matches, "file_to_cipher");
             return Configuration {
+                database: database.clone(),
+                normalization,
+                languages: languages.clone(),
                 running_mode: Modes::Cipher {
                     algorithm: CipheringAlgorithms::try_from(_matches.value_of("algorithm").unwrap()).unwrap(),
-                    key: String::from(_matches.value_of("key").unwrap()),
-                    file_to_cipher: PathBuf::from(_matches.value_of("file_to_cipher").unwrap()),
+                    key,
+                    file_to_cipher,
                     ciphered_file: if _matches.is_present("ciphered_file") {
                         Some(PathBuf::from(_matches.value_of("ciphered_file").unwrap()))
                     } else {
@@ -204,11 +449,15 @@ impl From<ArgMatches> for Configuration {
                 }
             }
         } else if let Some(_matches) = matches.subcommand_matches("decipher") {
+            let (key, file_to_decipher) = get_key_and_file(_matches, "file_to_decipher");
             return Configuration {
+                database: database.clone(),
+                normalization,
+                languages: languages.clone(),
                 running_mode: Modes::Decipher {
                     algorithm: CipheringAlgorithms::try_from(_matches.value_of("algorithm").unwrap()).unwrap(),
-                    key: String::from(_matches.value_of("key").unwrap()),
-                    file_to_decipher: PathBuf::from(_matches.value_of("file_to_decipher").unwrap()),
+                    key,
+                    file_to_decipher,
                     deciphered_file: if _matches.is_present("deciphered_file") {
                         Some(PathBuf::from(_matches.value_of("deciphered_file").unwrap()))
                     } else {
@@ -221,9 +470,41 @@ impl From<ArgMatches> for Configuration {
                     }
                 }
             }
+        } else if let Some(_matches) = matches.subcommand_matches("analyze") {
+            return Configuration {
+                database: database.clone(),
+                normalization,
+                languages: languages.clone(),
+                running_mode: Modes::Analyze {
+                    file_to_analyze: PathBuf::from(_matches.value_of("file_to_analyze").unwrap()),
+                    json: _matches.is_present("json"),
+                    charset: if _matches.is_present("charset") {
+                        Some(String::from(_matches.value_of("charset").unwrap()))
+                    } else {
+                        None
+                    }
+                }
+            }
+        } else if let Some(_matches) = matches.subcommand_matches("workbench") {
+            return Configuration {
+                database: database.clone(),
+                normalization,
+                languages: languages.clone(),
+                running_mode: Modes::Workbench {
+                    file_to_solve: PathBuf::from(_matches.value_of("file_to_solve").unwrap()),
+                    charset: if _matches.is_present("charset") {
+                        Some(String::from(_matches.value_of("charset").unwrap()))
+                    } else {
+                        None
+                    }
+                }
+            }
         } else {
             let _matches = matches.subcommand_matches("attack").unwrap();
             return Configuration {
+                database: database.clone(),
+                normalization,
+                languages: languages.clone(),
                 running_mode: Modes::Attack {
                     algorithm: CipheringAlgorithms::try_from(_matches.value_of("algorithm").unwrap()).unwrap(),
                     file_to_attack: PathBuf::from(_matches.value_of("file_to_attack").unwrap()),
@@ -237,13 +518,113 @@ impl From<ArgMatches> for Configuration {
                         Some(String::from(_matches.value_of("charset").unwrap()))
                     } else {
                         None
-                    }
+                    },
+                    crib: if _matches.is_present("crib") {
+                        Some(Crib::new(_matches.value_of("crib").unwrap(),
+                                       _matches.value_of("crib_offset").map(|offset| usize::from_str(offset).unwrap())))
+                    } else {
+                        None
+                    },
+                    scorer: _matches.value_of("scorer").map(|scorer| Scorer::try_from(scorer).unwrap()),
+                    segment: _matches.is_present("segment")
                 }
             }
         }
         }
 }
 
+/// Get how many times a word must appear at a file to be added to a dictionary.
+///
+/// # Parameters:
+/// * matches: Output from clap parsing for create or update subcommands.
+///
+/// # Returns:
+/// * Minimum frequency given at arguments or 1, to add every word, if none was given.
+fn get_minimum_frequency(matches: &ArgMatches)-> u64 {
+    matches.value_of("minimum_frequency")
+        .map_or(1, |minimum_frequency| u64::from_str(minimum_frequency).unwrap())
+}
+
+/// Get format of file to export dictionary to or import it from.
+///
+/// # Parameters:
+/// * matches: Output from clap parsing for export or import subcommands.
+/// * file: File to export dictionary to or import it from.
+///
+/// # Returns:
+/// * Format given at arguments or, if none was given, the one guessed from file extension.
+fn get_dictionary_format(matches: &ArgMatches, file: &PathBuf)-> DictionaryFormat {
+    match matches.value_of("format") {
+        Some(format)=> DictionaryFormat::try_from(format).unwrap(),
+        None=> DictionaryFormat::from_path(file)
+    }
+}
+
+/// Normalize text before ciphering it.
+///
+/// With folded normalization, only characters not present at charset are folded, so
+/// charsets with accented letters keep them.
+///
+/// # Parameters:
+/// * text: Text to cipher.
+/// * charset: Charset algorithm is going to use, if any.
+/// * normalization: Normalization set for this run.
+///
+/// # Returns:
+/// * Text ready to be ciphered.
+fn normalize_text_to_cipher(text: &str, charset: Option<&str>, normalization: Normalization)-> String {
+    match (normalization, charset) {
+        (Normalization::Folded, Some(charset_str))=> fold_to_charset(text, charset_str),
+        _=> normalize_unicode(text, normalization)
+    }
+}
+
+/// Get key and file to process from cipher and decipher arguments.
+///
+/// Keyless algorithms need no key, so for them the only positional argument after
+/// algorithm name is the file to process.
+///
+/// # Parameters:
+/// * matches: Output from clap parsing for cipher or decipher subcommands.
+/// * file_argument: Name of argument with file to process.
+///
+/// # Returns:
+/// * A tuple with key, empty for keyless algorithms, and file to process.
+fn get_key_and_file(matches: &ArgMatches, file_argument: &str)-> (String, PathBuf) {
+    let algorithm = matches.value_of("algorithm").unwrap().to_lowercase();
+    let key = String::from(matches.value_of("key").unwrap());
+    match matches.value_of(file_argument) {
+        Some(file) if !CipheringAlgorithms::get_keyless_algorithms().contains(&algorithm)=>
+            (key, PathBuf::from(file)),
+        Some(file)=> (String::new(), PathBuf::from(file)),
+        None=> (String::new(), PathBuf::from(key))
+    }
+}
+
+/// Check that cipher and decipher subcommands got the arguments their algorithm needs.
+///
+/// Clap can't do it by itself because whether a key is needed depends on algorithm value.
+///
+/// # Parameters:
+/// * matches: Output from clap parsing.
+fn check_key_and_file_arguments(matches: &ArgMatches) {
+    for (subcommand, file_argument) in [("cipher", "file_to_cipher"), ("decipher", "file_to_decipher")].iter() {
+        if let Some(_matches) = matches.subcommand_matches(subcommand) {
+            let algorithm = _matches.value_of("algorithm").unwrap().to_lowercase();
+            if CipheringAlgorithms::get_keyless_algorithms().contains(&algorithm) {
+                if !_matches.is_present(file_argument) {
+                    if let Err(message) = file_exists(_matches.value_of("key").unwrap()) {
+                        clap::Error::raw(clap::ErrorKind::ValueValidation, message).exit();
+                    }
+                }
+            } else if !_matches.is_present(file_argument) {
+                clap::Error::raw(clap::ErrorKind::MissingRequiredArgument,
+                                 format!("{} algorithm needs a key and a file to process.\n", algorithm)).exit();
+            }
+        }
+    }
+}
+
 /// Check that provided path actually exists.
 ///
 /// This function is used as a validator in parse_arguments.
@@ -262,6 +643,22 @@ fn file_exists(path: &str)-> std::result::Result<(), String>{
     }
 }
 
+/// Check that provided value is a positive integer.
+///
+/// This function is used as a validator in parse_arguments.
+///
+/// # Parameters:
+/// * value: Value to check.
+///
+/// # Returns:
+/// * Ok(()) if value is a positive integer Err if not.
+fn is_positive_integer(value: &str)-> std::result::Result<(), String>{
+    match usize::from_str(value) {
+        Ok(_)=> Ok(()),
+        Err(_)=> Err(format!("Not a positive integer: {}", value))
+    }
+}
+
 /// Parse given console arguments.
 ///
 /// # Parameters:
@@ -273,6 +670,12 @@ fn file_exists(path: &str)-> std::result::Result<(), String>{
 fn parse_arguments(arg_vec: &Vec<&str>) -> Configuration {
     let algorithm_options = CipheringAlgorithms::get_all_possible_values();
     let algorithm_options_str: Vec<&str> = algorithm_options.iter().map(|str| str.as_str()).collect();
+    let scorer_options: Vec<String> = Scorer::ALL.iter().map(|scorer| scorer.to_string()).collect();
+    let scorer_options_str: Vec<&str> = scorer_options.iter().map(|str| str.as_str()).collect();
+    let dictionary_format_options: Vec<String> = DictionaryFormat::ALL.iter().map(|format| format.to_string()).collect();
+    let dictionary_format_options_str: Vec<&str> = dictionary_format_options.iter().map(|str| str.as_str()).collect();
+    let normalization_options: Vec<String> = Normalization::ALL.iter().map(|normalization| normalization.to_string()).collect();
+    let normalization_options_str: Vec<&str> = normalization_options.iter().map(|str| str.as_str()).collect();
     let charset_message = &format!("Default charset is: {}, but you can set here another", DEFAULT_CHARSET);
     let matches = App::new("cifra")
         .version(get_version().as_str())
@@ -280,6 +683,26 @@ fn parse_arguments(arg_vec: &Vec<&str>) -> Configuration {
         .long_about(env!("CARGO_PKG_DESCRIPTION"))
         // Don't allow an empty command call (command with no args).
         .setting(AppSettings::ArgRequiredElseHelp)
+        .arg(Arg::new("database")
+            .long("database")
+            .value_name("PATH")
+            .takes_value(true)
+            .global(true)
+            .help("Database with dictionaries to use instead of default one. It is created if it does not exist yet."))
+        .arg(Arg::new("normalization")
+            .long("normalization")
+            .value_name("NORMALIZATION")
+            .takes_value(true)
+            .global(true)
+            .possible_values(normalization_options_str.as_slice())
+            .help("Unicode normalization for texts and dictionary words. Default is nfc. Use folded to strip diacritics and fit accented texts into plain latin alphabets."))
+        .arg(Arg::new("language")
+            .long("language")
+            .value_name("LANGUAGE")
+            .takes_value(true)
+            .multiple_occurrences(true)
+            .global(true)
+            .help("Restrict attacks to this language dictionary. Repeat it to use several languages. By default every language is used."))
         .subcommand(App::new("dictionary")
                         .about("Manage dictionaries to perform crypto attacks.")
                         .subcommand(App::new("create")
@@ -295,7 +718,14 @@ fn parse_arguments(arg_vec: &Vec<&str>) -> Configuration {
                                 .value_name("PATH_TO FILE_WITH_WORDS")
                                 .takes_value(true)
                                 .validator(file_exists)
-                                .help("Optionally you can load in the dictionary words located in a text file")))
+                                .help("Optionally you can load in the dictionary words located in a text file"))
+                            .arg(Arg::new("minimum_frequency")
+                                .long("minimum_frequency")
+                                .short('m')
+                                .value_name("TIMES")
+                                .takes_value(true)
+                                .validator(is_positive_integer)
+                                .help("Only add words that appear at least this many times at file. Useful to leave typos out.")))
                         .subcommand(App::new("delete")
                             .about("Remove an existing dictionary.")
                             .arg(Arg::new("dictionary_name").index(1)
@@ -315,9 +745,86 @@ fn parse_arguments(arg_vec: &Vec<&str>) -> Configuration {
                                 .value_name("PATH_TO_FILE_WITH_WORDS")
                                 .takes_value(true)
                                 .validator(file_exists)
-                                .help("Pathname to a file with words to add to dictionary")))
+                                .help("Pathname to a file with words to add to dictionary"))
+                            .arg(Arg::new("minimum_frequency")
+                                .long("minimum_frequency")
+                                .short('m')
+                                .value_name("TIMES")
+                                .takes_value(true)
+                                .validator(is_positive_integer)
+                                .help("Only add words that appear at least this many times at file. Useful to leave typos out.")))
                         .subcommand(App::new("list")
-                            .about("Show existing dictionaries.")))
+                            .about("Show existing dictionaries."))
+                        .subcommand(App::new("export")
+                            .about("Write a dictionary words to a file.")
+                            .arg(Arg::new("dictionary_name").index(1)
+                                .required(true)
+                                .value_name("DICTIONARY_NAME_TO_EXPORT")
+                                .takes_value(true)
+                                .help("Name for the dictionary to export."))
+                            .arg(Arg::new("output_file").index(2)
+                                .required(true)
+                                .value_name("PATH_TO_OUTPUT_FILE")
+                                .takes_value(true)
+                                .help("Pathname to file to write words to."))
+                            .arg(Arg::new("format")
+                                .long("format")
+                                .short('f')
+                                .value_name("FORMAT")
+                                .takes_value(true)
+                                .possible_values(dictionary_format_options_str.as_slice())
+                                .help("Format to write words with. If not given, it is guessed from file extension: .csv for CSV, .jsonl for JSON Lines and word list for any other.")))
+                        .subcommand(App::new("import")
+                            .about("Add words from an exported file to a dictionary, creating it if it does not exist yet.")
+                            .arg(Arg::new("dictionary_name").index(1)
+                                .required(true)
+                                .value_name("DICTIONARY_NAME_TO_IMPORT_INTO")
+                                .takes_value(true)
+                                .help("Name for the dictionary to import words into."))
+                            .arg(Arg::new("input_file").index(2)
+                                .required(true)
+                                .value_name("PATH_TO_INPUT_FILE")
+                                .takes_value(true)
+                                .validator(file_exists)
+                                .help("Pathname to file to read words from."))
+                            .arg(Arg::new("format")
+                                .long("format")
+                                .short('f')
+                                .value_name("FORMAT")
+                                .takes_value(true)
+                                .possible_values(dictionary_format_options_str.as_slice())
+                                .help("Format words are written with. If not given, it is guessed from file extension: .csv for CSV, .jsonl for JSON Lines and word list for any other.")))
+                        .subcommand(App::new("stats")
+                            .about("Show statistics about a dictionary contents.")
+                            .arg(Arg::new("dictionary_name").index(1)
+                                .required(true)
+                                .value_name("DICTIONARY_NAME")
+                                .takes_value(true)
+                                .help("Name for the dictionary to inspect.")))
+                        .subcommand(App::new("search")
+                            .about("Show dictionary words matching a word pattern or a regex.")
+                            .arg(Arg::new("dictionary_name").index(1)
+                                .required(true)
+                                .value_name("DICTIONARY_NAME")
+                                .takes_value(true)
+                                .help("Name for the dictionary to look words into."))
+                            .arg(Arg::new("query").index(2)
+                                .required(true)
+                                .value_name("PATTERN_OR_REGEX")
+                                .takes_value(true)
+                                .help("Word pattern, like 0.1.2.2, or a regex that must match whole words."))
+                            .arg(Arg::new("cipherword")
+                                .long("cipherword")
+                                .short('c')
+                                .takes_value(false)
+                                .help("Take query as a ciphered word and show words with its same pattern, as substitution attack gets them.")))
+                        .subcommand(App::new("install-defaults")
+                            .about("Build dictionaries for every language bundled with cifra: english, spanish, french and german.")
+                            .arg(Arg::new("reinstall")
+                                .long("reinstall")
+                                .short('r')
+                                .takes_value(false)
+                                .help("Remove bundled languages already present and build them again. Otherwise they are left as they are."))))
         .subcommand(App::new("cipher")
             .about("Cipher a text using a key.")
             .arg(Arg::new("algorithm").index(1)
@@ -330,9 +837,8 @@ fn parse_arguments(arg_vec: &Vec<&str>) -> Configuration {
                 .required(true)
                 .value_name("CIPHERING_KEY")
                 .takes_value(true)
-                .help("Key to use to cipher."))
+                .help("Key to use to cipher. Keyless algorithms (atbash, rot13 and rot47) don't need it."))
             .arg(Arg::new("file_to_cipher").index(3)
-                .required(true)
                 .value_name("FILE_TO_CIPHER")
                 .takes_value(true)
                 .validator(file_exists)
@@ -361,9 +867,8 @@ fn parse_arguments(arg_vec: &Vec<&str>) -> Configuration {
                 .required(true)
                 .value_name("CIPHERING_KEY")
                 .takes_value(true)
-                .help("Key to use to decipher."))
+                .help("Key to use to decipher. Keyless algorithms (atbash, rot13 and rot47) don't need it."))
             .arg(Arg::new("file_to_decipher").index(3)
-                .required(true)
                 .value_name("FILE_TO_DECIPHER")
                 .takes_value(true)
                 .validator(file_exists)
@@ -404,13 +909,69 @@ fn parse_arguments(arg_vec: &Vec<&str>) -> Configuration {
                 .short('k')
                 .long("output_recovered_key")
                 .help("Include guessed key in output. If not used only recovered text is output."))
+            .arg(Arg::new("crib")
+                .long("crib")
+                .value_name("TEXT")
+                .takes_value(true)
+                .help("Known plaintext fragment to recover key from. Available for caesar, affine, \
+                vigenere, substitution and transposition."))
+            .arg(Arg::new("crib_offset")
+                .long("crib-offset")
+                .value_name("N")
+                .takes_value(true)
+                .requires("crib")
+                .validator(is_positive_integer)
+                .help("Position, in characters, of crib in plaintext. If not used crib is looked for everywhere."))
+            .arg(Arg::new("scorer")
+                .long("scorer")
+                .value_name("SCORER")
+                .takes_value(true)
+                .possible_values(scorer_options_str.as_slice())
+                .conflicts_with("crib")
+                .help("Recover key comparing letter frequencies with language profiles instead of \
+                using dictionaries. Available for caesar, affine and vigenere."))
+            .arg(Arg::new("segment")
+                .long("segment")
+                .help("Split recovered text into words if it has no spaces."))
+            .arg(Arg::new("charset")
+                .short('c')
+                .long("charset")
+                .value_name("CHARSET")
+                .takes_value(true)
+                .help(charset_message.as_str())))
+        .subcommand(App::new("analyze")
+            .about("Show frequency statistics of a text.")
+            .arg(Arg::new("file_to_analyze").index(1)
+                .required(true)
+                .value_name("FILE_TO_ANALYZE")
+                .takes_value(true)
+                .validator(file_exists)
+                .help("Path to file with text to analyze."))
+            .arg(Arg::new("json")
+                .long("json")
+                .help("Output statistics as JSON instead of plain text."))
             .arg(Arg::new("charset")
                 .short('c')
                 .long("charset")
                 .value_name("CHARSET")
                 .takes_value(true)
                 .help(charset_message.as_str())))
+        .subcommand(App::new("workbench")
+            .about("Solve interactively a substitution ciphered text.")
+            .arg(Arg::new("file_to_solve").index(1)
+                .required(true)
+                .value_name("FILE_TO_SOLVE")
+                .takes_value(true)
+                .validator(file_exists)
+                .help("Path to file with text to solve."))
+            .arg(Arg::new("charset")
+                .short('c')
+                .long("charset")
+                .value_name("CHARSET")
+                .takes_value(true)
+                .help(format!("Default charset is: {}, but you can set here another", SUBSTITUTION_DEFAULT_CHARSET).as_str())))
         .get_matches_from(arg_vec);
+    check_key_and_file_arguments(&matches);
     let configuration = Configuration::from(matches);
     configuration
 }
@@ -429,19 +990,22 @@ fn process_file_with_key(configuration: &Configuration)-> Result<String> {
                 let input_file_path = file_to_cipher;
                 let content_to_process = read_to_string(input_file_path)
                     .chain_err(|| ErrorKind::IOError(String::from(input_file_path.to_str().unwrap())))?;
+                let cipher_charset = match algorithm {
+                    CipheringAlgorithms::Caesar | CipheringAlgorithms::Affine=> Some(charset.as_deref().unwrap_or(DEFAULT_CHARSET)),
+                    CipheringAlgorithms::Substitution | CipheringAlgorithms::Vigenere=> Some(charset.as_deref().unwrap_or(SUBSTITUTION_DEFAULT_CHARSET)),
+                    CipheringAlgorithms::Quagmire1 | CipheringAlgorithms::Quagmire2 |
+                    CipheringAlgorithms::Quagmire3 | CipheringAlgorithms::Quagmire4=> Some(charset.as_deref().unwrap_or(QUAGMIRE_DEFAULT_CHARSET)),
+                    _=> None
+                };
+                let content_to_process = normalize_text_to_cipher(&content_to_process, cipher_charset, configuration.normalization);
                 let processed_content: String;
                 match algorithm {
                     CipheringAlgorithms::Caesar | CipheringAlgorithms::Affine=> {
                         let process_function: fn(&str, usize, &str)-> Result<String> = get_integer_key_and_charset_ciphering_function(algorithm)?;
-                        let process_key = usize::from_str(key.as_str())
-                            .chain_err(|| ErrorKind::ConversionError("key", "&String", "usize"))?;
-                        if let Some(charset_string) = charset {
-                            processed_content = process_function(&content_to_process, process_key, charset_string)
-                                .chain_err(|| "Error ciphering text.")?;
-                        } else {
-                            processed_content = process_function(&content_to_process, process_key, DEFAULT_CHARSET)
-                                .chain_err(|| "Error ciphering text.")?;
-                        }
+                        let charset_string = charset.as_deref().unwrap_or(DEFAULT_CHARSET);
+                        let process_key = get_integer_key(algorithm, key, charset_string)?;
+                        processed_content = process_function(&content_to_process, process_key, charset_string)
+                            .chain_err(|| "Error ciphering text.")?;
                     },
                     CipheringAlgorithms::Substitution | CipheringAlgorithms::Vigenere=> {
                         let process_function: fn(&str, &str, &str)-> Result<String> = get_string_key_and_charset_ciphering_function(algorithm)?;
@@ -458,6 +1022,22 @@ fn process_file_with_key(configuration: &Configuration)-> Result<String> {
                         let process_key = usize::from_str(key.as_str())
                             .chain_err(|| ErrorKind::ConversionError("key", "&String", "usize"))?;
                         processed_content = process_function(&content_to_process, process_key);
+                    },
+                    CipheringAlgorithms::Quagmire1 | CipheringAlgorithms::Quagmire2 |
+                    CipheringAlgorithms::Quagmire3 | CipheringAlgorithms::Quagmire4=> {
+                        let process_key = QuagmireKey::from_key_string(algorithm.get_quagmire_variant()?, key)?;
+                        let charset_string = charset.as_deref().unwrap_or(QUAGMIRE_DEFAULT_CHARSET);
+                        processed_content = cifra::cipher::quagmire::cipher(&content_to_process, &process_key, charset_string)
+                            .chain_err(|| "Error ciphering text.")?;
+                    },
+                    CipheringAlgorithms::Enigma=> {
+                        let process_key = EnigmaKey::from_key_string(key)?;
+                        processed_content = cifra::cipher::enigma::cipher(&content_to_process, &process_key)
+                            .chain_err(|| "Error ciphering text.")?;
+                    },
+                    CipheringAlgorithms::Atbash | CipheringAlgorithms::Rot13 | CipheringAlgorithms::Rot47=> {
+                        processed_content = algorithm.get_preset()?.cipher(&content_to_process)
+                            .chain_err(|| "Error ciphering text.")?;
                     }
                 }
                 return Ok(processed_content)
@@ -471,15 +1051,10 @@ fn process_file_with_key(configuration: &Configuration)-> Result<String> {
             match algorithm {
                 CipheringAlgorithms::Caesar | CipheringAlgorithms::Affine=> {
                     let process_function: fn(&str, usize, &str)-> Result<String> = get_integer_key_and_charset_deciphering_function(algorithm)?;
-                    let process_key = usize::from_str(key.as_str())
-                        .chain_err(|| ErrorKind::ConversionError("key", "&String", "usize"))?;
-                    if let Some(charset_string) = charset {
-                        processed_content = process_function(&content_to_process, process_key, charset_string)
-                            .chain_err(|| "Error deciphering text.")?;
-                    } else {
-                        processed_content = process_function(&content_to_process, process_key, DEFAULT_CHARSET)
-                            .chain_err(|| "Error deciphering text.")?;
-                    }
+                    let charset_string = charset.as_deref().unwrap_or(DEFAULT_CHARSET);
+                    let process_key = get_integer_key(algorithm, key, charset_string)?;
+                    processed_content = process_function(&content_to_process, process_key, charset_string)
+                        .chain_err(|| "Error deciphering text.")?;
                 },
                 CipheringAlgorithms::Substitution | CipheringAlgorithms::Vigenere=> {
                     let process_function: fn(&str, &str, &str)-> Result<String> = get_string_key_and_charset_deciphering_function(algorithm)?;
@@ -497,6 +1072,22 @@ fn process_file_with_key(configuration: &Configuration)-> Result<String> {
                         .chain_err(|| ErrorKind::ConversionError("key", "&String", "usize"))?;
                     processed_content = process_function(&content_to_process, process_key)
                         .chain_err(|| "Error deciphering text.")?;
+                },
+                CipheringAlgorithms::Quagmire1 | CipheringAlgorithms::Quagmire2 |
+                CipheringAlgorithms::Quagmire3 | CipheringAlgorithms::Quagmire4=> {
+                    let process_key = QuagmireKey::from_key_string(algorithm.get_quagmire_variant()?, key)?;
+                    let charset_string = charset.as_deref().unwrap_or(QUAGMIRE_DEFAULT_CHARSET);
+                    processed_content = cifra::cipher::quagmire::decipher(&content_to_process, &process_key, charset_string)
+                        .chain_err(|| "Error deciphering text.")?;
+                },
+                CipheringAlgorithms::Enigma=> {
+                    let process_key = EnigmaKey::from_key_string(key)?;
+                    processed_content = cifra::cipher::enigma::decipher(&content_to_process, &process_key)
+                        .chain_err(|| "Error deciphering text.")?;
+                },
+                CipheringAlgorithms::Atbash | CipheringAlgorithms::Rot13 | CipheringAlgorithms::Rot47=> {
+                    processed_content = algorithm.get_preset()?.decipher(&content_to_process)
+                        .chain_err(|| "Error deciphering text.")?;
                 }
             }
             return Ok(processed_content)
@@ -506,6 +1097,25 @@ fn process_file_with_key(configuration: &Configuration)-> Result<String> {
     }
 }
 
+/// Get integer key for algorithms that use them along with a charset.
+///
+/// Affine keys can be given as a "a,b" pair, while the rest are single integers.
+///
+/// # Parameters:
+/// * algorithm: Algorithm the key is for.
+/// * key: Key string as given by user.
+/// * charset: Charset key is going to be used with.
+///
+/// # Returns:
+/// * Key as a single integer.
+fn get_integer_key(algorithm: &CipheringAlgorithms, key: &str, charset: &str)-> Result<usize> {
+    match algorithm {
+        CipheringAlgorithms::Affine=> cifra::cipher::affine::parse_key(key, charset.len()),
+        _=> usize::from_str(key)
+            .chain_err(|| ErrorKind::ConversionError("key", "&String", "usize"))
+    }
+}
+
 /// Helper generic function to output resulting content.
 ///
 /// # Parameters:
@@ -527,7 +1137,7 @@ where T: AsRef<str>{
             output_file_option = deciphered_file;
         },
         Modes::Attack { algorithm, file_to_attack,
-            deciphered_file, charset, output_recovered_key
+            deciphered_file, charset, output_recovered_key, ..
         }=> {
             output_file_option = deciphered_file;
             output_guessed_key = *output_recovered_key;
@@ -560,18 +1170,42 @@ where T: AsRef<str>{
 /// * Most likely original plain text and most likely key string.
 fn attack_file(configuration: &Configuration)-> Result<(String, String)> {
     if let Modes::Attack { algorithm, file_to_attack,
-        deciphered_file, output_recovered_key, charset
+        deciphered_file, output_recovered_key, charset, crib, scorer, ..
     } = &configuration.running_mode {
         let ciphered_content = read_to_string(file_to_attack)
             .expect("Error reading file to attack.");
+        let context = configuration.get_context();
+        if let Some(known_plaintext) = crib {
+            let key = get_key_from_crib(algorithm, ciphered_content.as_str(), known_plaintext, charset, &context)?;
+            let deciphered_text = process_file_with_key(&Configuration::new(Modes::Decipher {
+                algorithm: algorithm.clone(),
+                key: key.clone(),
+                file_to_decipher: file_to_attack.clone(),
+                deciphered_file: deciphered_file.clone(),
+                charset: charset.clone()
+            }));
+            return Ok((deciphered_text?, key))
+        }
+        if let Some(frequency_scorer) = scorer {
+            let key = get_key_from_letter_frequencies(algorithm, ciphered_content.as_str(), *frequency_scorer, charset,
+                                                      &context)?;
+            let deciphered_text = process_file_with_key(&Configuration::new(Modes::Decipher {
+                algorithm: algorithm.clone(),
+                key: key.clone(),
+                file_to_decipher: file_to_attack.clone(),
+                deciphered_file: deciphered_file.clone(),
+                charset: charset.clone()
+            }));
+            return Ok((deciphered_text?, key))
+        }
         match algorithm {
-            CipheringAlgorithms::Caesar | CipheringAlgorithms::Affine => {
-                let attack_function: fn(&str, &str)-> Result<usize> = get_charset_attack_function(algorithm)
+            CipheringAlgorithms::Caesar => {
+                let attack_function: fn(&str, &str, &dyn DictionaryStore)-> Result<usize> = get_charset_attack_function(algorithm)
                     .chain_err(||"Error getting attack function.")?;
                 let key = if let Some(charset_str) = charset {
-                        attack_function(ciphered_content.as_str(), charset_str)?
+                        attack_function(ciphered_content.as_str(), charset_str, &context)?
                     } else {
-                        attack_function(ciphered_content.as_str(), DEFAULT_CHARSET)?
+                        attack_function(ciphered_content.as_str(), DEFAULT_CHARSET, &context)?
                     };
                 let deciphered_text = process_file_with_key(&Configuration::new(Modes::Decipher {
                     algorithm: algorithm.clone(),
@@ -582,13 +1216,27 @@ fn attack_file(configuration: &Configuration)-> Result<(String, String)> {
                 }));
                 return Ok((deciphered_text?, key.to_string()))
             },
+            CipheringAlgorithms::Affine => {
+                let charset_str = charset.as_deref().unwrap_or(DEFAULT_CHARSET);
+                let (multiplying_key, adding_key) = cifra::attack::affine::brute_force_mp(ciphered_content.as_str(),
+                                                                                          charset_str, &context)?;
+                let key = format!("{},{}", multiplying_key, adding_key);
+                let deciphered_text = process_file_with_key(&Configuration::new(Modes::Decipher {
+                    algorithm: algorithm.clone(),
+                    key: key.clone(),
+                    file_to_decipher: file_to_attack.clone(),
+                    deciphered_file: deciphered_file.clone(),
+                    charset: charset.clone()
+                }));
+                return Ok((deciphered_text?, key))
+            },
             CipheringAlgorithms::Substitution => {
-                let attack_function: fn(&str, &str)-> Result<(String, f64)> = get_string_key_and_charset_attack_function(algorithm)
+                let attack_function: fn(&str, &str, &dyn DictionaryStore)-> Result<(String, f64)> = get_string_key_and_charset_attack_function(algorithm)
                     .chain_err(||"Error getting attack function.")?;
                 let (key, _) = if let Some(charset_str) = charset {
-                    attack_function(ciphered_content.as_str(), charset_str)?
+                    attack_function(ciphered_content.as_str(), charset_str, &context)?
                 } else {
-                    attack_function(ciphered_content.as_str(), DEFAULT_CHARSET)?
+                    attack_function(ciphered_content.as_str(), DEFAULT_CHARSET, &context)?
                 };
                 let deciphered_text = process_file_with_key(&Configuration::new(Modes::Decipher {
                     algorithm: algorithm.clone(),
@@ -600,9 +1248,9 @@ fn attack_file(configuration: &Configuration)-> Result<(String, String)> {
                 return Ok((deciphered_text?, key))
             },
             CipheringAlgorithms::Transposition => {
-                let attack_function: fn(&str)-> Result<usize> = get_no_charset_attack_function(algorithm)
+                let attack_function: fn(&str, &dyn DictionaryStore)-> Result<usize> = get_no_charset_attack_function(algorithm)
                     .chain_err(||"Error getting attack function.")?;
-                let key = attack_function(ciphered_content.as_str())?;
+                let key = attack_function(ciphered_content.as_str(), &context)?;
                 let deciphered_text = process_file_with_key(&Configuration::new(Modes::Decipher {
                     algorithm: algorithm.clone(),
                     key: usize::to_string(&key),
@@ -629,6 +1277,46 @@ fn attack_file(configuration: &Configuration)-> Result<(String, String)> {
                 }));
                 return Ok((deciphered_text?, key))
             },
+            CipheringAlgorithms::Quagmire1 | CipheringAlgorithms::Quagmire2 |
+            CipheringAlgorithms::Quagmire3 | CipheringAlgorithms::Quagmire4 => {
+                let charset_str = charset.as_deref().unwrap_or(QUAGMIRE_DEFAULT_CHARSET);
+                let (key, _) = cifra::attack::quagmire::hack_quagmire_mp(ciphered_content.as_str(),
+                                                                         algorithm.get_quagmire_variant()?,
+                                                                         charset_str, &context)?;
+                let deciphered_text = process_file_with_key(&Configuration::new(Modes::Decipher {
+                    algorithm: algorithm.clone(),
+                    key: key.to_string(),
+                    file_to_decipher: file_to_attack.clone(),
+                    deciphered_file: deciphered_file.clone(),
+                    charset: charset.clone()
+                }));
+                return Ok((deciphered_text?, key.to_string()))
+            },
+            CipheringAlgorithms::Enigma => {
+                // Enigma I was the most widespread machine, so we look for its rotors
+                // with any of its reflectors.
+                let enigma_i_rotors = [Rotor::I, Rotor::II, Rotor::III, Rotor::IV, Rotor::V];
+                let (key_with_b, score_with_b) = cifra::attack::enigma::hack_enigma_mp(ciphered_content.as_str(),
+                                                                                       Reflector::B, &enigma_i_rotors)?;
+                let (key_with_c, score_with_c) = cifra::attack::enigma::hack_enigma_mp(ciphered_content.as_str(),
+                                                                                       Reflector::C, &enigma_i_rotors)?;
+                let key = if score_with_b >= score_with_c { key_with_b } else { key_with_c };
+                let deciphered_text = process_file_with_key(&Configuration::new(Modes::Decipher {
+                    algorithm: algorithm.clone(),
+                    key: key.to_string(),
+                    file_to_decipher: file_to_attack.clone(),
+                    deciphered_file: deciphered_file.clone(),
+                    charset: charset.clone()
+                }));
+                return Ok((deciphered_text?, key.to_string()))
+            },
+            CipheringAlgorithms::Atbash | CipheringAlgorithms::Rot13 | CipheringAlgorithms::Rot47 => {
+                // Trying every preset is so quick that there is no point in trying only
+                // the one asked for.
+                let preset = cifra::attack::presets::brute_force(ciphered_content.as_str(), &context)?;
+                let deciphered_text = preset.decipher(ciphered_content.as_str())?;
+                return Ok((deciphered_text, preset.to_string()))
+            },
         }
     } else {
         return bail!("You tried to use attack_file function with a configuration that is not for attack mode.")
@@ -636,24 +1324,296 @@ fn attack_file(configuration: &Configuration)-> Result<(String, String)> {
 }
 
 
-/// Get a pointer to ciphering function for given algorithm.
+/// Use a known plaintext fragment to get key used to cipher a text.
 ///
-/// Use only with algorithms that use integer keys and charsets.
-fn get_integer_key_and_charset_ciphering_function(algorithm: &CipheringAlgorithms)-> Result<fn(&str, usize, &str)-> Result<String>> {
-    let function = match algorithm {
-        CipheringAlgorithms::Caesar=> cifra::cipher::caesar::cipher,
-        CipheringAlgorithms::Affine=> cifra::cipher::affine::cipher,
-        _ => return bail!("Given algorithm does not use integer key and charset.")
+/// # Parameters:
+/// * algorithm: Algorithm used to cipher text.
+/// * ciphered_content: Ciphered text.
+/// * crib: Known plaintext fragment.
+/// * charset: Charset used to cipher, if not default one.
+/// * store: Dictionaries to assess candidate keys with.
+///
+/// # Returns:
+/// * Key found, as a string ready to be used to decipher.
+fn get_key_from_crib(algorithm: &CipheringAlgorithms, ciphered_content: &str, crib: &Crib,
+                     charset: &Option<String>, store: &dyn DictionaryStore)-> Result<String> {
+    let key = match algorithm {
+        CipheringAlgorithms::Caesar=> {
+            let charset_str = charset.as_deref().unwrap_or(DEFAULT_CHARSET);
+            let candidates = cifra::attack::crib::caesar_keys(ciphered_content, crib, charset_str)?;
+            get_best_candidate(&candidates, |&key| cifra::cipher::caesar::decipher(ciphered_content, key, charset_str), store)?
+                .to_string()
+        },
+        CipheringAlgorithms::Affine=> {
+            let charset_str = charset.as_deref().unwrap_or(DEFAULT_CHARSET);
+            let candidates = cifra::attack::crib::affine_keys(ciphered_content, crib, charset_str)?;
+            let (multiplying_key, adding_key) = get_best_candidate(&candidates, |&key|
+                cifra::cipher::affine::decipher_with_key_parts(ciphered_content, key, charset_str), store)?;
+            format!("{},{}", multiplying_key, adding_key)
+        },
+        CipheringAlgorithms::Vigenere=> {
+            let charset_str = charset.as_deref().unwrap_or(SUBSTITUTION_DEFAULT_CHARSET);
+            let candidates = cifra::attack::crib::vigenere_keys(ciphered_content, crib, charset_str)?;
+            get_best_candidate(&candidates, |key| cifra::cipher::vigenere::decipher(ciphered_content, key, charset_str), store)?
+        },
+        CipheringAlgorithms::Transposition=> {
+            let candidates = cifra::attack::crib::transposition_keys(ciphered_content, crib)?;
+            get_best_candidate(&candidates, |&key| cifra::cipher::transposition::decipher(ciphered_content, key), store)?
+                .to_string()
+        },
+        CipheringAlgorithms::Substitution=> {
+            let charset_str = charset.as_deref().unwrap_or(SUBSTITUTION_DEFAULT_CHARSET);
+            let (key, _) = cifra::attack::substitution::hack_substitution_with_crib_mp(ciphered_content, charset_str,
+                                                                                       crib, store)?;
+            key
+        },
+        _=> bail!(format!("Crib attacks are not available for {} algorithm.", algorithm))
     };
-    Ok(function)
+    Ok(key)
 }
 
-/// Get a pointer to ciphering function for given algorithm.
+/// Compare letter frequencies with language profiles to get key used to cipher a text.
 ///
-/// Use only with algorithms that use string keys and charsets.
-fn get_string_key_and_charset_ciphering_function(algorithm: &CipheringAlgorithms)-> Result<fn(&str, &str, &str)-> Result<String>> {
-    let function = match algorithm {
-        CipheringAlgorithms::Substitution => cifra::cipher::substitution::cipher,
+/// # Parameters:
+/// * algorithm: Algorithm used to cipher text.
+/// * ciphered_content: Ciphered text.
+/// * scorer: Scorer to compare deciphered texts with language profiles.
+/// * charset: Charset used to cipher, if not default one.
+/// * store: Dictionaries whose letter profiles deciphered texts are compared with.
+///
+/// # Returns:
+/// * Key found, as a string ready to be used to decipher.
+fn get_key_from_letter_frequencies(algorithm: &CipheringAlgorithms, ciphered_content: &str, scorer: Scorer,
+                                   charset: &Option<String>, store: &dyn DictionaryStore)-> Result<String> {
+    let charset_str = match algorithm {
+        CipheringAlgorithms::Vigenere=> charset.as_deref().unwrap_or(SUBSTITUTION_DEFAULT_CHARSET),
+        _=> charset.as_deref().unwrap_or(DEFAULT_CHARSET)
+    };
+    let key = match algorithm {
+        CipheringAlgorithms::Caesar=> cifra::attack::caesar::frequency_attack(ciphered_content, charset_str,
+                                                                              scorer, store)?
+            .to_string(),
+        CipheringAlgorithms::Affine=> {
+            let (multiplying_key, adding_key) = cifra::attack::affine::frequency_attack(ciphered_content,
+                                                                                        charset_str, scorer,
+                                                                                        store)?;
+            format!("{},{}", multiplying_key, adding_key)
+        },
+        CipheringAlgorithms::Vigenere=> cifra::attack::vigenere::frequency_attack(ciphered_content, charset_str,
+                                                                                  scorer, store)?,
+        _=> bail!(format!("Letter frequency attacks are not available for {} algorithm.", algorithm))
+    };
+    Ok(key)
+}
+
+/// How many bigrams and trigrams are shown at plain text analysis output.
+const ANALYSIS_TEXT_NGRAMS: usize = 20;
+/// How many word patterns are shown at dictionary statistics output.
+const DICTIONARY_STATISTICS_PATTERNS: usize = 20;
+
+/// Format text statistics to be read by a human.
+///
+/// Only the most common bigrams and trigrams are included.
+///
+/// # Parameters:
+/// * statistics: Statistics to format.
+///
+/// # Returns:
+/// * Formatted statistics.
+fn format_statistics_as_text(statistics: &TextStatistics)-> String {
+    let total_letters: u64 = statistics.letters.iter().map(|(_, count)| count).sum();
+    let mut output = String::from("Letters:\n");
+    for (letter, count) in statistics.letters.iter() {
+        let percentage = *count as f64 * 100.0 / total_letters as f64;
+        output.push_str(&format!("  {} {:6} {:6.2}% {}\n", letter, count, percentage, "#".repeat(percentage.round() as usize)));
+    }
+    for (title, ngrams) in [("Bigrams", &statistics.bigrams), ("Trigrams", &statistics.trigrams)].iter() {
+        output.push_str(&format!("{}:\n", title));
+        for (ngram, count) in ngrams.iter().take(ANALYSIS_TEXT_NGRAMS) {
+            output.push_str(&format!("  {} {}\n", ngram, count));
+        }
+    }
+    output.push_str(&format!("Index of coincidence: {:.4}\n", statistics.index_of_coincidence));
+    output.push_str(&format!("Entropy: {:.4} bits per letter\n", statistics.entropy));
+    output.push_str("Repeated sequences:\n");
+    for (sequence, separations) in statistics.repeated_sequences.iter() {
+        let separations_strings: Vec<String> = separations.iter().map(|separation| separation.to_string()).collect();
+        output.push_str(&format!("  {} {}\n", sequence, separations_strings.join(" ")));
+    }
+    output.push_str("Separations factors:\n");
+    for (factor, count) in statistics.factors.iter() {
+        output.push_str(&format!("  {} {}\n", factor, count));
+    }
+    output
+}
+
+/// Format dictionary statistics to be read by a human.
+///
+/// Only the most common patterns are included.
+///
+/// # Parameters:
+/// * statistics: Statistics to format.
+///
+/// # Returns:
+/// * Formatted statistics.
+fn format_dictionary_statistics(statistics: &DictionaryStatistics)-> String {
+    let mut output = format!("Words: {}\nTotal frequency: {}\nLengths:\n", statistics.word_count, statistics.total_frequency);
+    for (length, count) in statistics.lengths.iter() {
+        let percentage = *count as f64 * 100.0 / statistics.word_count as f64;
+        output.push_str(&format!("  {:2} {:8} {:6.2}% {}\n", length, count, percentage, "#".repeat(percentage.round() as usize)));
+    }
+    output.push_str("Patterns:\n");
+    for (pattern, count) in statistics.patterns.iter().take(DICTIONARY_STATISTICS_PATTERNS) {
+        output.push_str(&format!("  {} {}\n", pattern, count));
+    }
+    let total_letters: u64 = statistics.letters.iter().map(|(_, count)| count).sum();
+    output.push_str("Letters:\n");
+    for (letter, count) in statistics.letters.iter() {
+        let percentage = *count as f64 * 100.0 / total_letters as f64;
+        output.push_str(&format!("  {} {:10} {:6.2}% {}\n", letter, count, percentage, "#".repeat(percentage.round() as usize)));
+    }
+    output
+}
+
+/// Show how many words were added to a dictionary.
+///
+/// # Parameters:
+/// * report: Summary of words added to a dictionary.
+fn print_population_report(report: &PopulationReport) {
+    println!("New words: {}\nExisting words: {}\nDiscarded words: {}",
+             report.new_words, report.existing_words, report.discarded_words);
+}
+
+/// Format text statistics as a JSON document.
+///
+/// # Parameters:
+/// * statistics: Statistics to format.
+///
+/// # Returns:
+/// * JSON string.
+fn format_statistics_as_json(statistics: &TextStatistics)-> Result<String> {
+    serde_json::to_string_pretty(statistics).chain_err(|| "Error formatting statistics as JSON.")
+}
+
+/// Run workbench interactive mode until user quits or input ends.
+///
+/// # Parameters:
+/// * workbench: Workbench with text to solve.
+/// * input: Where to read user commands from. Usually standard input.
+/// * output: Where to write commands results to. Usually standard output.
+fn run_workbench<R, W>(workbench: &mut Workbench, input: R, output: &mut W, store: &dyn DictionaryStore)-> Result<()>
+    where R: BufRead,
+          W: Write {
+    writeln!(output, "{}", WORKBENCH_HELP).chain_err(|| "Error writing to output.")?;
+    show_workbench(workbench, output)?;
+    let mut lines = input.lines();
+    loop {
+        write!(output, "> ").chain_err(|| "Error writing to output.")?;
+        output.flush().chain_err(|| "Error writing to output.")?;
+        let line = match lines.next() {
+            Some(line) => line.chain_err(|| "Error reading command.")?,
+            None => break
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let command = match WorkbenchCommands::try_from(line.as_str()) {
+            Ok(command) => command,
+            Err(e) => {
+                writeln!(output, "{}", e).chain_err(|| "Error writing to output.")?;
+                continue;
+            }
+        };
+        if command == WorkbenchCommands::Quit {
+            break;
+        }
+        // Wrong commands must not end session, so their errors are just reported.
+        if let Err(e) = run_workbench_command(workbench, command, output, store) {
+            writeln!(output, "{}", e).chain_err(|| "Error writing to output.")?;
+        }
+    }
+    Ok(())
+}
+
+/// Run a single workbench command.
+///
+/// # Parameters:
+/// * workbench: Workbench with text to solve.
+/// * command: Command to run.
+/// * output: Where to write command result to.
+fn run_workbench_command<W>(workbench: &mut Workbench, command: WorkbenchCommands, output: &mut W,
+                             store: &dyn DictionaryStore)-> Result<()>
+    where W: Write {
+    match command {
+        WorkbenchCommands::Show=> show_workbench(workbench, output)?,
+        WorkbenchCommands::Assign { cipherletter, plain_letter }=> {
+            workbench.assign(cipherletter, plain_letter)?;
+            show_workbench(workbench, output)?;
+        },
+        WorkbenchCommands::Unassign { cipherletter }=> {
+            if workbench.unassign(cipherletter).is_none() {
+                bail!(format!("{} was not assigned.", cipherletter))
+            }
+            show_workbench(workbench, output)?;
+        },
+        WorkbenchCommands::Forbid { cipherletter, plain_letter }=> workbench.forbid(cipherletter, plain_letter)?,
+        WorkbenchCommands::Histogram=> {
+            for (letter, frequency) in workbench.histogram()? {
+                writeln!(output, "{} {:6.2}% {}", letter, frequency * 100.0, "#".repeat((frequency * 100.0).round() as usize))
+                    .chain_err(|| "Error writing to output.")?;
+            }
+        },
+        WorkbenchCommands::Matches { ciphered_word, language }=> {
+            let languages = match language {
+                Some(language)=> vec![language],
+                None=> store.get_dictionaries_names()?
+            };
+            for language in languages {
+                let matches = workbench.pattern_matches(&ciphered_word, &language, store)?;
+                writeln!(output, "{}: {}", language, matches.join(" ")).chain_err(|| "Error writing to output.")?;
+            }
+        },
+        WorkbenchCommands::AutoComplete=> {
+            let probability = workbench.auto_complete(store)?;
+            writeln!(output, "Solver found a key with {:.2} probability.", probability)
+                .chain_err(|| "Error writing to output.")?;
+            show_workbench(workbench, output)?;
+        },
+        WorkbenchCommands::Key=> writeln!(output, "{}", workbench.partial_key()).chain_err(|| "Error writing to output.")?,
+        WorkbenchCommands::Help=> writeln!(output, "{}", WORKBENCH_HELP).chain_err(|| "Error writing to output.")?,
+        WorkbenchCommands::Quit=> {}
+    }
+    Ok(())
+}
+
+/// Write every ciphered text line followed by its partial decryption.
+fn show_workbench<W>(workbench: &Workbench, output: &mut W)-> Result<()>
+    where W: Write {
+    let partial_decryption = workbench.partial_decryption();
+    for (ciphered_line, deciphered_line) in workbench.ciphered_text().lines().zip(partial_decryption.lines()) {
+        writeln!(output, "{}\n{}\n", ciphered_line, deciphered_line).chain_err(|| "Error writing to output.")?;
+    }
+    Ok(())
+}
+
+/// Get a pointer to ciphering function for given algorithm.
+///
+/// Use only with algorithms that use integer keys and charsets.
+fn get_integer_key_and_charset_ciphering_function(algorithm: &CipheringAlgorithms)-> Result<fn(&str, usize, &str)-> Result<String>> {
+    let function = match algorithm {
+        CipheringAlgorithms::Caesar=> cifra::cipher::caesar::cipher,
+        CipheringAlgorithms::Affine=> cifra::cipher::affine::cipher,
+        _ => return bail!("Given algorithm does not use integer key and charset.")
+    };
+    Ok(function)
+}
+
+/// Get a pointer to ciphering function for given algorithm.
+///
+/// Use only with algorithms that use string keys and charsets.
+fn get_string_key_and_charset_ciphering_function(algorithm: &CipheringAlgorithms)-> Result<fn(&str, &str, &str)-> Result<String>> {
+    let function = match algorithm {
+        CipheringAlgorithms::Substitution => cifra::cipher::substitution::cipher,
         CipheringAlgorithms::Vigenere=> cifra::cipher::vigenere::cipher,
         _ => return bail!("Given algorithm does not use string key and charset.")
     };
@@ -709,10 +1669,9 @@ fn get_integer_key_deciphering_function(algorithm: &CipheringAlgorithms)-> Resul
 /// Get a pointer to attack function for given algorithm.
 ///
 /// Use only with algorithms that use charsets.
-fn get_charset_attack_function(algorithm: &CipheringAlgorithms)-> Result<fn(&str, &str)-> Result<usize>>{
+fn get_charset_attack_function(algorithm: &CipheringAlgorithms)-> Result<fn(&str, &str, &dyn DictionaryStore)-> Result<usize>>{
     let function = match algorithm {
         CipheringAlgorithms::Caesar => cifra::attack::caesar::brute_force_mp,
-        CipheringAlgorithms::Affine=> cifra::attack::affine::brute_force_mp,
         _ => return bail!("Given algorithm does not use charset.")
     };
     Ok(function)
@@ -721,7 +1680,7 @@ fn get_charset_attack_function(algorithm: &CipheringAlgorithms)-> Result<fn(&str
 /// Get a pointer to attack function for given algorithm.
 ///
 /// Use only with algorithms that return a string key and a float tuple.
-fn get_string_key_and_charset_attack_function(algorithm: &CipheringAlgorithms)-> Result<fn(&str, &str)-> Result<(String, f64)>>{
+fn get_string_key_and_charset_attack_function(algorithm: &CipheringAlgorithms)-> Result<fn(&str, &str, &dyn DictionaryStore)-> Result<(String, f64)>>{
     let function = match algorithm {
         CipheringAlgorithms::Substitution=> cifra::attack::substitution::hack_substitution_mp,
         _ => return bail!("Given algorithm do use string key and charset")
@@ -732,7 +1691,7 @@ fn get_string_key_and_charset_attack_function(algorithm: &CipheringAlgorithms)->
 /// Get a pointer to attack function for given algorithm.
 ///
 /// Use only with algorithms that don't use charsets.
-fn get_no_charset_attack_function(algorithm: &CipheringAlgorithms)-> Result<fn(&str)-> Result<usize>>{
+fn get_no_charset_attack_function(algorithm: &CipheringAlgorithms)-> Result<fn(&str, &dyn DictionaryStore)-> Result<usize>>{
     let function = match algorithm {
         CipheringAlgorithms::Transposition=> cifra::attack::transposition::brute_force_mp,
         _ => return bail!("Given algorithm do use charset.")
@@ -754,39 +1713,103 @@ fn get_testing_mode_attack_function(algorithm: &CipheringAlgorithms)-> Result<fn
 
 fn _main(argv: Vec<&str>) {
     let configuration = parse_arguments(&argv);
+    let database_store = configuration.get_database_store();
+    let context = configuration.get_context();
 
     match configuration.running_mode {
         // Dictionary management.
         Modes::Dictionary(DictionaryActions::Create
-                            {dictionary_name, initial_words_file})=> {
-            let mut new_dictionary = Dictionary::new(dictionary_name, true)
+                            {dictionary_name, initial_words_file, minimum_frequency})=> {
+            let database = database_store.open_database()
+                .expect("Error opening database.");
+            let mut new_dictionary = Dictionary::from_database(database, dictionary_name, true)
                 .expect("Error creating new dictionary.");
             if let Some(path) = initial_words_file{
                 let pathname = path.to_str()
                     .expect("Error processing initial words file name.");
-                new_dictionary.populate(pathname);
+                let report = new_dictionary.populate_with_normalization(pathname, minimum_frequency, configuration.normalization)
+                    .expect("Error populating dictionary.");
+                print_population_report(&report);
             }
         }
         Modes::Dictionary(DictionaryActions::Delete
                             { dictionary_name })=> {
-            Dictionary::remove_dictionary(dictionary_name)
+            let database = database_store.open_database()
+                .expect("Error opening database.");
+            Dictionary::remove_dictionary_from(&database, dictionary_name)
                 .expect("Error removing dictionary.");
         }
         Modes::Dictionary(DictionaryActions::Update
-                          { dictionary_name, words_file })=> {
-            let mut dictionary = Dictionary::new(dictionary_name, false)
+                          { dictionary_name, words_file, minimum_frequency })=> {
+            let database = database_store.open_database()
+                .expect("Error opening database.");
+            let mut dictionary = Dictionary::from_database(database, dictionary_name, false)
                 .expect("Error opening dictionary.");
             let pathname = words_file.to_str()
                 .expect("Error processing words file name.");
-            dictionary.populate(pathname);
+            let report = dictionary.populate_with_normalization(pathname, minimum_frequency, configuration.normalization)
+                .expect("Error populating dictionary.");
+            print_population_report(&report);
         }
         Modes::Dictionary(DictionaryActions::List)=> {
-            let dictionaries = Dictionary::get_dictionaries_names()
+            let dictionaries = database_store.get_dictionaries_names()
                 .expect("Error retrieving available dictionaries.");
             for dictionary in &dictionaries{
                 println!("{}", dictionary);
             }
         }
+        Modes::Dictionary(DictionaryActions::Export
+                          { dictionary_name, output_file, format })=> {
+            let dictionary = database_store.open_dictionary(&dictionary_name)
+                .expect("Error opening dictionary.");
+            let mut output = File::create(&output_file)
+                .expect("Error creating output file.");
+            let exported_words = export_dictionary(dictionary.as_ref(), format, &mut output)
+                .expect("Error exporting dictionary.");
+            println!("Exported {} words from {} to {}.", exported_words, dictionary_name, output_file.display());
+        }
+        Modes::Dictionary(DictionaryActions::Stats { dictionary_name })=> {
+            let dictionary = database_store.open_dictionary(&dictionary_name)
+                .expect("Error opening dictionary.");
+            let statistics = get_dictionary_statistics(dictionary.as_ref())
+                .expect("Error getting dictionary statistics.");
+            print!("{}", format_dictionary_statistics(&statistics));
+        }
+        Modes::Dictionary(DictionaryActions::Search { dictionary_name, query, cipherword })=> {
+            let dictionary = database_store.open_dictionary(&dictionary_name)
+                .expect("Error opening dictionary.");
+            let query = if cipherword { get_word_pattern(&query) } else { query };
+            let found_words = search_words(dictionary.as_ref(), &query)
+                .expect("Error searching dictionary.");
+            for found_word in found_words.iter() {
+                println!("{}", found_word);
+            }
+        }
+        Modes::Dictionary(DictionaryActions::InstallDefaults { reinstall })=> {
+            let installations = install_default_dictionaries(&database_store, reinstall, configuration.normalization)
+                .expect("Error installing default dictionaries.");
+            for installation in installations.iter() {
+                match &installation.report {
+                    Some(report)=> {
+                        println!("{}:", installation.language);
+                        print_population_report(report);
+                    },
+                    None=> println!("{}: already installed", installation.language)
+                }
+            }
+        }
+        Modes::Dictionary(DictionaryActions::Import
+                          { dictionary_name, input_file, format })=> {
+            let database = database_store.open_database()
+                .expect("Error opening database.");
+            let mut dictionary = Dictionary::from_database(database, &dictionary_name, true)
+                .expect("Error opening dictionary.");
+            let input = File::open(&input_file)
+                .expect("Error opening input file.");
+            let imported_words = import_dictionary(&mut dictionary, format, BufReader::new(input))
+                .expect("Error importing dictionary.");
+            println!("Imported {} words from {} to {}.", imported_words, input_file.display(), dictionary_name);
+        }
         // Ciphering management.
         Modes::Cipher{ .. }
         | Modes::Decipher { .. }=> {
@@ -795,20 +1818,50 @@ fn _main(argv: Vec<&str>) {
             output_result(&ciphered_content, None, &configuration)
                 .expect("Error outputting recovered text.");
         }
-        Modes::Attack{ .. }=> {
+        Modes::Attack{ segment, .. }=> {
             if let Ok((recovered_content, key)) = attack_file(&configuration) {
+                let recovered_content = if segment {
+                    split_words(&recovered_content, &context).expect("Error splitting recovered text into words.")
+                } else {
+                    recovered_content
+                };
                 output_result(&recovered_content, Some(key), &configuration)
                     .expect("Error outputting recovered text.");
             } else {
                 panic!("Error attacking ciphered text.");
             }
         }
+        // Text analysis.
+        Modes::Analyze{ file_to_analyze, json, charset }=> {
+            let content_to_analyze = read_to_string(&file_to_analyze)
+                .expect("Error reading file to analyze.");
+            let statistics = get_text_statistics(&content_to_analyze, charset.as_deref().unwrap_or(DEFAULT_CHARSET));
+            if json {
+                println!("{}", format_statistics_as_json(&statistics)
+                    .expect("Error formatting statistics."));
+            } else {
+                print!("{}", format_statistics_as_text(&statistics));
+            }
+        }
+        // Interactive solving.
+        Modes::Workbench{ file_to_solve, charset }=> {
+            let ciphered_content = read_to_string(&file_to_solve)
+                .expect("Error reading file to solve.");
+            let mut workbench = Workbench::new(&ciphered_content,
+                                               charset.as_deref().unwrap_or(SUBSTITUTION_DEFAULT_CHARSET));
+            let stdin = std::io::stdin();
+            run_workbench(&mut workbench, stdin.lock(), &mut std::io::stdout(), &context)
+                .expect("Error running workbench.");
+        }
     }
 }
 
 fn main() {
     // I make an indirection to run functional test feeding my own vector to _main().
     // Shame on Rust for not having default arguments!
+    // DATABASE_URL can be set at a .env file. It is loaded only once, here, so library
+    // just reads environment.
+    dotenv().ok();
     let args: Vec<String> = args().collect();
     let args_str: Vec<&str> = args.iter().map(|Str| Str.as_str()).collect();
     _main(args_str);
@@ -928,9 +1981,13 @@ mod tests {
     fn test_parser_create_dictionary() {
         let provided_args: Vec<&str> = "cifra dictionary create klingon".split_whitespace().collect();
         let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
             running_mode: Modes::Dictionary(DictionaryActions::Create {
                 dictionary_name: String::from("klingon"),
-                initial_words_file: None
+                initial_words_file: None,
+                minimum_frequency: 1
             })
         };
         let recovered_configuration = parse_arguments(&provided_args);
@@ -943,9 +2000,13 @@ mod tests {
         let command = format!("cifra dictionary create klingon --initial_words_file {}", output_file.path().to_str().unwrap());
         let provided_args: Vec<&str> = command.split_whitespace().collect();
         let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
             running_mode: Modes::Dictionary(DictionaryActions::Create {
                 dictionary_name: String::from("klingon"),
-                initial_words_file: Some(PathBuf::from(output_file.path().to_str().unwrap()))
+                initial_words_file: Some(PathBuf::from(output_file.path().to_str().unwrap())),
+                minimum_frequency: 1
             })
         };
         let recovered_configuration = parse_arguments(&provided_args);
@@ -956,6 +2017,9 @@ mod tests {
     fn test_parser_delete_dictionary(){
         let provided_args = "cifra dictionary delete klingon".split_whitespace().collect();
         let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
             running_mode: Modes::Dictionary(DictionaryActions::Delete {
                 dictionary_name: String::from("klingon"),
             })
@@ -970,9 +2034,13 @@ mod tests {
         let command = format!("cifra dictionary update klingon {}", words_file.path().to_str().unwrap());
         let provided_args: Vec<&str> = command.split_whitespace().collect();
         let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
             running_mode: Modes::Dictionary(DictionaryActions::Update {
                 dictionary_name: "klingon".to_string(),
-                words_file: PathBuf::from(words_file.path().to_str().unwrap()) }
+                words_file: PathBuf::from(words_file.path().to_str().unwrap()),
+                minimum_frequency: 1 }
             )
         };
         let recovered_configuration = parse_arguments(&provided_args);
@@ -985,6 +2053,9 @@ mod tests {
         let command = format!("cifra cipher caesar 3 {}", message_file.path().to_str().unwrap());
         let provided_args: Vec<&str> = command.split_whitespace().collect();
         let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
             running_mode: Modes::Cipher {
                 algorithm: CipheringAlgorithms::Caesar,
                 key: "3".to_string(),
@@ -1003,6 +2074,9 @@ mod tests {
         let command = format!("cifra decipher caesar 3 {}", message_file.path().to_str().unwrap());
         let provided_args: Vec<&str> = command.split_whitespace().collect();
         let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
             running_mode: Modes::Decipher {
                 algorithm: CipheringAlgorithms::Caesar,
                 key: "3".to_string(),
@@ -1021,6 +2095,9 @@ mod tests {
         let command = format!("cifra decipher caesar 3 {} --deciphered_file deciphered_message.txt", message_file.path().to_str().unwrap());
         let provided_args: Vec<&str> = command.split_whitespace().collect();
         let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
             running_mode: Modes::Decipher {
                 algorithm: CipheringAlgorithms::Caesar,
                 key: "3".to_string(),
@@ -1039,12 +2116,18 @@ mod tests {
         let command = format!("cifra attack caesar {} --deciphered_file recovered_message.txt", message_file.path().to_str().unwrap());
         let provided_args: Vec<&str> = command.split_whitespace().collect();
         let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
             running_mode: Modes::Attack {
                 algorithm: CipheringAlgorithms::Caesar,
                 charset: None,
                 deciphered_file: Some(PathBuf::from("recovered_message.txt")),
                 file_to_attack: PathBuf::from(message_file.path().to_str().unwrap()),
-                output_recovered_key: false
+                output_recovered_key: false,
+                crib: None,
+                scorer: None,
+                segment: false
             }
         };
         let recovered_configuration = parse_arguments(&provided_args);
@@ -1057,12 +2140,18 @@ mod tests {
         let command = format!("cifra attack caesar {} --deciphered_file recovered_message.txt --output_recovered_key", message_file.path().to_str().unwrap());
         let provided_args: Vec<&str> = command.split_whitespace().collect();
         let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
             running_mode: Modes::Attack {
                 algorithm: CipheringAlgorithms::Caesar,
                 charset: None,
                 deciphered_file: Some(PathBuf::from("recovered_message.txt")),
                 file_to_attack: PathBuf::from(message_file.path().to_str().unwrap()),
-                output_recovered_key: true
+                output_recovered_key: true,
+                crib: None,
+                scorer: None,
+                segment: false
             }
         };
         let recovered_configuration = parse_arguments(&provided_args);
@@ -1075,28 +2164,431 @@ mod tests {
         let command = format!("cifra attack caesar {} --deciphered_file recovered_message.txt --charset abcdefghijklmnñopqrstuvwxyz", message_file.path().to_str().unwrap());
         let provided_args: Vec<&str> = command.split_whitespace().collect();
         let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
             running_mode: Modes::Attack {
                 algorithm: CipheringAlgorithms::Caesar,
                 charset: Some(String::from("abcdefghijklmnñopqrstuvwxyz")),
                 deciphered_file: Some(PathBuf::from("recovered_message.txt")),
                 file_to_attack: PathBuf::from(message_file.path().to_str().unwrap()),
-                output_recovered_key: false
+                output_recovered_key: false,
+                crib: None,
+                scorer: None,
+                segment: false
+            }
+        };
+        let recovered_configuration = parse_arguments(&provided_args);
+        assert_eq!(expected_configuration, recovered_configuration);
+    }
+
+    #[test]
+    fn test_parser_attack_caesar_with_crib() {
+        let message_file = TestFile::new();
+        let command = format!("cifra attack caesar {} --crib Dear --crib-offset 3", message_file.path().to_str().unwrap());
+        let provided_args: Vec<&str> = command.split_whitespace().collect();
+        let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
+            running_mode: Modes::Attack {
+                algorithm: CipheringAlgorithms::Caesar,
+                charset: None,
+                deciphered_file: None,
+                file_to_attack: PathBuf::from(message_file.path().to_str().unwrap()),
+                output_recovered_key: false,
+                crib: Some(Crib::new("Dear", Some(3))),
+                scorer: None,
+                segment: false
+            }
+        };
+        let recovered_configuration = parse_arguments(&provided_args);
+        assert_eq!(expected_configuration, recovered_configuration);
+    }
+
+    #[test]
+    fn test_parser_attack_vigenere_with_scorer() {
+        let message_file = TestFile::new();
+        let command = format!("cifra attack vigenere {} --scorer chi-squared", message_file.path().to_str().unwrap());
+        let provided_args: Vec<&str> = command.split_whitespace().collect();
+        let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
+            running_mode: Modes::Attack {
+                algorithm: CipheringAlgorithms::Vigenere,
+                charset: None,
+                deciphered_file: None,
+                file_to_attack: PathBuf::from(message_file.path().to_str().unwrap()),
+                output_recovered_key: false,
+                crib: None,
+                scorer: Some(Scorer::ChiSquared),
+                segment: false
+            }
+        };
+        let recovered_configuration = parse_arguments(&provided_args);
+        assert_eq!(expected_configuration, recovered_configuration);
+    }
+
+    #[test]
+    fn test_parser_attack_with_segment() {
+        let message_file = TestFile::new();
+        let command = format!("cifra attack caesar {} --segment", message_file.path().to_str().unwrap());
+        let provided_args: Vec<&str> = command.split_whitespace().collect();
+        let recovered_configuration = parse_arguments(&provided_args);
+        if let Modes::Attack { segment, .. } = recovered_configuration.running_mode {
+            assert!(segment);
+        } else {
+            assert!(false, "Attack mode not recognized.");
+        }
+    }
+
+    #[test]
+    fn test_parser_cipher_quagmire() {
+        let message_file = TestFile::new();
+        let command = format!("cifra cipher quagmire4 senory,percussion,flower {}", message_file.path().to_str().unwrap());
+        let provided_args: Vec<&str> = command.split_whitespace().collect();
+        let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
+            running_mode: Modes::Cipher {
+                algorithm: CipheringAlgorithms::Quagmire4,
+                key: "senory,percussion,flower".to_string(),
+                file_to_cipher: PathBuf::from(message_file.path().to_str().unwrap()),
+                ciphered_file: None,
+                charset: None
             }
         };
         let recovered_configuration = parse_arguments(&provided_args);
         assert_eq!(expected_configuration, recovered_configuration);
     }
 
+    #[test]
+    fn test_get_integer_key() {
+        assert_eq!(get_integer_key(&CipheringAlgorithms::Affine, "43,56", DEFAULT_CHARSET).unwrap(), 2894);
+        assert_eq!(get_integer_key(&CipheringAlgorithms::Affine, "2894", DEFAULT_CHARSET).unwrap(), 2894);
+        assert_eq!(get_integer_key(&CipheringAlgorithms::Caesar, "13", DEFAULT_CHARSET).unwrap(), 13);
+        assert!(get_integer_key(&CipheringAlgorithms::Caesar, "1,3", DEFAULT_CHARSET).is_err());
+    }
+
+    #[test]
+    fn test_parser_cipher_keyless() {
+        let message_file = TestFile::new();
+        let command = format!("cifra cipher atbash {}", message_file.path().to_str().unwrap());
+        let provided_args: Vec<&str> = command.split_whitespace().collect();
+        let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
+            running_mode: Modes::Cipher {
+                algorithm: CipheringAlgorithms::Atbash,
+                key: String::new(),
+                file_to_cipher: PathBuf::from(message_file.path().to_str().unwrap()),
+                ciphered_file: None,
+                charset: None
+            }
+        };
+        let recovered_configuration = parse_arguments(&provided_args);
+        assert_eq!(expected_configuration, recovered_configuration);
+    }
+
+    #[test]
+    fn test_parser_workbench() {
+        let message_file = TestFile::new();
+        let command = format!("cifra workbench {}", message_file.path().to_str().unwrap());
+        let provided_args: Vec<&str> = command.split_whitespace().collect();
+        let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
+            running_mode: Modes::Workbench {
+                file_to_solve: PathBuf::from(message_file.path().to_str().unwrap()),
+                charset: None
+            }
+        };
+        let recovered_configuration = parse_arguments(&provided_args);
+        assert_eq!(expected_configuration, recovered_configuration);
+    }
+
+    #[test]
+    fn test_workbench_commands() {
+        assert_eq!(WorkbenchCommands::try_from("assign x e").unwrap(),
+                   WorkbenchCommands::Assign { cipherletter: 'x', plain_letter: 'e' });
+        assert_eq!(WorkbenchCommands::try_from("matches pyyacao english").unwrap(),
+                   WorkbenchCommands::Matches { ciphered_word: "pyyacao".to_string(), language: Some("english".to_string()) });
+        assert_eq!(WorkbenchCommands::try_from(" QUIT ").unwrap(), WorkbenchCommands::Quit);
+        assert!(WorkbenchCommands::try_from("assign xy e").is_err());
+        assert!(WorkbenchCommands::try_from("unassign").is_err());
+        assert!(WorkbenchCommands::try_from("solve").is_err());
+    }
+
+    #[test]
+    fn test_run_workbench() {
+        let mut workbench = Workbench::new("Sy l nlx", SUBSTITUTION_TEST_CHARSET);
+        let input = "assign s i\nassign y f\nassign l i\nunassign y\nkey\nquit\nassign l a\n".as_bytes();
+        let mut output: Vec<u8> = Vec::new();
+        run_workbench(&mut workbench, input, &mut output, &DatabaseStore::default()).unwrap();
+        let output_text = String::from_utf8(output).unwrap();
+        assert!(output_text.contains("Sy l nlx\nIf _ ___\n"));
+        assert!(output_text.contains("Sy l nlx\nI_ _ ___\n"));
+        assert!(output_text.contains("Problem with key l->i"));
+        assert!(output_text.contains("________s_________________"));
+        // Commands after quit are ignored.
+        assert_eq!(workbench.partial_key(), "________s_________________");
+    }
+
+    #[test]
+    fn test_parser_analyze() {
+        let message_file = TestFile::new();
+        let command = format!("cifra analyze {} --json", message_file.path().to_str().unwrap());
+        let provided_args: Vec<&str> = command.split_whitespace().collect();
+        let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
+            running_mode: Modes::Analyze {
+                file_to_analyze: PathBuf::from(message_file.path().to_str().unwrap()),
+                json: true,
+                charset: None
+            }
+        };
+        let recovered_configuration = parse_arguments(&provided_args);
+        assert_eq!(expected_configuration, recovered_configuration);
+    }
+
+    #[test]
+    fn test_format_statistics() {
+        let statistics = get_text_statistics("abc abc", SUBSTITUTION_TEST_CHARSET);
+        let text_output = format_statistics_as_text(&statistics);
+        assert!(text_output.starts_with("Letters:\n  a      2  33.33% #################################\n"));
+        assert!(text_output.contains("Repeated sequences:\n  abc 3\n"));
+        let json_output: serde_json::Value = serde_json::from_str(&format_statistics_as_json(&statistics).unwrap()).unwrap();
+        assert_eq!(json_output["letters"], serde_json::json!({"a": 2, "b": 2, "c": 2}));
+        assert_eq!(json_output["repeated_sequences"], serde_json::json!({"abc": [3]}));
+        assert_eq!(json_output["factors"], serde_json::json!({"3": 1}));
+        assert_eq!(json_output["entropy"], serde_json::json!(statistics.entropy));
+    }
+
     #[test]
     fn test_parser_list_dictionaries() {
         let provided_args = "cifra dictionary list".split_whitespace().collect();
         let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
             running_mode: Modes::Dictionary(DictionaryActions::List)
         };
         let recovered_configuration = parse_arguments(&provided_args);
         assert_eq!(expected_configuration, recovered_configuration);
     }
 
+    #[test]
+    fn test_parser_update_dictionary_with_minimum_frequency(){
+        let words_file = TestFile::new();
+        let command = format!("cifra dictionary update klingon {} --minimum_frequency 3", words_file.path().to_str().unwrap());
+        let provided_args: Vec<&str> = command.split_whitespace().collect();
+        let recovered_configuration = parse_arguments(&provided_args);
+        if let Modes::Dictionary(DictionaryActions::Update { minimum_frequency, .. }) = recovered_configuration.running_mode {
+            assert_eq!(3, minimum_frequency);
+        } else {
+            assert!(false, "Update mode not recognized.");
+        }
+    }
+
+    #[test]
+    fn test_parser_export_dictionary(){
+        let provided_args: Vec<&str> = "cifra dictionary export klingon klingon.csv".split_whitespace().collect();
+        let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
+            running_mode: Modes::Dictionary(DictionaryActions::Export {
+                dictionary_name: "klingon".to_string(),
+                output_file: PathBuf::from("klingon.csv"),
+                format: DictionaryFormat::Csv
+            })
+        };
+        let recovered_configuration = parse_arguments(&provided_args);
+        assert_eq!(expected_configuration, recovered_configuration);
+    }
+
+    #[test]
+    fn test_parser_import_dictionary(){
+        let words_file = TestFile::new();
+        let command = format!("cifra dictionary import klingon {} --format jsonl", words_file.path().to_str().unwrap());
+        let provided_args: Vec<&str> = command.split_whitespace().collect();
+        let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
+            running_mode: Modes::Dictionary(DictionaryActions::Import {
+                dictionary_name: "klingon".to_string(),
+                input_file: PathBuf::from(words_file.path().to_str().unwrap()),
+                format: DictionaryFormat::JsonLines
+            })
+        };
+        let recovered_configuration = parse_arguments(&provided_args);
+        assert_eq!(expected_configuration, recovered_configuration);
+    }
+
+    #[rstest]
+    fn test_export_and_import_dictionary(temp_dir: TestEnvironment) {
+        let source_database = temp_dir.path().join("source.sqlite");
+        let target_database = temp_dir.path().join("target.sqlite");
+        let words_file = temp_dir.path().join("words.txt");
+        write(&words_file, "Qapla batlh Qapla").unwrap();
+        let exported_file = temp_dir.path().join("klingon.jsonl");
+        _main(format!("cifra --database {} dictionary create klingon -i {}",
+                      source_database.to_str().unwrap(), words_file.to_str().unwrap()).split_whitespace().collect());
+        _main(format!("cifra --database {} dictionary export klingon {}",
+                      source_database.to_str().unwrap(), exported_file.to_str().unwrap()).split_whitespace().collect());
+        _main(format!("cifra --database {} dictionary import klingon {}",
+                      target_database.to_str().unwrap(), exported_file.to_str().unwrap()).split_whitespace().collect());
+        let target_store = DatabaseStore::at(&target_database);
+        let dictionary = target_store.open_dictionary("klingon").unwrap();
+        assert_eq!(2, dictionary.get_word_frequency("qapla").unwrap());
+        assert_eq!(1, dictionary.get_word_frequency("batlh").unwrap());
+    }
+
+    #[test]
+    fn test_parser_dictionary_stats(){
+        let provided_args: Vec<&str> = "cifra dictionary stats klingon".split_whitespace().collect();
+        let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
+            running_mode: Modes::Dictionary(DictionaryActions::Stats { dictionary_name: "klingon".to_string() })
+        };
+        let recovered_configuration = parse_arguments(&provided_args);
+        assert_eq!(expected_configuration, recovered_configuration);
+    }
+
+    #[test]
+    fn test_parser_dictionary_install_defaults(){
+        let provided_args: Vec<&str> = "cifra dictionary install-defaults --reinstall".split_whitespace().collect();
+        let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
+            running_mode: Modes::Dictionary(DictionaryActions::InstallDefaults { reinstall: true })
+        };
+        let recovered_configuration = parse_arguments(&provided_args);
+        assert_eq!(expected_configuration, recovered_configuration);
+    }
+
+    #[test]
+    fn test_parser_dictionary_search(){
+        let provided_args: Vec<&str> = "cifra dictionary search klingon xyzz --cipherword".split_whitespace().collect();
+        let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
+            running_mode: Modes::Dictionary(DictionaryActions::Search {
+                dictionary_name: "klingon".to_string(),
+                query: "xyzz".to_string(),
+                cipherword: true
+            })
+        };
+        let recovered_configuration = parse_arguments(&provided_args);
+        assert_eq!(expected_configuration, recovered_configuration);
+    }
+
+    #[test]
+    fn test_format_dictionary_statistics() {
+        let statistics = DictionaryStatistics {
+            word_count: 2,
+            total_frequency: 3,
+            lengths: vec![(3, 2)],
+            patterns: vec![("0.1.2".to_string(), 2)],
+            letters: vec![('a', 2), ('t', 1)]
+        };
+        let output = format_dictionary_statistics(&statistics);
+        assert!(output.starts_with("Words: 2\nTotal frequency: 3\nLengths:\n"));
+        assert!(output.contains("  0.1.2 2\n"));
+        assert!(output.contains("  a          2  66.67% "));
+    }
+
+    #[test]
+    fn test_parser_database() {
+        let provided_args = "cifra dictionary list --database /tmp/klingon.sqlite".split_whitespace().collect();
+        let expected_configuration = Configuration {
+            database: Some(PathBuf::from("/tmp/klingon.sqlite")),
+            normalization: Normalization::Nfc,
+            languages: Vec::new(),
+            running_mode: Modes::Dictionary(DictionaryActions::List)
+        };
+        let recovered_configuration = parse_arguments(&provided_args);
+        assert_eq!(expected_configuration, recovered_configuration);
+        let provided_args = "cifra --database /tmp/klingon.sqlite dictionary list".split_whitespace().collect();
+        let recovered_configuration = parse_arguments(&provided_args);
+        assert_eq!(expected_configuration, recovered_configuration);
+    }
+
+    #[test]
+    fn test_parser_normalization() {
+        let provided_args = "cifra dictionary create spanish --normalization folded".split_whitespace().collect();
+        let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Folded,
+            languages: Vec::new(),
+            running_mode: Modes::Dictionary(DictionaryActions::Create {
+                dictionary_name: String::from("spanish"),
+                initial_words_file: None,
+                minimum_frequency: 1
+            })
+        };
+        let recovered_configuration = parse_arguments(&provided_args);
+        assert_eq!(expected_configuration, recovered_configuration);
+        assert_eq!(Normalization::Folded, recovered_configuration.get_context().normalization());
+    }
+
+    #[test]
+    fn test_parser_languages() {
+        let message_file = TestFile::new();
+        let command = format!("cifra attack caesar {} --language german --language spanish", message_file.path().to_str().unwrap());
+        let provided_args: Vec<&str> = command.split_whitespace().collect();
+        let expected_configuration = Configuration {
+            database: None,
+            normalization: Normalization::Nfc,
+            languages: vec![String::from("german"), String::from("spanish")],
+            running_mode: Modes::Attack {
+                algorithm: CipheringAlgorithms::Caesar,
+                charset: None,
+                deciphered_file: None,
+                file_to_attack: PathBuf::from(message_file.path().to_str().unwrap()),
+                output_recovered_key: false,
+                crib: None,
+                scorer: None,
+                segment: false
+            }
+        };
+        let recovered_configuration = parse_arguments(&provided_args);
+        assert_eq!(expected_configuration, recovered_configuration);
+        assert_eq!(vec![String::from("german"), String::from("spanish")], recovered_configuration.get_context().languages());
+    }
+
+    #[test]
+    fn test_normalize_text_to_cipher() {
+        assert_eq!("Cancion", normalize_text_to_cipher("Canción", Some(DEFAULT_CHARSET), Normalization::Folded));
+        assert_eq!("Canción", normalize_text_to_cipher("Canción", Some("abcdefghijklmnopqrstuvwxyzó"), Normalization::Folded));
+        assert_eq!("Cancion", normalize_text_to_cipher("Canción", None, Normalization::Folded));
+        assert_eq!("Canción", normalize_text_to_cipher("Cancio\u{301}n", Some(DEFAULT_CHARSET), Normalization::Nfc));
+    }
+
+    #[rstest]
+    fn test_dictionary_at_given_database(temp_dir: TestEnvironment) {
+        let database_path = temp_dir.path().join("klingon/cifra_database.sqlite");
+        let database_pathname = database_path.to_str().unwrap();
+        _main(format!("cifra --database {} dictionary create klingon", database_pathname).split_whitespace().collect());
+        assert!(database_path.exists());
+        let dictionaries = DatabaseStore::at(&database_path).get_dictionaries_names().unwrap();
+        assert_eq!(vec![String::from("klingon")], dictionaries);
+        _main(format!("cifra --database {} dictionary delete klingon", database_pathname).split_whitespace().collect());
+        let dictionaries = DatabaseStore::at(&database_path).get_dictionaries_names().unwrap();
+        assert!(dictionaries.is_empty());
+    }
+
     #[rstest]
     fn test_cipher_caesar(temp_dir: TestEnvironment, full_loaded_temp_dictionaries: LoadedDictionaries) {
         let message_file = TestFile::new();
@@ -1133,6 +2625,23 @@ mod tests {
         }
     }
 
+    #[rstest]
+    fn test_cipher_rot13(temp_dir: TestEnvironment) {
+        let message_file = TestFile::new();
+        write(message_file.path(), CAESAR_ORIGINAL_MESSAGE);
+        let output_file_name = temp_dir.path().join("ciphered_message.txt");
+        let provided_args = format!("cifra cipher rot13 {} --ciphered_file {}",
+                                    message_file.path().to_str().unwrap(),
+                                    output_file_name.to_str().unwrap());
+        let provided_args_vec: Vec<&str> = provided_args.split_whitespace().collect();
+        _main(provided_args_vec);
+        if let Ok(recovered_content) = read_to_string(&output_file_name){
+            assert_eq!("Guvf vf zl frperg zrffntr.", recovered_content)
+        } else {
+            assert!(false);
+        }
+    }
+
     #[rstest]
     fn test_cipher_substitution(temp_dir: TestEnvironment, full_loaded_temp_dictionaries: LoadedDictionaries){
         let message_file = TestFile::new();
@@ -1188,6 +2697,71 @@ mod tests {
         }
     }
 
+    #[rstest]
+    fn test_attack_caesar_with_crib(temp_dir: TestEnvironment){
+        let message_file = TestFile::new();
+        write(message_file.path(), CAESAR_CIPHERED_MESSAGE_KEY_13);
+        let output_file_name = temp_dir.path().join("recovered_message.txt");
+        let provided_args = format!("cifra attack caesar {} --deciphered_file {} --crib secret",
+                                    message_file.path().to_str().unwrap(),
+                                    output_file_name.to_str().unwrap());
+        let provided_args_vec: Vec<&str> = provided_args.split_whitespace().collect();
+        _main(provided_args_vec);
+        if let Ok(recovered_content) = read_to_string(&output_file_name){
+            assert_eq!(CAESAR_ORIGINAL_MESSAGE, recovered_content)
+        } else {
+            assert!(false);
+        }
+    }
+
+    #[rstest]
+    fn test_attack_vigenere_with_crib(temp_dir: TestEnvironment){
+        // Cipher and attack with default charsets, as users do.
+        let message_file = TestFile::new();
+        write(message_file.path(), CAESAR_ORIGINAL_MESSAGE);
+        let ciphered_file_name = temp_dir.path().join("ciphered_message.txt");
+        let provided_args = format!("cifra cipher vigenere key {} --ciphered_file {}",
+                                    message_file.path().to_str().unwrap(),
+                                    ciphered_file_name.to_str().unwrap());
+        let provided_args_vec: Vec<&str> = provided_args.split_whitespace().collect();
+        _main(provided_args_vec);
+        let output_file_name = temp_dir.path().join("recovered_message.txt");
+        let provided_args = format!("cifra attack vigenere {} --deciphered_file {} --crib secret",
+                                    ciphered_file_name.to_str().unwrap(),
+                                    output_file_name.to_str().unwrap());
+        let provided_args_vec: Vec<&str> = provided_args.split_whitespace().collect();
+        _main(provided_args_vec);
+        if let Ok(recovered_content) = read_to_string(&output_file_name){
+            assert_eq!(CAESAR_ORIGINAL_MESSAGE, recovered_content)
+        } else {
+            assert!(false);
+        }
+    }
+
+    #[rstest]
+    fn test_attack_vigenere_with_scorer(temp_dir: TestEnvironment){
+        // Cipher and attack with default charsets, as users do.
+        let message_file = env::current_dir().unwrap()
+            .join("resources/english_book_c1.txt");
+        let ciphered_file_name = temp_dir.path().join("ciphered_message.txt");
+        let provided_args = format!("cifra cipher vigenere pizza {} --ciphered_file {}",
+                                    message_file.to_str().unwrap(),
+                                    ciphered_file_name.to_str().unwrap());
+        let provided_args_vec: Vec<&str> = provided_args.split_whitespace().collect();
+        _main(provided_args_vec);
+        let output_file_name = temp_dir.path().join("recovered_message.txt");
+        let provided_args = format!("cifra attack vigenere {} --deciphered_file {} --scorer chi-squared",
+                                    ciphered_file_name.to_str().unwrap(),
+                                    output_file_name.to_str().unwrap());
+        let provided_args_vec: Vec<&str> = provided_args.split_whitespace().collect();
+        _main(provided_args_vec);
+        if let Ok(recovered_content) = read_to_string(&output_file_name){
+            assert_eq!(read_to_string(&message_file).unwrap(), recovered_content)
+        } else {
+            assert!(false);
+        }
+    }
+
     #[rstest]
     fn test_attack_caesar_with_recovered_key(temp_dir: TestEnvironment, full_loaded_temp_dictionaries: LoadedDictionaries){
         let message_file = TestFile::new();
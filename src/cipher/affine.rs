@@ -7,7 +7,7 @@ use rand;
 
 use crate::{Result, ErrorKind, ResultExt};
 use crate::attack::simple_attacks::Parameters;
-use crate::cipher::common::{offset_text, Ciphers, DEFAULT_CHARSET, get_key_parts};
+use crate::cipher::common::{offset_text, Ciphers, get_key_parts, get_key_from_parts};
 use crate::cipher::cryptomath::gcd;
 use rand::Rng;
 
@@ -88,7 +88,7 @@ impl Display for WrongAffineKey {
 //           U: AsRef<str> {
 pub fn cipher(text: &str, key: usize, charset: &str)-> Result<String> {
     validate_key(key, charset.len())?;
-    let ciphered_text = offset_text(text, key, true, &Ciphers::AFFINE, DEFAULT_CHARSET);
+    let ciphered_text = offset_text(text, key, true, &Ciphers::AFFINE, charset);
     ciphered_text
 }
 
@@ -114,6 +114,88 @@ pub fn decipher(ciphered_text: &str, key: usize, charset: &str)-> Result<String>
 }
 
 
+/// Cipher given text using Affine method and a key given as its parts.
+///
+/// # Parameters:
+/// * text: Text to be ciphered.
+/// * key: Secret key as a (multiplying key, adding key) tuple, that is (a, b) in
+///     E(x) = a*x + b formula.
+/// * charset: Charset used for Affine method substitution. Both ends, ciphering
+///     and deciphering, should use the same charset or original text won't be properly
+///     recovered.
+///
+/// # Returns:
+/// * Ciphered text.
+pub fn cipher_with_key_parts(text: &str, key: (usize, usize), charset: &str)-> Result<String> {
+    cipher(text, join_key_parts(key, charset.len())?, charset)
+}
+
+/// Decipher given text using Affine method and a key given as its parts.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * key: Secret key as a (multiplying key, adding key) tuple. It should be the same
+///     used to cipher.
+/// * charset: Charset used for Affine method substitutions. Both end should
+///     use the same charset or original text won't be properly recovered.
+///
+/// # Returns:
+/// * Deciphered text.
+pub fn decipher_with_key_parts(ciphered_text: &str, key: (usize, usize), charset: &str)-> Result<String> {
+    decipher(ciphered_text, join_key_parts(key, charset.len())?, charset)
+}
+
+/// Join Affine key parts in a single key, checking adding key fits in charset.
+///
+/// A too long adding key would overflow into the multiplying part once joined, so
+/// it would end up being a different key than the one given.
+///
+/// # Parameters:
+/// * key: Key as a (multiplying key, adding key) tuple.
+/// * charset_length: Length of charset used for Affine method substitutions.
+///
+/// # Returns:
+/// * Key as a single integer.
+fn join_key_parts(key: (usize, usize), charset_length: usize)-> Result<usize> {
+    let (multiplying_key, adding_key) = key;
+    let joined_key = get_key_from_parts(multiplying_key, adding_key, charset_length);
+    if adding_key >= charset_length {
+        bail!(ErrorKind::WrongAffineKeyError(
+            WrongAffineKey::new(joined_key, WrongAffineKeyCauses::AddingKeyTooLong, charset_length)
+            ));
+    }
+    Ok(joined_key)
+}
+
+/// Get an Affine key from its string representation.
+///
+/// Key can be given either as its parts, like "5,8", or as a single integer, like "138".
+///
+/// # Parameters:
+/// * key: Key string.
+/// * charset_length: Length of charset used for Affine method substitutions.
+///
+/// # Returns:
+/// * Key as a single integer.
+pub fn parse_key(key: &str, charset_length: usize)-> Result<usize> {
+    let key_parts: Vec<&str> = key.split(',').map(|part| part.trim()).collect();
+    match key_parts.as_slice() {
+        [single_key]=> single_key.parse::<usize>()
+            .chain_err(|| ErrorKind::KeyError(key.to_string(), "Key is not an integer.".to_string())),
+        [multiplying_key, adding_key]=> {
+            let multiplying_key = multiplying_key.parse::<usize>()
+                .chain_err(|| ErrorKind::KeyError(key.to_string(), "Multiplying key is not an integer.".to_string()))?;
+            let adding_key = adding_key.parse::<usize>()
+                .chain_err(|| ErrorKind::KeyError(key.to_string(), "Adding key is not an integer.".to_string()))?;
+            if adding_key >= charset_length {
+                bail!(ErrorKind::KeyError(key.to_string(), "Adding key must be smaller than charset length.".to_string()))
+            }
+            Ok(get_key_from_parts(multiplying_key, adding_key, charset_length))
+        },
+        _=> bail!(ErrorKind::KeyError(key.to_string(), "Key should be an integer or an a,b pair.".to_string()))
+    }
+}
+
 /// Call decipher function using a Parameters type.
 ///
 /// You probably wont use this function. It's used by brute force attacks instead.
@@ -161,6 +243,20 @@ pub fn get_random_key<T>(charset: T)-> Result<usize>
     }
 }
 
+/// Get Affine key that makes this cipher behave as Atbash.
+///
+/// Atbash reverses charset, that is what you get with charset length minus one
+/// as both multiplying and adding keys.
+///
+/// # Parameters:
+/// * charset_length: Length of charset you are going to use to cipher.
+///
+/// # Returns:
+/// * Atbash Affine key for given charset length.
+pub fn atbash_key(charset_length: usize)-> usize {
+    (charset_length - 1) * charset_length + (charset_length - 1)
+}
+
 /// Check if given key is good for Affine cipher using this charset.
 ///
 /// Not every key is good to cipher using Affine with a given charset. It must
@@ -219,6 +315,27 @@ mod tests {
         assert_eq!(ORIGINAL_MESSAGE, deciphered_text);
     }
 
+    #[test]
+    fn test_key_parts() {
+        let key_parts = get_key_parts(TEST_KEY, DEFAULT_CHARSET.len());
+        assert_eq!(key_parts, (43, 56));
+        assert_eq!(cipher_with_key_parts(ORIGINAL_MESSAGE, key_parts, DEFAULT_CHARSET).unwrap(), CIPHERED_MESSAGE_KEY_2894);
+        assert_eq!(decipher_with_key_parts(CIPHERED_MESSAGE_KEY_2894, key_parts, DEFAULT_CHARSET).unwrap(), ORIGINAL_MESSAGE);
+        // (42, 122) would silently join into the same key than (43, 56).
+        let too_long_adding_key = (42, 56 + DEFAULT_CHARSET.len());
+        assert!(cipher_with_key_parts(ORIGINAL_MESSAGE, too_long_adding_key, DEFAULT_CHARSET).is_err());
+        assert!(decipher_with_key_parts(CIPHERED_MESSAGE_KEY_2894, too_long_adding_key, DEFAULT_CHARSET).is_err());
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("43,56", DEFAULT_CHARSET.len()).unwrap(), TEST_KEY);
+        assert_eq!(parse_key("2894", DEFAULT_CHARSET.len()).unwrap(), TEST_KEY);
+        assert!(parse_key("43,66", DEFAULT_CHARSET.len()).is_err());
+        assert!(parse_key("43,b", DEFAULT_CHARSET.len()).is_err());
+        assert!(parse_key("1,2,3", DEFAULT_CHARSET.len()).is_err());
+    }
+
     #[test]
     fn test_get_random_key() {
         let test_string = random_string(10);
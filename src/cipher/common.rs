@@ -3,6 +3,7 @@ use std::convert::TryInto;
 use std::ops::Add;
 // use crate::FromStr;
 use crate::cipher::cryptomath::{modulus, find_mod_inverse};
+use crate::cipher::normalization::{Normalization, normalize_unicode};
 // use std::error::Error;
 // use std::fmt;
 // use std::fmt::Formatter;
@@ -136,8 +137,28 @@ pub fn get_key_parts(key: usize, charset_length: usize)-> (usize, usize){
     (multiplying_key, adding_key)
 }
 
+/// Join Affine key parts in a single key.
+///
+/// This is the inverse of *get_key_parts*.
+///
+/// # Parameters:
+/// * multiplying_key: Key used for multiplying while ciphering.
+/// * adding_key: Key used for adding while ciphering.
+/// * charset_length: Length of charset used for Affine method substitutions.
+///
+/// # Returns:
+/// * Single key that decomposes in given parts.
+pub fn get_key_from_parts(multiplying_key: usize, adding_key: usize, charset_length: usize)-> usize {
+    multiplying_key * charset_length + adding_key
+}
+
+/// Regex to find words at lowercased texts: letter sequences without digits nor underscores.
+pub const WORDS_REGEX: &'static str = r"[^\W\d_]+";
+
 /// Get a list of lowercase words from text without any punctuation marks.
 ///
+/// Text is normalized to NFC first, so composed and decomposed characters give the same words.
+///
 /// # Parameters:
 /// * text: Text to extract words from.
 ///
@@ -145,11 +166,24 @@ pub fn get_key_parts(key: usize, charset_length: usize)-> (usize, usize){
 /// * A list with all text words in text with lowercased and without any punctuation mark.
 pub fn normalize_text<T>(text: T) -> Vec<String>
     where T: AsRef<str> {
-    let mut lowercase_text = text.as_ref().to_lowercase();
+    normalize_text_with(text, Normalization::default())
+}
+
+/// Get a list of lowercase words from text, normalized as requested, without any punctuation marks.
+///
+/// # Parameters:
+/// * text: Text to extract words from.
+/// * normalization: Unicode normalization to apply to text before extracting its words.
+///
+/// # Returns:
+/// * A list with all text words in text with lowercased and without any punctuation mark.
+pub fn normalize_text_with<T>(text: T, normalization: Normalization) -> Vec<String>
+    where T: AsRef<str> {
+    let mut lowercase_text = normalize_unicode(text, normalization).to_lowercase();
     // Line breaks are troublesome for further assesment so we remove it.
     lowercase_text = lowercase_text.replace("\n", " ");
     lowercase_text = lowercase_text.replace("\r", " ");
-    let re = Regex::new(r"[^\W\d_]+")
+    let re = Regex::new(WORDS_REGEX)
         .expect("Invalid regex to search for normalized words.");
     let mut words_list: Vec<String> = Vec::new();
     for _word in re.find_iter(&lowercase_text) {
@@ -240,6 +274,12 @@ with this eBook or online at 2020";
         assert_eq!(returned_list, expected_list);
     }
 
+    #[test]
+    fn test_normalize_text_with() {
+        assert_eq!(normalize_text("Cafe\u{301} crème"), normalize_text("Café crème"));
+        assert_eq!(vec!["cafe", "creme", "strasse"], normalize_text_with("Café crème, Straße.", Normalization::Folded));
+    }
+
     #[test]
     fn test_counter_char() {
         let text = "aaabbccd";
@@ -0,0 +1,559 @@
+/// Library to cipher and decipher texts using an Enigma machine simulator.
+///
+/// Simulated machines are the Wehrmacht three rotors Enigma I and the Kriegsmarine
+/// four rotors M4. Both of them use:
+///
+/// * A plugboard that swaps pairs of letters before and after going through rotors.
+/// * Three rotors (M4 adds a fourth, thinner, greek rotor at their left) chosen from
+///     the historical rotor set. Every rotor has a ring setting and a start position.
+/// * A reflector that sends current back through rotors.
+///
+/// Rightmost rotor steps at every key press. Middle and left rotors step when rotor
+/// at their right reaches a notch. Middle rotor steps again with left one when it
+/// reaches its own notch, which is the famous double stepping anomaly. M4 greek rotor
+/// never steps.
+///
+/// Enigma only knew about 26 latin letters, so any other character is kept as is and
+/// does not make rotors step. Caps are kept too.
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+
+use crate::{ErrorKind, Result};
+
+/// Letters Enigma machines were able to cipher.
+pub const CHARSET: &'static str = "abcdefghijklmnopqrstuvwxyz";
+const CHARSET_LENGTH: usize = 26;
+
+/// Historical Enigma rotors.
+///
+/// Rotors I to V were used by every branch, VI to VIII were Kriegsmarine only and
+/// Beta and Gamma were the thin greek rotors of M4.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Rotor {
+    I,
+    II,
+    III,
+    IV,
+    V,
+    VI,
+    VII,
+    VIII,
+    Beta,
+    Gamma
+}
+
+impl Rotor {
+    /// Every regular rotor, that is every rotor but greek ones.
+    pub const REGULAR: [Rotor; 8] = [Rotor::I, Rotor::II, Rotor::III, Rotor::IV, Rotor::V,
+                                     Rotor::VI, Rotor::VII, Rotor::VIII];
+
+    /// Letter every contact is wired to, with ring setting at A.
+    fn wiring(&self) -> &'static str {
+        match self {
+            Rotor::I => "ekmflgdqvzntowyhxuspaibrcj",
+            Rotor::II => "ajdksiruxblhwtmcqgznpyfvoe",
+            Rotor::III => "bdfhjlcprtxvznyeiwgakmusqo",
+            Rotor::IV => "esovpzjayquirhxlnftgkdcmwb",
+            Rotor::V => "vzbrgityupsdnhlxawmjqofeck",
+            Rotor::VI => "jpgvoumfyqbenhzrdkasxlictw",
+            Rotor::VII => "nzjhgrcxmyswboufaivlpekqdt",
+            Rotor::VIII => "fkqhtlxocbjspdzramewniuygv",
+            Rotor::Beta => "leyjvcnixwpbqmdrtakzgfuhos",
+            Rotor::Gamma => "fsokanuerhmbtiycwlqpzxvgjd"
+        }
+    }
+
+    /// Letters shown at window when this rotor makes its left neighbour step.
+    fn notches(&self) -> &'static str {
+        match self {
+            Rotor::I => "q",
+            Rotor::II => "e",
+            Rotor::III => "v",
+            Rotor::IV => "j",
+            Rotor::V => "z",
+            Rotor::VI | Rotor::VII | Rotor::VIII => "zm",
+            Rotor::Beta | Rotor::Gamma => ""
+        }
+    }
+
+    /// Whether this is a thin M4 greek rotor.
+    pub fn is_greek(&self) -> bool {
+        matches!(self, Rotor::Beta | Rotor::Gamma)
+    }
+}
+
+impl Display for Rotor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl TryFrom<&str> for Rotor {
+    type Error = crate::Error;
+
+    /// Get a rotor from its name.
+    ///
+    /// # Parameters:
+    /// * value: Rotor name, like "IV" or "beta". Case is ignored.
+    ///
+    /// # Returns:
+    /// * OK with rotor or Err if provided name is not a known rotor.
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "i" => Ok(Rotor::I),
+            "ii" => Ok(Rotor::II),
+            "iii" => Ok(Rotor::III),
+            "iv" => Ok(Rotor::IV),
+            "v" => Ok(Rotor::V),
+            "vi" => Ok(Rotor::VI),
+            "vii" => Ok(Rotor::VII),
+            "viii" => Ok(Rotor::VIII),
+            "beta" => Ok(Rotor::Beta),
+            "gamma" => Ok(Rotor::Gamma),
+            _ => bail!(ErrorKind::KeyError(value.to_string(), "Unknown rotor.".to_string()))
+        }
+    }
+}
+
+/// Historical Enigma reflectors.
+///
+/// Thin reflectors were used only with M4 and its greek rotors.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Reflector {
+    B,
+    C,
+    BThin,
+    CThin
+}
+
+impl Reflector {
+    fn wiring(&self) -> &'static str {
+        match self {
+            Reflector::B => "yruhqsldpxngokmiebfzcwvjat",
+            Reflector::C => "fvpjiaoyedrzxwgctkuqsbnmhl",
+            Reflector::BThin => "enkqauywjicopblmdxzvfthrgs",
+            Reflector::CThin => "rdobjntkvehmlfcwzaxgyipsuq"
+        }
+    }
+
+    /// Whether this is a thin M4 reflector.
+    pub fn is_thin(&self) -> bool {
+        matches!(self, Reflector::BThin | Reflector::CThin)
+    }
+}
+
+impl Display for Reflector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl TryFrom<&str> for Reflector {
+    type Error = crate::Error;
+
+    /// Get a reflector from its name.
+    ///
+    /// # Parameters:
+    /// * value: Reflector name: "B", "C", "BThin" or "CThin". Case is ignored.
+    ///
+    /// # Returns:
+    /// * OK with reflector or Err if provided name is not a known reflector.
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "b" => Ok(Reflector::B),
+            "c" => Ok(Reflector::C),
+            "bthin" => Ok(Reflector::BThin),
+            "cthin" => Ok(Reflector::CThin),
+            _ => bail!(ErrorKind::KeyError(value.to_string(), "Unknown reflector.".to_string()))
+        }
+    }
+}
+
+/// Enigma daily key.
+///
+/// Rotors, ring settings and positions are given from left to right. Ring settings and
+/// positions are charset indexes, so 0 is A, 1 is B and so on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnigmaKey {
+    pub reflector: Reflector,
+    pub rotors: Vec<Rotor>,
+    pub ring_settings: Vec<usize>,
+    pub positions: Vec<usize>,
+    pub plugboard: Vec<(char, char)>
+}
+
+impl EnigmaKey {
+    /// Create an Enigma key.
+    ///
+    /// # Parameters:
+    /// * reflector: Reflector to use.
+    /// * rotors: Rotors from left to right. Three regular rotors or, with a thin
+    ///     reflector, a greek rotor followed by three regular ones.
+    /// * ring_settings: Ring setting for every rotor, from left to right.
+    /// * positions: Start position for every rotor, from left to right.
+    /// * plugboard: Pairs of letters swapped by plugboard.
+    ///
+    /// # Returns:
+    /// * An Enigma key, or an error if given settings can not be set at a real machine.
+    pub fn new(reflector: Reflector, rotors: &[Rotor], ring_settings: &[usize],
+               positions: &[usize], plugboard: &[(char, char)]) -> Result<Self> {
+        let key = EnigmaKey {
+            reflector,
+            rotors: rotors.to_vec(),
+            ring_settings: ring_settings.to_vec(),
+            positions: positions.to_vec(),
+            plugboard: plugboard.iter()
+                .map(|(first, second)| (first.to_ascii_lowercase(), second.to_ascii_lowercase()))
+                .collect()
+        };
+        key.validate()?;
+        Ok(key)
+    }
+
+    /// Create a key from its string representation.
+    ///
+    /// String representation is "REFLECTOR,ROTORS,RINGS,POSITIONS,PLUGBOARD", where rotors
+    /// are separated by dashes, ring settings and positions are given as letters and
+    /// plugboard is a dash separated list of letter pairs. Plugboard can be omitted.
+    /// For instance: "B,I-II-III,AAA,AQT,AB-CD-EF".
+    ///
+    /// # Parameters:
+    /// * key_string: Key string representation.
+    ///
+    /// # Returns:
+    /// * An Enigma key.
+    pub fn from_key_string<T>(key_string: T) -> Result<Self>
+        where T: AsRef<str> {
+        let key_error = |message: &str| ErrorKind::KeyError(key_string.as_ref().to_string(), message.to_string());
+        let parts: Vec<&str> = key_string.as_ref().split(',').map(|part| part.trim()).collect();
+        if parts.len() != 4 && parts.len() != 5 {
+            bail!(key_error("Enigma keys should be written as REFLECTOR,ROTORS,RINGS,POSITIONS,PLUGBOARD"))
+        }
+        let reflector = Reflector::try_from(parts[0])?;
+        let rotors = parts[1].split('-')
+            .map(Rotor::try_from)
+            .collect::<Result<Vec<Rotor>>>()?;
+        let ring_settings = letters_to_indexes(parts[2])
+            .ok_or_else(|| key_error("Ring settings should be written as letters."))?;
+        let positions = letters_to_indexes(parts[3])
+            .ok_or_else(|| key_error("Positions should be written as letters."))?;
+        let mut plugboard = Vec::new();
+        if let Some(plugboard_string) = parts.get(4).filter(|plugboard_string| !plugboard_string.is_empty()) {
+            for pair in plugboard_string.split('-') {
+                let letters: Vec<char> = pair.chars().collect();
+                if letters.len() != 2 {
+                    bail!(key_error("Plugboard should be written as dash separated letter pairs."))
+                }
+                plugboard.push((letters[0], letters[1]));
+            }
+        }
+        Self::new(reflector, &rotors, &ring_settings, &positions, &plugboard)
+    }
+
+    /// Check these settings can be set at a real machine.
+    fn validate(&self) -> Result<()> {
+        let key_error = |message: &str| ErrorKind::KeyError(self.to_string(), message.to_string());
+        let regular_rotors = match (self.reflector.is_thin(), self.rotors.as_slice()) {
+            (false, [_, _, _]) => &self.rotors[..],
+            (true, [greek_rotor, regular_rotors @ ..]) if regular_rotors.len() == 3 => {
+                if !greek_rotor.is_greek() {
+                    bail!(key_error("Leftmost rotor of a four rotors machine should be Beta or Gamma."))
+                }
+                regular_rotors
+            },
+            (false, _) => bail!(key_error("B and C reflectors need three rotors.")),
+            (true, _) => bail!(key_error("Thin reflectors need a greek rotor and three regular rotors."))
+        };
+        if regular_rotors.iter().any(|rotor| rotor.is_greek()) {
+            bail!(key_error("Greek rotors can only be used at the leftmost slot of a four rotors machine."))
+        }
+        for (i, rotor) in regular_rotors.iter().enumerate() {
+            if regular_rotors[i + 1..].contains(rotor) {
+                bail!(key_error("A rotor can not be used twice."))
+            }
+        }
+        if self.ring_settings.len() != self.rotors.len() || self.positions.len() != self.rotors.len() {
+            bail!(key_error("There should be a ring setting and a position for every rotor."))
+        }
+        if self.ring_settings.iter().chain(self.positions.iter()).any(|&index| index >= CHARSET_LENGTH) {
+            bail!(key_error("Ring settings and positions should be charset indexes."))
+        }
+        let mut plugged_letters: Vec<char> = Vec::new();
+        for &(first, second) in self.plugboard.iter() {
+            for letter in [first, second].iter() {
+                if !CHARSET.contains(*letter) || plugged_letters.contains(letter) {
+                    bail!(key_error("Every plugboard letter should be a charset letter used only once."))
+                }
+                plugged_letters.push(*letter);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for EnigmaKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let rotors: Vec<String> = self.rotors.iter().map(|rotor| rotor.to_string().to_uppercase()).collect();
+        let plugboard: Vec<String> = self.plugboard.iter()
+            .map(|(first, second)| format!("{}{}", first, second).to_uppercase())
+            .collect();
+        write!(f, "{},{},{},{}", self.reflector.to_string().to_uppercase(), rotors.join("-"),
+               indexes_to_letters(&self.ring_settings), indexes_to_letters(&self.positions))?;
+        if !plugboard.is_empty() {
+            write!(f, ",{}", plugboard.join("-"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Convert a string of letters into charset indexes.
+///
+/// # Returns:
+/// * Charset indexes or None if any character is not a charset letter.
+fn letters_to_indexes(letters: &str) -> Option<Vec<usize>> {
+    letters.to_lowercase().chars()
+        .map(|letter| CHARSET.chars().position(|x| x == letter))
+        .collect()
+}
+
+fn indexes_to_letters(indexes: &[usize]) -> String {
+    indexes.iter()
+        .map(|&index| (b'A' + index as u8) as char)
+        .collect()
+}
+
+/// Enigma machine state.
+///
+/// Rotors wirings are precalculated as index tables, both for current going through
+/// rotors towards the reflector and back, because attacks need to decipher the same
+/// text with many different settings.
+#[derive(Debug, Clone)]
+pub(crate) struct Machine {
+    forward_wirings: Vec<[usize; CHARSET_LENGTH]>,
+    backward_wirings: Vec<[usize; CHARSET_LENGTH]>,
+    notches: Vec<[bool; CHARSET_LENGTH]>,
+    reflector: [usize; CHARSET_LENGTH],
+    plugboard: [usize; CHARSET_LENGTH],
+    ring_settings: Vec<usize>,
+    positions: Vec<usize>
+}
+
+impl Machine {
+    /// Set up a machine with given key.
+    pub(crate) fn new(key: &EnigmaKey) -> Self {
+        let mut forward_wirings = Vec::new();
+        let mut backward_wirings = Vec::new();
+        let mut notches = Vec::new();
+        for rotor in key.rotors.iter() {
+            let forward_wiring = wiring_to_indexes(rotor.wiring());
+            let mut backward_wiring = [0; CHARSET_LENGTH];
+            forward_wiring.iter().enumerate()
+                .for_each(|(input, &output)| backward_wiring[output] = input);
+            let mut rotor_notches = [false; CHARSET_LENGTH];
+            // Unwrap is safe because every notch is a charset letter.
+            rotor.notches().chars()
+                .for_each(|notch| rotor_notches[CHARSET.chars().position(|x| x == notch).unwrap()] = true);
+            forward_wirings.push(forward_wiring);
+            backward_wirings.push(backward_wiring);
+            notches.push(rotor_notches);
+        }
+        let mut machine = Machine {
+            forward_wirings,
+            backward_wirings,
+            notches,
+            reflector: wiring_to_indexes(key.reflector.wiring()),
+            plugboard: [0; CHARSET_LENGTH],
+            ring_settings: key.ring_settings.clone(),
+            positions: key.positions.clone()
+        };
+        let plugboard: Vec<(usize, usize)> = key.plugboard.iter()
+            // Unwraps are safe because keys are validated at creation.
+            .map(|(first, second)| (CHARSET.chars().position(|x| x == *first).unwrap(),
+                                    CHARSET.chars().position(|x| x == *second).unwrap()))
+            .collect();
+        machine.set_plugboard(&plugboard);
+        machine
+    }
+
+    /// Replace plugboard wiring.
+    ///
+    /// # Parameters:
+    /// * pairs: Pairs of charset indexes to swap.
+    pub(crate) fn set_plugboard(&mut self, pairs: &[(usize, usize)]) {
+        (0..CHARSET_LENGTH).for_each(|letter| self.plugboard[letter] = letter);
+        for &(first, second) in pairs {
+            self.plugboard[first] = second;
+            self.plugboard[second] = first;
+        }
+    }
+
+    /// Move rotors one step, as it happened every time a key was pressed.
+    fn step(&mut self) {
+        let right = self.positions.len() - 1;
+        let middle = right - 1;
+        let left = middle - 1;
+        if self.notches[middle][self.positions[middle]] {
+            // Double stepping: middle rotor steps again together with left one.
+            self.positions[middle] = (self.positions[middle] + 1) % CHARSET_LENGTH;
+            self.positions[left] = (self.positions[left] + 1) % CHARSET_LENGTH;
+        } else if self.notches[right][self.positions[right]] {
+            self.positions[middle] = (self.positions[middle] + 1) % CHARSET_LENGTH;
+        }
+        self.positions[right] = (self.positions[right] + 1) % CHARSET_LENGTH;
+    }
+
+    /// Step rotors and get the letter lit when given letter key is pressed.
+    ///
+    /// # Parameters:
+    /// * letter: Charset index of pressed key.
+    ///
+    /// # Returns:
+    /// * Charset index of lit letter.
+    fn press(&mut self, letter: usize) -> usize {
+        self.step();
+        let mut current = self.plugboard[letter];
+        for rotor in (0..self.positions.len()).rev() {
+            current = self.through_rotor(current, rotor, true);
+        }
+        current = self.reflector[current];
+        for rotor in 0..self.positions.len() {
+            current = self.through_rotor(current, rotor, false);
+        }
+        self.plugboard[current]
+    }
+
+    fn through_rotor(&self, letter: usize, rotor: usize, forward: bool) -> usize {
+        let shift = (self.positions[rotor] + CHARSET_LENGTH - self.ring_settings[rotor]) % CHARSET_LENGTH;
+        let contact = (letter + shift) % CHARSET_LENGTH;
+        let wired_contact = if forward {
+            self.forward_wirings[rotor][contact]
+        } else {
+            self.backward_wirings[rotor][contact]
+        };
+        (wired_contact + CHARSET_LENGTH - shift) % CHARSET_LENGTH
+    }
+
+    /// Cipher a text already converted to charset indexes.
+    ///
+    /// # Parameters:
+    /// * letters: Charset indexes of text letters.
+    ///
+    /// # Returns:
+    /// * Charset indexes of ciphered letters.
+    pub(crate) fn process_indexes(&mut self, letters: &[usize]) -> Vec<usize> {
+        letters.iter().map(|&letter| self.press(letter)).collect()
+    }
+}
+
+fn wiring_to_indexes(wiring: &str) -> [usize; CHARSET_LENGTH] {
+    let mut indexes = [0; CHARSET_LENGTH];
+    wiring.chars().enumerate()
+        // Unwrap is safe because every wiring letter is a charset letter.
+        .for_each(|(i, letter)| indexes[i] = CHARSET.chars().position(|x| x == letter).unwrap());
+    indexes
+}
+
+/// Cipher given text using Enigma machine.
+///
+/// Only latin letters are ciphered. Every other character is kept as is.
+///
+/// # Parameters:
+/// * text: Text to be ciphered.
+/// * key: Machine settings. Both ends should know this and use the same one.
+///
+/// # Returns:
+/// * Ciphered text.
+pub fn cipher(text: &str, key: &EnigmaKey) -> Result<String> {
+    key.validate()?;
+    let mut machine = Machine::new(key);
+    let mut ciphered_text = String::new();
+    for char in text.chars() {
+        let lowercase_char = char.to_ascii_lowercase();
+        if let Some(letter) = CHARSET.chars().position(|x| x == lowercase_char) {
+            let ciphered_char = (b'a' + machine.press(letter) as u8) as char;
+            if char.is_uppercase() {
+                ciphered_text.push(ciphered_char.to_ascii_uppercase());
+            } else {
+                ciphered_text.push(ciphered_char);
+            }
+        } else {
+            ciphered_text.push(char);
+        }
+    }
+    Ok(ciphered_text)
+}
+
+/// Decipher given text using Enigma machine.
+///
+/// Enigma is reciprocal, so deciphering is just ciphering again with the same settings.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * key: Machine settings. Both ends should know this and use the same one.
+///
+/// # Returns:
+/// * Deciphered text.
+pub fn decipher(ciphered_text: &str, key: &EnigmaKey) -> Result<String> {
+    cipher(ciphered_text, key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINAL_MESSAGE: &'static str = "Common sense is not so common.";
+
+    fn default_key() -> EnigmaKey {
+        EnigmaKey::new(Reflector::B, &[Rotor::I, Rotor::II, Rotor::III], &[0, 0, 0], &[0, 0, 0], &[]).unwrap()
+    }
+
+    #[test]
+    fn test_cipher_known_vectors() {
+        assert_eq!(cipher("AAAAA", &default_key()).unwrap(), "BDZGO");
+        let key = EnigmaKey::from_key_string("B,I-II-III,BBB,AAA").unwrap();
+        assert_eq!(cipher("AAAAA", &key).unwrap(), "EWTYX");
+    }
+
+    #[test]
+    fn test_double_stepping() {
+        let key = EnigmaKey::new(Reflector::B, &[Rotor::I, Rotor::II, Rotor::III], &[0, 0, 0], &[0, 3, 20], &[]).unwrap();
+        let mut machine = Machine::new(&key);
+        let expected_positions = ["adv", "aew", "bfx", "bfy"];
+        for expected_position in expected_positions.iter() {
+            machine.step();
+            assert_eq!(indexes_to_letters(&machine.positions).to_lowercase(), *expected_position);
+        }
+    }
+
+    #[test]
+    fn test_m4_with_beta_at_a_is_enigma_i() {
+        let m4_key = EnigmaKey::from_key_string("BThin,Beta-I-II-III,AAAA,AAAA,AZ-BY").unwrap();
+        let m3_key = EnigmaKey::from_key_string("B,I-II-III,AAA,AAA,AZ-BY").unwrap();
+        assert_eq!(cipher(ORIGINAL_MESSAGE, &m4_key).unwrap(), cipher(ORIGINAL_MESSAGE, &m3_key).unwrap());
+    }
+
+    #[test]
+    fn test_cipher_decipher() {
+        let key = EnigmaKey::from_key_string("C,VIII-II-V,FOX,RAT,AB-CD-EF-GH").unwrap();
+        let ciphered_text = cipher(ORIGINAL_MESSAGE, &key).unwrap();
+        assert_ne!(ciphered_text, ORIGINAL_MESSAGE);
+        assert_eq!(decipher(&ciphered_text, &key).unwrap(), ORIGINAL_MESSAGE);
+    }
+
+    #[test]
+    fn test_key_string() {
+        let key_string = "CTHIN,GAMMA-VI-VII-VIII,ABCD,WXYZ,QW-ER";
+        let key = EnigmaKey::from_key_string(key_string).unwrap();
+        assert_eq!(key.rotors, vec![Rotor::Gamma, Rotor::VI, Rotor::VII, Rotor::VIII]);
+        assert_eq!(key.to_string(), key_string);
+    }
+
+    #[test]
+    fn test_wrong_keys_are_detected() {
+        assert!(EnigmaKey::from_key_string("B,I-I-III,AAA,AAA").is_err());
+        assert!(EnigmaKey::from_key_string("B,Beta-I-II-III,AAAA,AAAA").is_err());
+        assert!(EnigmaKey::from_key_string("BThin,I-II-III,AAA,AAA").is_err());
+        assert!(EnigmaKey::from_key_string("B,I-II-III,AAA,AAA,AB-BC").is_err());
+        assert!(EnigmaKey::from_key_string("B,I-II-III,AA,AAA").is_err());
+    }
+}
@@ -0,0 +1,335 @@
+/// Library to cipher and decipher texts using homophonic substitution method.
+///
+/// Whereas simple substitution maps every plaintext letter to a single cipher symbol,
+/// homophonic substitution gives every letter several symbols (its homophones). Frequent
+/// letters get more homophones than rare ones, so symbol frequencies get flattened and
+/// frequency analysis gets much harder.
+///
+/// Cipher symbols are taken from a symbol set, that can be a set of characters (like
+/// digits or the glyphs of a Zodiac-style puzzle) or fixed width numeric codes (like
+/// two digits codes from 00 to 99).
+///
+/// Like most homophonic puzzles, only charset letters are ciphered. Spaces, punctuation
+/// marks and caps are lost, so deciphered text is a lowercase letter stream.
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use rand::seq::SliceRandom;
+
+use crate::{ErrorKind, Result};
+
+pub const DEFAULT_CHARSET: &'static str = "abcdefghijklmnopqrstuvwxyz";
+/// Longest numeric codes allowed. Longer ones would make symbol sets of millions of symbols.
+const MAX_CODE_WIDTH: usize = 4;
+
+/// Symbols available to cipher letters.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolSet {
+    /// Every character of given string is a symbol, for instance "0123456789".
+    Chars(String),
+    /// Numeric codes of given width, zero padded. For instance, 2 gives codes from 00 to 99.
+    Codes(usize)
+}
+
+impl SymbolSet {
+    /// Get every symbol of this set.
+    pub fn symbols(&self) -> Vec<String> {
+        match self {
+            SymbolSet::Chars(chars) => {
+                let mut symbols: Vec<String> = Vec::new();
+                for char in chars.chars().map(|char| char.to_string()) {
+                    if !symbols.contains(&char) {
+                        symbols.push(char);
+                    }
+                }
+                symbols
+            },
+            SymbolSet::Codes(width) => (0..10_usize.pow(*width as u32))
+                .map(|code| format!("{:0width$}", code, width = width))
+                .collect()
+        }
+    }
+
+    /// Split a ciphered text into its symbols.
+    ///
+    /// Characters that can not be part of a symbol, like spaces, are ignored.
+    ///
+    /// # Parameters:
+    /// * ciphered_text: Text to split.
+    ///
+    /// # Returns:
+    /// * List of symbols found at text.
+    pub fn split<T>(&self, ciphered_text: T) -> Vec<String>
+        where T: AsRef<str> {
+        match self {
+            SymbolSet::Chars(chars) => ciphered_text.as_ref().chars()
+                .filter(|char| chars.contains(*char))
+                .map(|char| char.to_string())
+                .collect(),
+            SymbolSet::Codes(width) => {
+                let digits: Vec<char> = ciphered_text.as_ref().chars()
+                    .filter(|char| char.is_ascii_digit())
+                    .collect();
+                digits.chunks(*width)
+                    .map(|code| code.iter().collect())
+                    .collect()
+            }
+        }
+    }
+
+    /// Join symbols into a ciphered text.
+    ///
+    /// Character symbols are written together while numeric codes are separated
+    /// with spaces to make them readable.
+    fn join(&self, symbols: &[&str]) -> String {
+        match self {
+            SymbolSet::Chars(_) => symbols.concat(),
+            SymbolSet::Codes(_) => symbols.join(" ")
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        match self {
+            SymbolSet::Chars(chars) if chars.is_empty() =>
+                bail!("Symbol set needs at least a character."),
+            SymbolSet::Codes(width) if *width == 0 || *width > MAX_CODE_WIDTH =>
+                bail!(format!("Numeric codes width should be between 1 and {}.", MAX_CODE_WIDTH)),
+            _ => Ok(())
+        }
+    }
+}
+
+/// Key for homophonic substitution.
+///
+/// Homophones are stored in charset order, so the first list are the symbols for the
+/// first charset letter and so on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HomophonicKey {
+    pub symbol_set: SymbolSet,
+    pub charset: String,
+    pub homophones: Vec<Vec<String>>
+}
+
+impl HomophonicKey {
+    /// Create a homophonic key.
+    ///
+    /// # Parameters:
+    /// * symbol_set: Symbols available to cipher.
+    /// * charset: Letters to be ciphered.
+    /// * homophones: Symbols for every charset letter, in charset order. No symbol can be
+    ///     used for two letters. A letter with no symbols can be deciphered but not ciphered,
+    ///     which is what happens to keys recovered from texts where that letter is missing.
+    ///
+    /// # Returns:
+    /// * A homophonic key.
+    pub fn new<T>(symbol_set: SymbolSet, charset: T, homophones: Vec<Vec<String>>) -> Result<Self>
+        where T: AsRef<str> {
+        let key = HomophonicKey {
+            symbol_set,
+            charset: charset.as_ref().to_lowercase(),
+            homophones
+        };
+        key.validate()?;
+        Ok(key)
+    }
+
+    /// Create a random key with homophones allotted according to letter frequencies.
+    ///
+    /// Every letter gets at least a symbol and remaining symbols are given to letters
+    /// in proportion to their frequency at sample text, so ciphered symbols end up
+    /// with a flat distribution.
+    ///
+    /// # Parameters:
+    /// * symbol_set: Symbols available to cipher. It should have at least as many
+    ///     symbols as charset letters.
+    /// * charset: Letters to be ciphered.
+    /// * sample_text: Text in the language to be ciphered, to get letter frequencies from.
+    ///
+    /// # Returns:
+    /// * A random homophonic key.
+    pub fn generate<T, U>(symbol_set: SymbolSet, charset: T, sample_text: U) -> Result<Self>
+        where T: AsRef<str>,
+              U: AsRef<str> {
+        symbol_set.validate()?;
+        let charset_chars: Vec<char> = charset.as_ref().to_lowercase().chars().collect();
+        let mut symbols = symbol_set.symbols();
+        if symbols.len() < charset_chars.len() {
+            bail!("Symbol set should have at least as many symbols as charset letters.")
+        }
+        symbols.shuffle(&mut rand::thread_rng());
+        let mut counts = vec![0_usize; charset_chars.len()];
+        for char in sample_text.as_ref().to_lowercase().chars() {
+            if let Some(position) = charset_chars.iter().position(|&x| x == char) {
+                counts[position] += 1;
+            }
+        }
+        let total_letters: usize = counts.iter().sum::<usize>().max(1);
+        let spare_symbols = symbols.len() - charset_chars.len();
+        let mut allotments: Vec<usize> = counts.iter()
+            .map(|count| 1 + count * spare_symbols / total_letters)
+            .collect();
+        // Rounding leftovers go to the most frequent letters.
+        let mut letters_by_frequency: Vec<usize> = (0..charset_chars.len()).collect();
+        letters_by_frequency.sort_by_key(|&letter| std::cmp::Reverse(counts[letter]));
+        let mut leftover = symbols.len() - allotments.iter().sum::<usize>();
+        for &letter in letters_by_frequency.iter().cycle() {
+            if leftover == 0 {
+                break;
+            }
+            allotments[letter] += 1;
+            leftover -= 1;
+        }
+        let mut symbols_iter = symbols.into_iter();
+        let homophones: Vec<Vec<String>> = allotments.iter()
+            .map(|&allotment| symbols_iter.by_ref().take(allotment).collect())
+            .collect();
+        Self::new(symbol_set, charset, homophones)
+    }
+
+    /// Get a map from every key symbol to the charset letter it stands for.
+    pub fn get_symbols_map(&self) -> HashMap<&str, char> {
+        self.charset.chars().zip(self.homophones.iter())
+            .flat_map(|(letter, symbols)| symbols.iter().map(move |symbol| (symbol.as_str(), letter)))
+            .collect()
+    }
+
+    fn validate(&self) -> Result<()> {
+        let key_error = |message: &str| ErrorKind::KeyError(self.to_string(), message.to_string());
+        self.symbol_set.validate()?;
+        if self.homophones.len() != self.charset.chars().count() {
+            bail!(key_error("There should be a homophones list for every charset letter."))
+        }
+        let valid_symbols = self.symbol_set.symbols();
+        let mut used_symbols: Vec<&String> = Vec::new();
+        for symbol in self.homophones.iter().flatten() {
+            if !valid_symbols.contains(symbol) {
+                bail!(key_error(format!("Symbol {} is not at symbol set.", symbol).as_str()))
+            }
+            if used_symbols.contains(&symbol) {
+                bail!(key_error(format!("Symbol {} is used more than once.", symbol).as_str()))
+            }
+            used_symbols.push(symbol);
+        }
+        Ok(())
+    }
+}
+
+impl Display for HomophonicKey {
+    /// Keys are shown as a comma separated list of letters with their homophones,
+    /// like "a:01.17.45,b:02,...".
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let entries: Vec<String> = self.charset.chars().zip(self.homophones.iter())
+            .map(|(letter, symbols)| format!("{}:{}", letter, symbols.join(".")))
+            .collect();
+        write!(f, "{}", entries.join(","))
+    }
+}
+
+/// Cipher given text using homophonic substitution method.
+///
+/// Homophones of every letter are used in turns, so ciphering the same text with the
+/// same key always gives the same ciphered text.
+///
+/// # Parameters:
+/// * text: Text to be ciphered. Only charset letters are ciphered, anything else is lost.
+/// * key: Secret key. Both ends should know this and use the same one.
+///
+/// # Returns:
+/// * Ciphered text.
+///
+/// # Raises:
+/// * CharacterMappingError: If text has a letter with no symbols at key.
+pub fn cipher(text: &str, key: &HomophonicKey) -> Result<String> {
+    key.validate()?;
+    let charset_chars: Vec<char> = key.charset.chars().collect();
+    let mut uses = vec![0_usize; charset_chars.len()];
+    let mut ciphered_symbols: Vec<&str> = Vec::new();
+    for char in text.to_lowercase().chars() {
+        if let Some(position) = charset_chars.iter().position(|&x| x == char) {
+            let homophones = &key.homophones[position];
+            if homophones.is_empty() {
+                bail!(ErrorKind::CharacterMappingError(char.to_string()))
+            }
+            ciphered_symbols.push(homophones[uses[position] % homophones.len()].as_str());
+            uses[position] += 1;
+        }
+    }
+    Ok(key.symbol_set.join(&ciphered_symbols))
+}
+
+/// Decipher given text using homophonic substitution method.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * key: Secret key. Both ends should know this and use the same one.
+///
+/// # Returns:
+/// * Deciphered text, as a lowercase letter stream.
+///
+/// # Raises:
+/// * CharacterMappingError: If ciphered text has a symbol not present at key.
+pub fn decipher(ciphered_text: &str, key: &HomophonicKey) -> Result<String> {
+    key.validate()?;
+    let symbols_map = key.get_symbols_map();
+    let mut deciphered_text = String::new();
+    for symbol in key.symbol_set.split(ciphered_text) {
+        match symbols_map.get(symbol.as_str()) {
+            Some(letter) => deciphered_text.push(*letter),
+            None => bail!(ErrorKind::CharacterMappingError(symbol))
+        }
+    }
+    Ok(deciphered_text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINAL_MESSAGE: &'static str = "Common sense is not so common.";
+
+    fn digits_key() -> HomophonicKey {
+        let homophones: Vec<Vec<String>> = vec![vec!["1"], vec!["2", "3"], vec!["4"], vec!["5", "6", "7"],
+                                                vec!["8"], vec!["9"], vec!["0"], vec!["a"]].iter()
+            .map(|symbols| symbols.iter().map(|symbol| symbol.to_string()).collect())
+            .collect();
+        HomophonicKey::new(SymbolSet::Chars("0123456789a".to_string()), "cmnoseit", homophones).unwrap()
+    }
+
+    #[test]
+    fn test_cipher_with_chars() {
+        let ciphered_text = cipher(ORIGINAL_MESSAGE, &digits_key()).unwrap();
+        assert_eq!(ciphered_text, "152364894890847a85162374");
+        assert_eq!(decipher(&ciphered_text, &digits_key()).unwrap(), "commonsenseisnotsocommon");
+    }
+
+    #[test]
+    fn test_cipher_with_codes() {
+        let key = HomophonicKey::generate(SymbolSet::Codes(2), DEFAULT_CHARSET, ORIGINAL_MESSAGE).unwrap();
+        assert_eq!(key.homophones.iter().map(|symbols| symbols.len()).sum::<usize>(), 100);
+        assert!(key.homophones[14].len() > key.homophones[25].len());
+        let ciphered_text = cipher(ORIGINAL_MESSAGE, &key).unwrap();
+        assert_eq!(key.symbol_set.split(&ciphered_text).len(), 24);
+        assert_eq!(decipher(&ciphered_text, &key).unwrap(), "commonsenseisnotsocommon");
+    }
+
+    #[test]
+    fn test_symbol_sets() {
+        assert_eq!(SymbolSet::Codes(2).symbols().len(), 100);
+        assert_eq!(SymbolSet::Codes(2).symbols()[7], "07");
+        assert_eq!(SymbolSet::Chars("abca".to_string()).symbols(), vec!["a", "b", "c"]);
+        assert_eq!(SymbolSet::Codes(2).split("01 17\n4"), vec!["01", "17", "4"]);
+    }
+
+    #[test]
+    fn test_wrong_keys_are_detected() {
+        let symbol_set = SymbolSet::Chars("0123".to_string());
+        let repeated_symbol = vec![vec!["0".to_string()], vec!["0".to_string()]];
+        assert!(HomophonicKey::new(symbol_set.clone(), "ab", repeated_symbol).is_err());
+        let missing_letter = vec![vec!["0".to_string()], vec![]];
+        let partial_key = HomophonicKey::new(symbol_set.clone(), "ab", missing_letter).unwrap();
+        assert!(cipher("ab", &partial_key).is_err());
+        let foreign_symbol = vec![vec!["0".to_string()], vec!["x".to_string()]];
+        assert!(HomophonicKey::new(symbol_set, "ab", foreign_symbol).is_err());
+        assert!(HomophonicKey::generate(SymbolSet::Chars("0123".to_string()), DEFAULT_CHARSET, "abc").is_err());
+    }
+}
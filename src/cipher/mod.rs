@@ -1,7 +1,13 @@
 pub mod affine;
 pub mod caesar;
 pub mod common;
-mod cryptomath;
+pub(crate) mod cryptomath;
+pub mod enigma;
+pub mod homophonic;
+pub mod normalization;
+pub mod presets;
+pub mod quagmire;
 pub mod substitution;
 pub mod transposition;
+pub mod vernam;
 pub mod vigenere;
\ No newline at end of file
@@ -0,0 +1,217 @@
+/// Library to normalize unicode texts before extracting their words or ciphering them.
+///
+/// The same word can be written in many ways with unicode: "café" can use a single
+/// precomposed "é" or an "e" followed by a combining accent. Besides, many classic ciphers
+/// work over plain latin alphabets, so "café" ends up being "cafe" once ciphered. To let
+/// dictionaries, texts and ciphers agree on their words, every one of them should be
+/// normalized the same way:
+///
+/// * NFC: Canonical composition. Texts look the same as before, but composed and decomposed
+///     forms of a character become the same. This is the default.
+/// * NFKD: Compatibility decomposition. Ligatures like "ﬁ" are split into their letters and
+///     accented characters into their base letter followed by combining marks.
+/// * Folded: Plain letters. Diacritics are stripped and letters without decomposition, like
+///     "ß" or "æ", are replaced by their usual plain spelling ("ss" and "ae").
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+use crate::Result;
+
+/// Ways to normalize a text.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Normalization {
+    Nfc,
+    Nfkd,
+    Folded
+}
+
+impl Normalization {
+    /// Every available normalization.
+    pub const ALL: [Normalization; 3] = [Normalization::Nfc, Normalization::Nfkd, Normalization::Folded];
+}
+
+impl Default for Normalization {
+    fn default() -> Self {
+        Normalization::Nfc
+    }
+}
+
+impl Display for Normalization {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Normalization::Nfc => "nfc",
+            Normalization::Nfkd => "nfkd",
+            Normalization::Folded => "folded"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl TryFrom<&str> for Normalization {
+    type Error = crate::Error;
+
+    /// Get a normalization from its name.
+    ///
+    /// # Parameters:
+    /// * value: Normalization name, like "folded". Case is ignored.
+    ///
+    /// # Returns:
+    /// * OK with normalization or Err if provided name is not a known normalization.
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "nfc" => Ok(Normalization::Nfc),
+            "nfkd" => Ok(Normalization::Nfkd),
+            "folded" => Ok(Normalization::Folded),
+            _ => bail!(format!("Unknown normalization: {}", value))
+        }
+    }
+}
+
+/// Normalize given text.
+///
+/// Case is kept, so this can be used with texts to cipher.
+///
+/// # Parameters:
+/// * text: Text to normalize.
+/// * normalization: How to normalize text.
+///
+/// # Returns:
+/// * Normalized text.
+pub fn normalize_unicode<T>(text: T, normalization: Normalization) -> String
+    where T: AsRef<str> {
+    match normalization {
+        Normalization::Nfc => text.as_ref().nfc().collect(),
+        Normalization::Nfkd => text.as_ref().nfkd().collect(),
+        Normalization::Folded => text.as_ref().chars().map(fold_char).collect()
+    }
+}
+
+/// Get plain letters for given character.
+///
+/// # Parameters:
+/// * character: Character to fold.
+///
+/// # Returns:
+/// * Character without diacritics, or its usual spelling if it has no decomposition.
+fn fold_char(character: char) -> String {
+    match character {
+        'ß' => String::from("ss"),
+        'ẞ' => String::from("SS"),
+        'æ' => String::from("ae"),
+        'Æ' => String::from("AE"),
+        'œ' => String::from("oe"),
+        'Œ' => String::from("OE"),
+        'ø' => String::from("o"),
+        'Ø' => String::from("O"),
+        'đ' | 'ð' => String::from("d"),
+        'Đ' | 'Ð' => String::from("D"),
+        'ł' => String::from("l"),
+        'Ł' => String::from("L"),
+        'þ' => String::from("th"),
+        'Þ' => String::from("TH"),
+        'ı' => String::from("i"),
+        _ => character.to_string().nfkd()
+            .filter(|decomposed| !is_combining_mark(*decomposed))
+            .collect()
+    }
+}
+
+/// Fold characters not present at charset into plain letters that are.
+///
+/// Ciphers leave as is any character not present at their charset, so accented letters
+/// would be kept unciphered if charset is a plain alphabet. Characters are only folded
+/// if every resulting letter is at charset, in any case, so charsets with accented letters
+/// keep them.
+///
+/// # Parameters:
+/// * text: Text to adapt to charset.
+/// * charset: Charset text is going to be ciphered with.
+///
+/// # Returns:
+/// * Text with its characters folded when needed to fit into charset.
+pub fn fold_to_charset<T, U>(text: T, charset: U) -> String
+    where T: AsRef<str>,
+          U: AsRef<str> {
+    // Some ciphers work with lowercase charsets and keep caps apart, so any case is accepted.
+    // Case changes giving many letters, like "ß" to "SS", are not the same letter, though.
+    let is_at_charset = |character: char| {
+        charset.as_ref().contains(character) ||
+            [single_char(character.to_lowercase()), single_char(character.to_uppercase())].iter()
+                .any(|changed_case| changed_case.map_or(false, |changed_character| charset.as_ref().contains(changed_character)))
+    };
+    text.as_ref().nfc()
+        .map(|character| {
+            if is_at_charset(character) {
+                character.to_string()
+            } else {
+                let folded = fold_char(character);
+                if !folded.is_empty() && folded.chars().all(is_at_charset) { folded } else { character.to_string() }
+            }
+        })
+        .collect()
+}
+
+/// Get the only character given characters have.
+///
+/// # Parameters:
+/// * characters: Characters to check.
+///
+/// # Returns:
+/// * Character if there is only one, or None if there are none or many.
+fn single_char<I>(mut characters: I) -> Option<char>
+    where I: Iterator<Item=char> {
+    match (characters.next(), characters.next()) {
+        (Some(character), None) => Some(character),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    use crate::cipher::caesar;
+    use crate::cipher::common::DEFAULT_CHARSET;
+
+    #[rstest(normalization, text, expected,
+        case::nfc_composes(Normalization::Nfc, "cafe\u{301}", "café"),
+        case::nfc_keeps_composed(Normalization::Nfc, "café", "café"),
+        case::nfkd_decomposes(Normalization::Nfkd, "café", "cafe\u{301}"),
+        case::nfkd_splits_ligatures(Normalization::Nfkd, "ﬁn", "fin"),
+        case::folded_strips_accents(Normalization::Folded, "Canción, él, über", "Cancion, el, uber"),
+        case::folded_decomposed(Normalization::Folded, "cafe\u{301}", "cafe"),
+        case::folded_sharp_s(Normalization::Folded, "Straße", "Strasse"),
+        case::folded_ligatures(Normalization::Folded, "Œuvre, æther, ﬁn", "OEuvre, aether, fin")
+    )]
+    fn test_normalize_unicode(normalization: Normalization, text: &str, expected: &str) {
+        assert_eq!(expected, normalize_unicode(text, normalization));
+    }
+
+    #[test]
+    fn test_fold_to_charset() {
+        assert_eq!("Cancion de Strasse!", fold_to_charset("Canción de Straße!", "abcdefghijklmnopqrstuvwxyz"));
+        assert_eq!("Canción", fold_to_charset("Canción", "abcdefghijklmnopqrstuvwxyzó"));
+        assert_eq!("Canción", fold_to_charset("Canción", "abc"));
+        assert_eq!("Strasse", fold_to_charset("Straße", DEFAULT_CHARSET));
+    }
+
+    #[test]
+    fn test_folded_text_is_fully_ciphered() {
+        let text = fold_to_charset("Él comió paella", DEFAULT_CHARSET);
+        let ciphered_text = caesar::cipher(&text, 3, DEFAULT_CHARSET).unwrap();
+        assert!(ciphered_text.chars().all(|character| DEFAULT_CHARSET.contains(character)));
+        assert_eq!("El comio paella", caesar::decipher(&ciphered_text, 3, DEFAULT_CHARSET).unwrap());
+    }
+
+    #[test]
+    fn test_normalization_names() {
+        for normalization in Normalization::ALL.iter() {
+            assert_eq!(*normalization, Normalization::try_from(normalization.to_string().as_str()).unwrap());
+        }
+        assert!(Normalization::try_from("nfx").is_err());
+    }
+}
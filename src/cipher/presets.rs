@@ -0,0 +1,164 @@
+/// Library to cipher and decipher texts using classic keyless ciphers.
+///
+/// Some well known ciphers are just affine or Caesar ciphers with a fixed key and
+/// charset, so they need no key at all:
+///
+/// * Atbash: Reverses the latin alphabet (a becomes z, b becomes y...). It is an
+///     affine cipher whose multiplying and adding keys are both charset length minus one.
+/// * ROT13: Caesar cipher with key 13 over latin alphabet.
+/// * ROT47: Caesar cipher with key 47 over the 94 printable ASCII characters.
+///
+/// All of them are their own inverse, so ciphering twice gives back original text.
+/// Atbash and ROT13 keep caps and leave any character that is not a latin letter as is.
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+
+use crate::Result;
+use crate::cipher::{affine, caesar};
+
+const LOWERCASE_CHARSET: &'static str = "abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE_CHARSET: &'static str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+/// Printable ASCII characters, every one from "!" to "~".
+pub const ROT47_CHARSET: &'static str = "!\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+const ROT13_KEY: usize = 13;
+const ROT47_KEY: usize = 47;
+
+/// Keyless ciphers.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Preset {
+    Atbash,
+    Rot13,
+    Rot47
+}
+
+impl Preset {
+    /// Every available preset.
+    pub const ALL: [Preset; 3] = [Preset::Atbash, Preset::Rot13, Preset::Rot47];
+
+    /// Cipher given text with this preset.
+    ///
+    /// # Parameters:
+    /// * text: Text to be ciphered.
+    ///
+    /// # Returns:
+    /// * Ciphered text.
+    pub fn cipher(&self, text: &str) -> Result<String> {
+        match self {
+            Preset::Atbash => atbash(text),
+            Preset::Rot13 => rot13(text),
+            Preset::Rot47 => rot47(text)
+        }
+    }
+
+    /// Decipher given text with this preset.
+    ///
+    /// Every preset is its own inverse, so this is the same as ciphering.
+    ///
+    /// # Parameters:
+    /// * ciphered_text: Text to be deciphered.
+    ///
+    /// # Returns:
+    /// * Deciphered text.
+    pub fn decipher(&self, ciphered_text: &str) -> Result<String> {
+        self.cipher(ciphered_text)
+    }
+}
+
+impl Display for Preset {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Preset::Atbash => "atbash",
+            Preset::Rot13 => "rot13",
+            Preset::Rot47 => "rot47"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl TryFrom<&str> for Preset {
+    type Error = crate::Error;
+
+    /// Get a preset from its name.
+    ///
+    /// # Parameters:
+    /// * value: Preset name, like "atbash" or "ROT13". Case is ignored.
+    ///
+    /// # Returns:
+    /// * OK with preset or Err if provided name is not a known preset.
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "atbash" => Ok(Preset::Atbash),
+            "rot13" => Ok(Preset::Rot13),
+            "rot47" => Ok(Preset::Rot47),
+            _ => bail!(format!("Unknown preset: {}", value))
+        }
+    }
+}
+
+/// Cipher or decipher given text using Atbash.
+///
+/// # Parameters:
+/// * text: Text to be processed.
+///
+/// # Returns:
+/// * Processed text.
+pub fn atbash(text: &str) -> Result<String> {
+    let lowercase_processed_text = affine::cipher(text, affine::atbash_key(LOWERCASE_CHARSET.len()), LOWERCASE_CHARSET)?;
+    affine::cipher(&lowercase_processed_text, affine::atbash_key(UPPERCASE_CHARSET.len()), UPPERCASE_CHARSET)
+}
+
+/// Cipher or decipher given text using ROT13.
+///
+/// # Parameters:
+/// * text: Text to be processed.
+///
+/// # Returns:
+/// * Processed text.
+pub fn rot13(text: &str) -> Result<String> {
+    let lowercase_processed_text = caesar::cipher(text, ROT13_KEY, LOWERCASE_CHARSET)?;
+    caesar::cipher(&lowercase_processed_text, ROT13_KEY, UPPERCASE_CHARSET)
+}
+
+/// Cipher or decipher given text using ROT47.
+///
+/// # Parameters:
+/// * text: Text to be processed.
+///
+/// # Returns:
+/// * Processed text.
+pub fn rot47(text: &str) -> Result<String> {
+    caesar::cipher(text, ROT47_KEY, ROT47_CHARSET)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINAL_MESSAGE: &'static str = "Hello World! 123";
+
+    #[test]
+    fn test_atbash() {
+        assert_eq!(atbash(ORIGINAL_MESSAGE).unwrap(), "Svool Dliow! 123");
+        assert_eq!(Preset::Atbash.decipher("Svool Dliow! 123").unwrap(), ORIGINAL_MESSAGE);
+    }
+
+    #[test]
+    fn test_rot13() {
+        assert_eq!(rot13(ORIGINAL_MESSAGE).unwrap(), "Uryyb Jbeyq! 123");
+        assert_eq!(Preset::Rot13.decipher("Uryyb Jbeyq! 123").unwrap(), ORIGINAL_MESSAGE);
+    }
+
+    #[test]
+    fn test_rot47() {
+        assert_eq!(rot47(ORIGINAL_MESSAGE).unwrap(), "w6==@ (@C=5P `ab");
+        assert_eq!(Preset::Rot47.decipher("w6==@ (@C=5P `ab").unwrap(), ORIGINAL_MESSAGE);
+    }
+
+    #[test]
+    fn test_preset_names() {
+        for preset in Preset::ALL.iter() {
+            assert_eq!(Preset::try_from(preset.to_string().as_str()).unwrap(), *preset);
+        }
+        assert!(Preset::try_from("rot14").is_err());
+    }
+}
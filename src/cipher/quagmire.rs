@@ -0,0 +1,416 @@
+/// Library to cipher and decipher texts using Quagmire methods.
+///
+/// Quagmire ciphers are the keyed alphabets members of Vigenere family used by the
+/// American Cryptogram Association. Whereas Vigenere uses straight alphabets both for
+/// plaintext and ciphertext, Quagmires mix any or both of them with a keyword:
+///
+/// * Quagmire I: Keyed plaintext alphabet and straight ciphertext alphabet.
+/// * Quagmire II: Straight plaintext alphabet and keyed ciphertext alphabet.
+/// * Quagmire III: Plaintext and ciphertext alphabets keyed with the same keyword.
+/// * Quagmire IV: Plaintext and ciphertext alphabets keyed with different keywords.
+///
+/// An indicator keyword sets how ciphertext alphabet is slid for every letter. Following
+/// ACA conventions, each indicator letter is placed under plaintext alphabet first charset
+/// letter (usually "a").
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+use crate::{ErrorKind, Result};
+
+// Like Vigenere, Quagmire works with only lowercase charsets.
+pub const DEFAULT_CHARSET: &'static str = "abcdefghijklmnopqrstuvwxyz";
+
+/// Quagmire variants.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Quagmire {
+    I,
+    II,
+    III,
+    IV
+}
+
+/// Key for Quagmire ciphers.
+///
+/// Keyed alphabets are not stored but built from keywords when needed, so the same key
+/// can be used with different charsets.
+///
+/// An empty keyword means a straight alphabet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuagmireKey {
+    pub variant: Quagmire,
+    pub plaintext_keyword: String,
+    pub ciphertext_keyword: String,
+    pub indicator: String
+}
+
+impl QuagmireKey {
+    /// Create a Quagmire I key: keyed plaintext alphabet and straight ciphertext alphabet.
+    ///
+    /// # Parameters:
+    /// * keyword: Keyword used to mix plaintext alphabet.
+    /// * indicator: Keyword whose letters set ciphertext alphabet slides.
+    pub fn quagmire_i<T, U>(keyword: T, indicator: U) -> Self
+        where T: AsRef<str>,
+              U: AsRef<str> {
+        Self::new(Quagmire::I, keyword.as_ref(), "", indicator.as_ref())
+    }
+
+    /// Create a Quagmire II key: straight plaintext alphabet and keyed ciphertext alphabet.
+    ///
+    /// # Parameters:
+    /// * keyword: Keyword used to mix ciphertext alphabet.
+    /// * indicator: Keyword whose letters set ciphertext alphabet slides.
+    pub fn quagmire_ii<T, U>(keyword: T, indicator: U) -> Self
+        where T: AsRef<str>,
+              U: AsRef<str> {
+        Self::new(Quagmire::II, "", keyword.as_ref(), indicator.as_ref())
+    }
+
+    /// Create a Quagmire III key: both alphabets keyed with the same keyword.
+    ///
+    /// # Parameters:
+    /// * keyword: Keyword used to mix both alphabets.
+    /// * indicator: Keyword whose letters set ciphertext alphabet slides.
+    pub fn quagmire_iii<T, U>(keyword: T, indicator: U) -> Self
+        where T: AsRef<str>,
+              U: AsRef<str> {
+        Self::new(Quagmire::III, keyword.as_ref(), keyword.as_ref(), indicator.as_ref())
+    }
+
+    /// Create a Quagmire IV key: each alphabet keyed with its own keyword.
+    ///
+    /// # Parameters:
+    /// * plaintext_keyword: Keyword used to mix plaintext alphabet.
+    /// * ciphertext_keyword: Keyword used to mix ciphertext alphabet.
+    /// * indicator: Keyword whose letters set ciphertext alphabet slides.
+    pub fn quagmire_iv<T, U, V>(plaintext_keyword: T, ciphertext_keyword: U, indicator: V) -> Self
+        where T: AsRef<str>,
+              U: AsRef<str>,
+              V: AsRef<str> {
+        Self::new(Quagmire::IV, plaintext_keyword.as_ref(), ciphertext_keyword.as_ref(), indicator.as_ref())
+    }
+
+    fn new(variant: Quagmire, plaintext_keyword: &str, ciphertext_keyword: &str, indicator: &str) -> Self {
+        QuagmireKey {
+            variant,
+            plaintext_keyword: plaintext_keyword.to_lowercase(),
+            ciphertext_keyword: ciphertext_keyword.to_lowercase(),
+            indicator: indicator.to_lowercase()
+        }
+    }
+
+    /// Create a key from its string representation.
+    ///
+    /// String representation is "KEYWORD,INDICATOR" for Quagmire I, II and III and
+    /// "PLAINTEXT_KEYWORD,CIPHERTEXT_KEYWORD,INDICATOR" for Quagmire IV.
+    ///
+    /// # Parameters:
+    /// * variant: Quagmire variant this key is for.
+    /// * key_string: Key string representation.
+    ///
+    /// # Returns:
+    /// * A Quagmire key.
+    pub fn from_key_string<T>(variant: Quagmire, key_string: T) -> Result<Self>
+        where T: AsRef<str> {
+        let parts: Vec<&str> = key_string.as_ref().split(',').map(|part| part.trim()).collect();
+        match (variant, parts.as_slice()) {
+            (Quagmire::I, [keyword, indicator]) => Ok(Self::quagmire_i(keyword, indicator)),
+            (Quagmire::II, [keyword, indicator]) => Ok(Self::quagmire_ii(keyword, indicator)),
+            (Quagmire::III, [keyword, indicator]) => Ok(Self::quagmire_iii(keyword, indicator)),
+            (Quagmire::IV, [plaintext_keyword, ciphertext_keyword, indicator]) =>
+                Ok(Self::quagmire_iv(plaintext_keyword, ciphertext_keyword, indicator)),
+            (Quagmire::IV, _) => bail!(ErrorKind::KeyError(key_string.as_ref().to_string(),
+                "Quagmire IV keys should be written as PLAINTEXT_KEYWORD,CIPHERTEXT_KEYWORD,INDICATOR".to_string())),
+            _ => bail!(ErrorKind::KeyError(key_string.as_ref().to_string(),
+                "Quagmire keys should be written as KEYWORD,INDICATOR".to_string()))
+        }
+    }
+
+    /// Get plaintext alphabet for this key.
+    ///
+    /// # Parameters:
+    /// * charset: Charset used to build alphabet.
+    ///
+    /// # Returns:
+    /// * Plaintext alphabet.
+    pub fn plaintext_alphabet<T>(&self, charset: T) -> String
+        where T: AsRef<str> {
+        keyed_alphabet(&self.plaintext_keyword, charset)
+    }
+
+    /// Get ciphertext alphabet for this key.
+    ///
+    /// # Parameters:
+    /// * charset: Charset used to build alphabet.
+    ///
+    /// # Returns:
+    /// * Ciphertext alphabet.
+    pub fn ciphertext_alphabet<T>(&self, charset: T) -> String
+        where T: AsRef<str> {
+        keyed_alphabet(&self.ciphertext_keyword, charset)
+    }
+
+    /// Check every key letter is present at charset.
+    ///
+    /// # Parameters:
+    /// * charset: Charset to check key against.
+    fn validate<T>(&self, charset: T) -> Result<()>
+        where T: AsRef<str> {
+        if self.indicator.is_empty() {
+            bail!(ErrorKind::KeyError(self.to_string(), "Indicator keyword can not be empty.".to_string()))
+        }
+        let keywords = [&self.plaintext_keyword, &self.ciphertext_keyword, &self.indicator];
+        for keyword in keywords.iter() {
+            if let Some(wrong_char) = keyword.chars().find(|&char| !charset.as_ref().contains(char)) {
+                bail!(ErrorKind::KeyError(self.to_string(),
+                                          format!("Character {} is not present at charset.", wrong_char)))
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for QuagmireKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.variant {
+            Quagmire::I | Quagmire::III => write!(f, "{},{}", self.plaintext_keyword, self.indicator),
+            Quagmire::II => write!(f, "{},{}", self.ciphertext_keyword, self.indicator),
+            Quagmire::IV => write!(f, "{},{},{}", self.plaintext_keyword, self.ciphertext_keyword, self.indicator)
+        }
+    }
+}
+
+/// Build a keyword mixed alphabet.
+///
+/// Keyword letters are written first, without repetitions, and then remaining charset
+/// letters in their charset order.
+///
+/// # Parameters:
+/// * keyword: Keyword to mix alphabet with. If empty, then charset is returned as is.
+/// * charset: Charset to mix.
+///
+/// # Returns:
+/// * Keyed alphabet.
+pub fn keyed_alphabet<T, U>(keyword: T, charset: U) -> String
+    where T: AsRef<str>,
+          U: AsRef<str> {
+    let mut used_chars: HashSet<char> = HashSet::new();
+    let mut alphabet = String::new();
+    for char in keyword.as_ref().to_lowercase().chars().chain(charset.as_ref().chars()) {
+        if charset.as_ref().contains(char) && used_chars.insert(char) {
+            alphabet.push(char);
+        }
+    }
+    alphabet
+}
+
+/// Get keyword from a keyed alphabet.
+///
+/// Keyed alphabets end with charset letters not used at keyword, in charset order. So
+/// keyword is what goes before that ordered tail. Be aware that keyword letters that
+/// happen to continue that tail order can not be told apart, but returned keyword
+/// always rebuilds the same alphabet with *keyed_alphabet()*.
+///
+/// # Parameters:
+/// * alphabet: Keyed alphabet.
+/// * charset: Charset used to build alphabet.
+///
+/// # Returns:
+/// * Keyword.
+pub fn keyword_from_alphabet<T, U>(alphabet: T, charset: U) -> String
+    where T: AsRef<str>,
+          U: AsRef<str> {
+    let positions: Vec<usize> = alphabet.as_ref().chars()
+        .filter_map(|char| charset.as_ref().chars().position(|x| x == char))
+        .collect();
+    let mut tail_start = positions.len();
+    while tail_start > 0 && (tail_start == positions.len() || positions[tail_start - 1] < positions[tail_start]) {
+        tail_start -= 1;
+    }
+    alphabet.as_ref().chars().take(tail_start).collect()
+}
+
+/// Cipher given text using Quagmire method.
+///
+/// Be aware that different languages use different charsets. Default charset
+/// is for english language, if you are using any other you should use a proper
+/// dataset. For instance, if you are ciphering an spanish text, you should use
+/// a charset with "ñ" character.
+///
+/// This module uses only lowercase charsets. That means that caps will be kept
+/// but lowercase and uppercase will follow ths same substitutions.
+///
+/// # Parameters:
+/// * text: Text to be ciphered.
+/// * key: Secret key. Both ends should know this and use the same one.
+/// * charset: Charset used for Quagmire method. Both ends, ciphering
+///     and deciphering, should use the same charset or original text won't be properly
+///     recovered.
+///
+/// # Returns:
+/// * Ciphered text.
+pub fn cipher(text: &str, key: &QuagmireKey, charset: &str) -> Result<String> {
+    quagmire_offset(text, key, true, charset)
+}
+
+/// Decipher given text using Quagmire method.
+///
+/// Note you should use the same charset that ciphering end did.
+///
+/// # Parameters:
+/// * ciphered_text: Text to be deciphered.
+/// * key: Secret key. Both ends should know this and use the same one.
+/// * charset: Charset used for Quagmire method. Both end should
+///     use the same charset or original text won't be properly recovered.
+///
+/// # Returns:
+/// * Deciphered text.
+pub fn decipher(ciphered_text: &str, key: &QuagmireKey, charset: &str) -> Result<String> {
+    quagmire_offset(ciphered_text, key, false, charset)
+}
+
+/// Get how many positions ciphertext alphabet is slid for every indicator letter.
+///
+/// Every indicator letter is placed under first charset letter of plaintext alphabet.
+///
+/// # Parameters:
+/// * key: Quagmire key.
+/// * plaintext_alphabet: Plaintext alphabet as a vector of chars.
+/// * ciphertext_alphabet: Ciphertext alphabet as a vector of chars.
+/// * charset: Charset used for Quagmire method.
+///
+/// # Returns:
+/// * A list of offsets, one for every indicator letter.
+pub(crate) fn get_offsets(key: &QuagmireKey, plaintext_alphabet: &[char],
+                          ciphertext_alphabet: &[char], charset: &str) -> Result<Vec<usize>> {
+    let charset_length = plaintext_alphabet.len();
+    let anchor = charset.chars().next()
+        .ok_or_else(|| ErrorKind::KeyError(key.to_string(), "Charset can not be empty.".to_string()))?;
+    // Unwrap is safe because every charset letter is at plaintext alphabet.
+    let anchor_position = plaintext_alphabet.iter().position(|&x| x == anchor).unwrap();
+    let offsets = key.indicator.chars()
+        .map(|indicator_char| {
+            // Unwrap is safe because key has been validated against charset.
+            let indicator_position = ciphertext_alphabet.iter().position(|&x| x == indicator_char).unwrap();
+            (indicator_position + charset_length - anchor_position) % charset_length
+        })
+        .collect();
+    Ok(offsets)
+}
+
+/// Utility function to reduce code redundancy with Quagmire operations.
+///
+/// Don't use this function directly.
+///
+/// # Parameters:
+/// * text: Text to be ciphered or deciphered.
+/// * key: Secret key.
+/// * ciphering: True to cipher and false to decipher.
+/// * charset: Charset used for Quagmire method.
+///
+/// # Returns:
+/// * Offset text.
+fn quagmire_offset(text: &str, key: &QuagmireKey, ciphering: bool, charset: &str) -> Result<String> {
+    key.validate(charset)?;
+    let plaintext_alphabet: Vec<char> = key.plaintext_alphabet(charset).chars().collect();
+    let ciphertext_alphabet: Vec<char> = key.ciphertext_alphabet(charset).chars().collect();
+    let (source_alphabet, destination_alphabet) = if ciphering {
+        (&plaintext_alphabet, &ciphertext_alphabet)
+    } else {
+        (&ciphertext_alphabet, &plaintext_alphabet)
+    };
+    let offsets = get_offsets(key, &plaintext_alphabet, &ciphertext_alphabet, charset)?;
+    let charset_length = plaintext_alphabet.len();
+    let mut offset_text = String::new();
+    let mut letter_index: usize = 0;
+    for char in text.chars() {
+        let lowercase_char = char.to_lowercase().next().unwrap_or(char);
+        if let Some(source_position) = source_alphabet.iter().position(|&x| x == lowercase_char) {
+            let offset = offsets[letter_index % offsets.len()];
+            let destination_position = if ciphering {
+                (source_position + offset) % charset_length
+            } else {
+                (source_position + charset_length - offset) % charset_length
+            };
+            let new_char = destination_alphabet[destination_position];
+            if char.is_lowercase() {
+                offset_text.push(new_char);
+            } else {
+                offset_text.push_str(new_char.to_uppercase().to_string().as_str());
+            }
+            letter_index += 1;
+        } else {
+            offset_text.push(char);
+        }
+    }
+    Ok(offset_text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINAL_MESSAGE: &'static str = "Common sense is not so common.";
+
+    #[test]
+    fn test_keyed_alphabet() {
+        let alphabet = keyed_alphabet("SpringFever", DEFAULT_CHARSET);
+        assert_eq!(alphabet, "springfevabcdhjklmoqtuwxyz");
+    }
+
+    #[test]
+    fn test_keyword_from_alphabet() {
+        let alphabet = keyed_alphabet("springfever", DEFAULT_CHARSET);
+        let keyword = keyword_from_alphabet(&alphabet, DEFAULT_CHARSET);
+        assert_eq!(keyword, "springfev");
+        assert_eq!(keyed_alphabet(&keyword, DEFAULT_CHARSET), alphabet);
+        assert_eq!(keyword_from_alphabet(DEFAULT_CHARSET, DEFAULT_CHARSET), "");
+    }
+
+    #[test]
+    fn test_indicator_is_under_plaintext_a() {
+        let keys = vec![QuagmireKey::quagmire_i("springfever", "flower"),
+                        QuagmireKey::quagmire_ii("springfever", "flower"),
+                        QuagmireKey::quagmire_iii("springfever", "flower"),
+                        QuagmireKey::quagmire_iv("senory", "percussion", "flower")];
+        for key in keys {
+            let ciphered_text = cipher("aaaaaa", &key, DEFAULT_CHARSET).unwrap();
+            assert_eq!(ciphered_text, "flower", "Wrong ciphering with {:?}", key);
+        }
+    }
+
+    #[test]
+    fn test_cipher_decipher() {
+        let keys = vec![QuagmireKey::quagmire_i("springfever", "flower"),
+                        QuagmireKey::quagmire_ii("springfever", "flower"),
+                        QuagmireKey::quagmire_iii("springfever", "flower"),
+                        QuagmireKey::quagmire_iv("senory", "percussion", "flower")];
+        for key in keys {
+            let ciphered_text = cipher(ORIGINAL_MESSAGE, &key, DEFAULT_CHARSET).unwrap();
+            assert_ne!(ciphered_text, ORIGINAL_MESSAGE);
+            let deciphered_text = decipher(&ciphered_text, &key, DEFAULT_CHARSET).unwrap();
+            assert_eq!(deciphered_text, ORIGINAL_MESSAGE, "Wrong deciphering with {:?}", key);
+        }
+    }
+
+    #[test]
+    fn test_quagmire_iii_with_straight_alphabets_is_vigenere() {
+        let key = QuagmireKey::quagmire_iii("", "pizza");
+        let ciphered_text = cipher(ORIGINAL_MESSAGE, &key, DEFAULT_CHARSET).unwrap();
+        assert_eq!(ciphered_text, "Rwlloc admst qr moi an bobunm.");
+    }
+
+    #[test]
+    fn test_key_string() {
+        let key = QuagmireKey::from_key_string(Quagmire::IV, "senory,percussion,flower").unwrap();
+        assert_eq!(key, QuagmireKey::quagmire_iv("senory", "percussion", "flower"));
+        assert_eq!(key.to_string(), "senory,percussion,flower");
+        assert!(QuagmireKey::from_key_string(Quagmire::I, "senory,percussion,flower").is_err());
+    }
+
+    #[test]
+    fn test_wrong_key_is_detected() {
+        let key = QuagmireKey::quagmire_iii("springfever", "fl0wer");
+        assert!(cipher(ORIGINAL_MESSAGE, &key, DEFAULT_CHARSET).is_err());
+    }
+}
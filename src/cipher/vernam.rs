@@ -0,0 +1,193 @@
+/// Library to cipher and decipher data using Vernam method.
+///
+/// Unlike the rest of cifra ciphers, Vernam works with bytes instead of charset letters,
+/// so it can cipher any kind of data. Every data byte is XORed with a pad byte, so
+/// ciphering and deciphering are the same operation.
+///
+/// When pad is truly random, at least as long as data and never reused, this is the
+/// one-time pad: the only unbreakable cipher. Reusing a pad is a fatal mistake
+/// (see *attack::vernam*), so this module offers pad files that burn pad bytes as they
+/// are used.
+use std::fs::{read, write};
+use std::path::{Path, PathBuf};
+
+use rand::RngCore;
+
+use crate::{ErrorKind, Result, ResultExt};
+
+/// Cipher given data using Vernam method.
+///
+/// # Parameters:
+/// * data: Data to be ciphered.
+/// * pad: Secret key. It should be at least as long as data. Only its first bytes
+///     are used if it is longer.
+///
+/// # Returns:
+/// * Ciphered data.
+pub fn cipher(data: &[u8], pad: &[u8]) -> Result<Vec<u8>> {
+    if pad.len() < data.len() {
+        bail!(ErrorKind::KeyError(format!("{} bytes long pad", pad.len()),
+                                  format!("Pad should be at least {} bytes long.", data.len())))
+    }
+    Ok(data.iter().zip(pad.iter())
+        .map(|(data_byte, pad_byte)| data_byte ^ pad_byte)
+        .collect())
+}
+
+/// Decipher given data using Vernam method.
+///
+/// # Parameters:
+/// * ciphered_data: Data to be deciphered.
+/// * pad: Secret key. It should be the same pad used to cipher.
+///
+/// # Returns:
+/// * Deciphered data.
+pub fn decipher(ciphered_data: &[u8], pad: &[u8]) -> Result<Vec<u8>> {
+    cipher(ciphered_data, pad)
+}
+
+/// File with pad bytes not used yet.
+///
+/// Both ends should have a copy of the same pad file. Every time a message is ciphered
+/// or deciphered, used bytes are removed from file, so both copies go on in step and
+/// no pad byte is ever used twice.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PadFile {
+    path: PathBuf
+}
+
+impl PadFile {
+    /// Create a pad file filled with random bytes.
+    ///
+    /// # Parameters:
+    /// * path: Path to pad file to create. Any existing file there is overwritten.
+    /// * length: Pad length in bytes. It limits how many bytes can be ciphered with it.
+    ///
+    /// # Returns:
+    /// * Created pad file.
+    pub fn create<T>(path: T, length: usize) -> Result<Self>
+        where T: AsRef<Path> {
+        let mut pad = vec![0_u8; length];
+        rand::thread_rng().fill_bytes(&mut pad);
+        let pad_file = PadFile { path: path.as_ref().to_path_buf() };
+        pad_file.write(&pad)?;
+        Ok(pad_file)
+    }
+
+    /// Use an existing pad file.
+    ///
+    /// # Parameters:
+    /// * path: Path to pad file.
+    ///
+    /// # Returns:
+    /// * Pad file, or an error if it does not exist.
+    pub fn open<T>(path: T) -> Result<Self>
+        where T: AsRef<Path> {
+        let pad_file = PadFile { path: path.as_ref().to_path_buf() };
+        if !pad_file.path.is_file() {
+            bail!(ErrorKind::IOError(pad_file.path_string()))
+        }
+        Ok(pad_file)
+    }
+
+    /// Get how many pad bytes are left.
+    pub fn remaining(&self) -> Result<usize> {
+        Ok(self.read()?.len())
+    }
+
+    /// Take pad bytes, removing them from file.
+    ///
+    /// # Parameters:
+    /// * length: How many bytes to take.
+    ///
+    /// # Returns:
+    /// * Pad bytes, or an error if there are not enough left. In that case file is
+    ///     left untouched.
+    pub fn take(&mut self, length: usize) -> Result<Vec<u8>> {
+        let mut pad = self.read()?;
+        if pad.len() < length {
+            bail!(ErrorKind::KeyError(self.path_string(),
+                                      format!("Only {} pad bytes are left but {} are needed.", pad.len(), length)))
+        }
+        let remaining_pad = pad.split_off(length);
+        self.write(&remaining_pad)?;
+        Ok(pad)
+    }
+
+    /// Cipher data with next pad bytes.
+    ///
+    /// # Parameters:
+    /// * data: Data to be ciphered.
+    ///
+    /// # Returns:
+    /// * Ciphered data.
+    pub fn cipher(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        let pad = self.take(data.len())?;
+        cipher(data, &pad)
+    }
+
+    /// Decipher data with next pad bytes.
+    ///
+    /// # Parameters:
+    /// * ciphered_data: Data to be deciphered.
+    ///
+    /// # Returns:
+    /// * Deciphered data.
+    pub fn decipher(&mut self, ciphered_data: &[u8]) -> Result<Vec<u8>> {
+        let pad = self.take(ciphered_data.len())?;
+        decipher(ciphered_data, &pad)
+    }
+
+    fn read(&self) -> Result<Vec<u8>> {
+        read(&self.path)
+            .chain_err(|| ErrorKind::IOError(self.path_string()))
+    }
+
+    fn write(&self, pad: &[u8]) -> Result<()> {
+        write(&self.path, pad)
+            .chain_err(|| ErrorKind::IOError(self.path_string()))
+    }
+
+    fn path_string(&self) -> String {
+        self.path.to_string_lossy().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_common::fs::tmp::TestEnvironment;
+
+    const ORIGINAL_MESSAGE: &'static str = "Common sense is not so common.";
+
+    #[test]
+    fn test_cipher_decipher() {
+        let pad: Vec<u8> = (0..ORIGINAL_MESSAGE.len() as u8).map(|byte| byte.wrapping_mul(37)).collect();
+        let ciphered_data = cipher(ORIGINAL_MESSAGE.as_bytes(), &pad).unwrap();
+        assert_eq!(ciphered_data[0], b'C' ^ pad[0]);
+        assert_eq!(decipher(&ciphered_data, &pad).unwrap(), ORIGINAL_MESSAGE.as_bytes());
+    }
+
+    #[test]
+    fn test_short_pad_is_detected() {
+        assert!(cipher(ORIGINAL_MESSAGE.as_bytes(), &[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn test_pad_file() {
+        let temp_dir = TestEnvironment::new();
+        let pad_path = temp_dir.path().join("pad.bin");
+        let mut sender_pad = PadFile::create(&pad_path, 100).unwrap();
+        let receiver_pad_path = temp_dir.path().join("receiver_pad.bin");
+        std::fs::copy(&pad_path, &receiver_pad_path).unwrap();
+        let mut receiver_pad = PadFile::open(&receiver_pad_path).unwrap();
+        for _ in 0..3 {
+            let ciphered_data = sender_pad.cipher(ORIGINAL_MESSAGE.as_bytes()).unwrap();
+            assert_eq!(receiver_pad.decipher(&ciphered_data).unwrap(), ORIGINAL_MESSAGE.as_bytes());
+        }
+        assert_eq!(sender_pad.remaining().unwrap(), 100 - 3 * ORIGINAL_MESSAGE.len());
+        assert!(sender_pad.cipher(ORIGINAL_MESSAGE.as_bytes()).is_err());
+        assert_eq!(sender_pad.remaining().unwrap(), 10);
+    }
+}
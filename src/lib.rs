@@ -2,7 +2,6 @@
 #![feature(iter_advance_by)]
 #[macro_use]
 extern crate diesel;
-extern crate dotenv;
 
 pub mod attack;
 pub mod cipher;
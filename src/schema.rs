@@ -5,18 +5,30 @@ table! {
     }
 }
 
+table! {
+    letter_frequencies (id) {
+        id -> Integer,
+        ngram -> Text,
+        language_id -> Integer,
+        occurrences -> BigInt,
+    }
+}
+
 table! {
     words (id) {
         id -> Integer,
         word -> Text,
         word_pattern -> Text,
         language_id -> Integer,
+        frequency -> Integer,
     }
 }
 
+joinable!(letter_frequencies -> languages (language_id));
 joinable!(words -> languages (language_id));
 
 allow_tables_to_appear_in_same_query!(
     languages,
+    letter_frequencies,
     words,
 );